@@ -0,0 +1,433 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cw_storage_plus::U64Key;
+
+use crate::error::ContractError;
+use crate::querier::query_address_voting_balance_at_timestamp;
+use crate::state::{
+    read_active_gauge_weights, read_gauges, Config, Gauge, State, Vote, CONFIG, GAUGES,
+    GAUGE_WEIGHTS, STATE, VOTES,
+};
+
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Uint128, WasmMsg,
+};
+
+use glow_protocol::distributor::ExecuteMsg as DistributorExecuteMsg;
+use glow_protocol::gauge::{
+    ConfigResponse, ExecuteMsg, GaugeInfo, GaugeWeight, GaugeWeightsResponse, GaugesResponse,
+    InstantiateMsg, MigrateMsg, QueryMsg, StateResponse, VoterResponse,
+};
+
+pub const SECONDS_PER_WEEK: u64 = 7 * 24 * 60 * 60;
+
+/// Start timestamp of the epoch containing timestamp
+pub fn epoch_at(timestamp: u64) -> u64 {
+    timestamp / SECONDS_PER_WEEK * SECONDS_PER_WEEK
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let config = Config {
+        owner: info.sender,
+        ve_token: Addr::unchecked(""),
+        distributor: Addr::unchecked(""),
+        weekly_emission: msg.weekly_emission,
+    };
+
+    let state = State {
+        last_distributed_epoch: 0,
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::RegisterContracts {
+            ve_token,
+            distributor,
+        } => register_contracts(deps, ve_token, distributor),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            weekly_emission,
+        } => update_config(deps, info, owner, weekly_emission),
+        ExecuteMsg::AddGauge { address, label } => add_gauge(deps, info, address, label),
+        ExecuteMsg::RemoveGauge { address } => remove_gauge(deps, info, address),
+        ExecuteMsg::Vote { allocations } => vote(deps, env, info, allocations),
+        ExecuteMsg::Distribute {} => distribute(deps, env),
+    }
+}
+
+/// Register the addresses of the ve_token and distributor contracts
+pub fn register_contracts(
+    deps: DepsMut,
+    ve_token: String,
+    distributor: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if config.ve_token != Addr::unchecked("") {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.ve_token = deps.api.addr_validate(&ve_token)?;
+    config.distributor = deps.api.addr_validate(&distributor)?;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::default())
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    weekly_emission: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let api = deps.api;
+    CONFIG.update(deps.storage, |mut config| {
+        if config.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if let Some(owner) = owner {
+            config.owner = api.addr_validate(&owner)?;
+        }
+
+        if let Some(weekly_emission) = weekly_emission {
+            config.weekly_emission = weekly_emission;
+        }
+
+        Ok(config)
+    })?;
+
+    Ok(Response::new().add_attributes(vec![("action", "update_config")]))
+}
+
+/// Register a gauge, or reactivate a removed one
+pub fn add_gauge(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    label: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let gauge = deps.api.addr_validate(&address)?;
+    GAUGES.save(
+        deps.storage,
+        &gauge,
+        &Gauge {
+            label: label.clone(),
+            active: true,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "add_gauge"),
+        attr("gauge", gauge.to_string()),
+        attr("label", label),
+    ]))
+}
+
+/// Deactivate a gauge. Its weights are kept, but it no longer
+/// takes part in votes or distributions.
+pub fn remove_gauge(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let gauge_addr = deps.api.addr_validate(&address)?;
+    let mut gauge = GAUGES
+        .may_load(deps.storage, &gauge_addr)?
+        .ok_or(ContractError::GaugeNotFound {})?;
+    gauge.active = false;
+    GAUGES.save(deps.storage, &gauge_addr, &gauge)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_gauge"),
+        attr("gauge", gauge_addr.to_string()),
+    ]))
+}
+
+/// Allocate the voting power of the sender for the next epoch.
+/// Voting power is the veGLOW balance at the start of that epoch, so it decays
+/// along the lock curve and votes have to be cast again every epoch.
+/// Voting again in the same epoch replaces the previous vote.
+pub fn vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    allocations: Vec<(String, Decimal)>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let epoch = epoch_at(env.block.time.seconds()) + SECONDS_PER_WEEK;
+
+    // Validate the allocations
+    let mut total_share = Decimal::zero();
+    let mut gauge_allocations: Vec<(Addr, Decimal)> = vec![];
+    for (address, share) in allocations {
+        let gauge_addr = deps.api.addr_validate(&address)?;
+        if share.is_zero() || gauge_allocations.iter().any(|(g, _)| *g == gauge_addr) {
+            return Err(ContractError::InvalidAllocations {});
+        }
+
+        let gauge = GAUGES
+            .may_load(deps.storage, &gauge_addr)?
+            .ok_or(ContractError::GaugeNotFound {})?;
+        if !gauge.active {
+            return Err(ContractError::GaugeInactive {});
+        }
+
+        total_share = total_share + share;
+        gauge_allocations.push((gauge_addr, share));
+    }
+
+    if total_share > Decimal::one() {
+        return Err(ContractError::InvalidAllocations {});
+    }
+
+    let voting_power = query_address_voting_balance_at_timestamp(
+        &deps.querier,
+        &config.ve_token,
+        Some(epoch),
+        &info.sender,
+    )?;
+
+    if voting_power.is_zero() {
+        return Err(ContractError::NothingStaked {});
+    }
+
+    // Take back the weights of a previous vote in this epoch
+    if let Some(previous_vote) =
+        VOTES.may_load(deps.storage, (&info.sender, U64Key::from(epoch)))?
+    {
+        for (gauge, weight) in previous_vote.weights() {
+            GAUGE_WEIGHTS.update(
+                deps.storage,
+                (U64Key::from(epoch), gauge),
+                |current| -> Result<Uint128, ContractError> {
+                    Ok(current.unwrap_or_default().checked_sub(weight)?)
+                },
+            )?;
+        }
+    }
+
+    let vote = Vote {
+        voting_power,
+        allocations: gauge_allocations,
+    };
+
+    for (gauge, weight) in vote.weights() {
+        GAUGE_WEIGHTS.update(
+            deps.storage,
+            (U64Key::from(epoch), gauge),
+            |current| -> Result<Uint128, ContractError> {
+                Ok(current.unwrap_or_default().checked_add(weight)?)
+            },
+        )?;
+    }
+
+    VOTES.save(deps.storage, (&info.sender, U64Key::from(epoch)), &vote)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "vote"),
+        attr("voter", info.sender.to_string()),
+        attr("epoch", epoch.to_string()),
+        attr("voting_power", voting_power.to_string()),
+    ]))
+}
+
+/// Split the weekly emission across the active gauges by their weights for the
+/// current epoch, spending it from the distributor straight to each gauge.
+/// Epochs that are not distributed during their week are skipped.
+pub fn distribute(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    let epoch = epoch_at(env.block.time.seconds());
+    if state.last_distributed_epoch >= epoch {
+        return Err(ContractError::AlreadyDistributed {});
+    }
+
+    let weights = read_active_gauge_weights(deps.storage, epoch)?;
+    let total_weight = weights
+        .iter()
+        .fold(Uint128::zero(), |sum, (_, weight)| sum + *weight);
+
+    if total_weight.is_zero() || config.weekly_emission.is_zero() {
+        return Err(ContractError::NothingToDistribute {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut distributed_amount = Uint128::zero();
+    for (gauge, weight) in weights {
+        let amount = config.weekly_emission.multiply_ratio(weight, total_weight);
+        if amount.is_zero() {
+            continue;
+        }
+
+        distributed_amount += amount;
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.distributor.to_string(),
+            funds: vec![],
+            msg: to_binary(&DistributorExecuteMsg::Spend {
+                recipient: gauge.to_string(),
+                amount,
+            })?,
+        }));
+    }
+
+    state.last_distributed_epoch = epoch;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "distribute"),
+        attr("epoch", epoch.to_string()),
+        attr("distributed_amount", distributed_amount.to_string()),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::State {} => Ok(to_binary(&query_state(deps, env)?)?),
+        QueryMsg::Gauges { start_after, limit } => {
+            Ok(to_binary(&query_gauges(deps, start_after, limit)?)?)
+        }
+        QueryMsg::GaugeWeights { epoch } => Ok(to_binary(&query_gauge_weights(deps, env, epoch)?)?),
+        QueryMsg::Voter { address, epoch } => {
+            Ok(to_binary(&query_voter(deps, env, address, epoch)?)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: config.owner.to_string(),
+        ve_token: config.ve_token.to_string(),
+        distributor: config.distributor.to_string(),
+        weekly_emission: config.weekly_emission,
+    })
+}
+
+fn query_state(deps: Deps, env: Env) -> Result<StateResponse, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    Ok(StateResponse {
+        current_epoch: epoch_at(env.block.time.seconds()),
+        last_distributed_epoch: state.last_distributed_epoch,
+    })
+}
+
+fn query_gauges(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<GaugesResponse, ContractError> {
+    let start_after = start_after
+        .map(|a| deps.api.addr_validate(&a))
+        .transpose()?;
+
+    let gauges = read_gauges(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(address, gauge)| GaugeInfo {
+            address: address.to_string(),
+            label: gauge.label,
+            active: gauge.active,
+        })
+        .collect();
+
+    Ok(GaugesResponse { gauges })
+}
+
+fn query_gauge_weights(
+    deps: Deps,
+    env: Env,
+    epoch: Option<u64>,
+) -> Result<GaugeWeightsResponse, ContractError> {
+    let epoch = epoch_at(epoch.unwrap_or_else(|| env.block.time.seconds() + SECONDS_PER_WEEK));
+
+    let weights = read_active_gauge_weights(deps.storage, epoch)?;
+    let total_weight = weights
+        .iter()
+        .fold(Uint128::zero(), |sum, (_, weight)| sum + *weight);
+
+    Ok(GaugeWeightsResponse {
+        epoch,
+        total_weight,
+        weights: weights
+            .into_iter()
+            .map(|(address, weight)| GaugeWeight {
+                address: address.to_string(),
+                weight,
+                share: Decimal::from_ratio(weight, total_weight),
+            })
+            .collect(),
+    })
+}
+
+fn query_voter(
+    deps: Deps,
+    env: Env,
+    address: String,
+    epoch: Option<u64>,
+) -> Result<VoterResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let voter = deps.api.addr_validate(&address)?;
+    let epoch = epoch_at(epoch.unwrap_or_else(|| env.block.time.seconds() + SECONDS_PER_WEEK));
+
+    // Without a vote, report the voting power the voter would have
+    let (voting_power, allocations) =
+        match VOTES.may_load(deps.storage, (&voter, U64Key::from(epoch)))? {
+            Some(vote) => (vote.voting_power, vote.allocations),
+            None => (
+                query_address_voting_balance_at_timestamp(
+                    &deps.querier,
+                    &config.ve_token,
+                    Some(epoch),
+                    &voter,
+                )?,
+                vec![],
+            ),
+        };
+
+    Ok(VoterResponse {
+        address: voter.to_string(),
+        epoch,
+        voting_power,
+        allocations: allocations
+            .into_iter()
+            .map(|(gauge, share)| (gauge.to_string(), share))
+            .collect(),
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    Ok(Response::default())
+}
@@ -0,0 +1,32 @@
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Nothing staked")]
+    NothingStaked {},
+
+    #[error("Nothing to distribute")]
+    NothingToDistribute {},
+
+    #[error("Emissions have already been distributed for this epoch")]
+    AlreadyDistributed {},
+
+    #[error("Gauge not found")]
+    GaugeNotFound {},
+
+    #[error("Gauge is not active")]
+    GaugeInactive {},
+
+    #[error("Allocations must be positive, unique and add up to at most one")]
+    InvalidAllocations {},
+}
@@ -0,0 +1,11 @@
+pub mod contract;
+
+mod error;
+mod querier;
+mod state;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+mod mock_querier;
@@ -0,0 +1,96 @@
+use cosmwasm_std::{Addr, Decimal, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map, U64Key};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const STATE: Item<State> = Item::new("state");
+
+pub const GAUGES: Map<&Addr, Gauge> = Map::new("gauges");
+
+/// Weight of each gauge for an epoch, keyed by the epoch start timestamp
+pub const GAUGE_WEIGHTS: Map<(U64Key, &Addr), Uint128> = Map::new("gauge_weights");
+
+/// Vote of each voter for an epoch, keyed by the epoch start timestamp
+pub const VOTES: Map<(&Addr, U64Key), Vote> = Map::new("votes");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: Addr,
+    pub ve_token: Addr,
+    pub distributor: Addr,
+    pub weekly_emission: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub last_distributed_epoch: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Gauge {
+    pub label: String,
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Vote {
+    /// veGLOW balance of the voter at the start of the epoch
+    pub voting_power: Uint128,
+    pub allocations: Vec<(Addr, Decimal)>,
+}
+
+impl Vote {
+    /// Weight the vote adds to each of its gauges
+    pub fn weights(&self) -> Vec<(&Addr, Uint128)> {
+        self.allocations
+            .iter()
+            .map(|(gauge, share)| (gauge, self.voting_power * *share))
+            .collect()
+    }
+}
+
+pub fn read_gauges(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, Gauge)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|a| Bound::Exclusive(a.as_bytes().to_vec()));
+
+    GAUGES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((Addr::unchecked(String::from_utf8(k).unwrap()), v))
+        })
+        .collect()
+}
+
+/// Weights of the active gauges for an epoch
+pub fn read_active_gauge_weights(
+    storage: &dyn Storage,
+    epoch: u64,
+) -> StdResult<Vec<(Addr, Uint128)>> {
+    let weights = GAUGE_WEIGHTS
+        .prefix(U64Key::from(epoch))
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((Addr::unchecked(String::from_utf8(k).unwrap()), v))
+        })
+        .collect::<StdResult<Vec<(Addr, Uint128)>>>()?;
+
+    let mut active_weights = vec![];
+    for (gauge, weight) in weights {
+        if GAUGES.load(storage, &gauge)?.active && !weight.is_zero() {
+            active_weights.push((gauge, weight));
+        }
+    }
+
+    Ok(active_weights)
+}
@@ -0,0 +1,431 @@
+use crate::contract::{epoch_at, execute, instantiate, query, SECONDS_PER_WEEK};
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::state::{Config, State, CONFIG, STATE};
+
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, CosmosMsg, Decimal, DepsMut, Env, SubMsg, Timestamp, Uint128,
+    WasmMsg,
+};
+use glow_protocol::distributor::ExecuteMsg as DistributorExecuteMsg;
+use glow_protocol::gauge::{
+    ExecuteMsg, GaugeInfo, GaugeWeight, GaugeWeightsResponse, GaugesResponse, InstantiateMsg,
+    QueryMsg, VoterResponse,
+};
+
+const VE_TOKEN: &str = "ve_token";
+const DISTRIBUTOR: &str = "distributor";
+const TEST_CREATOR: &str = "creator";
+const TEST_VOTER: &str = "voter1";
+const TEST_VOTER_2: &str = "voter2";
+const GAUGE_1: &str = "gauge1";
+const GAUGE_2: &str = "gauge2";
+const WEEKLY_EMISSION: u128 = 1_000_000u128;
+
+fn increase_env_time(env: &mut Env, increase_time: u64) {
+    env.block.time = Timestamp::from_seconds(env.block.time.seconds() + increase_time);
+}
+
+fn mock_instantiate(deps: DepsMut) {
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = instantiate(deps, mock_env(), info, instantiate_msg())
+        .expect("contract successfully executes instantiateMsg");
+}
+
+fn mock_register_contracts(deps: DepsMut) {
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::RegisterContracts {
+        ve_token: VE_TOKEN.to_string(),
+        distributor: DISTRIBUTOR.to_string(),
+    };
+    let _res = execute(deps, mock_env(), info, msg)
+        .expect("contract successfully executes RegisterContracts");
+}
+
+fn mock_add_gauge(deps: DepsMut, address: &str) {
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::AddGauge {
+        address: address.to_string(),
+        label: address.to_string(),
+    };
+    let _res =
+        execute(deps, mock_env(), info, msg).expect("contract successfully executes AddGauge");
+}
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        weekly_emission: Uint128::from(WEEKLY_EMISSION),
+    }
+}
+
+fn spend_msg(recipient: &str, amount: u128) -> SubMsg {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: DISTRIBUTOR.to_string(),
+        funds: vec![],
+        msg: to_binary(&DistributorExecuteMsg::Spend {
+            recipient: recipient.to_string(),
+            amount: Uint128::from(amount),
+        })
+        .unwrap(),
+    }))
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let msg = ExecuteMsg::RegisterContracts {
+        ve_token: VE_TOKEN.to_string(),
+        distributor: DISTRIBUTOR.to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        config,
+        Config {
+            owner: Addr::unchecked(TEST_CREATOR),
+            ve_token: Addr::unchecked(VE_TOKEN),
+            distributor: Addr::unchecked(DISTRIBUTOR),
+            weekly_emission: Uint128::from(WEEKLY_EMISSION),
+        }
+    );
+
+    let state = STATE.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state,
+        State {
+            last_distributed_epoch: 0,
+        }
+    );
+
+    // Contracts can only be registered once
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER"),
+    };
+}
+
+#[test]
+fn add_and_remove_gauges() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    // Only the owner can add gauges
+    let msg = ExecuteMsg::AddGauge {
+        address: GAUGE_1.to_string(),
+        label: "pool 1".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER"),
+    };
+
+    mock_add_gauge(deps.as_mut(), GAUGE_1);
+    mock_add_gauge(deps.as_mut(), GAUGE_2);
+
+    let msg = ExecuteMsg::RemoveGauge {
+        address: GAUGE_1.to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::RemoveGauge {
+        address: "unknown".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg);
+    match res {
+        Err(ContractError::GaugeNotFound {}) => {}
+        _ => panic!("DO NOT ENTER"),
+    };
+
+    let res: GaugesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Gauges {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.gauges,
+        vec![
+            GaugeInfo {
+                address: GAUGE_1.to_string(),
+                label: GAUGE_1.to_string(),
+                active: false,
+            },
+            GaugeInfo {
+                address: GAUGE_2.to_string(),
+                label: GAUGE_2.to_string(),
+                active: true,
+            },
+        ]
+    );
+
+    // Removed gauges can't receive votes
+    deps.querier.with_token_balances(&[(
+        &VE_TOKEN.to_string(),
+        &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+    )]);
+    let msg = ExecuteMsg::Vote {
+        allocations: vec![(GAUGE_1.to_string(), Decimal::one())],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::GaugeInactive {}) => {}
+        _ => panic!("DO NOT ENTER"),
+    };
+}
+
+#[test]
+fn vote_for_next_epoch() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    mock_add_gauge(deps.as_mut(), GAUGE_1);
+    mock_add_gauge(deps.as_mut(), GAUGE_2);
+
+    // Voting without veGLOW fails
+    let msg = ExecuteMsg::Vote {
+        allocations: vec![(GAUGE_1.to_string(), Decimal::one())],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::NothingStaked {}) => {}
+        _ => panic!("DO NOT ENTER"),
+    };
+
+    deps.querier.with_token_balances(&[(
+        &VE_TOKEN.to_string(),
+        &[
+            (&TEST_VOTER.to_string(), &Uint128::from(100u128)),
+            (&TEST_VOTER_2.to_string(), &Uint128::from(50u128)),
+        ],
+    )]);
+
+    // Shares must be positive, unique, add up to at most one and target known gauges
+    for allocations in [
+        vec![(GAUGE_1.to_string(), Decimal::zero())],
+        vec![
+            (GAUGE_1.to_string(), Decimal::percent(50)),
+            (GAUGE_1.to_string(), Decimal::percent(50)),
+        ],
+        vec![
+            (GAUGE_1.to_string(), Decimal::percent(60)),
+            (GAUGE_2.to_string(), Decimal::percent(50)),
+        ],
+    ] {
+        let msg = ExecuteMsg::Vote { allocations };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg);
+        match res {
+            Err(ContractError::InvalidAllocations {}) => {}
+            _ => panic!("DO NOT ENTER"),
+        };
+    }
+
+    let msg = ExecuteMsg::Vote {
+        allocations: vec![("unknown".to_string(), Decimal::one())],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::GaugeNotFound {}) => {}
+        _ => panic!("DO NOT ENTER"),
+    };
+
+    let msg = ExecuteMsg::Vote {
+        allocations: vec![(GAUGE_1.to_string(), Decimal::one())],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::Vote {
+        allocations: vec![
+            (GAUGE_1.to_string(), Decimal::percent(20)),
+            (GAUGE_2.to_string(), Decimal::percent(80)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+
+    // Voting again replaces the previous vote
+    let msg = ExecuteMsg::Vote {
+        allocations: vec![
+            (GAUGE_1.to_string(), Decimal::percent(50)),
+            (GAUGE_2.to_string(), Decimal::percent(50)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    let next_epoch = epoch_at(mock_env().block.time.seconds()) + SECONDS_PER_WEEK;
+    let res: GaugeWeightsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GaugeWeights { epoch: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        GaugeWeightsResponse {
+            epoch: next_epoch,
+            total_weight: Uint128::from(150u128),
+            weights: vec![
+                GaugeWeight {
+                    address: GAUGE_1.to_string(),
+                    weight: Uint128::from(60u128),
+                    share: Decimal::percent(40),
+                },
+                GaugeWeight {
+                    address: GAUGE_2.to_string(),
+                    weight: Uint128::from(90u128),
+                    share: Decimal::percent(60),
+                },
+            ],
+        }
+    );
+
+    let res: VoterResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Voter {
+                address: TEST_VOTER.to_string(),
+                epoch: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        VoterResponse {
+            address: TEST_VOTER.to_string(),
+            epoch: next_epoch,
+            voting_power: Uint128::from(100u128),
+            allocations: vec![
+                (GAUGE_1.to_string(), Decimal::percent(50)),
+                (GAUGE_2.to_string(), Decimal::percent(50)),
+            ],
+        }
+    );
+
+    // Votes don't carry over to the following epoch
+    let mut env = mock_env();
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+    let res: GaugeWeightsResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::GaugeWeights { epoch: None }).unwrap())
+            .unwrap();
+    assert_eq!(res.total_weight, Uint128::zero());
+    assert_eq!(res.weights, vec![]);
+}
+
+#[test]
+fn distribute_emissions() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    mock_add_gauge(deps.as_mut(), GAUGE_1);
+    mock_add_gauge(deps.as_mut(), GAUGE_2);
+
+    deps.querier.with_token_balances(&[(
+        &VE_TOKEN.to_string(),
+        &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+    )]);
+
+    let msg = ExecuteMsg::Vote {
+        allocations: vec![
+            (GAUGE_1.to_string(), Decimal::percent(30)),
+            (GAUGE_2.to_string(), Decimal::percent(70)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    // Nothing has been voted for the current epoch
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::Distribute {},
+    );
+    match res {
+        Err(ContractError::NothingToDistribute {}) => {}
+        _ => panic!("DO NOT ENTER"),
+    };
+
+    let mut env = mock_env();
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            spend_msg(GAUGE_1, WEEKLY_EMISSION * 3 / 10),
+            spend_msg(GAUGE_2, WEEKLY_EMISSION * 7 / 10),
+        ]
+    );
+
+    let state = STATE.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.last_distributed_epoch,
+        epoch_at(env.block.time.seconds())
+    );
+
+    // Emissions are distributed once per epoch
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::Distribute {},
+    );
+    match res {
+        Err(ContractError::AlreadyDistributed {}) => {}
+        _ => panic!("DO NOT ENTER"),
+    };
+
+    // Removed gauges are left out and the emission goes to the remaining ones
+    let msg = ExecuteMsg::Vote {
+        allocations: vec![
+            (GAUGE_1.to_string(), Decimal::percent(30)),
+            (GAUGE_2.to_string(), Decimal::percent(70)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::RemoveGauge {
+        address: GAUGE_1.to_string(),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![spend_msg(GAUGE_2, WEEKLY_EMISSION)]);
+}
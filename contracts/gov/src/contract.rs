@@ -7,34 +7,52 @@ use crate::querier::{
 };
 use crate::staking::{query_staker, stake_voting_tokens, withdraw_voting_tokens};
 use crate::state::{
-    config_read, config_store, old_config_read, poll_indexer_store, poll_read, poll_store,
-    poll_voter_read, poll_voter_store, read_poll_voters, read_polls, state_read, state_store,
-    Config, ExecuteData, Poll, State,
+    config_read, config_store, contract_category_read, contract_category_store, delegation_read,
+    delegation_store, delegator_store, old_config_read, old_poll_read, poll_category_read,
+    poll_category_store, poll_delegated_vote_read, poll_delegated_vote_store, poll_indexer_store,
+    poll_read, poll_store, poll_voter_read, poll_voter_store, read_delegators, read_poll_voters,
+    read_polls, state_read, state_store, Config, DelegatedVote, ExecuteData, OldPoll, Poll,
+    PollOption, State,
 };
 
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Binary, CanonicalAddr, Coin, CosmosMsg, Decimal, Deps, DepsMut,
-    Env, MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, ContractResult,
+    CosmosMsg, Decimal, Deps, DepsMut, Env, Event, MessageInfo, Order, Reply, Response, StdError,
+    StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
+use cw0::{Duration, Expiration};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
 use glow_protocol::common::OrderBy;
 use glow_protocol::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PollExecuteMsg,
-    PollResponse, PollStatus, PollsResponse, QueryMsg, StateResponse, VoteOption, VoterInfo,
-    VotersResponse, VotersResponseItem,
+    ConfigResponse, ContractCategoryResponse, Cw20HookMsg, DelegationResponse, DelegatorsResponse,
+    ExecuteMsg, ExecutionResult, InstantiateMsg, MigrateMsg, PollCategory, PollCategoryConfig,
+    PollCategoryResponse, PollExecuteMsg, PollOptionMsg, PollOptionResponse, PollResponse,
+    PollStatus, PollsResponse, QueryMsg, StateResponse, VoteOption, VoterInfo, VotersResponse,
+    VotersResponseItem,
 };
 
 use terraswap::asset::{Asset, AssetInfo, PairInfo};
 use terraswap::pair::ExecuteMsg as TerraswapExecuteMsg;
 use terraswap::querier::{query_balance, query_pair_info};
 
+use std::ops::Add;
+
 const MIN_TITLE_LENGTH: usize = 4;
 const MAX_TITLE_LENGTH: usize = 64;
 const MIN_DESC_LENGTH: usize = 4;
 const MAX_DESC_LENGTH: usize = 1024;
 const MIN_LINK_LENGTH: usize = 12;
 const MAX_LINK_LENGTH: usize = 128;
+// Delegators whose voting power is counted when their delegate votes.
+// The voting power of the rest is counted by TallyDelegations
+const VOTE_DELEGATORS_LIMIT: u32 = 30;
+const MIN_OPTION_LABEL_LENGTH: usize = 1;
+const MAX_OPTION_LABEL_LENGTH: usize = 64;
+const MAX_POLL_OPTIONS: usize = 16;
+// Reply ids carry the poll id above the message index
+const POLL_MSG_INDEX_BITS: u32 = 8;
+const MAX_POLL_MSGS: usize = 1 << POLL_MSG_INDEX_BITS;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -45,6 +63,11 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     validate_quorum(msg.quorum)?;
     validate_threshold(msg.threshold)?;
+    validate_periods(
+        &msg.voting_period,
+        &msg.timelock_period,
+        &msg.expiration_period,
+    )?;
 
     let config = Config {
         glow_token: CanonicalAddr::from(vec![]),
@@ -58,6 +81,10 @@ pub fn instantiate(
         expiration_period: msg.expiration_period,
         proposal_deposit: msg.proposal_deposit,
         snapshot_period: msg.snapshot_period,
+        guardian: msg
+            .guardian
+            .map(|guardian| deps.api.addr_canonicalize(&guardian))
+            .transpose()?,
     };
 
     let state = State {
@@ -86,8 +113,15 @@ pub fn execute(
             glow_token,
             ve_token,
             terraswap_factory,
-        } => register_contracts(deps, glow_token, ve_token, terraswap_factory),
-        ExecuteMsg::Sweep { denom } => sweep(deps, env, denom),
+            community_contract,
+        } => register_contracts(
+            deps,
+            glow_token,
+            ve_token,
+            terraswap_factory,
+            community_contract,
+        ),
+        ExecuteMsg::Sweep { denom } => sweep(deps, env, info, denom),
         ExecuteMsg::UpdateConfig {
             owner,
             quorum,
@@ -109,11 +143,31 @@ pub fn execute(
             proposal_deposit,
             snapshot_period,
         ),
+        ExecuteMsg::UpdateGuardian { guardian } => update_guardian(deps, info, guardian),
+        ExecuteMsg::UpdatePollCategory { category, config } => {
+            update_poll_category(deps, info, category, config)
+        }
+        ExecuteMsg::UpdateContractCategory { contract, category } => {
+            update_contract_category(deps, info, contract, category)
+        }
         ExecuteMsg::WithdrawVotingTokens { amount } => withdraw_voting_tokens(deps, info, amount),
         ExecuteMsg::CastVote { poll_id, vote } => cast_vote(deps, env, info, poll_id, vote),
+        ExecuteMsg::CastWeightedVote { poll_id, weights } => {
+            cast_weighted_vote(deps, env, info, poll_id, weights)
+        }
+        ExecuteMsg::Delegate { to } => delegate(deps, info, to),
+        ExecuteMsg::Undelegate {} => undelegate(deps, info),
+        ExecuteMsg::TallyDelegations {
+            poll_id,
+            delegate,
+            start_after,
+            limit,
+        } => tally_delegations(deps, env, poll_id, delegate, start_after, limit),
         ExecuteMsg::EndPoll { poll_id } => end_poll(deps, env, poll_id),
         ExecuteMsg::ExecutePoll { poll_id } => execute_poll(deps, env, poll_id),
         ExecuteMsg::ExpirePoll { poll_id } => expire_poll(deps, env, poll_id),
+        ExecuteMsg::VetoPoll { poll_id } => veto_poll(deps, env, info, poll_id),
+        ExecuteMsg::CancelPoll { poll_id } => cancel_poll(deps, env, info, poll_id),
     }
 }
 
@@ -139,6 +193,7 @@ pub fn receive_cw20(
             description,
             link,
             execute_msgs,
+            category,
         }) => create_poll(
             deps,
             env,
@@ -148,6 +203,26 @@ pub fn receive_cw20(
             description,
             link,
             execute_msgs,
+            None,
+            category,
+        ),
+        Ok(Cw20HookMsg::CreateMultipleChoicePoll {
+            title,
+            description,
+            link,
+            options,
+            category,
+        }) => create_poll(
+            deps,
+            env,
+            cw20_msg.sender,
+            cw20_msg.amount,
+            title,
+            description,
+            link,
+            None,
+            Some(options),
+            category,
         ),
         _ => Err(ContractError::DataShouldBeGiven {}),
     }
@@ -158,6 +233,7 @@ pub fn register_contracts(
     glow_token: String,
     ve_token: String,
     terraswap_factory: String,
+    community_contract: String,
 ) -> Result<Response, ContractError> {
     let mut config: Config = config_read(deps.storage).load()?;
     if config.glow_token != CanonicalAddr::from(vec![]) {
@@ -169,14 +245,28 @@ pub fn register_contracts(
     config.terraswap_factory = deps.api.addr_canonicalize(&terraswap_factory)?;
     config_store(deps.storage).save(&config)?;
 
+    // Spending from the community contract is a treasury decision
+    let community_contract_raw = deps.api.addr_canonicalize(&community_contract)?;
+    contract_category_store(deps.storage)
+        .save(community_contract_raw.as_slice(), &PollCategory::Treasury)?;
+
     Ok(Response::default())
 }
 
 /// Sweep
-/// Anyone can execute sweep function to swap
-/// asset native denom => GLOW token
-pub fn sweep(deps: DepsMut, env: Env, denom: String) -> Result<Response, ContractError> {
+/// Only the owner can execute sweep function to swap
+/// asset native denom => GLOW token, since poll messages may send native funds
+pub fn sweep(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
     let config: Config = config_read(deps.storage).load()?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
     let glow_token = deps.api.addr_humanize(&config.glow_token)?;
     let terraswap_factory_addr = deps.api.addr_humanize(&config.terraswap_factory)?;
 
@@ -236,9 +326,9 @@ pub fn update_config(
     owner: Option<String>,
     quorum: Option<Decimal>,
     threshold: Option<Decimal>,
-    voting_period: Option<u64>,
-    timelock_period: Option<u64>,
-    expiration_period: Option<u64>,
+    voting_period: Option<Duration>,
+    timelock_period: Option<Duration>,
+    expiration_period: Option<Duration>,
     proposal_deposit: Option<Uint128>,
     snapshot_period: Option<u64>,
 ) -> Result<Response, ContractError> {
@@ -280,12 +370,167 @@ pub fn update_config(
             config.snapshot_period = period;
         }
 
+        validate_periods(
+            &config.voting_period,
+            &config.timelock_period,
+            &config.expiration_period,
+        )?;
+
         Ok(config)
     })?;
 
+    // The periods of the categories must stay in the unit of the expiration period
+    let config: Config = config_read(deps.storage).load()?;
+    for item in poll_category_read(deps.storage).range(None, None, Order::Ascending) {
+        let (_, category_config) = item?;
+        validate_periods(
+            &category_config.voting_period,
+            &category_config.timelock_period,
+            &config.expiration_period,
+        )?;
+    }
+
     Ok(Response::new().add_attributes(vec![("action", "update_config")]))
 }
 
+pub fn update_guardian(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = config_read(deps.storage).load()?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.guardian = guardian
+        .as_ref()
+        .map(|guardian| deps.api.addr_canonicalize(guardian))
+        .transpose()?;
+    config_store(deps.storage).save(&config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_guardian"),
+        ("guardian", guardian.as_deref().unwrap_or("")),
+    ]))
+}
+
+pub fn update_poll_category(
+    deps: DepsMut,
+    info: MessageInfo,
+    category: PollCategory,
+    category_config: Option<PollCategoryConfig>,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let key = category.to_string();
+    if let Some(category_config) = category_config {
+        validate_quorum(category_config.quorum)?;
+        validate_threshold(category_config.threshold)?;
+        validate_periods(
+            &category_config.voting_period,
+            &category_config.timelock_period,
+            &config.expiration_period,
+        )?;
+        poll_category_store(deps.storage).save(key.as_bytes(), &category_config)?;
+    } else {
+        poll_category_store(deps.storage).remove(key.as_bytes());
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_poll_category"),
+        ("category", key.as_str()),
+    ]))
+}
+
+pub fn update_contract_category(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    category: Option<PollCategory>,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let contract_raw = deps.api.addr_canonicalize(&contract)?;
+    if let Some(category) = category {
+        contract_category_store(deps.storage).save(contract_raw.as_slice(), &category)?;
+    } else {
+        contract_category_store(deps.storage).remove(contract_raw.as_slice());
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_contract_category"),
+        ("contract", contract.as_str()),
+        (
+            "category",
+            category
+                .map(|category| category.to_string())
+                .unwrap_or_default()
+                .as_str(),
+        ),
+    ]))
+}
+
+/// Parameters of the polls of a category, which default to the config
+fn load_poll_category_config(
+    storage: &dyn Storage,
+    config: &Config,
+    category: PollCategory,
+) -> StdResult<PollCategoryConfig> {
+    Ok(poll_category_read(storage)
+        .may_load(category.to_string().as_bytes())?
+        .unwrap_or(PollCategoryConfig {
+            quorum: config.quorum,
+            threshold: config.threshold,
+            proposal_deposit: config.proposal_deposit,
+            voting_period: config.voting_period,
+            timelock_period: config.timelock_period,
+        }))
+}
+
+/// Infers the least risky category covering all the messages of a poll.
+/// Messages to contracts without category are parameter changes, unless they send funds.
+/// Messages to gov itself can rewrite the category rules, so they are code upgrades
+fn infer_poll_category(
+    deps: Deps,
+    gov_addr: &CanonicalAddr,
+    execute_data: &[ExecuteData],
+) -> StdResult<PollCategory> {
+    let mut category = PollCategory::Text;
+    for data in execute_data {
+        let msg_category = match data {
+            ExecuteData::WasmExecute { contract, .. } if contract == gov_addr => {
+                PollCategory::CodeUpgrade
+            }
+            ExecuteData::WasmExecute {
+                contract, funds, ..
+            } => {
+                let contract_category = contract_category_read(deps.storage)
+                    .may_load(contract.as_slice())?
+                    .unwrap_or(PollCategory::Parameter);
+                if funds.is_empty() {
+                    contract_category
+                } else {
+                    contract_category.max(PollCategory::Treasury)
+                }
+            }
+            ExecuteData::BankSend { .. } => PollCategory::Treasury,
+            ExecuteData::WasmMigrate { .. }
+            | ExecuteData::UpdateAdmin { .. }
+            | ExecuteData::ClearAdmin { .. } => PollCategory::CodeUpgrade,
+        };
+        category = category.max(msg_category);
+    }
+
+    Ok(category)
+}
+
 /// validate_title returns an error if the title is invalid
 fn validate_title(title: &str) -> StdResult<()> {
     if title.len() < MIN_TITLE_LENGTH {
@@ -323,6 +568,263 @@ fn validate_link(link: &Option<String>) -> StdResult<()> {
     }
 }
 
+fn validate_poll_options(options: &[PollOptionMsg]) -> Result<(), ContractError> {
+    if options.len() < 2
+        || options.len() > MAX_POLL_OPTIONS
+        || options.iter().any(|option| {
+            option.label.len() < MIN_OPTION_LABEL_LENGTH
+                || option.label.len() > MAX_OPTION_LABEL_LENGTH
+        })
+    {
+        return Err(ContractError::InvalidPollOptions(MAX_POLL_OPTIONS));
+    }
+
+    Ok(())
+}
+
+/// validate_execute_msgs returns an error if there are too many messages,
+/// or if a message sends zero coins or migrates to no code
+fn validate_execute_msgs(execute_msgs: &[PollExecuteMsg]) -> Result<(), ContractError> {
+    if execute_msgs.len() > MAX_POLL_MSGS {
+        return Err(ContractError::InvalidPollExecuteMsg(format!(
+            "at most {} messages are allowed",
+            MAX_POLL_MSGS
+        )));
+    }
+
+    for execute_msg in execute_msgs {
+        match execute_msg {
+            PollExecuteMsg::WasmExecute { funds, .. } => {
+                if funds.iter().any(|coin| coin.amount.is_zero()) {
+                    return Err(ContractError::InvalidPollExecuteMsg(
+                        "funds must not contain zero amounts".to_string(),
+                    ));
+                }
+            }
+            PollExecuteMsg::WasmMigrate { new_code_id, .. } => {
+                if *new_code_id == 0 {
+                    return Err(ContractError::InvalidPollExecuteMsg(
+                        "new_code_id must be set".to_string(),
+                    ));
+                }
+            }
+            PollExecuteMsg::BankSend { amount, .. } => {
+                if amount.is_empty() || amount.iter().any(|coin| coin.amount.is_zero()) {
+                    return Err(ContractError::InvalidPollExecuteMsg(
+                        "amount must be non-zero".to_string(),
+                    ));
+                }
+            }
+            PollExecuteMsg::UpdateAdmin { .. } | PollExecuteMsg::ClearAdmin { .. } => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn to_execute_data(
+    deps: Deps,
+    execute_msgs: Option<Vec<PollExecuteMsg>>,
+) -> StdResult<Option<Vec<ExecuteData>>> {
+    execute_msgs
+        .map(|execute_msgs| {
+            execute_msgs
+                .into_iter()
+                .map(|msg| {
+                    Ok(match msg {
+                        PollExecuteMsg::WasmExecute {
+                            order,
+                            contract,
+                            msg,
+                            funds,
+                        } => ExecuteData::WasmExecute {
+                            order,
+                            contract: deps.api.addr_canonicalize(&contract)?,
+                            msg,
+                            funds,
+                        },
+                        PollExecuteMsg::WasmMigrate {
+                            order,
+                            contract,
+                            new_code_id,
+                            msg,
+                        } => ExecuteData::WasmMigrate {
+                            order,
+                            contract: deps.api.addr_canonicalize(&contract)?,
+                            new_code_id,
+                            msg,
+                        },
+                        PollExecuteMsg::UpdateAdmin {
+                            order,
+                            contract,
+                            admin,
+                        } => ExecuteData::UpdateAdmin {
+                            order,
+                            contract: deps.api.addr_canonicalize(&contract)?,
+                            admin: deps.api.addr_canonicalize(&admin)?,
+                        },
+                        PollExecuteMsg::ClearAdmin { order, contract } => ExecuteData::ClearAdmin {
+                            order,
+                            contract: deps.api.addr_canonicalize(&contract)?,
+                        },
+                        PollExecuteMsg::BankSend {
+                            order,
+                            to_address,
+                            amount,
+                        } => ExecuteData::BankSend {
+                            order,
+                            to_address: deps.api.addr_canonicalize(&to_address)?,
+                            amount,
+                        },
+                    })
+                })
+                .collect::<StdResult<Vec<ExecuteData>>>()
+        })
+        .transpose()
+}
+
+fn to_poll_execute_msgs(
+    deps: Deps,
+    execute_data: Option<Vec<ExecuteData>>,
+) -> StdResult<Option<Vec<PollExecuteMsg>>> {
+    execute_data
+        .map(|execute_data| {
+            execute_data
+                .into_iter()
+                .map(|data| {
+                    Ok(match data {
+                        ExecuteData::WasmExecute {
+                            order,
+                            contract,
+                            msg,
+                            funds,
+                        } => PollExecuteMsg::WasmExecute {
+                            order,
+                            contract: deps.api.addr_humanize(&contract)?.to_string(),
+                            msg,
+                            funds,
+                        },
+                        ExecuteData::WasmMigrate {
+                            order,
+                            contract,
+                            new_code_id,
+                            msg,
+                        } => PollExecuteMsg::WasmMigrate {
+                            order,
+                            contract: deps.api.addr_humanize(&contract)?.to_string(),
+                            new_code_id,
+                            msg,
+                        },
+                        ExecuteData::UpdateAdmin {
+                            order,
+                            contract,
+                            admin,
+                        } => PollExecuteMsg::UpdateAdmin {
+                            order,
+                            contract: deps.api.addr_humanize(&contract)?.to_string(),
+                            admin: deps.api.addr_humanize(&admin)?.to_string(),
+                        },
+                        ExecuteData::ClearAdmin { order, contract } => PollExecuteMsg::ClearAdmin {
+                            order,
+                            contract: deps.api.addr_humanize(&contract)?.to_string(),
+                        },
+                        ExecuteData::BankSend {
+                            order,
+                            to_address,
+                            amount,
+                        } => PollExecuteMsg::BankSend {
+                            order,
+                            to_address: deps.api.addr_humanize(&to_address)?.to_string(),
+                            amount,
+                        },
+                    })
+                })
+                .collect::<StdResult<Vec<PollExecuteMsg>>>()
+        })
+        .transpose()
+}
+
+fn to_cosmos_msg(deps: Deps, execute_data: ExecuteData) -> StdResult<CosmosMsg> {
+    Ok(match execute_data {
+        ExecuteData::WasmExecute {
+            contract,
+            msg,
+            funds,
+            ..
+        } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&contract)?.to_string(),
+            msg,
+            funds,
+        }),
+        ExecuteData::WasmMigrate {
+            contract,
+            new_code_id,
+            msg,
+            ..
+        } => CosmosMsg::Wasm(WasmMsg::Migrate {
+            contract_addr: deps.api.addr_humanize(&contract)?.to_string(),
+            new_code_id,
+            msg,
+        }),
+        ExecuteData::UpdateAdmin {
+            contract, admin, ..
+        } => CosmosMsg::Wasm(WasmMsg::UpdateAdmin {
+            contract_addr: deps.api.addr_humanize(&contract)?.to_string(),
+            admin: deps.api.addr_humanize(&admin)?.to_string(),
+        }),
+        ExecuteData::ClearAdmin { contract, .. } => CosmosMsg::Wasm(WasmMsg::ClearAdmin {
+            contract_addr: deps.api.addr_humanize(&contract)?.to_string(),
+        }),
+        ExecuteData::BankSend {
+            to_address, amount, ..
+        } => CosmosMsg::Bank(BankMsg::Send {
+            to_address: deps.api.addr_humanize(&to_address)?.to_string(),
+            amount,
+        }),
+    })
+}
+
+fn to_poll_options_response(
+    deps: Deps,
+    options: Option<Vec<PollOption>>,
+) -> StdResult<Option<Vec<PollOptionResponse>>> {
+    options
+        .map(|options| {
+            options
+                .into_iter()
+                .map(|option| {
+                    Ok(PollOptionResponse {
+                        label: option.label,
+                        execute_data: to_poll_execute_msgs(deps, option.execute_data)?,
+                        votes: option.votes,
+                    })
+                })
+                .collect::<StdResult<Vec<PollOptionResponse>>>()
+        })
+        .transpose()
+}
+
+/// Index of the option with the most votes, unless no option got votes or the top options are tied
+fn plurality_winner(options: &[PollOption]) -> Option<u8> {
+    let mut winner: Option<usize> = None;
+    let mut tied = false;
+    for (index, option) in options.iter().enumerate() {
+        match winner {
+            Some(winner_index) if option.votes == options[winner_index].votes => tied = true,
+            Some(winner_index) if option.votes < options[winner_index].votes => {}
+            _ => {
+                winner = Some(index);
+                tied = false;
+            }
+        }
+    }
+
+    match winner {
+        Some(index) if !tied && !options[index].votes.is_zero() => Some(index as u8),
+        _ => None,
+    }
+}
+
 /// validate_quorum returns an error if the quorum is invalid
 /// (we require 0-1)
 fn validate_quorum(quorum: Decimal) -> StdResult<()> {
@@ -343,6 +845,22 @@ fn validate_threshold(threshold: Decimal) -> StdResult<()> {
     }
 }
 
+/// validate_periods returns an error if the periods mix heights and times,
+/// as the timelock and expiration periods start at the end of the voting period
+fn validate_periods(
+    voting_period: &Duration,
+    timelock_period: &Duration,
+    expiration_period: &Duration,
+) -> StdResult<()> {
+    match (voting_period, timelock_period, expiration_period) {
+        (Duration::Height(_), Duration::Height(_), Duration::Height(_))
+        | (Duration::Time(_), Duration::Time(_), Duration::Time(_)) => Ok(()),
+        _ => Err(StdError::generic_err(
+            "periods must all be heights or all be times",
+        )),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 /// create a new poll
 pub fn create_poll(
@@ -354,15 +872,64 @@ pub fn create_poll(
     description: String,
     link: Option<String>,
     execute_msgs: Option<Vec<PollExecuteMsg>>,
+    options: Option<Vec<PollOptionMsg>>,
+    category: Option<PollCategory>,
 ) -> Result<Response, ContractError> {
     validate_title(&title)?;
     validate_description(&description)?;
     validate_link(&link)?;
+    if let Some(execute_msgs) = &execute_msgs {
+        validate_execute_msgs(execute_msgs)?;
+    }
+    if let Some(options) = &options {
+        validate_poll_options(options)?;
+        for option in options {
+            if let Some(execute_msgs) = &option.execute_msgs {
+                validate_execute_msgs(execute_msgs)?;
+            }
+        }
+    }
+
+    let all_execute_data = to_execute_data(deps.as_ref(), execute_msgs)?;
+    let options = options
+        .map(|options| {
+            options
+                .into_iter()
+                .map(|option| {
+                    Ok(PollOption {
+                        label: option.label,
+                        execute_data: to_execute_data(deps.as_ref(), option.execute_msgs)?,
+                        votes: Uint128::zero(),
+                    })
+                })
+                .collect::<StdResult<Vec<PollOption>>>()
+        })
+        .transpose()?;
+
+    // The poll may be declared in a riskier category than its messages, not a safer one
+    let gov_addr = deps.api.addr_canonicalize(env.contract.address.as_str())?;
+    let mut inferred_category = infer_poll_category(
+        deps.as_ref(),
+        &gov_addr,
+        all_execute_data.as_deref().unwrap_or_default(),
+    )?;
+    for option in options.iter().flatten() {
+        inferred_category = inferred_category.max(infer_poll_category(
+            deps.as_ref(),
+            &gov_addr,
+            option.execute_data.as_deref().unwrap_or_default(),
+        )?);
+    }
+    let category = category.unwrap_or(inferred_category);
+    if category < inferred_category {
+        return Err(ContractError::InvalidPollCategory(inferred_category));
+    }
 
     let config: Config = config_store(deps.storage).load()?;
-    if deposit_amount < config.proposal_deposit {
+    let category_config = load_poll_category_config(deps.storage, &config, category)?;
+    if deposit_amount < category_config.proposal_deposit {
         return Err(ContractError::InsufficientProposalDeposit(
-            config.proposal_deposit.u128(),
+            category_config.proposal_deposit.u128(),
         ));
     }
 
@@ -373,21 +940,6 @@ pub fn create_poll(
     state.poll_count += 1;
     state.total_deposit += deposit_amount;
 
-    let mut data_list: Vec<ExecuteData> = vec![];
-    let all_execute_data = if let Some(exe_msgs) = execute_msgs {
-        for msgs in exe_msgs {
-            let execute_data = ExecuteData {
-                order: msgs.order,
-                contract: deps.api.addr_canonicalize(&msgs.contract)?,
-                msg: msgs.msg,
-            };
-            data_list.push(execute_data)
-        }
-        Some(data_list)
-    } else {
-        None
-    };
-
     let staked_amount = query_total_voting_balance_at_timestamp(
         &deps.querier,
         &deps.api.addr_humanize(&config.ve_token)?,
@@ -399,10 +951,16 @@ pub fn create_poll(
         id: poll_id,
         creator: sender_address_raw,
         status: PollStatus::InProgress,
+        category,
         yes_votes: Uint128::zero(),
         no_votes: Uint128::zero(),
+        abstain_votes: Uint128::zero(),
         start_time: env.block.time.seconds(),
-        end_height: env.block.height + config.voting_period,
+        end: category_config.voting_period.after(&env.block),
+        quorum: category_config.quorum,
+        threshold: category_config.threshold,
+        timelock_period: category_config.timelock_period,
+        expiration_period: config.expiration_period,
         title,
         description,
         link,
@@ -410,6 +968,9 @@ pub fn create_poll(
         deposit_amount,
         total_balance_at_end_poll: None,
         staked_amount: Some(staked_amount),
+        options,
+        winning_option: None,
+        execution_results: None,
     };
 
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &new_poll)?;
@@ -418,6 +979,12 @@ pub fn create_poll(
 
     state_store(deps.storage).save(&state)?;
 
+    let end_attribute = match new_poll.end {
+        Expiration::AtTime(time) => ("end_time", time.seconds().to_string()),
+        Expiration::AtHeight(height) => ("end_height", height.to_string()),
+        Expiration::Never {} => ("end_height", "never".to_string()),
+    };
+
     Ok(Response::new().add_attributes(vec![
         ("action", "create_poll"),
         (
@@ -428,7 +995,7 @@ pub fn create_poll(
                 .as_str(),
         ),
         ("poll_id", &poll_id.to_string()),
-        ("end_height", new_poll.end_height.to_string().as_str()),
+        (end_attribute.0, end_attribute.1.as_str()),
     ]))
 }
 
@@ -442,14 +1009,22 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
         return Err(ContractError::PollNotInProgress {});
     }
 
-    if a_poll.end_height > env.block.height {
+    if !a_poll.end.is_expired(&env.block) {
         return Err(ContractError::PollVotingPeriod {});
     }
 
     let no = a_poll.no_votes.u128();
     let yes = a_poll.yes_votes.u128();
+    let abstain = a_poll.abstain_votes.u128();
+    let choices = a_poll.options.as_ref().map_or(0u128, |options| {
+        options
+            .iter()
+            .map(|option| option.votes.u128())
+            .sum::<u128>()
+    });
 
-    let tallied_weight = yes + no;
+    // Abstain votes count toward the quorum only
+    let tallied_weight = yes + no + abstain + choices;
 
     let mut poll_status = PollStatus::Rejected;
     let mut rejected_reason = "";
@@ -470,12 +1045,21 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
         )
     };
 
-    if tallied_weight == 0 || quorum < config.quorum {
+    if tallied_weight == 0 || quorum < a_poll.quorum {
         // Quorum: More than quorum of the total staked tokens at the end of the voting
         // period need to have participated in the vote.
         rejected_reason = "Quorum not reached";
     } else {
-        if Decimal::from_ratio(yes, tallied_weight) > config.threshold {
+        if let Some(options) = &a_poll.options {
+            // Plurality: the option with the most votes wins
+            a_poll.winning_option = plurality_winner(options);
+            if a_poll.winning_option.is_some() {
+                poll_status = PollStatus::Passed;
+                passed = true;
+            } else {
+                rejected_reason = "No winning option";
+            }
+        } else if yes + no != 0 && Decimal::from_ratio(yes, yes + no) > a_poll.threshold {
             //Threshold: More than 50% of the tokens that participated in the vote
             // (after excluding “Abstain” votes) need to have voted in favor of the proposal (“Yes”).
             poll_status = PollStatus::Passed;
@@ -510,11 +1094,108 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
     a_poll.total_balance_at_end_poll = Some(staked_weight);
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
+    let mut response = Response::new().add_messages(messages).add_attributes(vec![
         ("action", "end_poll"),
         ("poll_id", &poll_id.to_string()),
         ("rejected_reason", rejected_reason),
         ("passed", &passed.to_string()),
+    ]);
+    if let Some(winning_option) = a_poll.winning_option {
+        response = response.add_attribute("winning_option", winning_option.to_string());
+    }
+
+    Ok(response)
+}
+
+/// VetoPoll is used by the guardian to stop a passed poll before it can be executed.
+/// The deposit was already refunded when the poll ended, so it is left untouched
+pub fn veto_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if config.guardian != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.status != PollStatus::Passed {
+        return Err(ContractError::PollNotPassed {});
+    }
+
+    if a_poll
+        .end
+        .add(a_poll.timelock_period)?
+        .is_expired(&env.block)
+    {
+        return Err(ContractError::TimelockExpired {});
+    }
+
+    poll_indexer_store(deps.storage, &PollStatus::Passed).remove(&poll_id.to_be_bytes());
+    poll_indexer_store(deps.storage, &PollStatus::Vetoed).save(&poll_id.to_be_bytes(), &true)?;
+
+    a_poll.status = PollStatus::Vetoed;
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "veto_poll"),
+        ("poll_id", poll_id.to_string().as_str()),
+    ]))
+}
+
+/// CancelPoll is used by the creator to withdraw a poll nobody voted on yet,
+/// as long as its voting period is open.
+/// The whole deposit is refunded to the creator
+pub fn cancel_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.creator != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if a_poll.status != PollStatus::InProgress || a_poll.voting_closed(&env.block) {
+        return Err(ContractError::PollNotInProgress {});
+    }
+
+    // Voters without voting power don't tally anything, so they can't block a cancel
+    if a_poll.has_votes() {
+        return Err(ContractError::PollHasVotes {});
+    }
+
+    let config: Config = config_read(deps.storage).load()?;
+    let mut state: State = state_read(deps.storage).load()?;
+    state.total_deposit = state.total_deposit.checked_sub(a_poll.deposit_amount)?;
+    state_store(deps.storage).save(&state)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !a_poll.deposit_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.glow_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: a_poll.deposit_amount,
+            })?,
+        }))
+    }
+
+    poll_indexer_store(deps.storage, &PollStatus::InProgress).remove(&poll_id.to_be_bytes());
+    poll_indexer_store(deps.storage, &PollStatus::Cancelled).save(&poll_id.to_be_bytes(), &true)?;
+
+    a_poll.status = PollStatus::Cancelled;
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "cancel_poll"),
+        ("poll_id", poll_id.to_string().as_str()),
+        ("refund_amount", a_poll.deposit_amount.to_string().as_str()),
     ]))
 }
 
@@ -522,62 +1203,121 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
  * Execute a msg of passed poll.
  */
 pub fn execute_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, ContractError> {
-    let config: Config = config_read(deps.storage).load()?;
     let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
 
-    if a_poll.status != PollStatus::Passed {
+    if a_poll.status != PollStatus::Passed && a_poll.status != PollStatus::ExecutionFailed {
         return Err(ContractError::PollNotPassed {});
     }
 
-    if a_poll.end_height + config.timelock_period > env.block.height {
+    if !a_poll
+        .end
+        .add(a_poll.timelock_period)?
+        .is_expired(&env.block)
+    {
         return Err(ContractError::TimelockNotExpired {});
     }
 
-    poll_indexer_store(deps.storage, &PollStatus::Passed).remove(&poll_id.to_be_bytes());
+    let all_msgs = match a_poll.passed_execute_data() {
+        Some(all_msgs) => all_msgs.clone(),
+        None => return Err(ContractError::NoExecuteData {}),
+    };
+
+    // Only messages that did not succeed yet are sent when retrying a failed execution
+    let mut execution_results = a_poll
+        .execution_results
+        .unwrap_or_else(|| vec![ExecutionResult::Pending; all_msgs.len()]);
+    let mut msgs: Vec<(usize, ExecuteData)> = all_msgs
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| execution_results[*index] != ExecutionResult::Success)
+        .collect();
+    msgs.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+    let mut messages: Vec<SubMsg> = vec![];
+    for (index, msg) in msgs {
+        execution_results[index] = ExecutionResult::Pending;
+        messages.push(SubMsg::reply_always(
+            to_cosmos_msg(deps.as_ref(), msg)?,
+            (poll_id << POLL_MSG_INDEX_BITS) | index as u64,
+        ));
+    }
+
+    poll_indexer_store(deps.storage, &a_poll.status).remove(&poll_id.to_be_bytes());
     poll_indexer_store(deps.storage, &PollStatus::Executed).save(&poll_id.to_be_bytes(), &true)?;
 
     a_poll.status = PollStatus::Executed;
+    a_poll.execution_results = Some(execution_results);
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
 
-    let mut messages: Vec<CosmosMsg> = vec![];
-    if let Some(all_msgs) = a_poll.execute_data {
-        let mut msgs = all_msgs;
-        msgs.sort();
-        for msg in msgs {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&msg.contract)?.to_string(),
-                msg: msg.msg,
-                funds: vec![],
-            }))
-        }
-    } else {
-        return Err(ContractError::NoExecuteData {});
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            ("action", "execute_poll"),
+            ("poll_id", poll_id.to_string().as_str()),
+        ]))
+}
+
+/// Records the result of a message sent by execute_poll. The reply id holds the poll id
+/// in its upper bits and the index of the message in the execute data of the poll in its
+/// lower POLL_MSG_INDEX_BITS. A failed message moves the poll to the execution failed
+/// status, from which it can be executed again
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let poll_id = msg.id >> POLL_MSG_INDEX_BITS;
+    let msg_index = msg.id & (MAX_POLL_MSGS as u64 - 1);
+    let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+
+    let execution_result = match msg.result {
+        ContractResult::Ok(_) => ExecutionResult::Success,
+        ContractResult::Err(error) => ExecutionResult::Failure { error },
+    };
+    let succeeded = execution_result == ExecutionResult::Success;
+
+    let execution_results = a_poll
+        .execution_results
+        .as_mut()
+        .ok_or(ContractError::NoExecuteData {})?;
+    *execution_results
+        .get_mut(msg_index as usize)
+        .ok_or(ContractError::NoExecuteData {})? = execution_result;
+
+    if !succeeded && a_poll.status == PollStatus::Executed {
+        poll_indexer_store(deps.storage, &PollStatus::Executed).remove(&poll_id.to_be_bytes());
+        poll_indexer_store(deps.storage, &PollStatus::ExecutionFailed)
+            .save(&poll_id.to_be_bytes(), &true)?;
+        a_poll.status = PollStatus::ExecutionFailed;
     }
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        ("action", "execute_poll"),
+    Ok(Response::new().add_attributes(vec![
+        ("action", "execute_poll_msg"),
         ("poll_id", poll_id.to_string().as_str()),
+        ("msg_index", msg_index.to_string().as_str()),
+        ("succeeded", succeeded.to_string().as_str()),
     ]))
 }
 
 /// ExpirePoll is used to make the poll as expired state for querying purpose
 pub fn expire_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, ContractError> {
-    let config: Config = config_read(deps.storage).load()?;
     let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
 
-    if a_poll.status != PollStatus::Passed {
+    if a_poll.status != PollStatus::Passed && a_poll.status != PollStatus::ExecutionFailed {
         return Err(ContractError::PollNotPassed {});
     }
 
-    if a_poll.execute_data.is_none() {
+    if a_poll.passed_execute_data().is_none() {
         return Err(ContractError::NoExecuteData {});
     }
 
-    if a_poll.end_height + config.expiration_period > env.block.height {
+    if !a_poll
+        .end
+        .add(a_poll.expiration_period)?
+        .is_expired(&env.block)
+    {
         return Err(ContractError::PollNotExpired {});
     }
 
-    poll_indexer_store(deps.storage, &PollStatus::Passed).remove(&poll_id.to_be_bytes());
+    poll_indexer_store(deps.storage, &a_poll.status).remove(&poll_id.to_be_bytes());
     poll_indexer_store(deps.storage, &PollStatus::Expired).save(&poll_id.to_be_bytes(), &true)?;
 
     a_poll.status = PollStatus::Expired;
@@ -589,12 +1329,290 @@ pub fn expire_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Co
     ]))
 }
 
+/*
+ * Delegates the voting power of the sender. It counts toward the vote of
+ * the delegate on the polls where the sender doesn't vote directly.
+ * Only holders of voting power can delegate.
+ */
+pub fn delegate(deps: DepsMut, info: MessageInfo, to: String) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    let delegator_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let delegate_raw = deps.api.addr_canonicalize(&to)?;
+    if delegator_raw == delegate_raw {
+        return Err(ContractError::CannotDelegateToSelf {});
+    }
+
+    let voting_power = query_address_voting_balance_at_timestamp(
+        &deps.querier,
+        &deps.api.addr_humanize(&config.ve_token)?,
+        None,
+        &info.sender,
+    )?;
+    if voting_power.is_zero() {
+        return Err(ContractError::NoVotingPower {});
+    }
+
+    if let Some(old_delegate_raw) =
+        delegation_read(deps.storage).may_load(delegator_raw.as_slice())?
+    {
+        delegator_store(deps.storage, &old_delegate_raw).remove(delegator_raw.as_slice());
+    }
+
+    delegation_store(deps.storage).save(delegator_raw.as_slice(), &delegate_raw)?;
+    delegator_store(deps.storage, &delegate_raw).save(delegator_raw.as_slice(), &true)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "delegate"),
+        ("delegator", info.sender.as_str()),
+        ("delegate", to.as_str()),
+    ]))
+}
+
+/*
+ * Removes the delegation of the sender. Votes already cast by the delegate keep counting.
+ */
+pub fn undelegate(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let delegator_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let delegate_raw = match delegation_read(deps.storage).may_load(delegator_raw.as_slice())? {
+        Some(delegate_raw) => delegate_raw,
+        None => return Err(ContractError::NotDelegating {}),
+    };
+
+    delegation_store(deps.storage).remove(delegator_raw.as_slice());
+    delegator_store(deps.storage, &delegate_raw).remove(delegator_raw.as_slice());
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "undelegate"),
+        ("delegator", info.sender.as_str()),
+        ("delegate", deps.api.addr_humanize(&delegate_raw)?.as_str()),
+    ]))
+}
+
+/*
+ * Counts the voting power of a page of the delegators of a delegate toward its vote on a poll
+ * in progress, skipping the delegators that voted or were already counted.
+ * Anyone can tally the delegations of a delegate that voted.
+ */
+pub fn tally_delegations(
+    mut deps: DepsMut,
+    env: Env,
+    poll_id: u64,
+    delegate: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = config_read(deps.storage).load()?;
+    let state = state_read(deps.storage).load()?;
+    if poll_id == 0 || state.poll_count < poll_id {
+        return Err(ContractError::PollNotFound {});
+    }
+
+    let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.status != PollStatus::InProgress || a_poll.voting_closed(&env.block) {
+        return Err(ContractError::PollNotInProgress {});
+    }
+
+    let delegate_raw = deps.api.addr_canonicalize(&delegate)?;
+    let mut delegate_info =
+        match poll_voter_read(deps.storage, poll_id).may_load(delegate_raw.as_slice())? {
+            Some(delegate_info) => delegate_info,
+            None => return Err(ContractError::DelegateNotVoted {}),
+        };
+
+    let start_after = start_after
+        .map(|delegator| deps.api.addr_canonicalize(&delegator))
+        .transpose()?;
+    let delegators = read_delegators(deps.storage, &delegate_raw, start_after, limit)?;
+    let last_delegator = delegators
+        .last()
+        .map(|delegator| deps.api.addr_humanize(delegator))
+        .transpose()?;
+
+    let ve_token = deps.api.addr_humanize(&config.ve_token)?;
+    let delegated_amount =
+        count_delegated_votes(deps.branch(), &ve_token, &a_poll, &delegate_raw, delegators)?;
+
+    // Move the vote of the delegate to its new voting power
+    for (option, votes) in voter_tally(&delegate_info) {
+        sub_votes(&mut a_poll, &option, votes)?;
+    }
+    delegate_info.delegated_balance += delegated_amount;
+    for (option, votes) in voter_tally(&delegate_info) {
+        add_votes(&mut a_poll, &option, votes);
+    }
+
+    poll_voter_store(deps.storage, poll_id).save(delegate_raw.as_slice(), &delegate_info)?;
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    let mut response = Response::new().add_attributes(vec![
+        ("action", "tally_delegations"),
+        ("poll_id", poll_id.to_string().as_str()),
+        ("delegate", delegate.as_str()),
+        ("delegated_amount", delegated_amount.to_string().as_str()),
+    ]);
+    if let Some(last_delegator) = last_delegator {
+        response = response.add_attribute("last_delegator", last_delegator);
+    }
+
+    Ok(response)
+}
+
+/// Records the delegated votes of the delegators of a delegate that haven't voted on the poll
+/// and haven't been counted yet, returning the voting power they add to the delegate
+fn count_delegated_votes(
+    deps: DepsMut,
+    ve_token: &Addr,
+    poll: &Poll,
+    delegate: &CanonicalAddr,
+    delegators: Vec<CanonicalAddr>,
+) -> StdResult<Uint128> {
+    let mut delegated_balance = Uint128::zero();
+    for delegator in delegators {
+        let already_counted = poll_voter_read(deps.storage, poll.id)
+            .may_load(delegator.as_slice())?
+            .is_some()
+            || poll_delegated_vote_read(deps.storage, poll.id)
+                .may_load(delegator.as_slice())?
+                .is_some();
+        if already_counted {
+            continue;
+        }
+
+        let delegator_amount = query_address_voting_balance_at_timestamp(
+            &deps.querier,
+            ve_token,
+            Some(poll.start_time),
+            &deps.api.addr_humanize(&delegator)?,
+        )?;
+        if delegator_amount.is_zero() {
+            continue;
+        }
+
+        poll_delegated_vote_store(deps.storage, poll.id).save(
+            delegator.as_slice(),
+            &DelegatedVote {
+                delegate: delegate.clone(),
+                balance: delegator_amount,
+            },
+        )?;
+        delegated_balance += delegator_amount;
+    }
+
+    Ok(delegated_balance)
+}
+
 pub fn cast_vote(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     poll_id: u64,
     vote: VoteOption,
+) -> Result<Response, ContractError> {
+    record_vote(deps, env, info, poll_id, vote, None)
+}
+
+pub fn cast_weighted_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    weights: Vec<(VoteOption, Decimal)>,
+) -> Result<Response, ContractError> {
+    validate_vote_weights(&weights)?;
+
+    // The main vote is the option with the largest weight
+    let mut vote = weights[0].0.clone();
+    let mut max_weight = weights[0].1;
+    for (option, weight) in weights.iter() {
+        if *weight > max_weight {
+            vote = option.clone();
+            max_weight = *weight;
+        }
+    }
+
+    record_vote(deps, env, info, poll_id, vote, Some(weights))
+}
+
+fn validate_vote_weights(weights: &[(VoteOption, Decimal)]) -> Result<(), ContractError> {
+    let mut total_weight = Decimal::zero();
+    for (index, (option, weight)) in weights.iter().enumerate() {
+        if weight.is_zero() || weights[..index].iter().any(|(other, _)| other == option) {
+            return Err(ContractError::InvalidVoteWeights {});
+        }
+        total_weight = total_weight + *weight;
+    }
+
+    if total_weight != Decimal::one() {
+        return Err(ContractError::InvalidVoteWeights {});
+    }
+
+    Ok(())
+}
+
+/// Voting power a voter puts on each option, including the power delegated to them
+fn voter_tally(voter_info: &VoterInfo) -> Vec<(VoteOption, Uint128)> {
+    let balance = voter_info.balance + voter_info.delegated_balance;
+    match &voter_info.weights {
+        Some(weights) => weights
+            .iter()
+            .map(|(option, weight)| (option.clone(), balance * *weight))
+            .collect(),
+        None => vec![(voter_info.vote.clone(), balance)],
+    }
+}
+
+fn add_votes(poll: &mut Poll, option: &VoteOption, amount: Uint128) {
+    match option {
+        VoteOption::Yes => poll.yes_votes += amount,
+        VoteOption::No => poll.no_votes += amount,
+        VoteOption::Abstain => poll.abstain_votes += amount,
+        VoteOption::Choice(index) => {
+            if let Some(option) = poll
+                .options
+                .as_mut()
+                .and_then(|options| options.get_mut(*index as usize))
+            {
+                option.votes += amount;
+            }
+        }
+    }
+}
+
+fn sub_votes(poll: &mut Poll, option: &VoteOption, amount: Uint128) -> StdResult<()> {
+    match option {
+        VoteOption::Yes => poll.yes_votes = poll.yes_votes.checked_sub(amount)?,
+        VoteOption::No => poll.no_votes = poll.no_votes.checked_sub(amount)?,
+        VoteOption::Abstain => poll.abstain_votes = poll.abstain_votes.checked_sub(amount)?,
+        VoteOption::Choice(index) => {
+            if let Some(option) = poll
+                .options
+                .as_mut()
+                .and_then(|options| options.get_mut(*index as usize))
+            {
+                option.votes = option.votes.checked_sub(amount)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Binary polls take yes and no votes, multiple choice polls take choices of one of their options
+fn is_valid_vote_option(poll: &Poll, option: &VoteOption) -> bool {
+    match (option, &poll.options) {
+        (VoteOption::Abstain, _) => true,
+        (VoteOption::Choice(index), Some(options)) => (*index as usize) < options.len(),
+        (VoteOption::Choice(_), None) => false,
+        (VoteOption::Yes, options) | (VoteOption::No, options) => options.is_none(),
+    }
+}
+
+fn record_vote(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    vote: VoteOption,
+    weights: Option<Vec<(VoteOption, Decimal)>>,
 ) -> Result<Response, ContractError> {
     let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
     let config = config_read(deps.storage).load()?;
@@ -604,49 +1622,131 @@ pub fn cast_vote(
     }
 
     let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
-    if a_poll.status != PollStatus::InProgress || env.block.height > a_poll.end_height {
+    if a_poll.status != PollStatus::InProgress || a_poll.voting_closed(&env.block) {
         return Err(ContractError::PollNotInProgress {});
     }
 
-    // Check the voter already has a vote on the poll
-    if poll_voter_read(deps.storage, poll_id)
-        .load(sender_address_raw.as_slice())
-        .is_ok()
-    {
-        return Err(ContractError::AlreadyVoted {});
+    let valid_vote = match &weights {
+        Some(weights) => weights
+            .iter()
+            .all(|(option, _)| is_valid_vote_option(&a_poll, option)),
+        None => is_valid_vote_option(&a_poll, &vote),
+    };
+    if !valid_vote {
+        return Err(ContractError::InvalidVoteOption {});
     }
 
-    let amount = query_address_voting_balance_at_timestamp(
-        &deps.querier,
-        &deps.api.addr_humanize(&config.ve_token)?,
-        Some(a_poll.start_time),
-        &info.sender,
-    )?;
+    // A voter changing its vote takes its previous vote off the tally
+    let previous_vote =
+        poll_voter_read(deps.storage, poll_id).may_load(sender_address_raw.as_slice())?;
+    if let Some(previous_vote) = &previous_vote {
+        for (option, votes) in voter_tally(previous_vote) {
+            sub_votes(&mut a_poll, &option, votes)?;
+        }
+    }
 
-    // update tally info
-    if VoteOption::Yes == vote {
-        a_poll.yes_votes += amount;
-    } else {
-        a_poll.no_votes += amount;
+    let ve_token = deps.api.addr_humanize(&config.ve_token)?;
+    let amount = match &previous_vote {
+        Some(previous_vote) => previous_vote.balance,
+        None => query_address_voting_balance_at_timestamp(
+            &deps.querier,
+            &ve_token,
+            Some(a_poll.start_time),
+            &info.sender,
+        )?,
+    };
+
+    // Voting directly overrides the vote of the delegate, if it already counted this voting power
+    if let Some(delegated_vote) =
+        poll_delegated_vote_read(deps.storage, poll_id).may_load(sender_address_raw.as_slice())?
+    {
+        let mut delegate_info =
+            poll_voter_read(deps.storage, poll_id).load(delegated_vote.delegate.as_slice())?;
+        for (option, votes) in voter_tally(&delegate_info) {
+            sub_votes(&mut a_poll, &option, votes)?;
+        }
+        delegate_info.delegated_balance = delegate_info
+            .delegated_balance
+            .checked_sub(delegated_vote.balance)?;
+        for (option, votes) in voter_tally(&delegate_info) {
+            add_votes(&mut a_poll, &option, votes);
+        }
+
+        poll_voter_store(deps.storage, poll_id)
+            .save(delegated_vote.delegate.as_slice(), &delegate_info)?;
+        poll_delegated_vote_store(deps.storage, poll_id).remove(sender_address_raw.as_slice());
     }
 
+    // Count the voting power of the first delegators that haven't voted yet
+    let delegators = read_delegators(
+        deps.storage,
+        &sender_address_raw,
+        None,
+        Some(VOTE_DELEGATORS_LIMIT),
+    )?;
+    let delegated_balance = previous_vote
+        .as_ref()
+        .map_or(Uint128::zero(), |previous_vote| {
+            previous_vote.delegated_balance
+        })
+        + count_delegated_votes(
+            deps.branch(),
+            &ve_token,
+            &a_poll,
+            &sender_address_raw,
+            delegators,
+        )?;
+
     let vote_info = VoterInfo {
         vote,
         balance: amount,
+        weights,
+        delegated_balance,
     };
 
+    // update tally info
+    for (option, votes) in voter_tally(&vote_info) {
+        add_votes(&mut a_poll, &option, votes);
+    }
+
     // store poll voter && and update poll data
     poll_voter_store(deps.storage, poll_id).save(sender_address_raw.as_slice(), &vote_info)?;
 
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
 
-    Ok(Response::new().add_attributes(vec![
+    let mut response = Response::new().add_attributes(vec![
         ("action", "cast_vote"),
         ("poll_id", poll_id.to_string().as_str()),
         ("amount", amount.to_string().as_str()),
         ("voter", info.sender.as_str()),
         ("vote_option", vote_info.vote.to_string().as_str()),
-    ]))
+    ]);
+    if !delegated_balance.is_zero() {
+        response = response.add_attribute("delegated_amount", delegated_balance.to_string());
+    }
+    if let Some(previous_vote) = previous_vote {
+        response = response.add_event(Event::new("vote_changed").add_attributes(vec![
+            ("poll_id", poll_id.to_string().as_str()),
+            ("voter", info.sender.as_str()),
+            (
+                "previous_vote_option",
+                previous_vote.vote.to_string().as_str(),
+            ),
+            ("vote_option", vote_info.vote.to_string().as_str()),
+        ]));
+    }
+    if let Some(weights) = vote_info.weights {
+        response = response.add_attribute(
+            "vote_weights",
+            weights
+                .iter()
+                .map(|(option, weight)| format!("{}:{}", option, weight))
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+    }
+
+    Ok(response)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -680,6 +1780,23 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
             limit,
             order_by,
         )?)?),
+        QueryMsg::Delegation { address } => Ok(to_binary(&query_delegation(deps, address)?)?),
+        QueryMsg::Delegators {
+            delegate,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_delegators(
+            deps,
+            delegate,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::PollCategory { category } => {
+            Ok(to_binary(&query_poll_category(deps, category)?)?)
+        }
+        QueryMsg::ContractCategory { contract } => {
+            Ok(to_binary(&query_contract_category(deps, contract)?)?)
+        }
     }
 }
 
@@ -699,6 +1816,11 @@ fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
         expiration_period: config.expiration_period,
         proposal_deposit: config.proposal_deposit,
         snapshot_period: config.snapshot_period,
+        guardian: config
+            .guardian
+            .map(|guardian| deps.api.addr_humanize(&guardian))
+            .transpose()?
+            .map(|guardian| guardian.to_string()),
     })
 }
 
@@ -718,35 +1840,26 @@ fn query_poll(deps: Deps, poll_id: u64) -> Result<PollResponse, ContractError> {
     }
     .unwrap();
 
-    let mut data_list: Vec<PollExecuteMsg> = vec![];
-
     Ok(PollResponse {
         id: poll.id,
         creator: deps.api.addr_humanize(&poll.creator)?.to_string(),
         status: poll.status,
+        category: poll.category,
         start_time: poll.start_time,
-        end_height: poll.end_height,
+        end: poll.end,
         title: poll.title,
         description: poll.description,
         link: poll.link,
         deposit_amount: poll.deposit_amount,
-        execute_data: if let Some(exe_msgs) = poll.execute_data.clone() {
-            for msg in exe_msgs {
-                let execute_data = PollExecuteMsg {
-                    order: msg.order,
-                    contract: deps.api.addr_humanize(&msg.contract)?.to_string(),
-                    msg: msg.msg,
-                };
-                data_list.push(execute_data)
-            }
-            Some(data_list)
-        } else {
-            None
-        },
+        execute_data: to_poll_execute_msgs(deps, poll.execute_data)?,
         yes_votes: poll.yes_votes,
         no_votes: poll.no_votes,
+        abstain_votes: poll.abstain_votes,
         staked_amount: poll.staked_amount,
         total_balance_at_end_poll: poll.total_balance_at_end_poll,
+        options: to_poll_options_response(deps, poll.options)?,
+        winning_option: poll.winning_option,
+        execution_results: poll.execution_results,
     })
 }
 
@@ -766,31 +1879,22 @@ fn query_polls(
                 id: poll.id,
                 creator: deps.api.addr_humanize(&poll.creator)?.to_string(),
                 status: poll.status.clone(),
+                category: poll.category,
                 start_time: poll.start_time,
-                end_height: poll.end_height,
+                end: poll.end,
                 title: poll.title.to_string(),
                 description: poll.description.to_string(),
                 link: poll.link.clone(),
                 deposit_amount: poll.deposit_amount,
-                execute_data: if let Some(exe_msgs) = poll.execute_data.clone() {
-                    let mut data_list: Vec<PollExecuteMsg> = vec![];
-
-                    for msg in exe_msgs {
-                        let execute_data = PollExecuteMsg {
-                            order: msg.order,
-                            contract: deps.api.addr_humanize(&msg.contract)?.to_string(),
-                            msg: msg.msg,
-                        };
-                        data_list.push(execute_data)
-                    }
-                    Some(data_list)
-                } else {
-                    None
-                },
+                execute_data: to_poll_execute_msgs(deps, poll.execute_data.clone())?,
                 yes_votes: poll.yes_votes,
                 no_votes: poll.no_votes,
+                abstain_votes: poll.abstain_votes,
                 staked_amount: poll.staked_amount,
                 total_balance_at_end_poll: poll.total_balance_at_end_poll,
+                options: to_poll_options_response(deps, poll.options.clone())?,
+                winning_option: poll.winning_option,
+                execution_results: poll.execution_results.clone(),
             })
         })
         .collect();
@@ -834,6 +1938,8 @@ fn query_voters(
                 voter: deps.api.addr_humanize(&voter_info.0)?.to_string(),
                 vote: voter_info.1.vote.clone(),
                 balance: voter_info.1.balance,
+                weights: voter_info.1.weights.clone(),
+                delegated_balance: voter_info.1.delegated_balance,
             })
         })
         .collect();
@@ -843,6 +1949,68 @@ fn query_voters(
     })
 }
 
+fn query_poll_category(
+    deps: Deps,
+    category: PollCategory,
+) -> Result<PollCategoryResponse, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    let category_config = load_poll_category_config(deps.storage, &config, category)?;
+
+    Ok(PollCategoryResponse {
+        category,
+        quorum: category_config.quorum,
+        threshold: category_config.threshold,
+        proposal_deposit: category_config.proposal_deposit,
+        voting_period: category_config.voting_period,
+        timelock_period: category_config.timelock_period,
+    })
+}
+
+fn query_contract_category(
+    deps: Deps,
+    contract: String,
+) -> Result<ContractCategoryResponse, ContractError> {
+    let category = contract_category_read(deps.storage)
+        .may_load(deps.api.addr_canonicalize(&contract)?.as_slice())?;
+
+    Ok(ContractCategoryResponse { contract, category })
+}
+
+fn query_delegation(deps: Deps, address: String) -> Result<DelegationResponse, ContractError> {
+    let delegate = delegation_read(deps.storage)
+        .may_load(deps.api.addr_canonicalize(&address)?.as_slice())?
+        .map(|delegate| deps.api.addr_humanize(&delegate))
+        .transpose()?
+        .map(|delegate| delegate.to_string());
+
+    Ok(DelegationResponse { address, delegate })
+}
+
+fn query_delegators(
+    deps: Deps,
+    delegate: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<DelegatorsResponse, ContractError> {
+    let start_after = start_after
+        .map(|start_after| deps.api.addr_canonicalize(&start_after))
+        .transpose()?;
+    let delegators = read_delegators(
+        deps.storage,
+        &deps.api.addr_canonicalize(&delegate)?,
+        start_after,
+        limit,
+    )?
+    .iter()
+    .map(|delegator| Ok(deps.api.addr_humanize(delegator)?.to_string()))
+    .collect::<StdResult<Vec<String>>>()?;
+
+    Ok(DelegatorsResponse {
+        delegate,
+        delegators,
+    })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
     let old_config = old_config_read(deps.storage).load()?;
@@ -854,14 +2022,68 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response>
         terraswap_factory: old_config.terraswap_factory,
         quorum: old_config.quorum,
         threshold: old_config.threshold,
-        voting_period: old_config.voting_period,
-        timelock_period: old_config.timelock_period,
-        expiration_period: old_config.expiration_period,
+        voting_period: Duration::Height(old_config.voting_period),
+        timelock_period: Duration::Height(old_config.timelock_period),
+        expiration_period: Duration::Height(old_config.expiration_period),
         proposal_deposit: old_config.proposal_deposit,
         snapshot_period: old_config.snapshot_period,
+        guardian: None,
     };
 
     config_store(deps.storage).save(&new_config)?;
 
+    let community_contract_raw = deps.api.addr_canonicalize(&msg.community_contract)?;
+    contract_category_store(deps.storage)
+        .save(community_contract_raw.as_slice(), &PollCategory::Treasury)?;
+
+    // Existing polls end at a block height, with the periods of the old config
+    let old_polls = old_poll_read(deps.storage)
+        .range(None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, OldPoll)>>>()?;
+    for (key, old_poll) in old_polls {
+        poll_store(deps.storage).save(
+            &key,
+            &Poll {
+                id: old_poll.id,
+                creator: old_poll.creator,
+                status: old_poll.status,
+                category: if old_poll.execute_data.is_some() {
+                    PollCategory::Parameter
+                } else {
+                    PollCategory::Text
+                },
+                yes_votes: old_poll.yes_votes,
+                no_votes: old_poll.no_votes,
+                abstain_votes: Uint128::zero(),
+                start_time: old_poll.start_time,
+                end: Expiration::AtHeight(old_poll.end_height),
+                quorum: new_config.quorum,
+                threshold: new_config.threshold,
+                timelock_period: new_config.timelock_period,
+                expiration_period: new_config.expiration_period,
+                title: old_poll.title,
+                description: old_poll.description,
+                link: old_poll.link,
+                execute_data: old_poll.execute_data.map(|execute_data| {
+                    execute_data
+                        .into_iter()
+                        .map(|data| ExecuteData::WasmExecute {
+                            order: data.order,
+                            contract: data.contract,
+                            msg: data.msg,
+                            funds: vec![],
+                        })
+                        .collect()
+                }),
+                deposit_amount: old_poll.deposit_amount,
+                total_balance_at_end_poll: old_poll.total_balance_at_end_poll,
+                staked_amount: old_poll.staked_amount,
+                options: None,
+                winning_option: None,
+                execution_results: None,
+            },
+        )?;
+    }
+
     Ok(Response::default())
 }
@@ -1,4 +1,5 @@
 use cosmwasm_std::{OverflowError, StdError};
+use glow_protocol::gov::PollCategory;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -54,12 +55,15 @@ pub enum ContractError {
     #[error("Poll is not in passed status")]
     PollNotPassed {},
 
+    #[error("Timelock period has expired")]
+    TimelockExpired {},
+
+    #[error("Cannot cancel a poll that has votes")]
+    PollHasVotes {},
+
     #[error("Cannot snapshot at this height")]
     SnapshotHeight {},
 
-    #[error("User has already voted")]
-    AlreadyVoted {},
-
     #[error("Cannot make a text proposal to expired state")]
     NoExecuteData {},
 
@@ -68,4 +72,31 @@ pub enum ContractError {
 
     #[error("Voting period has not expired")]
     PollVotingPeriod {},
+
+    #[error("Vote weights must be positive, unique per option and add up to one")]
+    InvalidVoteWeights {},
+
+    #[error("Cannot delegate to self")]
+    CannotDelegateToSelf {},
+
+    #[error("Sender is not delegating")]
+    NotDelegating {},
+
+    #[error("Cannot delegate without voting power")]
+    NoVotingPower {},
+
+    #[error("Delegate has not voted on this poll")]
+    DelegateNotVoted {},
+
+    #[error("Multiple choice polls must have between 2 and {0} labelled options")]
+    InvalidPollOptions(usize),
+
+    #[error("Invalid poll message: {0}")]
+    InvalidPollExecuteMsg(String),
+
+    #[error("Poll messages require the {0} category or a riskier one")]
+    InvalidPollCategory(PollCategory),
+
+    #[error("Vote option is not valid for this poll")]
+    InvalidVoteOption {},
 }
@@ -1,13 +1,16 @@
-use cosmwasm_std::{Binary, CanonicalAddr, Decimal, StdResult, Storage, Uint128};
+use cosmwasm_std::{Binary, BlockInfo, CanonicalAddr, Coin, Decimal, StdResult, Storage, Uint128};
 use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
     Singleton,
 };
+use cw0::{Duration, Expiration};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use glow_protocol::common::OrderBy;
-use glow_protocol::gov::{PollStatus, VoterInfo};
+use glow_protocol::gov::{
+    ExecutionResult, PollCategory, PollCategoryConfig, PollStatus, VoterInfo,
+};
 use std::cmp::Ordering;
 
 static KEY_CONFIG: &[u8] = b"config";
@@ -17,6 +20,11 @@ static PREFIX_POLL_INDEXER: &[u8] = b"poll_indexer";
 static PREFIX_POLL_VOTER: &[u8] = b"poll_voter";
 static PREFIX_POLL: &[u8] = b"poll";
 static PREFIX_BANK: &[u8] = b"bank";
+static PREFIX_DELEGATION: &[u8] = b"delegation";
+static PREFIX_DELEGATOR: &[u8] = b"delegator";
+static PREFIX_POLL_DELEGATED_VOTE: &[u8] = b"poll_delegated_vote";
+static PREFIX_POLL_CATEGORY: &[u8] = b"poll_category";
+static PREFIX_CONTRACT_CATEGORY: &[u8] = b"contract_category";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct OldConfig {
@@ -40,11 +48,13 @@ pub struct Config {
     pub terraswap_factory: CanonicalAddr,
     pub quorum: Decimal,
     pub threshold: Decimal,
-    pub voting_period: u64,
-    pub timelock_period: u64,
-    pub expiration_period: u64,
+    pub voting_period: Duration,
+    pub timelock_period: Duration,
+    pub expiration_period: Duration,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    #[serde(default)]
+    pub guardian: Option<CanonicalAddr>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -66,10 +76,18 @@ pub struct Poll {
     pub id: u64,
     pub creator: CanonicalAddr,
     pub status: PollStatus,
+    pub category: PollCategory,
     pub yes_votes: Uint128,
     pub no_votes: Uint128,
+    #[serde(default)]
+    pub abstain_votes: Uint128,
     pub start_time: u64,
-    pub end_height: u64,
+    pub end: Expiration,
+    // Parameters of the category of the poll, as configured when the poll was created
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub timelock_period: Duration,
+    pub expiration_period: Duration,
     pub title: String,
     pub description: String,
     pub link: Option<String>,
@@ -78,19 +96,130 @@ pub struct Poll {
     /// Total balance at the end poll
     pub total_balance_at_end_poll: Option<Uint128>,
     pub staked_amount: Option<Uint128>,
+    /// Options of a multiple choice poll
+    #[serde(default)]
+    pub options: Option<Vec<PollOption>>,
+    #[serde(default)]
+    pub winning_option: Option<u8>,
+    /// Results of the passed execute data, by index. None until the poll is executed
+    #[serde(default)]
+    pub execution_results: Option<Vec<ExecutionResult>>,
+}
+
+impl Poll {
+    /// Whether votes can no longer be cast. Votes are still accepted at the end height or time
+    pub fn voting_closed(&self, block: &BlockInfo) -> bool {
+        match self.end {
+            Expiration::AtHeight(height) => block.height > height,
+            Expiration::AtTime(time) => block.time > time,
+            Expiration::Never {} => false,
+        }
+    }
+
+    /// Whether any voting power was cast on the poll, directly or delegated
+    pub fn has_votes(&self) -> bool {
+        !self.yes_votes.is_zero()
+            || !self.no_votes.is_zero()
+            || !self.abstain_votes.is_zero()
+            || self.options.as_ref().map_or(false, |options| {
+                options.iter().any(|option| !option.votes.is_zero())
+            })
+    }
+
+    /// Messages to execute once the poll passed. For multiple choice polls,
+    /// these are the messages of the winning option
+    pub fn passed_execute_data(&self) -> Option<&Vec<ExecuteData>> {
+        match (&self.options, self.winning_option) {
+            (Some(options), Some(winning_option)) => options
+                .get(winning_option as usize)
+                .and_then(|option| option.execute_data.as_ref()),
+            (Some(_), None) => None,
+            (None, _) => self.execute_data.as_ref(),
+        }
+    }
+}
+
+/// Poll layout before periods could be configured as time durations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OldPoll {
+    pub id: u64,
+    pub creator: CanonicalAddr,
+    pub status: PollStatus,
+    pub yes_votes: Uint128,
+    pub no_votes: Uint128,
+    pub start_time: u64,
+    pub end_height: u64,
+    pub title: String,
+    pub description: String,
+    pub link: Option<String>,
+    pub execute_data: Option<Vec<OldExecuteData>>,
+    pub deposit_amount: Uint128,
+    pub total_balance_at_end_poll: Option<Uint128>,
+    pub staked_amount: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollOption {
+    pub label: String,
+    pub execute_data: Option<Vec<ExecuteData>>,
+    pub votes: Uint128,
+}
+
+/// Voting power of a delegator counted in the vote of its delegate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegatedVote {
+    pub delegate: CanonicalAddr,
+    pub balance: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
-pub struct ExecuteData {
-    pub order: u64,
-    pub contract: CanonicalAddr,
-    pub msg: Binary,
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteData {
+    WasmExecute {
+        order: u64,
+        contract: CanonicalAddr,
+        msg: Binary,
+        funds: Vec<Coin>,
+    },
+    WasmMigrate {
+        order: u64,
+        contract: CanonicalAddr,
+        new_code_id: u64,
+        msg: Binary,
+    },
+    UpdateAdmin {
+        order: u64,
+        contract: CanonicalAddr,
+        admin: CanonicalAddr,
+    },
+    ClearAdmin {
+        order: u64,
+        contract: CanonicalAddr,
+    },
+    BankSend {
+        order: u64,
+        to_address: CanonicalAddr,
+        amount: Vec<Coin>,
+    },
 }
+
+impl ExecuteData {
+    pub fn order(&self) -> u64 {
+        match self {
+            ExecuteData::WasmExecute { order, .. }
+            | ExecuteData::WasmMigrate { order, .. }
+            | ExecuteData::UpdateAdmin { order, .. }
+            | ExecuteData::ClearAdmin { order, .. }
+            | ExecuteData::BankSend { order, .. } => *order,
+        }
+    }
+}
+
 impl Eq for ExecuteData {}
 
 impl Ord for ExecuteData {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.order.cmp(&other.order)
+        self.order().cmp(&other.order())
     }
 }
 
@@ -102,10 +231,18 @@ impl PartialOrd for ExecuteData {
 
 impl PartialEq for ExecuteData {
     fn eq(&self, other: &Self) -> bool {
-        self.order == other.order
+        self.order() == other.order()
     }
 }
 
+/// ExecuteData layout before polls could send other messages than a Wasm execute
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OldExecuteData {
+    pub order: u64,
+    pub contract: CanonicalAddr,
+    pub msg: Binary,
+}
+
 pub fn config_store(storage: &mut dyn Storage) -> Singleton<Config> {
     singleton(storage, KEY_CONFIG)
 }
@@ -134,6 +271,10 @@ pub fn poll_read(storage: &dyn Storage) -> ReadonlyBucket<Poll> {
     bucket_read(storage, PREFIX_POLL)
 }
 
+pub fn old_poll_read(storage: &dyn Storage) -> ReadonlyBucket<OldPoll> {
+    bucket_read(storage, PREFIX_POLL)
+}
+
 pub fn poll_indexer_store<'a>(
     storage: &'a mut dyn Storage,
     status: &PollStatus,
@@ -152,6 +293,79 @@ pub fn poll_voter_read(storage: &dyn Storage, poll_id: u64) -> ReadonlyBucket<Vo
     ReadonlyBucket::multilevel(storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()])
 }
 
+pub fn poll_delegated_vote_store(storage: &mut dyn Storage, poll_id: u64) -> Bucket<DelegatedVote> {
+    Bucket::multilevel(
+        storage,
+        &[PREFIX_POLL_DELEGATED_VOTE, &poll_id.to_be_bytes()],
+    )
+}
+
+pub fn poll_delegated_vote_read(
+    storage: &dyn Storage,
+    poll_id: u64,
+) -> ReadonlyBucket<DelegatedVote> {
+    ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_POLL_DELEGATED_VOTE, &poll_id.to_be_bytes()],
+    )
+}
+
+/// Parameters of the polls of each category, when they differ from the config
+pub fn poll_category_store(storage: &mut dyn Storage) -> Bucket<PollCategoryConfig> {
+    bucket(storage, PREFIX_POLL_CATEGORY)
+}
+
+pub fn poll_category_read(storage: &dyn Storage) -> ReadonlyBucket<PollCategoryConfig> {
+    bucket_read(storage, PREFIX_POLL_CATEGORY)
+}
+
+/// Maps contracts to the category of the polls sending them messages
+pub fn contract_category_store(storage: &mut dyn Storage) -> Bucket<PollCategory> {
+    bucket(storage, PREFIX_CONTRACT_CATEGORY)
+}
+
+pub fn contract_category_read(storage: &dyn Storage) -> ReadonlyBucket<PollCategory> {
+    bucket_read(storage, PREFIX_CONTRACT_CATEGORY)
+}
+
+/// Maps delegators to their delegate
+pub fn delegation_store(storage: &mut dyn Storage) -> Bucket<CanonicalAddr> {
+    bucket(storage, PREFIX_DELEGATION)
+}
+
+pub fn delegation_read(storage: &dyn Storage) -> ReadonlyBucket<CanonicalAddr> {
+    bucket_read(storage, PREFIX_DELEGATION)
+}
+
+/// Indexes the delegators of each delegate
+pub fn delegator_store<'a>(
+    storage: &'a mut dyn Storage,
+    delegate: &CanonicalAddr,
+) -> Bucket<'a, bool> {
+    Bucket::multilevel(storage, &[PREFIX_DELEGATOR, delegate.as_slice()])
+}
+
+pub fn read_delegators(
+    storage: &dyn Storage,
+    delegate: &CanonicalAddr,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<CanonicalAddr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start_addr(start_after);
+
+    let delegators: ReadonlyBucket<bool> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_DELEGATOR, delegate.as_slice()]);
+    delegators
+        .range(start.as_deref(), None, OrderBy::Asc.into())
+        .take(limit)
+        .map(|item| {
+            let (k, _) = item?;
+            Ok(CanonicalAddr::from(k))
+        })
+        .collect()
+}
+
 pub fn read_poll_voters<'a>(
     storage: &'a dyn Storage,
     poll_id: u64,
@@ -1,27 +1,32 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, query, reply};
 use crate::error::ContractError;
 use crate::mock_querier::mock_dependencies;
 use crate::state::{config_read, poll_voter_read, state_read, Config, State};
 
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coins, from_binary, to_binary, Addr, Api, CanonicalAddr, CosmosMsg, Decimal, Deps,
-    DepsMut, Env, Response, StdError, SubMsg, Timestamp, Uint128, WasmMsg,
+    attr, coins, from_binary, to_binary, Addr, Api, BankMsg, Binary, CanonicalAddr, ContractResult,
+    CosmosMsg, Decimal, Deps, DepsMut, Env, Event, Reply, Response, StdError, SubMsg,
+    SubMsgExecutionResponse, Timestamp, Uint128, WasmMsg,
 };
+use cw0::{Duration, Expiration};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use glow_protocol::common::OrderBy;
 use glow_protocol::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollExecuteMsg, PollResponse,
-    PollStatus, PollsResponse, QueryMsg, VoteOption, VoterInfo, VotersResponse, VotersResponseItem,
+    ConfigResponse, ContractCategoryResponse, Cw20HookMsg, DelegationResponse, DelegatorsResponse,
+    ExecuteMsg, ExecutionResult, InstantiateMsg, PollCategory, PollCategoryConfig,
+    PollCategoryResponse, PollExecuteMsg, PollOptionMsg, PollResponse, PollStatus, PollsResponse,
+    QueryMsg, VoteOption, VoterInfo, VotersResponse, VotersResponseItem,
 };
 
 const VOTING_TOKEN: &str = "voting_token";
 const VE_TOKEN: &str = "ve_token";
 const TERRASWAP_FACTORY: &str = "terraswap_factory";
+const COMMUNITY: &str = "community";
 const TEST_CREATOR: &str = "creator";
 const TEST_VOTER: &str = "voter1";
 const TEST_VOTER_2: &str = "voter2";
-// const TEST_VOTER_3: &str = "voter3";
+const TEST_VOTER_3: &str = "voter3";
 const DEFAULT_QUORUM: u64 = 30u64;
 const DEFAULT_THRESHOLD: u64 = 50u64;
 const DEFAULT_VOTING_PERIOD: u64 = 10000u64;
@@ -34,11 +39,12 @@ fn mock_instantiate(deps: DepsMut) {
     let msg = InstantiateMsg {
         quorum: Decimal::percent(DEFAULT_QUORUM),
         threshold: Decimal::percent(DEFAULT_THRESHOLD),
-        voting_period: DEFAULT_VOTING_PERIOD,
-        timelock_period: DEFAULT_TIMELOCK_PERIOD,
-        expiration_period: DEFAULT_EXPIRATION_PERIOD,
+        voting_period: Duration::Height(DEFAULT_VOTING_PERIOD),
+        timelock_period: Duration::Height(DEFAULT_TIMELOCK_PERIOD),
+        expiration_period: Duration::Height(DEFAULT_EXPIRATION_PERIOD),
         proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        guardian: None,
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -52,6 +58,7 @@ fn mock_register_contracts(deps: DepsMut) {
         glow_token: VOTING_TOKEN.to_string(),
         ve_token: VE_TOKEN.to_string(),
         terraswap_factory: TERRASWAP_FACTORY.to_string(),
+        community_contract: COMMUNITY.to_string(),
     };
     let _res = execute(deps, mock_env(), info, msg)
         .expect("contract successfully executes RegisterContracts");
@@ -68,11 +75,12 @@ fn instantiate_msg() -> InstantiateMsg {
     InstantiateMsg {
         quorum: Decimal::percent(DEFAULT_QUORUM),
         threshold: Decimal::percent(DEFAULT_THRESHOLD),
-        voting_period: DEFAULT_VOTING_PERIOD,
-        timelock_period: DEFAULT_TIMELOCK_PERIOD,
-        expiration_period: DEFAULT_EXPIRATION_PERIOD,
+        voting_period: Duration::Height(DEFAULT_VOTING_PERIOD),
+        timelock_period: Duration::Height(DEFAULT_TIMELOCK_PERIOD),
+        expiration_period: Duration::Height(DEFAULT_EXPIRATION_PERIOD),
         proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        guardian: None,
     }
 }
 
@@ -95,11 +103,12 @@ fn proper_initialization() {
             owner: deps.api.addr_canonicalize(TEST_CREATOR).unwrap(),
             quorum: Decimal::percent(DEFAULT_QUORUM),
             threshold: Decimal::percent(DEFAULT_THRESHOLD),
-            voting_period: DEFAULT_VOTING_PERIOD,
-            timelock_period: DEFAULT_TIMELOCK_PERIOD,
-            expiration_period: DEFAULT_EXPIRATION_PERIOD,
+            voting_period: Duration::Height(DEFAULT_VOTING_PERIOD),
+            timelock_period: Duration::Height(DEFAULT_TIMELOCK_PERIOD),
+            expiration_period: Duration::Height(DEFAULT_EXPIRATION_PERIOD),
             proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
-            snapshot_period: DEFAULT_FIX_PERIOD
+            snapshot_period: DEFAULT_FIX_PERIOD,
+            guardian: None
         }
     );
 
@@ -107,6 +116,7 @@ fn proper_initialization() {
         glow_token: VOTING_TOKEN.to_string(),
         ve_token: VE_TOKEN.to_string(),
         terraswap_factory: TERRASWAP_FACTORY.to_string(),
+        community_contract: COMMUNITY.to_string(),
     };
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     let config: Config = config_read(deps.as_ref().storage).load().unwrap();
@@ -149,11 +159,12 @@ fn fails_init_invalid_quorum() {
     let msg = InstantiateMsg {
         quorum: Decimal::percent(101),
         threshold: Decimal::percent(DEFAULT_THRESHOLD),
-        voting_period: DEFAULT_VOTING_PERIOD,
-        timelock_period: DEFAULT_TIMELOCK_PERIOD,
-        expiration_period: DEFAULT_EXPIRATION_PERIOD,
+        voting_period: Duration::Height(DEFAULT_VOTING_PERIOD),
+        timelock_period: Duration::Height(DEFAULT_TIMELOCK_PERIOD),
+        expiration_period: Duration::Height(DEFAULT_EXPIRATION_PERIOD),
         proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        guardian: None,
     };
 
     let res = instantiate(deps.as_mut(), mock_env(), info, msg);
@@ -174,11 +185,12 @@ fn fails_init_invalid_threshold() {
     let msg = InstantiateMsg {
         quorum: Decimal::percent(DEFAULT_QUORUM),
         threshold: Decimal::percent(101),
-        voting_period: DEFAULT_VOTING_PERIOD,
-        timelock_period: DEFAULT_TIMELOCK_PERIOD,
-        expiration_period: DEFAULT_EXPIRATION_PERIOD,
+        voting_period: Duration::Height(DEFAULT_VOTING_PERIOD),
+        timelock_period: Duration::Height(DEFAULT_TIMELOCK_PERIOD),
+        expiration_period: Duration::Height(DEFAULT_EXPIRATION_PERIOD),
         proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        guardian: None,
     };
 
     let res = instantiate(deps.as_mut(), mock_env(), info, msg);
@@ -199,11 +211,12 @@ fn fails_contract_already_registered() {
     let msg = InstantiateMsg {
         quorum: Decimal::percent(DEFAULT_QUORUM),
         threshold: Decimal::percent(DEFAULT_THRESHOLD),
-        voting_period: DEFAULT_VOTING_PERIOD,
-        timelock_period: DEFAULT_TIMELOCK_PERIOD,
-        expiration_period: DEFAULT_EXPIRATION_PERIOD,
+        voting_period: Duration::Height(DEFAULT_VOTING_PERIOD),
+        timelock_period: Duration::Height(DEFAULT_TIMELOCK_PERIOD),
+        expiration_period: Duration::Height(DEFAULT_EXPIRATION_PERIOD),
         proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        guardian: None,
     };
 
     let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -212,6 +225,7 @@ fn fails_contract_already_registered() {
         glow_token: VOTING_TOKEN.to_string(),
         ve_token: VE_TOKEN.to_string(),
         terraswap_factory: TERRASWAP_FACTORY.to_string(),
+        community_contract: COMMUNITY.to_string(),
     };
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -337,6 +351,7 @@ fn fails_create_poll_invalid_deposit() {
             description: "TESTTEST".to_string(),
             link: None,
             execute_msgs: None,
+            category: None,
         })
         .unwrap(),
     });
@@ -363,6 +378,7 @@ fn create_poll_msg(
             description,
             link,
             execute_msgs: execute_msg,
+            category: None,
         })
         .unwrap(),
     })
@@ -412,20 +428,23 @@ fn query_polls() {
     .unwrap();
 
     let execute_msgs: Vec<PollExecuteMsg> = vec![
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 1u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz,
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 3u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz3,
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 2u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz2,
+            funds: vec![],
         },
     ];
 
@@ -459,8 +478,9 @@ fn query_polls() {
                 id: 1u64,
                 creator: TEST_CREATOR.to_string(),
                 status: PollStatus::InProgress,
+                category: PollCategory::Parameter,
                 start_time: 10000u64,
-                end_height: 10000u64,
+                end: Expiration::AtHeight(10000u64),
                 title: "test".to_string(),
                 description: "test".to_string(),
                 link: Some("http://google.com".to_string()),
@@ -468,15 +488,20 @@ fn query_polls() {
                 execute_data: Some(execute_msgs.clone()),
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                abstain_votes: Uint128::zero(),
                 staked_amount: Some(Uint128::zero()),
                 total_balance_at_end_poll: None,
+                options: None,
+                winning_option: None,
+                execution_results: None,
             },
             PollResponse {
                 id: 2u64,
                 creator: TEST_CREATOR.to_string(),
                 status: PollStatus::InProgress,
+                category: PollCategory::Text,
                 start_time: 10000u64,
-                end_height: 10000u64,
+                end: Expiration::AtHeight(10000u64),
                 title: "test2".to_string(),
                 description: "test2".to_string(),
                 link: None,
@@ -484,8 +509,12 @@ fn query_polls() {
                 execute_data: None,
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                abstain_votes: Uint128::zero(),
                 staked_amount: Some(Uint128::zero()),
                 total_balance_at_end_poll: None,
+                options: None,
+                winning_option: None,
+                execution_results: None,
             },
         ]
     );
@@ -508,8 +537,9 @@ fn query_polls() {
             id: 2u64,
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
+            category: PollCategory::Text,
             start_time: 10000u64,
-            end_height: 10000u64,
+            end: Expiration::AtHeight(10000u64),
             title: "test2".to_string(),
             description: "test2".to_string(),
             link: None,
@@ -517,8 +547,12 @@ fn query_polls() {
             execute_data: None,
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            abstain_votes: Uint128::zero(),
             staked_amount: Some(Uint128::zero()),
             total_balance_at_end_poll: None,
+            options: None,
+            winning_option: None,
+            execution_results: None,
         },]
     );
 
@@ -540,8 +574,9 @@ fn query_polls() {
             id: 1u64,
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
+            category: PollCategory::Parameter,
             start_time: 10000u64,
-            end_height: 10000u64,
+            end: Expiration::AtHeight(10000u64),
             title: "test".to_string(),
             description: "test".to_string(),
             link: Some("http://google.com".to_string()),
@@ -549,8 +584,12 @@ fn query_polls() {
             execute_data: Some(execute_msgs),
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            abstain_votes: Uint128::zero(),
             staked_amount: Some(Uint128::zero()),
             total_balance_at_end_poll: None,
+            options: None,
+            winning_option: None,
+            execution_results: None,
         }]
     );
 
@@ -572,8 +611,9 @@ fn query_polls() {
             id: 2u64,
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
+            category: PollCategory::Text,
             start_time: 10000u64,
-            end_height: 10000u64,
+            end: Expiration::AtHeight(10000u64),
             title: "test2".to_string(),
             description: "test2".to_string(),
             link: None,
@@ -581,8 +621,12 @@ fn query_polls() {
             execute_data: None,
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            abstain_votes: Uint128::zero(),
             staked_amount: Some(Uint128::zero()),
             total_balance_at_end_poll: None,
+            options: None,
+            winning_option: None,
+            execution_results: None,
         },]
     );
 
@@ -643,7 +687,7 @@ fn fails_end_poll_before_end_height() {
 
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
     let value: PollResponse = from_binary(&res).unwrap();
-    assert_eq!(DEFAULT_VOTING_PERIOD, value.end_height);
+    assert_eq!(Expiration::AtHeight(DEFAULT_VOTING_PERIOD), value.end);
 
     let msg = ExecuteMsg::EndPoll { poll_id: 1 };
     let env = mock_env_height(0, 10000);
@@ -704,20 +748,23 @@ fn happy_days_end_poll() {
 
     //add three messages with different order
     let execute_msgs: Vec<PollExecuteMsg> = vec![
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 3u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz3.clone(),
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 2u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz2.clone(),
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 1u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz.clone(),
+            funds: vec![],
         },
     ];
 
@@ -848,21 +895,30 @@ fn happy_days_end_poll() {
     assert_eq!(
         execute_res.messages,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz2,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz3,
-                funds: vec![],
-            }))
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz,
+                    funds: vec![],
+                }),
+                (1 << 8) | 2
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz2,
+                    funds: vec![],
+                }),
+                (1 << 8) | 1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz3,
+                    funds: vec![],
+                }),
+                1 << 8
+            )
         ]
     );
     assert_eq!(
@@ -938,10 +994,183 @@ fn happy_days_end_poll() {
         VoterInfo {
             vote: VoteOption::Yes,
             balance: Uint128::from(stake_amount),
+            weights: None,
+            delegated_balance: Uint128::zero(),
         }
     );
 }
 
+#[test]
+fn happy_days_time_based_poll() {
+    const POLL_START_TIME: u64 = 10000;
+    const VOTING_PERIOD: u64 = 86400;
+    const TIMELOCK_PERIOD: u64 = 3600;
+    let stake_amount = 1000;
+
+    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+    let msg = InstantiateMsg {
+        voting_period: Duration::Time(VOTING_PERIOD),
+        timelock_period: Duration::Time(TIMELOCK_PERIOD),
+        expiration_period: Duration::Time(DEFAULT_EXPIRATION_PERIOD),
+        ..instantiate_msg()
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT as u128),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(
+                &TEST_VOTER.to_string(),
+                &Uint128::from((stake_amount) as u128),
+            )],
+        ),
+    ]);
+
+    let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(123),
+    })
+    .unwrap();
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(vec![PollExecuteMsg::WasmExecute {
+            order: 1u64,
+            contract: VOTING_TOKEN.to_string(),
+            msg: exec_msg_bz.clone(),
+            funds: vec![],
+        }]),
+    );
+    let mut creator_env = mock_env_height(1000, POLL_START_TIME);
+    let creator_info = mock_info(VOTING_TOKEN, &[]);
+    let execute_res = execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "create_poll"),
+            attr("creator", TEST_CREATOR),
+            attr("poll_id", "1"),
+            attr("end_time", (POLL_START_TIME + VOTING_PERIOD).to_string()),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        Expiration::AtTime(Timestamp::from_seconds(POLL_START_TIME + VOTING_PERIOD)),
+        value.end
+    );
+
+    // votes are accepted until the end time, whatever the block height
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+    let env = mock_env_height(1_000_000, POLL_START_TIME + VOTING_PERIOD);
+    let info = mock_info(TEST_VOTER, &[]);
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, stake_amount, 1, VoteOption::Yes, execute_res);
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let env = mock_env_height(1_000_000, POLL_START_TIME + VOTING_PERIOD - 1);
+    let info = mock_info(TEST_CREATOR, &[]);
+    match execute(deps.as_mut(), env, info, msg) {
+        Err(ContractError::PollVotingPeriod {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    creator_env.block.time = creator_env.block.time.plus_seconds(VOTING_PERIOD);
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+    };
+    let mut env = creator_env.clone();
+    env.block.time = env.block.time.plus_seconds(1);
+    let info = mock_info(TEST_VOTER_2, &[]);
+    match execute(deps.as_mut(), env, info, msg) {
+        Err(ContractError::PollNotInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let execute_res = execute(deps.as_mut(), creator_env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
+    );
+
+    // the timelock also runs on time
+    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    match execute(deps.as_mut(), creator_env.clone(), info.clone(), msg) {
+        Err(ContractError::TimelockNotExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    creator_env.block.time = creator_env.block.time.plus_seconds(TIMELOCK_PERIOD);
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), creator_env, info, msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::reply_always(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz,
+                funds: vec![],
+            }),
+            1 << 8
+        )]
+    );
+}
+
+#[test]
+fn fails_mixed_height_and_time_periods() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        voting_period: Duration::Time(DEFAULT_VOTING_PERIOD),
+        ..instantiate_msg()
+    };
+
+    match instantiate(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "periods must all be heights or all be times")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: Some(Duration::Time(DEFAULT_TIMELOCK_PERIOD)),
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+    };
+    match execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "periods must all be heights or all be times")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
 #[test]
 fn expire_poll() {
     const POLL_START_HEIGHT: u64 = 1000;
@@ -976,10 +1205,11 @@ fn expire_poll() {
         amount: Uint128::new(123),
     })
     .unwrap();
-    let execute_msgs: Vec<PollExecuteMsg> = vec![PollExecuteMsg {
+    let execute_msgs: Vec<PollExecuteMsg> = vec![PollExecuteMsg::WasmExecute {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
         msg: exec_msg_bz,
+        funds: vec![],
     }];
     let msg = create_poll_msg(
         "test".to_string(),
@@ -1120,13 +1350,14 @@ fn end_poll_zero_quorum() {
     let mut creator_env = mock_env_height(1000, 10000);
     let mut creator_info = mock_info(VOTING_TOKEN, &[]);
 
-    let execute_msgs: Vec<PollExecuteMsg> = vec![PollExecuteMsg {
+    let execute_msgs: Vec<PollExecuteMsg> = vec![PollExecuteMsg::WasmExecute {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
         msg: to_binary(&Cw20ExecuteMsg::Burn {
             amount: Uint128::new(123),
         })
         .unwrap(),
+        funds: vec![],
     }];
 
     let msg = create_poll_msg(
@@ -1440,152 +1671,1326 @@ fn end_poll_nay_rejected() {
 }
 
 #[test]
-fn happy_days_cast_vote() {
+fn end_poll_abstain_counts_toward_quorum() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_contracts(deps.as_mut());
 
-    let env = mock_env_height(0, 10000);
-    let info = mock_info(VOTING_TOKEN, &[]);
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
-
-    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_create_poll_result(
-        1,
-        DEFAULT_VOTING_PERIOD,
-        TEST_CREATOR,
-        execute_res,
-        deps.as_ref(),
-    );
-
     deps.querier.with_token_balances(&[
         (
             &VOTING_TOKEN.to_string(),
             &[(
                 &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             )],
         ),
         (
             &VE_TOKEN.to_string(),
-            &[(&TEST_VOTER.to_string(), &Uint128::from(6u128))],
+            &[
+                (&TEST_VOTER.to_string(), &Uint128::from(100u128)),
+                (&TEST_VOTER_2.to_string(), &Uint128::from(300u128)),
+                (&TEST_VOTER_3.to_string(), &Uint128::from(600u128)),
+            ],
         ),
     ]);
 
-    assert_stake_tokens_result(6, DEFAULT_PROPOSAL_DEPOSIT, 6, 1, deps.as_ref());
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let mut creator_env = mock_env();
+    let mut creator_info = mock_info(VOTING_TOKEN, &[]);
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
 
-    let env = mock_env_height(0, 10000);
-    let info = mock_info(TEST_VOTER, &coins(11, VOTING_TOKEN));
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
     };
+    let execute_res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 100, 1, VoteOption::Yes, execute_res);
 
-    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_cast_vote_success(TEST_VOTER, 6, 1, VoteOption::Yes, execute_res);
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Abstain,
+    };
+    let execute_res =
+        execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER_2, 300, 1, VoteOption::Abstain, execute_res);
 
-    // balance be double
-    deps.querier.with_token_balances(&[(
-        &VOTING_TOKEN.to_string(),
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(22u128 + DEFAULT_PROPOSAL_DEPOSIT),
-        )],
-    )]);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::from(100u128));
+    assert_eq!(value.no_votes, Uint128::zero());
+    assert_eq!(value.abstain_votes, Uint128::from(300u128));
 
-    // Query voters
-    let res = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::Voters {
-            poll_id: 1u64,
-            start_after: None,
-            limit: None,
-            order_by: Some(OrderBy::Desc),
-        },
-    )
-    .unwrap();
-    let response: VotersResponse = from_binary(&res).unwrap();
+    // Yes votes alone are 10% of the voting power, but abstain votes bring the
+    // participation to 40%. The threshold only considers yes and no votes
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
     assert_eq!(
-        response.voters,
-        vec![VotersResponseItem {
-            voter: TEST_VOTER.to_string(),
-            vote: VoteOption::Yes,
-            balance: Uint128::from(6u128),
-        }]
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
     );
-
-    let res = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::Voters {
-            poll_id: 1u64,
-            start_after: Some(TEST_VOTER.to_string()),
-            limit: None,
-            order_by: None,
-        },
-    )
-    .unwrap();
-    let response: VotersResponse = from_binary(&res).unwrap();
-    assert_eq!(response.voters.len(), 0);
 }
 
 #[test]
-fn fails_cast_vote_twice() {
+fn end_poll_only_abstain_rejected() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_contracts(deps.as_mut());
 
-    let env = mock_env_height(0, 10000);
-    let info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
-
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
-    let execute_res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-
-    assert_create_poll_result(
-        1,
-        env.block.height + DEFAULT_VOTING_PERIOD,
-        TEST_CREATOR,
-        execute_res,
-        deps.as_ref(),
-    );
-
     deps.querier.with_token_balances(&[
         (
             &VOTING_TOKEN.to_string(),
             &[(
                 &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             )],
         ),
         (
             &VE_TOKEN.to_string(),
-            &[(&TEST_VOTER.to_string(), &Uint128::from(11u128))],
+            &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
         ),
     ]);
 
-    assert_stake_tokens_result(11, DEFAULT_PROPOSAL_DEPOSIT, 11, 1, deps.as_ref());
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let mut creator_env = mock_env();
+    let mut creator_info = mock_info(VOTING_TOKEN, &[]);
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
 
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
-        vote: VoteOption::Yes,
+        vote: VoteOption::Abstain,
     };
-    let env = mock_env_height(0, 10000);
-    let info = mock_info(TEST_VOTER, &[]);
-    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-    assert_cast_vote_success(TEST_VOTER, 11u128, 1, VoteOption::Yes, execute_res);
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Threshold not reached"),
+            attr("passed", "false"),
+        ]
+    );
+}
+
+#[test]
+fn happy_days_cast_weighted_vote() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let invalid_weights = vec![
+        vec![],
+        vec![
+            (VoteOption::Yes, Decimal::percent(60)),
+            (VoteOption::No, Decimal::percent(30)),
+        ],
+        vec![
+            (VoteOption::Yes, Decimal::percent(50)),
+            (VoteOption::Yes, Decimal::percent(50)),
+        ],
+        vec![
+            (VoteOption::Yes, Decimal::one()),
+            (VoteOption::No, Decimal::zero()),
+        ],
+    ];
+    for weights in invalid_weights {
+        let msg = ExecuteMsg::CastWeightedVote {
+            poll_id: 1,
+            weights,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg);
+        assert_eq!(res, Err(ContractError::InvalidVoteWeights {}));
+    }
+
+    let weights = vec![
+        (VoteOption::No, Decimal::percent(30)),
+        (VoteOption::Yes, Decimal::percent(60)),
+        (VoteOption::Abstain, Decimal::percent(10)),
+    ];
+    let msg = ExecuteMsg::CastWeightedVote {
+        poll_id: 1,
+        weights: weights.clone(),
+    };
+    let execute_res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", "1"),
+            attr("amount", "100"),
+            attr("voter", TEST_VOTER),
+            attr("vote_option", "yes"),
+            attr("vote_weights", "no:0.3,yes:0.6,abstain:0.1"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::from(60u128));
+    assert_eq!(value.no_votes, Uint128::from(30u128));
+    assert_eq!(value.abstain_votes, Uint128::from(10u128));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Voters {
+            poll_id: 1u64,
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: VotersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.voters,
+        vec![VotersResponseItem {
+            voter: TEST_VOTER.to_string(),
+            vote: VoteOption::Yes,
+            balance: Uint128::from(100u128),
+            weights: Some(weights),
+            delegated_balance: Uint128::zero(),
+        }]
+    );
+
+    // Changing the vote takes the whole weighted vote off the tally
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::zero());
+    assert_eq!(value.no_votes, Uint128::from(100u128));
+    assert_eq!(value.abstain_votes, Uint128::zero());
+}
+
+fn create_multiple_choice_poll_msg(options: Vec<PollOptionMsg>) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: to_binary(&Cw20HookMsg::CreateMultipleChoicePoll {
+            title: "test".to_string(),
+            description: "test".to_string(),
+            link: None,
+            options,
+            category: None,
+        })
+        .unwrap(),
+    })
+}
+
+#[test]
+fn happy_days_multiple_choice_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[
+                (&TEST_VOTER.to_string(), &Uint128::from(100u128)),
+                (&TEST_VOTER_2.to_string(), &Uint128::from(200u128)),
+                (&TEST_VOTER_3.to_string(), &Uint128::from(300u128)),
+            ],
+        ),
+    ]);
+
+    // A multiple choice poll needs at least two options
+    let msg = create_multiple_choice_poll_msg(vec![PollOptionMsg {
+        label: "a".to_string(),
+        execute_msgs: None,
+    }]);
+    let res = execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg);
+    assert_eq!(res, Err(ContractError::InvalidPollOptions(16)));
+
+    let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(123),
+    })
+    .unwrap();
+    let options = vec![
+        PollOptionMsg {
+            label: "a".to_string(),
+            execute_msgs: None,
+        },
+        PollOptionMsg {
+            label: "b".to_string(),
+            execute_msgs: Some(vec![PollExecuteMsg::WasmExecute {
+                order: 1u64,
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz.clone(),
+                funds: vec![],
+            }]),
+        },
+        PollOptionMsg {
+            label: "c".to_string(),
+            execute_msgs: None,
+        },
+    ];
+    let msg = create_multiple_choice_poll_msg(options);
+    let mut creator_env = mock_env();
+    let mut creator_info = mock_info(VOTING_TOKEN, &[]);
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+
+    // Only choices of the poll options are valid
+    for vote in [VoteOption::Yes, VoteOption::Choice(3)] {
+        let msg = ExecuteMsg::CastVote { poll_id: 1, vote };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg);
+        assert_eq!(res, Err(ContractError::InvalidVoteOption {}));
+    }
+
+    for (voter, vote) in [
+        (TEST_VOTER, VoteOption::Choice(0)),
+        (TEST_VOTER_2, VoteOption::Choice(1)),
+        (TEST_VOTER_3, VoteOption::Choice(1)),
+    ] {
+        let msg = ExecuteMsg::CastVote { poll_id: 1, vote };
+        execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), msg).unwrap();
+    }
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value
+            .options
+            .unwrap()
+            .iter()
+            .map(|option| (option.label.as_str(), option.votes))
+            .collect::<Vec<(&str, Uint128)>>(),
+        vec![
+            ("a", Uint128::from(100u128)),
+            ("b", Uint128::from(500u128)),
+            ("c", Uint128::zero()),
+        ]
+    );
+
+    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+            attr("winning_option", "1"),
+        ]
+    );
+
+    // The messages of the winning option are executed
+    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::reply_always(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz,
+                funds: vec![],
+            }),
+            1 << 8
+        )]
+    );
+}
+
+#[test]
+fn end_multiple_choice_poll_tied_rejected() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[
+                (&TEST_VOTER.to_string(), &Uint128::from(100u128)),
+                (&TEST_VOTER_2.to_string(), &Uint128::from(100u128)),
+            ],
+        ),
+    ]);
+
+    let options = vec![
+        PollOptionMsg {
+            label: "a".to_string(),
+            execute_msgs: None,
+        },
+        PollOptionMsg {
+            label: "b".to_string(),
+            execute_msgs: None,
+        },
+    ];
+    let msg = create_multiple_choice_poll_msg(options);
+    let mut creator_env = mock_env();
+    let mut creator_info = mock_info(VOTING_TOKEN, &[]);
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+
+    for (voter, vote) in [
+        (TEST_VOTER, VoteOption::Choice(0)),
+        (TEST_VOTER_2, VoteOption::Choice(1)),
+    ] {
+        let msg = ExecuteMsg::CastVote { poll_id: 1, vote };
+        execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), msg).unwrap();
+    }
+
+    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "No winning option"),
+            attr("passed", "false"),
+        ]
+    );
+}
+
+#[test]
+fn happy_days_cast_vote() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_create_poll_result(
+        1,
+        DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        execute_res,
+        deps.as_ref(),
+    );
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(6u128))],
+        ),
+    ]);
+
+    assert_stake_tokens_result(6, DEFAULT_PROPOSAL_DEPOSIT, 6, 1, deps.as_ref());
+
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(TEST_VOTER, &coins(11, VOTING_TOKEN));
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 6, 1, VoteOption::Yes, execute_res);
+
+    // balance be double
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(22u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    // Query voters
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Voters {
+            poll_id: 1u64,
+            start_after: None,
+            limit: None,
+            order_by: Some(OrderBy::Desc),
+        },
+    )
+    .unwrap();
+    let response: VotersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.voters,
+        vec![VotersResponseItem {
+            voter: TEST_VOTER.to_string(),
+            vote: VoteOption::Yes,
+            balance: Uint128::from(6u128),
+            weights: None,
+            delegated_balance: Uint128::zero(),
+        }]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Voters {
+            poll_id: 1u64,
+            start_after: Some(TEST_VOTER.to_string()),
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: VotersResponse = from_binary(&res).unwrap();
+    assert_eq!(response.voters.len(), 0);
+}
+
+#[test]
+fn happy_days_delegated_votes() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[
+                (&TEST_VOTER.to_string(), &Uint128::from(100u128)),
+                (&TEST_VOTER_2.to_string(), &Uint128::from(200u128)),
+                (&TEST_VOTER_3.to_string(), &Uint128::from(300u128)),
+            ],
+        ),
+    ]);
+
+    let msg = ExecuteMsg::Delegate {
+        to: TEST_VOTER.to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg);
+    assert_eq!(res, Err(ContractError::CannotDelegateToSelf {}));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::Undelegate {},
+    );
+    assert_eq!(res, Err(ContractError::NotDelegating {}));
+
+    for delegator in [TEST_VOTER_2, TEST_VOTER_3] {
+        let msg = ExecuteMsg::Delegate {
+            to: TEST_VOTER.to_string(),
+        };
+        let execute_res =
+            execute(deps.as_mut(), mock_env(), mock_info(delegator, &[]), msg).unwrap();
+        assert_eq!(
+            execute_res.attributes,
+            vec![
+                attr("action", "delegate"),
+                attr("delegator", delegator),
+                attr("delegate", TEST_VOTER),
+            ]
+        );
+    }
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Delegators {
+            delegate: TEST_VOTER.to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let mut response: DelegatorsResponse = from_binary(&res).unwrap();
+    response.delegators.sort();
+    assert_eq!(
+        response.delegators,
+        vec![TEST_VOTER_2.to_string(), TEST_VOTER_3.to_string()]
+    );
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    // The delegate votes with the voting power of its delegators
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+    let execute_res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", "1"),
+            attr("amount", "100"),
+            attr("voter", TEST_VOTER),
+            attr("vote_option", "yes"),
+            attr("delegated_amount", "500"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::from(600u128));
+
+    // A delegator voting directly overrides the vote of the delegate
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+    };
+    let execute_res =
+        execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_3, &[]), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER_3, 300, 1, VoteOption::No, execute_res);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::from(300u128));
+    assert_eq!(value.no_votes, Uint128::from(300u128));
+
+    let voter_addr_raw = deps.api.addr_canonicalize(TEST_VOTER).unwrap();
+    let voter = poll_voter_read(&deps.storage, 1u64)
+        .load(voter_addr_raw.as_slice())
+        .unwrap();
+    assert_eq!(
+        voter,
+        VoterInfo {
+            vote: VoteOption::Yes,
+            balance: Uint128::from(100u128),
+            weights: None,
+            delegated_balance: Uint128::from(200u128),
+        }
+    );
+
+    // Undelegating keeps the votes already cast by the delegate
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER_2, &[]),
+        ExecuteMsg::Undelegate {},
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "undelegate"),
+            attr("delegator", TEST_VOTER_2),
+            attr("delegate", TEST_VOTER),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Delegation {
+            address: TEST_VOTER_2.to_string(),
+        },
+    )
+    .unwrap();
+    let response: DelegationResponse = from_binary(&res).unwrap();
+    assert_eq!(response.delegate, None);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::from(300u128));
+
+    // Until the delegator votes directly
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Abstain,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::from(100u128));
+    assert_eq!(value.no_votes, Uint128::from(300u128));
+    assert_eq!(value.abstain_votes, Uint128::from(200u128));
+}
+
+#[test]
+fn tally_delegations() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let delegators: Vec<String> = (0..31).map(|i| format!("delegator{:02}", i)).collect();
+    let mut balances: Vec<(String, Uint128)> = delegators
+        .iter()
+        .map(|delegator| (delegator.clone(), Uint128::from(10u128)))
+        .collect();
+    balances.push((TEST_VOTER.to_string(), Uint128::from(100u128)));
+    let balance_refs: Vec<(&String, &Uint128)> = balances
+        .iter()
+        .map(|(address, balance)| (address, balance))
+        .collect();
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        ),
+        (&VE_TOKEN.to_string(), &balance_refs),
+    ]);
+
+    // Delegating requires voting power
+    let msg = ExecuteMsg::Delegate {
+        to: TEST_VOTER.to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg);
+    assert_eq!(res, Err(ContractError::NoVotingPower {}));
+
+    // A delegate accepts any number of delegators
+    for delegator in &delegators {
+        let msg = ExecuteMsg::Delegate {
+            to: TEST_VOTER.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(delegator, &[]), msg).unwrap();
+    }
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    // Only a delegate that voted can have its delegations tallied
+    let msg = ExecuteMsg::TallyDelegations {
+        poll_id: 1,
+        delegate: TEST_VOTER.to_string(),
+        start_after: None,
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg);
+    assert_eq!(res, Err(ContractError::DelegateNotVoted {}));
+
+    // Voting counts the first 30 delegators
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+    let execute_res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", "1"),
+            attr("amount", "100"),
+            attr("voter", TEST_VOTER),
+            attr("vote_option", "yes"),
+            attr("delegated_amount", "300"),
+        ]
+    );
+
+    // Anyone can tally the rest, skipping the delegators already counted
+    let msg = ExecuteMsg::TallyDelegations {
+        poll_id: 1,
+        delegate: TEST_VOTER.to_string(),
+        start_after: None,
+        limit: None,
+    };
+    let execute_res =
+        execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+    assert_eq!(execute_res.attributes[3], attr("delegated_amount", "0"));
+    let mut start_after = execute_res.attributes[4].value.clone();
+    let mut tallied = 0u128;
+    loop {
+        let msg = ExecuteMsg::TallyDelegations {
+            poll_id: 1,
+            delegate: TEST_VOTER.to_string(),
+            start_after: Some(start_after.clone()),
+            limit: None,
+        };
+        let execute_res =
+            execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+        tallied += execute_res.attributes[3].value.parse::<u128>().unwrap();
+        match execute_res.attributes.get(4) {
+            Some(last_delegator) => start_after = last_delegator.value.clone(),
+            None => break,
+        }
+    }
+    assert_eq!(tallied, 10u128);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::from(410u128));
+
+    let voter_addr_raw = deps.api.addr_canonicalize(TEST_VOTER).unwrap();
+    let voter = poll_voter_read(&deps.storage, 1u64)
+        .load(voter_addr_raw.as_slice())
+        .unwrap();
+    assert_eq!(voter.delegated_balance, Uint128::from(310u128));
+}
+
+#[test]
+fn happy_days_change_vote() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let execute_res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    assert_create_poll_result(
+        1,
+        env.block.height + DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        execute_res,
+        deps.as_ref(),
+    );
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(11u128))],
+        ),
+    ]);
+
+    assert_stake_tokens_result(11, DEFAULT_PROPOSAL_DEPOSIT, 11, 1, deps.as_ref());
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(TEST_VOTER, &[]);
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 11u128, 1, VoteOption::Yes, execute_res);
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+    };
+    let execute_res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 11u128, 1, VoteOption::No, execute_res.clone());
+    assert_eq!(
+        execute_res.events,
+        vec![Event::new("vote_changed").add_attributes(vec![
+            attr("poll_id", "1"),
+            attr("voter", TEST_VOTER),
+            attr("previous_vote_option", "yes"),
+            attr("vote_option", "no"),
+        ])]
+    );
+
+    // The balance moves from the old side of the tally to the new one
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::zero());
+    assert_eq!(value.no_votes, Uint128::from(11u128));
+
+    // Votes can't be changed after the poll end height
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+    let env = mock_env_height(DEFAULT_VOTING_PERIOD + 1, 10000);
+    let res = execute(deps.as_mut(), env, info, msg);
+    assert_eq!(res, Err(ContractError::PollNotInProgress {}));
+}
+
+#[test]
+fn veto_poll() {
+    const POLL_START_HEIGHT: u64 = 1000;
+    let stake_amount = 1000;
+
+    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT as u128),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(
+                &TEST_VOTER.to_string(),
+                &Uint128::from((stake_amount) as u128),
+            )],
+        ),
+    ]);
+
+    let execute_msgs = vec![PollExecuteMsg::WasmExecute {
+        order: 1u64,
+        contract: VOTING_TOKEN.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn {
+            amount: Uint128::new(123),
+        })
+        .unwrap(),
+        funds: vec![],
+    }];
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs),
+    );
+    let mut creator_env = mock_env_height(POLL_START_HEIGHT, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
 
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
     };
-    let res = execute(deps.as_mut(), env, info, msg);
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
 
-    match res {
-        Ok(_) => panic!("Must return error"),
-        Err(ContractError::AlreadyVoted {}) => (),
-        Err(e) => panic!("Unexpected error: {:?}", e),
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
+
+    // no guardian is set
+    let msg = ExecuteMsg::VetoPoll { poll_id: 1 };
+    let info = mock_info(TEST_VOTER_2, &[]);
+    match execute(deps.as_mut(), creator_env.clone(), info, msg) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the owner can set the guardian
+    let msg = ExecuteMsg::UpdateGuardian {
+        guardian: Some(TEST_VOTER_2.to_string()),
+    };
+    let info = mock_info(TEST_VOTER_2, &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(TEST_VOTER_2.to_string()), config.guardian);
+
+    let msg = ExecuteMsg::VetoPoll { poll_id: 1 };
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(deps.as_mut(), creator_env.clone(), info, msg) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the guardian can only veto during the timelock
+    let mut env = creator_env.clone();
+    env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    let msg = ExecuteMsg::VetoPoll { poll_id: 1 };
+    let info = mock_info(TEST_VOTER_2, &[]);
+    match execute(deps.as_mut(), env, info.clone(), msg.clone()) {
+        Err(ContractError::TimelockExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let execute_res = execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![attr("action", "veto_poll"), attr("poll_id", "1")]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Vetoed),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 1);
+    assert_eq!(response.polls[0].status, PollStatus::Vetoed);
+
+    // a vetoed poll cannot be executed
+    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let info = mock_info(TEST_CREATOR, &[]);
+    match execute(deps.as_mut(), creator_env, info, msg) {
+        Err(ContractError::PollNotPassed {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn cancel_poll() {
+    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[(
+        &VE_TOKEN.to_string(),
+        &[(&TEST_VOTER.to_string(), &Uint128::from(1000u128))],
+    )]);
+
+    let env = mock_env_height(0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    for _ in 0..4 {
+        execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    }
+
+    // only the creator can cancel the poll
+    let msg = ExecuteMsg::CancelPoll { poll_id: 1 };
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(deps.as_mut(), env.clone(), info, msg.clone()) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cancel_poll"),
+            attr("poll_id", "1"),
+            attr("refund_amount", DEFAULT_PROPOSAL_DEPOSIT.to_string()),
+        ]
+    );
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollStatus::Cancelled, value.status);
+
+    let state: State = state_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(
+        Uint128::from(DEFAULT_PROPOSAL_DEPOSIT * 3),
+        state.total_deposit
+    );
+
+    // a cancelled poll cannot be cancelled again
+    match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+        Err(ContractError::PollNotInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // a poll with votes cannot be cancelled
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 2,
+        vote: VoteOption::No,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::CancelPoll { poll_id: 2 };
+    match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+        Err(ContractError::PollHasVotes {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // a vote without voting power doesn't block the cancel
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 3,
+        vote: VoteOption::Yes,
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_2, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::CancelPoll { poll_id: 3 };
+    execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+    // a poll cannot be cancelled once its voting period is over
+    let msg = ExecuteMsg::CancelPoll { poll_id: 4 };
+    match execute(
+        deps.as_mut(),
+        mock_env_height(DEFAULT_VOTING_PERIOD + 1, 10000),
+        info,
+        msg,
+    ) {
+        Err(ContractError::PollNotInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn poll_category_parameters() {
+    let treasury_config = PollCategoryConfig {
+        quorum: Decimal::percent(50),
+        threshold: Decimal::percent(60),
+        proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT * 2),
+        voting_period: Duration::Height(DEFAULT_VOTING_PERIOD * 2),
+        timelock_period: Duration::Height(DEFAULT_TIMELOCK_PERIOD / 2),
+    };
+
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[(
+        &VE_TOKEN.to_string(),
+        &[
+            (&TEST_VOTER.to_string(), &Uint128::from(400u128)),
+            (&TEST_VOTER_2.to_string(), &Uint128::from(600u128)),
+        ],
+    )]);
+
+    // only the owner can configure categories
+    let msg = ExecuteMsg::UpdatePollCategory {
+        category: PollCategory::Treasury,
+        config: Some(treasury_config.clone()),
+    };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    // the community contract is registered as a treasury contract
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ContractCategory {
+            contract: COMMUNITY.to_string(),
+        },
+    )
+    .unwrap();
+    let response: ContractCategoryResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(PollCategory::Treasury), response.category);
+
+    let msg = ExecuteMsg::UpdateContractCategory {
+        contract: COMMUNITY.to_string(),
+        category: Some(PollCategory::Treasury),
+    };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    // categories without parameters use the config
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PollCategory {
+            category: PollCategory::Parameter,
+        },
+    )
+    .unwrap();
+    let response: PollCategoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response,
+        PollCategoryResponse {
+            category: PollCategory::Parameter,
+            quorum: Decimal::percent(DEFAULT_QUORUM),
+            threshold: Decimal::percent(DEFAULT_THRESHOLD),
+            proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            voting_period: Duration::Height(DEFAULT_VOTING_PERIOD),
+            timelock_period: Duration::Height(DEFAULT_TIMELOCK_PERIOD),
+        }
+    );
+
+    let treasury_poll_msg = |amount: u128, category: Option<PollCategory>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: TEST_CREATOR.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                link: None,
+                execute_msgs: Some(vec![PollExecuteMsg::WasmExecute {
+                    order: 1u64,
+                    contract: COMMUNITY.to_string(),
+                    msg: Binary::default(),
+                    funds: vec![],
+                }]),
+                category,
+            })
+            .unwrap(),
+        })
+    };
+
+    let env = mock_env_height(1000, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let msg = treasury_poll_msg(DEFAULT_PROPOSAL_DEPOSIT * 2, Some(PollCategory::Parameter));
+    match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+        Err(ContractError::InvalidPollCategory(PollCategory::Treasury)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // messages to gov itself are code upgrades
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT * 2),
+        msg: to_binary(&Cw20HookMsg::CreatePoll {
+            title: "test".to_string(),
+            description: "test".to_string(),
+            link: None,
+            execute_msgs: Some(vec![PollExecuteMsg::WasmExecute {
+                order: 1u64,
+                contract: MOCK_CONTRACT_ADDR.to_string(),
+                msg: Binary::default(),
+                funds: vec![],
+            }]),
+            category: Some(PollCategory::Treasury),
+        })
+        .unwrap(),
+    });
+    match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+        Err(ContractError::InvalidPollCategory(PollCategory::CodeUpgrade)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = treasury_poll_msg(DEFAULT_PROPOSAL_DEPOSIT, None);
+    match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+        Err(ContractError::InsufficientProposalDeposit(amount)) => {
+            assert_eq!(amount, DEFAULT_PROPOSAL_DEPOSIT * 2)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
     }
+
+    let msg = treasury_poll_msg(DEFAULT_PROPOSAL_DEPOSIT * 2, None);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollCategory::Treasury, value.category);
+    assert_eq!(
+        Expiration::AtHeight(env.block.height + DEFAULT_VOTING_PERIOD * 2),
+        value.end
+    );
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    // 40% of the voting power would reach the quorum of the config, not the one of treasury polls
+    let mut env = env;
+    env.block.height += DEFAULT_VOTING_PERIOD * 2;
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Quorum not reached"),
+            attr("passed", "false"),
+        ]
+    );
 }
 
 #[test]
@@ -1681,6 +3086,19 @@ fn assert_cast_vote_success(
     );
 }
 
+#[test]
+fn sweep_is_owner_only() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let msg = ExecuteMsg::Sweep {
+        denom: "uusd".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg);
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+}
+
 #[test]
 fn update_config() {
     let mut deps = mock_dependencies(&[]);
@@ -1709,8 +3127,14 @@ fn update_config() {
     assert_eq!("addr0001", config.owner.as_str());
     assert_eq!(Decimal::percent(DEFAULT_QUORUM), config.quorum);
     assert_eq!(Decimal::percent(DEFAULT_THRESHOLD), config.threshold);
-    assert_eq!(DEFAULT_VOTING_PERIOD, config.voting_period);
-    assert_eq!(DEFAULT_TIMELOCK_PERIOD, config.timelock_period);
+    assert_eq!(
+        Duration::Height(DEFAULT_VOTING_PERIOD),
+        config.voting_period
+    );
+    assert_eq!(
+        Duration::Height(DEFAULT_TIMELOCK_PERIOD),
+        config.timelock_period
+    );
     assert_eq!(DEFAULT_PROPOSAL_DEPOSIT, config.proposal_deposit.u128());
 
     // update left items
@@ -1719,9 +3143,9 @@ fn update_config() {
         owner: None,
         quorum: Some(Decimal::percent(20)),
         threshold: Some(Decimal::percent(75)),
-        voting_period: Some(20000u64),
-        timelock_period: Some(20000u64),
-        expiration_period: Some(30000u64),
+        voting_period: Some(Duration::Height(20000u64)),
+        timelock_period: Some(Duration::Height(20000u64)),
+        expiration_period: Some(Duration::Height(30000u64)),
         proposal_deposit: Some(Uint128::from(123u128)),
         snapshot_period: Some(11),
     };
@@ -1735,9 +3159,9 @@ fn update_config() {
     assert_eq!("addr0001", config.owner.as_str());
     assert_eq!(Decimal::percent(20), config.quorum);
     assert_eq!(Decimal::percent(75), config.threshold);
-    assert_eq!(20000u64, config.voting_period);
-    assert_eq!(20000u64, config.timelock_period);
-    assert_eq!(30000u64, config.expiration_period);
+    assert_eq!(Duration::Height(20000u64), config.voting_period);
+    assert_eq!(Duration::Height(20000u64), config.timelock_period);
+    assert_eq!(Duration::Height(30000u64), config.expiration_period);
     assert_eq!(123u128, config.proposal_deposit.u128());
     assert_eq!(11u64, config.snapshot_period);
 
@@ -1786,20 +3210,23 @@ fn add_several_execute_msgs() {
 
     // push two execute msgs to the list
     let execute_msgs: Vec<PollExecuteMsg> = vec![
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 1u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz,
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 3u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz3,
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 2u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz2,
+            funds: vec![],
         },
     ];
 
@@ -1827,6 +3254,366 @@ fn add_several_execute_msgs() {
     assert_eq!(response_execute_data, execute_msgs);
 }
 
+#[test]
+fn execute_poll_with_non_wasm_execute_msgs() {
+    let stake_amount = 1000;
+
+    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT as u128),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(
+                &TEST_VOTER.to_string(),
+                &Uint128::from((stake_amount) as u128),
+            )],
+        ),
+    ]);
+
+    let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(123),
+    })
+    .unwrap();
+    let execute_msgs = vec![
+        PollExecuteMsg::BankSend {
+            order: 5u64,
+            to_address: TEST_VOTER.to_string(),
+            amount: coins(100, "uusd"),
+        },
+        PollExecuteMsg::ClearAdmin {
+            order: 4u64,
+            contract: VE_TOKEN.to_string(),
+        },
+        PollExecuteMsg::UpdateAdmin {
+            order: 3u64,
+            contract: VOTING_TOKEN.to_string(),
+            admin: TEST_CREATOR.to_string(),
+        },
+        PollExecuteMsg::WasmMigrate {
+            order: 2u64,
+            contract: VOTING_TOKEN.to_string(),
+            new_code_id: 2u64,
+            msg: exec_msg_bz.clone(),
+        },
+        PollExecuteMsg::WasmExecute {
+            order: 1u64,
+            contract: VOTING_TOKEN.to_string(),
+            msg: exec_msg_bz.clone(),
+            funds: coins(10, "uusd"),
+        },
+    ];
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs.clone()),
+    );
+    let mut creator_env = mock_env_height(1000, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(execute_msgs), value.execute_data);
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
+
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), creator_env.clone(), info.clone(), msg).unwrap();
+
+    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), creator_env, info, msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz.clone(),
+                    funds: coins(10, "uusd"),
+                }),
+                (1 << 8) | 4
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Migrate {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    new_code_id: 2u64,
+                    msg: exec_msg_bz,
+                }),
+                (1 << 8) | 3
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::UpdateAdmin {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    admin: TEST_CREATOR.to_string(),
+                }),
+                (1 << 8) | 2
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::ClearAdmin {
+                    contract_addr: VE_TOKEN.to_string(),
+                }),
+                (1 << 8) | 1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: TEST_VOTER.to_string(),
+                    amount: coins(100, "uusd"),
+                }),
+                1 << 8
+            ),
+        ]
+    );
+}
+
+#[test]
+fn execute_poll_records_results_and_retries_failures() {
+    let stake_amount = 1000;
+
+    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT as u128),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(
+                &TEST_VOTER.to_string(),
+                &Uint128::from((stake_amount) as u128),
+            )],
+        ),
+    ]);
+
+    let execute_msgs = vec![
+        PollExecuteMsg::BankSend {
+            order: 2u64,
+            to_address: TEST_VOTER.to_string(),
+            amount: coins(100, "uusd"),
+        },
+        PollExecuteMsg::ClearAdmin {
+            order: 1u64,
+            contract: VE_TOKEN.to_string(),
+        },
+    ];
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs),
+    );
+    let mut creator_env = mock_env_height(1000, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
+
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), creator_env.clone(), info.clone(), msg).unwrap();
+
+    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), creator_env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(execute_res.messages.len(), 2);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollStatus::Executed, value.status);
+    assert_eq!(
+        Some(vec![ExecutionResult::Pending, ExecutionResult::Pending]),
+        value.execution_results
+    );
+
+    // the clear admin message succeeds, the bank send fails
+    let reply_res = reply(
+        deps.as_mut(),
+        creator_env.clone(),
+        Reply {
+            id: (1 << 8) | 1,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        reply_res.attributes,
+        vec![
+            attr("action", "execute_poll_msg"),
+            attr("poll_id", "1"),
+            attr("msg_index", "1"),
+            attr("succeeded", "true"),
+        ]
+    );
+    reply(
+        deps.as_mut(),
+        creator_env.clone(),
+        Reply {
+            id: 1 << 8,
+            result: ContractResult::Err("insufficient funds".to_string()),
+        },
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::ExecutionFailed),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 1);
+    assert_eq!(
+        Some(vec![
+            ExecutionResult::Failure {
+                error: "insufficient funds".to_string()
+            },
+            ExecutionResult::Success,
+        ]),
+        response.polls[0].execution_results
+    );
+
+    // only the failed message is sent again
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::reply_always(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: TEST_VOTER.to_string(),
+                amount: coins(100, "uusd"),
+            }),
+            1 << 8
+        )]
+    );
+
+    reply(
+        deps.as_mut(),
+        creator_env,
+        Reply {
+            id: 1 << 8,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollStatus::Executed, value.status);
+    assert_eq!(
+        Some(vec![ExecutionResult::Success, ExecutionResult::Success]),
+        value.execution_results
+    );
+}
+
+#[test]
+fn fails_create_poll_invalid_execute_msgs() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let invalid_msgs = vec![
+        (
+            PollExecuteMsg::WasmExecute {
+                order: 1u64,
+                contract: VOTING_TOKEN.to_string(),
+                msg: Binary::default(),
+                funds: coins(0, "uusd"),
+            },
+            "funds must not contain zero amounts",
+        ),
+        (
+            PollExecuteMsg::WasmMigrate {
+                order: 1u64,
+                contract: VOTING_TOKEN.to_string(),
+                new_code_id: 0u64,
+                msg: Binary::default(),
+            },
+            "new_code_id must be set",
+        ),
+        (
+            PollExecuteMsg::BankSend {
+                order: 1u64,
+                to_address: TEST_VOTER.to_string(),
+                amount: vec![],
+            },
+            "amount must be non-zero",
+        ),
+    ];
+
+    for (execute_msg, reason) in invalid_msgs {
+        let msg = create_poll_msg(
+            "test".to_string(),
+            "test".to_string(),
+            None,
+            Some(vec![execute_msg]),
+        );
+        let info = mock_info(VOTING_TOKEN, &[]);
+        match execute(deps.as_mut(), mock_env(), info, msg) {
+            Err(ContractError::InvalidPollExecuteMsg(msg)) => assert_eq!(msg, reason),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let too_many_msgs = (0..257u64)
+        .map(|order| PollExecuteMsg::ClearAdmin {
+            order,
+            contract: VOTING_TOKEN.to_string(),
+        })
+        .collect();
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(too_many_msgs),
+    );
+    match execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg) {
+        Err(ContractError::InvalidPollExecuteMsg(msg)) => {
+            assert_eq!(msg, "at most 256 messages are allowed")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
 #[test]
 fn execute_poll_with_order() {
     const POLL_START_HEIGHT: u64 = 1000;
@@ -1882,30 +3669,35 @@ fn execute_poll_with_order() {
 
     //add three messages with different order
     let execute_msgs: Vec<PollExecuteMsg> = vec![
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 3u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz3.clone(),
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 4u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz4.clone(),
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 2u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz2.clone(),
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 5u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz5.clone(),
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 1u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz.clone(),
+            funds: vec![],
         },
     ];
 
@@ -2008,31 +3800,46 @@ fn execute_poll_with_order() {
     assert_eq!(
         execute_res.messages,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz2,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz3,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz4,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz5,
-                funds: vec![],
-            })),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz,
+                    funds: vec![],
+                }),
+                (1 << 8) | 4
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz2,
+                    funds: vec![],
+                }),
+                (1 << 8) | 2
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz3,
+                    funds: vec![],
+                }),
+                1 << 8
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz4,
+                    funds: vec![],
+                }),
+                (1 << 8) | 1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz5,
+                    funds: vec![],
+                }),
+                (1 << 8) | 3
+            ),
         ]
     );
     assert_eq!(
@@ -2078,15 +3885,17 @@ fn happy_days_end_poll_with_controlled_quorum() {
 
     //add two messages
     let execute_msgs: Vec<PollExecuteMsg> = vec![
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 1u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz.clone(),
+            funds: vec![],
         },
-        PollExecuteMsg {
+        PollExecuteMsg::WasmExecute {
             order: 2u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz,
+            funds: vec![],
         },
     ];
 
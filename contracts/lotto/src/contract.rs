@@ -3,22 +3,45 @@ use cosmwasm_std::entry_point;
 
 use crate::error::ContractError;
 use crate::helpers::{
-    calculate_value_of_aust_to_be_redeemed_for_lottery, calculate_winner_prize,
+    base64_encoded_tickets_to_vec_string_tickets, calculate_boost_multiplier,
+    calculate_boost_voting_balance_needed, calculate_claimable_prizes,
+    calculate_deposit_minted_shares, calculate_instant_withdrawal_fee, calculate_locked_shares,
+    calculate_max_affordable_tickets, calculate_operator_commission, calculate_release_epoch,
+    calculate_reserve_transfers, calculate_value_of_aust_to_be_redeemed_for_lottery,
+    calculate_winner_prize, calculate_withdraw_amounts, calculate_withdraw_payout,
     claim_unbonded_withdrawals, compute_global_operator_reward, compute_global_sponsor_reward,
     compute_operator_reward, compute_sponsor_reward, decimal_from_ratio_or_one,
-    handle_depositor_operator_updates, handle_depositor_ticket_updates,
-    ExecuteLotteryRedeemedAustInfo,
+    handle_depositor_operator_updates, handle_depositor_ticket_updates, hash_deposit_intent,
+    hash_gift_code_commitment, hash_gift_code_preimage, is_action_paused, is_valid_sequence,
+    prune_share_locks_and_get_unlocked_shares, pubkey_to_address, validate_depositor_addr,
+    validate_reserve_destinations, validate_unbonding_units, ClaimablePrizes, DepositMintedShares,
+    ExecuteLotteryRedeemedAustInfo, WithdrawAmounts, WithdrawPayout,
 };
 use crate::prize_strategy::{execute_lottery, execute_prize};
-use crate::querier::{query_balance, query_exchange_rate};
+use crate::querier::{
+    query_address_voting_balance_at_timestamp, query_balance, query_exchange_rate,
+    query_total_voting_balance_at_timestamp,
+};
 use crate::state::{
-    old_read_depositors, old_read_lottery_info, old_remove_depositor_info, old_remove_lottery_info,
-    parse_length, read_depositor_info, read_depositor_stats, read_depositor_stats_at_height,
-    read_depositors_info, read_depositors_stats, read_lottery_info, read_lottery_prizes,
-    read_operator_info, read_sponsor_info, store_depositor_info, store_lottery_info,
-    store_operator_info, store_sponsor_info, Config, DepositorInfo, LotteryInfo, OperatorInfo,
-    Pool, PrizeInfo, SponsorInfo, State, CONFIG, OLDCONFIG, OLDPOOL, OLDSTATE, OLD_PRIZES, POOL,
-    PRIZES, STATE, TICKETS,
+    add_unbonding_claim, append_depositor_history, import_depositor_history, old_read_depositors,
+    old_read_lottery_info, old_remove_depositor_info, old_remove_lottery_info, parse_length,
+    read_all_depositor_history, read_all_unbonding_claims, read_awarded_lotteries, read_charities,
+    read_deposit_age, read_depositor_history, read_depositor_info, read_depositor_stats,
+    read_depositor_unbonding_info, read_depositors, read_depositors_info, read_depositors_stats,
+    read_gift_codes, read_lotteries, read_lottery_info, read_lottery_prizes,
+    read_operator_commission_rate, read_operator_info, read_operators, read_prize_beneficiary,
+    read_prizes, read_share_locks, read_sponsor_info, read_sponsors, read_syndicate_member_shares,
+    read_syndicate_member_shares_at_lottery, read_syndicate_members, read_syndicate_prize_claims,
+    read_syndicate_prize_shares, read_syndicates, read_tickets, read_unbonding_claims,
+    read_winner_stats, remove_unbonding_claims, store_depositor_info, store_lottery_info,
+    store_operator_info, store_share_locks, store_sponsor_info, syndicate_address,
+    update_deposit_age, Charity, Config, DepositorInfo, DepositorStatsInfo, GiftCode, LotteryInfo,
+    OperatorInfo, Pool, PrizeInfo, SponsorInfo, State, Syndicate, CHARITIES, CONFIG, DEPOSIT_AGES,
+    DEPOSIT_AGES_START, GIFT_CODES, GIFT_CODE_COMMITMENTS, IMPORTED_SYNDICATE_MEMBER_SHARES,
+    IMPORTED_WINNER_STATS, OLDCONFIG, OLDPOOL, OLDSTATE, OLD_PRIZES, OPERATOR_COMMISSION_RATES,
+    POOL, PRIZES, PRIZE_BENEFICIARIES, RELAY_NONCES, STATE, SYNDICATES, SYNDICATE_COUNT,
+    SYNDICATE_MEMBERS, SYNDICATE_PRIZE_CLAIMED_SHARES, SYNDICATE_PRIZE_CLAIMS, TICKETS,
+    UNBONDING_CLAIMS,
 };
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
@@ -31,11 +54,21 @@ use cw_storage_plus::U64Key;
 use glow_protocol::distributor::ExecuteMsg as FaucetExecuteMsg;
 use glow_protocol::lotto::NUM_PRIZE_BUCKETS;
 use glow_protocol::lotto::{
-    BoostConfig, Claim, ConfigResponse, DepositorInfoResponse, DepositorStatsResponse,
-    DepositorsInfoResponse, DepositorsStatsResponse, ExecuteMsg, InstantiateMsg,
-    LotteryBalanceResponse, LotteryInfoResponse, MigrateMsg, OperatorInfoResponse, PoolResponse,
-    PrizeInfoResponse, PrizeInfosResponse, QueryMsg, RewardEmissionsIndex, SponsorInfoResponse,
-    StateResponse, TicketInfoResponse,
+    BoostConfig, BoostResponse, CharitiesResponse, CharityResponse, ConfigResponse, DepositIntent,
+    DepositorActivity, DepositorActivityKind, DepositorActivityResponse, DepositorHistoryResponse,
+    DepositorInfoResponse, DepositorLocksResponse, DepositorStatsResponse, DepositorsInfoResponse,
+    DepositorsStatsResponse, ExecuteMsg, ExportedCharity, ExportedDepositor,
+    ExportedDepositorActivity, ExportedDepositorStats, ExportedGiftCode, ExportedLottery,
+    ExportedOperator, ExportedPrize, ExportedSponsor, ExportedSyndicate, ExportedSyndicateMember,
+    ExportedSyndicatePrizeClaim, ExportedSyndicatePrizeShares, ExportedTicket, ExportedTotals,
+    ExportedUnbondingClaim, GiftCodeResponse, InstantWithdrawalFeeDecay,
+    InstantWithdrawalFeeResponse, InstantiateMsg, LotteryBalanceResponse, LotteryInfoResponse,
+    LotteryStats, LotteryStatsResponse, MigrateMsg, OperatorInfoResponse, PauseFlags, PoolResponse,
+    PrizeBeneficiaryResponse, PrizeInfoResponse, PrizeInfosResponse, QueryMsg, RelayNonceResponse,
+    ReserveDestinations, RewardEmissionsIndex, ShareLock, SimulateClaimLotteryResponse,
+    SimulateDepositResponse, SimulateWithdrawResponse, SponsorInfoResponse, StateExportResponse,
+    StateRecord, StateResponse, SyndicateMemberResponse, SyndicateResponse, TicketInfoResponse,
+    UnbondingClaimResponse, UnbondingClaimsResponse, STATE_EXPORT_VERSION, TICKET_LENGTH,
 };
 use glow_protocol::querier::deduct_tax;
 use moneymarket::market::{Cw20HookMsg, EpochStateResponse, ExecuteMsg as AnchorMsg};
@@ -44,10 +77,15 @@ use std::str::from_utf8;
 use terraswap::querier::query_token_balance;
 
 pub const INITIAL_DEPOSIT_AMOUNT: u128 = 10_000_000;
-pub const MAX_CLAIMS: u8 = 15;
+pub const DEFAULT_MAX_HISTORY_ENTRIES: u64 = 100;
+pub const MAX_HISTORY_ENTRIES_CAP: u64 = 1000;
 pub const THIRTY_MINUTE_TIME: u64 = 60 * 30;
 pub const MAX_HOLDERS_FLOOR: u8 = 10;
 pub const MAX_HOLDERS_CAP: u8 = 100;
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+pub const MAX_SHARE_LOCK_DURATION: u64 = 4 * SECONDS_PER_YEAR;
+pub const MAX_SHARE_LOCKS: usize = 10;
+pub const MAX_GIFT_CODE_DURATION: u64 = SECONDS_PER_YEAR;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -152,8 +190,13 @@ pub fn instantiate(
             unbonding_period: Duration::Time(msg.unbonding_period),
             max_tickets_per_depositor: msg.max_tickets_per_depositor,
             glow_prize_buckets: msg.glow_prize_buckets,
-            paused: false,
+            paused: PauseFlags::default(),
+            guardian: None,
             lotto_winner_boost_config,
+            max_history_entries: DEFAULT_MAX_HISTORY_ENTRIES,
+            instant_withdrawal_fee_decay: None,
+            max_operator_commission: Decimal256::zero(),
+            reserve_destinations: ReserveDestinations::default(),
         },
     )?;
 
@@ -177,6 +220,8 @@ pub fn instantiate(
         &State {
             total_tickets: Uint256::zero(),
             total_reserve: Uint256::zero(),
+            withdrawal_fee_reserve: Uint256::zero(),
+            prize_reserve: Uint256::zero(),
             prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             current_lottery: 0,
             next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(
@@ -198,6 +243,8 @@ pub fn instantiate(
         },
     )?;
 
+    DEPOSIT_AGES_START.save(deps.storage, &env.block.time)?;
+
     POOL.save(
         deps.storage,
         &Pool {
@@ -254,6 +301,9 @@ pub fn execute(
         lotto_winner_boost_config,
         operator_glow_emission_rate,
         sponsor_glow_emission_rate,
+        max_history_entries,
+        instant_withdrawal_fee_decay,
+        max_operator_commission,
     } = msg
     {
         return execute_update_config(
@@ -271,11 +321,14 @@ pub fn execute(
             lotto_winner_boost_config,
             operator_glow_emission_rate,
             sponsor_glow_emission_rate,
+            max_history_entries,
+            instant_withdrawal_fee_decay,
+            max_operator_commission,
         );
     }
 
     let config = CONFIG.load(deps.storage)?;
-    if config.paused {
+    if is_action_paused(&config.paused, &msg) {
         return Err(ContractError::ContractPaused {});
     }
 
@@ -296,7 +349,8 @@ pub fn execute(
         ExecuteMsg::Deposit {
             encoded_tickets,
             operator,
-        } => execute_deposit(deps, env, info, encoded_tickets, operator),
+            syndicate,
+        } => execute_deposit(deps, env, info, encoded_tickets, operator, syndicate),
         ExecuteMsg::ClaimTickets { encoded_tickets } => {
             execute_claim_tickets(deps, env, info, encoded_tickets)
         }
@@ -304,7 +358,35 @@ pub fn execute(
             encoded_tickets,
             recipient,
             operator,
-        } => execute_gift(deps, env, info, encoded_tickets, recipient, operator),
+            unlock_at,
+        } => execute_gift(
+            deps,
+            env,
+            info,
+            encoded_tickets,
+            recipient,
+            operator,
+            unlock_at,
+        ),
+        ExecuteMsg::RelayDeposit {
+            intent,
+            signature,
+            pubkey,
+        } => execute_relay_deposit(deps, env, info, intent, signature, pubkey),
+        ExecuteMsg::CreateGiftCode {
+            code_hash,
+            encoded_tickets,
+            expires_at,
+        } => execute_create_gift_code(deps, env, info, code_hash, encoded_tickets, expires_at),
+        ExecuteMsg::CommitGiftCodeRedemption { commitment } => {
+            execute_commit_gift_code_redemption(deps, env, info, commitment)
+        }
+        ExecuteMsg::RedeemGiftCode { preimage } => {
+            execute_redeem_gift_code(deps, env, info, preimage)
+        }
+        ExecuteMsg::RefundGiftCode { code_hash } => {
+            execute_refund_gift_code(deps, env, info, code_hash)
+        }
         ExecuteMsg::Sponsor {
             award,
             prize_distribution,
@@ -313,11 +395,40 @@ pub fn execute(
         ExecuteMsg::Withdraw { amount, instant } => {
             execute_withdraw(deps, env, info, amount, instant)
         }
-        ExecuteMsg::Claim {} => execute_claim_unbonded(deps, env, info),
+        ExecuteMsg::Claim { start_after, limit } => {
+            execute_claim_unbonded(deps, env, info, start_after, limit)
+        }
         ExecuteMsg::ClaimLottery { lottery_ids } => {
             execute_claim_lottery(deps, env, info, lottery_ids)
         }
+        ExecuteMsg::ClaimLotteryFor {
+            depositor,
+            lottery_ids,
+        } => execute_claim_lottery_for(deps, env, depositor, lottery_ids),
+        ExecuteMsg::SetPrizeBeneficiary { beneficiary } => {
+            execute_set_prize_beneficiary(deps, info, beneficiary)
+        }
+        ExecuteMsg::CreateSyndicate { name } => execute_create_syndicate(deps, info, name),
+        ExecuteMsg::ClaimSyndicateLottery {
+            syndicate_id,
+            lottery_ids,
+        } => execute_claim_syndicate_lottery(deps, env, info, syndicate_id, lottery_ids),
+        ExecuteMsg::ExitSyndicate { syndicate_id } => {
+            execute_exit_syndicate(deps, env, info, syndicate_id)
+        }
+        ExecuteMsg::RegisterCharity { address, name } => {
+            execute_register_charity(deps, info, address, name)
+        }
+        ExecuteMsg::UnregisterCharity { address } => {
+            execute_unregister_charity(deps, info, address)
+        }
         ExecuteMsg::ClaimRewards {} => execute_claim_rewards(deps, env, info),
+        ExecuteMsg::RegisterOperator { commission_rate } => {
+            execute_register_operator(deps, info, commission_rate)
+        }
+        ExecuteMsg::ClaimOperatorCommission {} => {
+            execute_claim_operator_commission(deps, env, info)
+        }
         ExecuteMsg::ExecuteLottery {} => execute_lottery(deps, env, info),
         ExecuteMsg::ExecutePrize { limit } => execute_prize(deps, env, info, limit),
         ExecuteMsg::ExecuteEpochOps {} => execute_epoch_ops(deps, env),
@@ -350,6 +461,14 @@ pub fn execute(
             operator_glow_emission_rate,
             sponsor_glow_emission_rate,
         ),
+        ExecuteMsg::SetPauseFlags { flags } => execute_set_pause_flags(deps, info, flags),
+        ExecuteMsg::UpdateGuardian { guardian } => execute_update_guardian(deps, info, guardian),
+        ExecuteMsg::UpdateReserveDestinations {
+            reserve_destinations,
+        } => execute_update_reserve_destinations(deps, info, reserve_destinations),
+        ExecuteMsg::ImportState { version, records } => {
+            execute_import_state(deps, env, info, version, records)
+        }
         ExecuteMsg::UpdateLotteryConfig {
             lottery_interval,
             block_time,
@@ -400,6 +519,7 @@ pub fn execute_register_contracts(
     Ok(Response::default())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn deposit(
     mut deps: DepsMut,
     env: Env,
@@ -407,6 +527,8 @@ pub fn deposit(
     recipient: Option<String>,
     new_operator_addr: Option<String>,
     encoded_tickets: String,
+    unlock_at: Option<u64>,
+    syndicate: Option<u64>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let mut state = STATE.load(deps.storage)?;
@@ -435,9 +557,19 @@ pub fn deposit(
         .unwrap_or_else(Uint256::zero);
 
     // Get the depositor info
-    // depositor being either the message sender
-    // or the recipient that will be reciving the deposited funds if specified
-    let depositor = if let Some(recipient) = recipient.clone() {
+    // depositor being either the message sender, the syndicate holding
+    // the deposits of its members or the recipient that will be reciving
+    // the deposited funds if specified
+    let depositor = if let Some(syndicate_id) = syndicate {
+        if !SYNDICATES.has(deps.storage, U64Key::from(syndicate_id)) {
+            return Err(ContractError::InvalidSyndicate(syndicate_id));
+        }
+        // Syndicates don't have operators, as members don't share one
+        if new_operator_addr.is_some() {
+            return Err(ContractError::SyndicateOperatorNotAllowed {});
+        }
+        syndicate_address(syndicate_id)
+    } else if let Some(recipient) = recipient.clone() {
         deps.api.addr_validate(recipient.as_str())?
     } else {
         info.sender.clone()
@@ -453,21 +585,28 @@ pub fn deposit(
         };
     }
 
-    // Deduct tx taxes when calculating the net deposited amount in anchor
-    let net_coin_amount = deduct_tax(
+    // Validate that locked gifts unlock in the future, within the maximum lock duration
+    if let Some(unlock_at) = unlock_at {
+        if unlock_at <= env.block.time.seconds()
+            || unlock_at > env.block.time.seconds() + MAX_SHARE_LOCK_DURATION
+        {
+            return Err(ContractError::InvalidUnlockTime {});
+        }
+    }
+
+    // Get the amount of minted aust and shares
+    let DepositMintedShares {
+        post_tax_deposit_amount,
+        minted_aust,
+        minted_shares,
+    } = calculate_deposit_minted_shares(
         deps.as_ref(),
-        coin(deposit_amount.into(), config.stable_denom.clone()),
+        &config,
+        &pool,
+        deposit_amount,
+        aust_exchange_rate,
     )?;
 
-    let post_tax_deposit_amount = Uint256::from(net_coin_amount.amount);
-
-    // Get the number of minted aust
-    let minted_aust = post_tax_deposit_amount / aust_exchange_rate;
-
-    // Get the amount of minted_shares
-    let minted_shares =
-        minted_aust * decimal_from_ratio_or_one(pool.total_user_shares, pool.total_user_aust);
-
     let number_of_new_tickets = handle_depositor_ticket_updates(
         deps.branch(),
         &env,
@@ -495,9 +634,63 @@ pub fn deposit(
         new_operator_addr,
     )?;
 
+    // Age the depositor's deposits with the minted shares
+    update_deposit_age(
+        deps.storage,
+        &depositor,
+        depositor_info.shares,
+        minted_shares,
+        env.block.time,
+    )?;
+
     // Increase the depositor's shares by the number of minted shares
     depositor_info.shares = depositor_info.shares.add(minted_shares);
 
+    // Credit the minted shares to the syndicate member
+    if let Some(syndicate_id) = syndicate {
+        SYNDICATE_MEMBERS.update(
+            deps.storage,
+            (U64Key::from(syndicate_id), &info.sender),
+            env.block.height,
+            |shares| -> StdResult<_> { Ok(shares.unwrap_or_else(Uint256::zero) + minted_shares) },
+        )?;
+    }
+
+    // Lock the minted shares until unlock_at for locked gifts.
+    // Anyone can gift to the depositor, so expired locks are pruned, gifts unlocking
+    // at the same time share a lock and the number of locks is capped. Once the cap
+    // is reached, gifts join the lock unlocking the soonest after their unlock time,
+    // so filling the locks of a depositor doesn't block locked gifts to it
+    if let Some(unlock_at) = unlock_at {
+        let unlock_at = Timestamp::from_seconds(unlock_at);
+        let mut share_locks: Vec<ShareLock> = read_share_locks(deps.storage, &depositor)
+            .into_iter()
+            .filter(|share_lock| share_lock.unlock_at > env.block.time)
+            .collect();
+
+        if let Some(share_lock) = share_locks
+            .iter_mut()
+            .find(|share_lock| share_lock.unlock_at == unlock_at)
+        {
+            share_lock.shares = share_lock.shares.add(minted_shares);
+        } else if share_locks.len() < MAX_SHARE_LOCKS {
+            share_locks.push(ShareLock {
+                shares: minted_shares,
+                unlock_at,
+            });
+        } else if let Some(share_lock) = share_locks
+            .iter_mut()
+            .filter(|share_lock| share_lock.unlock_at > unlock_at)
+            .min_by_key(|share_lock| share_lock.unlock_at)
+        {
+            share_lock.shares = share_lock.shares.add(minted_shares);
+        } else {
+            return Err(ContractError::TooManyShareLocks {});
+        }
+
+        store_share_locks(deps.storage, &depositor, share_locks)?;
+    }
+
     // Increase total_user_shares by the number of minted shares
     pool.total_user_shares = pool.total_user_shares.add(minted_shares);
 
@@ -507,6 +700,45 @@ pub fn deposit(
     // Update the number of total_tickets
     state.total_tickets = state.total_tickets.add(number_of_new_tickets.into());
 
+    // Record the deposit, and the gift on the sender's side
+    let activity = DepositorActivity {
+        kind: DepositorActivityKind::Deposit,
+        amount: deposit_amount,
+        shares: minted_shares,
+        tickets: number_of_new_tickets,
+        block_height: env.block.height,
+        lottery_id: None,
+    };
+
+    if recipient.is_some() {
+        append_depositor_history(
+            deps.storage,
+            &info.sender,
+            DepositorActivity {
+                kind: DepositorActivityKind::GiftSent,
+                ..activity.clone()
+            },
+            config.max_history_entries,
+        )?;
+        append_depositor_history(
+            deps.storage,
+            &depositor,
+            DepositorActivity {
+                kind: DepositorActivityKind::GiftReceived,
+                ..activity
+            },
+            config.max_history_entries,
+        )?;
+    } else {
+        // Syndicate deposits are recorded for the member
+        append_depositor_history(
+            deps.storage,
+            &info.sender,
+            activity,
+            config.max_history_entries,
+        )?;
+    }
+
     // update depositor and state information
     store_depositor_info(deps.storage, &depositor, depositor_info, env.block.height)?;
     STATE.save(deps.storage, &state)?;
@@ -539,6 +771,7 @@ pub fn execute_deposit(
     info: MessageInfo,
     encoded_tickets: String,
     operator_addr: Option<String>,
+    syndicate: Option<u64>,
 ) -> Result<Response, ContractError> {
     deposit(
         deps.branch(),
@@ -547,6 +780,8 @@ pub fn execute_deposit(
         None,
         operator_addr,
         encoded_tickets,
+        None,
+        syndicate,
     )
 }
 
@@ -612,6 +847,7 @@ pub fn execute_gift(
     encoded_tickets: String,
     to: String,
     operator_addr: Option<String>,
+    unlock_at: Option<u64>,
 ) -> Result<Response, ContractError> {
     if to == info.sender {
         return Err(ContractError::GiftToSelf {});
@@ -623,138 +859,593 @@ pub fn execute_gift(
         Some(to),
         operator_addr,
         encoded_tickets,
+        unlock_at,
+        None,
     )
 }
 
-// Make a donation deposit to the lottery pool
-pub fn execute_sponsor(
+// Deposit the stable funds fronted by the relayer on behalf of the signer of a deposit intent,
+// repaying the relayer with the signer's cw20 stable token
+pub fn execute_relay_deposit(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    intent: DepositIntent,
+    signature: Binary,
+    pubkey: Binary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Validate that the intent is meant for this contract and has not expired
+    if intent.contract != env.contract.address || intent.chain_id != env.block.chain_id {
+        return Err(ContractError::InvalidRelayIntent {});
+    }
+    if intent.expires_at <= env.block.time.seconds() {
+        return Err(ContractError::RelayIntentExpired {});
+    }
+
+    // The relayer fronts the amount of the intent in the base stable denom
+    let relayed_amount = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| c.amount)
+        .unwrap_or_else(Uint128::zero);
+    if relayed_amount != intent.amount {
+        return Err(ContractError::InvalidRelayFunds {
+            expected: intent.amount,
+            got: relayed_amount,
+        });
+    }
+
+    // Validate the signature of the intent and get its signer,
+    // whose address has the same prefix as the contract address
+    let intent_hash = hash_deposit_intent(&intent);
+    if !deps
+        .api
+        .secp256k1_verify(&intent_hash, &signature, &pubkey)
+        .unwrap_or(false)
+    {
+        return Err(ContractError::InvalidRelaySignature {});
+    }
+    let (prefix, _, _) = bech32::decode(env.contract.address.as_str())
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    let signer = pubkey_to_address(deps.as_ref(), &pubkey, &prefix)?;
+
+    // Consume the nonce of the signer so that the intent can't be replayed
+    let nonce = RELAY_NONCES
+        .may_load(deps.storage, &signer)?
+        .unwrap_or_default();
+    if intent.nonce != nonce {
+        return Err(ContractError::InvalidRelayNonce {
+            expected: nonce,
+            got: intent.nonce,
+        });
+    }
+    RELAY_NONCES.save(deps.storage, &signer, &(nonce + 1))?;
+
+    let stable_token = deps.api.addr_validate(&intent.stable_token)?;
+
+    let res = deposit(
+        deps.branch(),
+        env,
+        MessageInfo {
+            sender: signer.clone(),
+            funds: info.funds.clone(),
+        },
+        None,
+        intent.operator,
+        intent.encoded_tickets,
+        None,
+        None,
+    )?;
+
+    // Repay the relayer with the stable token of the signer, pulled through its allowance
+    Ok(res
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: stable_token.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: signer.to_string(),
+                recipient: info.sender.to_string(),
+                amount: intent.amount,
+            })?,
+        }))
+        .add_attribute("relayer", info.sender.to_string()))
+}
+
+// Escrow a deposit as shares that can be redeemed with the preimage of code_hash
+pub fn execute_create_gift_code(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    award: Option<bool>,
-    prize_distribution: Option<[Decimal256; NUM_PRIZE_BUCKETS]>,
+    code_hash: String,
+    encoded_tickets: String,
+    expires_at: u64,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let mut state = STATE.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
     let mut pool = POOL.load(deps.storage)?;
 
-    // get the amount of funds sent in the base stable denom
-    let sponsor_amount = info
+    // Validate that the lottery has not already started
+    let current_lottery = read_lottery_info(deps.storage, state.current_lottery);
+    if current_lottery.rand_round != 0 {
+        return Err(ContractError::LotteryAlreadyStarted {});
+    }
+
+    // Validate the code hash
+    let code_hash_bytes = decode_gift_code_hash(&code_hash)?;
+    if GIFT_CODES
+        .may_load(deps.storage, &code_hash_bytes)?
+        .is_some()
+    {
+        return Err(ContractError::GiftCodeAlreadyExists {});
+    }
+
+    // Validate that the gift code expires in the future, within the maximum duration
+    if expires_at <= env.block.time.seconds()
+        || expires_at > env.block.time.seconds() + MAX_GIFT_CODE_DURATION
+    {
+        return Err(ContractError::InvalidGiftCodeExpiry {});
+    }
+
+    // Get the amount of funds sent in the base stable denom
+    let deposit_amount = info
         .funds
         .iter()
         .find(|c| c.denom == config.stable_denom)
         .map(|c| Uint256::from(c.amount))
         .unwrap_or_else(Uint256::zero);
 
-    // validate that the sponsor amount is non zero
-    if sponsor_amount.is_zero() {
-        return Err(ContractError::ZeroSponsorshipAmount {});
+    // Validate that the deposit amount is non zero
+    if deposit_amount.is_zero() {
+        return Err(ContractError::ZeroGiftAmount {});
     }
 
-    compute_global_sponsor_reward(&mut state, &pool, env.block.height);
+    // Get the aust exchange rate
+    let aust_exchange_rate = query_exchange_rate(
+        deps.as_ref(),
+        config.anchor_contract.to_string(),
+        env.block.height,
+    )?
+    .exchange_rate;
 
-    let mut msgs: Vec<CosmosMsg> = vec![];
+    // Get the amount of minted aust and shares
+    let DepositMintedShares {
+        post_tax_deposit_amount,
+        minted_aust,
+        minted_shares,
+    } = calculate_deposit_minted_shares(
+        deps.as_ref(),
+        &config,
+        &pool,
+        deposit_amount,
+        aust_exchange_rate,
+    )?;
 
-    if let None | Some(false) = award {
-        // Can't specify prize distribution in this case
-        if prize_distribution.is_some() {
-            return Err(ContractError::InvalidPrizeDistribution {});
+    // Validate the tickets and that the escrowed deposit can afford them.
+    // They are validated again when the gift code is redeemed.
+    let combinations = base64_encoded_tickets_to_vec_string_tickets(encoded_tickets.clone())?;
+    for combination in combinations.iter() {
+        if !is_valid_sequence(combination, TICKET_LENGTH) {
+            return Err(ContractError::InvalidSequence(combination.clone()));
         }
+    }
 
-        // Deduct taxes that will be payed when transferring to anchor
-        let net_sponsor_amount = Uint256::from(
-            deduct_tax(
-                deps.as_ref(),
-                coin(sponsor_amount.into(), config.stable_denom.clone()),
-            )?
-            .amount,
-        );
-
-        // query exchange_rate from anchor money market
-        let epoch_state: EpochStateResponse = query_exchange_rate(
-            deps.as_ref(),
-            config.anchor_contract.to_string(),
-            env.block.height,
-        )?;
-
-        // add amount of aUST entitled from the deposit
-        let minted_aust = net_sponsor_amount / epoch_state.exchange_rate;
-
-        // Get minted_aust_value
-        let minted_aust_value = minted_aust * epoch_state.exchange_rate;
+    let gift_balance = minted_aust * aust_exchange_rate;
+    let max_gift_tickets = calculate_max_affordable_tickets(&config, gift_balance);
 
-        // fetch sponsor_info
-        let mut sponsor_info: SponsorInfo = read_sponsor_info(deps.storage, &info.sender);
+    if combinations.len() as u64 > max_gift_tickets {
+        return Err(ContractError::InsufficientPostTransactionDepositorBalance {
+            post_transaction_depositor_balance: gift_balance,
+            post_transaction_num_depositor_tickets: combinations.len() as u64,
+            post_transaction_max_depositor_tickets: max_gift_tickets,
+        });
+    }
 
-        // update sponsor sponsor rewards
-        compute_sponsor_reward(&state, &mut sponsor_info);
+    // Escrow the minted shares in the pool, without assigning them to a depositor
+    pool.total_user_shares = pool.total_user_shares.add(minted_shares);
+    pool.total_user_aust = pool.total_user_aust.add(minted_aust);
 
-        // add sponsor_amount to depositor
-        sponsor_info.lottery_deposit = sponsor_info.lottery_deposit.add(minted_aust_value);
-        store_sponsor_info(deps.storage, &info.sender, sponsor_info)?;
+    GIFT_CODES.save(
+        deps.storage,
+        &code_hash_bytes,
+        &GiftCode {
+            creator: info.sender.clone(),
+            shares: minted_shares,
+            encoded_tickets,
+            expires_at: Timestamp::from_seconds(expires_at),
+        },
+    )?;
+    POOL.save(deps.storage, &pool)?;
 
-        // update pool
-        pool.total_sponsor_lottery_deposits =
-            pool.total_sponsor_lottery_deposits.add(minted_aust_value);
+    append_depositor_history(
+        deps.storage,
+        &info.sender,
+        DepositorActivity {
+            kind: DepositorActivityKind::GiftSent,
+            amount: deposit_amount,
+            shares: minted_shares,
+            tickets: combinations.len() as u64,
+            block_height: env.block.height,
+            lottery_id: None,
+        },
+        config.max_history_entries,
+    )?;
 
-        // Push message to deposit stable coins into anchor
-        msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: config.anchor_contract.to_string(),
             funds: vec![Coin {
                 denom: config.stable_denom,
-                amount: net_sponsor_amount.into(),
+                amount: post_tax_deposit_amount.into(),
             }],
             msg: to_binary(&AnchorMsg::DepositStable {})?,
-        }));
-    } else {
-        // Award is instant
+        }))
+        .add_attributes(vec![
+            attr("action", "create_gift_code"),
+            attr("creator", info.sender.to_string()),
+            attr("code_hash", code_hash),
+            attr("deposit_amount", deposit_amount.to_string()),
+            attr("aust_minted", minted_aust.to_string()),
+        ]))
+}
 
-        // Get the prize_distribution or the prize_distribution in the config
-        let prize_distribution = prize_distribution.unwrap_or(config.prize_distribution);
+// Commit the sender to redeeming a gift code, without revealing its preimage
+pub fn execute_commit_gift_code_redemption(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    commitment: String,
+) -> Result<Response, ContractError> {
+    let commitment_bytes = decode_gift_code_hash(&commitment)?;
 
-        // Validate that the prize_distribution is of length NUM_PRIZE_BUCKETS
-        if prize_distribution.len() != NUM_PRIZE_BUCKETS {
-            return Err(ContractError::InvalidPrizeDistribution {});
-        }
+    GIFT_CODE_COMMITMENTS.save(
+        deps.storage,
+        (&info.sender, &commitment_bytes),
+        &env.block.height,
+    )?;
 
-        // Validate that the prize_distributions sums to 1
-        let mut sum = Decimal256::zero();
-        for item in prize_distribution.iter() {
-            sum += *item;
-        }
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "commit_gift_code_redemption"),
+        attr("redeemer", info.sender.to_string()),
+        attr("commitment", commitment),
+    ]))
+}
 
-        if sum != Decimal256::one() {
-            return Err(ContractError::InvalidPrizeDistribution {});
-        }
+// Assign the shares and tickets of a gift code to the sender
+pub fn execute_redeem_gift_code(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    preimage: String,
+) -> Result<Response, ContractError> {
+    let code_hash_bytes = hash_gift_code_preimage(&preimage);
 
-        // Distribute the sponsorship to the prize buckets according to the prize distribution
-        for (index, fraction_of_prize) in prize_distribution.iter().enumerate() {
-            // Add the proportional amount of the net redeemed amount to the relevant award bucket.
-            state.prize_buckets[index] += sponsor_amount * *fraction_of_prize
-        }
+    let gift_code = GIFT_CODES
+        .may_load(deps.storage, &code_hash_bytes)?
+        .ok_or(ContractError::GiftCodeNotFound {})?;
+
+    // Validate that the gift code hasn't expired
+    if gift_code.expires_at <= env.block.time {
+        return Err(ContractError::GiftCodeExpired {});
     }
 
-    STATE.save(deps.storage, &state)?;
-    POOL.save(deps.storage, &pool)?;
+    // Validate that the sender committed to the redemption before revealing the preimage
+    let commitment_bytes = hash_gift_code_commitment(&preimage, &info.sender);
+    match GIFT_CODE_COMMITMENTS.may_load(deps.storage, (&info.sender, &commitment_bytes))? {
+        Some(committed_at) if committed_at < env.block.height => {}
+        _ => return Err(ContractError::GiftCodeNotCommitted {}),
+    }
+    GIFT_CODE_COMMITMENTS.remove(deps.storage, (&info.sender, &commitment_bytes));
 
-    Ok(Response::new().add_messages(msgs).add_attributes(vec![
-        attr("action", "sponsorship"),
-        attr("sponsor", info.sender.to_string()),
-        attr("sponsorship_amount", sponsor_amount),
+    let encoded_tickets = gift_code.encoded_tickets.clone();
+    let shares = gift_code.shares;
+    let number_of_new_tickets = assign_gift_code(
+        deps,
+        &env,
+        &code_hash_bytes,
+        gift_code,
+        &info.sender,
+        Some(encoded_tickets),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "redeem_gift_code"),
+        attr("recipient", info.sender.to_string()),
+        attr("code_hash", hex::encode(code_hash_bytes)),
+        attr("shares", shares.to_string()),
+        attr("tickets", number_of_new_tickets.to_string()),
     ]))
 }
 
-pub fn execute_sponsor_withdraw(
+// Return the shares of an expired gift code to its creator
+pub fn execute_refund_gift_code(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    code_hash: String,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    let mut state = STATE.load(deps.storage)?;
-    let mut pool = POOL.load(deps.storage)?;
+    let code_hash_bytes = decode_gift_code_hash(&code_hash)?;
 
-    // Get the aust exchange rate
-    let rate = query_exchange_rate(
+    let gift_code = GIFT_CODES
+        .may_load(deps.storage, &code_hash_bytes)?
+        .ok_or(ContractError::GiftCodeNotFound {})?;
+
+    if info.sender != gift_code.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Validate that the gift code has expired
+    if gift_code.expires_at > env.block.time {
+        return Err(ContractError::GiftCodeNotExpired {});
+    }
+
+    // The creator gets the shares back without tickets, they can be claimed afterwards
+    let shares = gift_code.shares;
+    assign_gift_code(deps, &env, &code_hash_bytes, gift_code, &info.sender, None)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "refund_gift_code"),
+        attr("creator", info.sender.to_string()),
+        attr("code_hash", code_hash),
+        attr("shares", shares.to_string()),
+    ]))
+}
+
+fn decode_gift_code_hash(code_hash: &str) -> Result<Vec<u8>, ContractError> {
+    match hex::decode(code_hash) {
+        Ok(code_hash_bytes) if code_hash_bytes.len() == 32 => Ok(code_hash_bytes),
+        _ => Err(ContractError::InvalidGiftCodeHash {}),
+    }
+}
+
+/// Moves the escrowed shares of a gift code to the recipient and removes the gift code.
+/// Returns the number of tickets given to the recipient.
+fn assign_gift_code(
+    mut deps: DepsMut,
+    env: &Env,
+    code_hash: &[u8],
+    gift_code: GiftCode,
+    recipient: &Addr,
+    encoded_tickets: Option<String>,
+) -> Result<u64, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    let mut pool = POOL.load(deps.storage)?;
+
+    // Validate that the lottery has not already started
+    let current_lottery = read_lottery_info(deps.storage, state.current_lottery);
+    if current_lottery.rand_round != 0 {
+        return Err(ContractError::LotteryAlreadyStarted {});
+    }
+
+    let mut depositor_info: DepositorInfo = read_depositor_info(deps.storage, recipient);
+
+    // Get the aust exchange rate
+    let aust_exchange_rate = query_exchange_rate(
+        deps.as_ref(),
+        config.anchor_contract.to_string(),
+        env.block.height,
+    )?
+    .exchange_rate;
+
+    let escrowed_aust = gift_code
+        .shares
+        .multiply_ratio(pool.total_user_aust, pool.total_user_shares);
+
+    // Gift codes without tickets are refunds to their creator
+    let kind = if encoded_tickets.is_some() {
+        DepositorActivityKind::GiftReceived
+    } else {
+        DepositorActivityKind::GiftRefunded
+    };
+
+    let number_of_new_tickets = if let Some(encoded_tickets) = encoded_tickets {
+        // The escrowed shares are already part of the pool,
+        // so the tickets are validated against the pool without them
+        let pool_without_gift_code = Pool {
+            total_user_shares: pool.total_user_shares - gift_code.shares,
+            total_user_aust: pool.total_user_aust - escrowed_aust,
+            ..pool.clone()
+        };
+
+        handle_depositor_ticket_updates(
+            deps.branch(),
+            env,
+            &config,
+            &pool_without_gift_code,
+            recipient,
+            &mut depositor_info,
+            encoded_tickets,
+            aust_exchange_rate,
+            gift_code.shares,
+            escrowed_aust,
+        )?
+    } else {
+        0
+    };
+
+    // Update the global reward index
+    compute_global_operator_reward(&mut state, &pool, env.block.height);
+
+    // Update operator information
+    handle_depositor_operator_updates(
+        deps.branch(),
+        &mut state,
+        &mut pool,
+        recipient,
+        &mut depositor_info,
+        gift_code.shares,
+        None,
+    )?;
+
+    // Age the recipient's deposits with the escrowed shares
+    update_deposit_age(
+        deps.storage,
+        recipient,
+        depositor_info.shares,
+        gift_code.shares,
+        env.block.time,
+    )?;
+
+    // Move the escrowed shares to the recipient
+    depositor_info.shares = depositor_info.shares.add(gift_code.shares);
+
+    // Update the number of total_tickets
+    state.total_tickets = state.total_tickets.add(number_of_new_tickets.into());
+
+    append_depositor_history(
+        deps.storage,
+        recipient,
+        DepositorActivity {
+            kind,
+            amount: escrowed_aust * aust_exchange_rate,
+            shares: gift_code.shares,
+            tickets: number_of_new_tickets,
+            block_height: env.block.height,
+            lottery_id: None,
+        },
+        config.max_history_entries,
+    )?;
+
+    GIFT_CODES.remove(deps.storage, code_hash);
+    store_depositor_info(deps.storage, recipient, depositor_info, env.block.height)?;
+    STATE.save(deps.storage, &state)?;
+    POOL.save(deps.storage, &pool)?;
+
+    Ok(number_of_new_tickets)
+}
+
+// Make a donation deposit to the lottery pool
+pub fn execute_sponsor(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    award: Option<bool>,
+    prize_distribution: Option<[Decimal256; NUM_PRIZE_BUCKETS]>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    let mut pool = POOL.load(deps.storage)?;
+
+    // get the amount of funds sent in the base stable denom
+    let sponsor_amount = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+
+    // validate that the sponsor amount is non zero
+    if sponsor_amount.is_zero() {
+        return Err(ContractError::ZeroSponsorshipAmount {});
+    }
+
+    compute_global_sponsor_reward(&mut state, &pool, env.block.height);
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+
+    if let None | Some(false) = award {
+        // Can't specify prize distribution in this case
+        if prize_distribution.is_some() {
+            return Err(ContractError::InvalidPrizeDistribution {});
+        }
+
+        // Deduct taxes that will be payed when transferring to anchor
+        let net_sponsor_amount = Uint256::from(
+            deduct_tax(
+                deps.as_ref(),
+                coin(sponsor_amount.into(), config.stable_denom.clone()),
+            )?
+            .amount,
+        );
+
+        // query exchange_rate from anchor money market
+        let epoch_state: EpochStateResponse = query_exchange_rate(
+            deps.as_ref(),
+            config.anchor_contract.to_string(),
+            env.block.height,
+        )?;
+
+        // add amount of aUST entitled from the deposit
+        let minted_aust = net_sponsor_amount / epoch_state.exchange_rate;
+
+        // Get minted_aust_value
+        let minted_aust_value = minted_aust * epoch_state.exchange_rate;
+
+        // fetch sponsor_info
+        let mut sponsor_info: SponsorInfo = read_sponsor_info(deps.storage, &info.sender);
+
+        // update sponsor sponsor rewards
+        compute_sponsor_reward(&state, &mut sponsor_info);
+
+        // add sponsor_amount to depositor
+        sponsor_info.lottery_deposit = sponsor_info.lottery_deposit.add(minted_aust_value);
+        store_sponsor_info(deps.storage, &info.sender, sponsor_info)?;
+
+        // update pool
+        pool.total_sponsor_lottery_deposits =
+            pool.total_sponsor_lottery_deposits.add(minted_aust_value);
+
+        // Push message to deposit stable coins into anchor
+        msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.anchor_contract.to_string(),
+            funds: vec![Coin {
+                denom: config.stable_denom,
+                amount: net_sponsor_amount.into(),
+            }],
+            msg: to_binary(&AnchorMsg::DepositStable {})?,
+        }));
+    } else {
+        // Award is instant
+
+        // Get the prize_distribution or the prize_distribution in the config
+        let prize_distribution = prize_distribution.unwrap_or(config.prize_distribution);
+
+        // Validate that the prize_distribution is of length NUM_PRIZE_BUCKETS
+        if prize_distribution.len() != NUM_PRIZE_BUCKETS {
+            return Err(ContractError::InvalidPrizeDistribution {});
+        }
+
+        // Validate that the prize_distributions sums to 1
+        let mut sum = Decimal256::zero();
+        for item in prize_distribution.iter() {
+            sum += *item;
+        }
+
+        if sum != Decimal256::one() {
+            return Err(ContractError::InvalidPrizeDistribution {});
+        }
+
+        // Distribute the sponsorship to the prize buckets according to the prize distribution
+        for (index, fraction_of_prize) in prize_distribution.iter().enumerate() {
+            // Add the proportional amount of the net redeemed amount to the relevant award bucket.
+            state.prize_buckets[index] += sponsor_amount * *fraction_of_prize
+        }
+    }
+
+    STATE.save(deps.storage, &state)?;
+    POOL.save(deps.storage, &pool)?;
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "sponsorship"),
+        attr("sponsor", info.sender.to_string()),
+        attr("sponsorship_amount", sponsor_amount),
+    ]))
+}
+
+pub fn execute_sponsor_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    let mut pool = POOL.load(deps.storage)?;
+
+    // Get the aust exchange rate
+    let rate = query_exchange_rate(
         deps.as_ref(),
         config.anchor_contract.to_string(),
         env.block.height,
@@ -868,63 +1559,33 @@ pub fn execute_withdraw(
         return Err(ContractError::LotteryAlreadyStarted {});
     }
 
-    // Get the number of withdrawn shares
-    let withdrawn_shares = amount
-        .map(|amount| {
-            std::cmp::max(
-                (Uint256::from(amount) / aust_exchange_rate)
-                    .multiply_ratio(pool.total_user_shares, pool.total_user_aust),
-                // Always withdraw at least one share
-                Uint256::one(),
-            )
-        })
-        .unwrap_or_else(|| depositor_info.shares);
-
-    // Get the withdrawn amount
-    let withdrawn_aust =
-        withdrawn_shares.multiply_ratio(pool.total_user_aust, pool.total_user_shares);
-
-    let withdrawn_aust_value = withdrawn_aust * aust_exchange_rate;
-
-    // Calculate the depositor's balance from their aust balance
-    let depositor_balance = pool.total_user_aust
-        * Decimal256::from_ratio(depositor_info.shares, pool.total_user_shares)
-        * aust_exchange_rate;
+    // Get the shares that aren't locked by a gift
+    let unlocked_shares = prune_share_locks_and_get_unlocked_shares(
+        deps.storage,
+        &info.sender,
+        depositor_info.shares,
+        env.block.time,
+    )?;
 
-    if withdrawn_aust_value > depositor_balance {
-        return Err(ContractError::SpecifiedWithdrawAmountTooBig {
-            amount: Uint128::from(withdrawn_aust_value),
-            depositor_balance,
-        });
+    if unlocked_shares.is_zero() {
+        return Err(ContractError::DepositorSharesLocked {});
     }
 
-    // Get the depositor's balance post withdraw
-    let post_transaction_depositor_balance = (pool.total_user_aust - withdrawn_aust)
-        * decimal_from_ratio_or_one(
-            depositor_info.shares - withdrawn_shares,
-            pool.total_user_shares - withdrawn_shares,
-        )
-        * aust_exchange_rate;
-
-    let post_transaction_max_depositor_tickets = Uint128::from(
-        post_transaction_depositor_balance / Decimal256::from_uint256(config.ticket_price),
-    )
-    .u128();
-
-    // Calculate how many tickets to remove
-    let num_depositor_tickets = depositor_info.tickets.len() as u128;
-
-    // Get the number of tickets to withdraw
-    let withdrawn_tickets: u128 = num_depositor_tickets
-        .checked_sub(post_transaction_max_depositor_tickets)
-        .unwrap_or_default();
-
-    if withdrawn_tickets > num_depositor_tickets {
-        return Err(ContractError::WithdrawingTooManyTickets {
-            withdrawn_tickets,
-            num_depositor_tickets,
-        });
-    }
+    // Get the withdrawn shares, aust and tickets
+    let WithdrawAmounts {
+        withdrawn_shares,
+        withdrawn_aust,
+        withdrawn_aust_value,
+        withdrawn_tickets,
+        ..
+    } = calculate_withdraw_amounts(
+        &config,
+        &pool,
+        &depositor_info,
+        amount,
+        unlocked_shares,
+        aust_exchange_rate,
+    )?;
 
     for seq in depositor_info.tickets.drain(..withdrawn_tickets as usize) {
         TICKETS.update(deps.storage, seq.as_bytes(), |tickets| -> StdResult<_> {
@@ -968,14 +1629,27 @@ pub fn execute_withdraw(
     // Remove withdrawn_tickets from total_tickets
     state.total_tickets = state.total_tickets.sub(Uint256::from(withdrawn_tickets));
 
-    // Get the value of the returned amount after accounting for taxes.
-    let mut return_amount = Uint256::from(
-        deduct_tax(
-            deps.as_ref(),
-            coin(withdrawn_aust_value.into(), config.clone().stable_denom),
-        )?
-        .amount,
-    );
+    // Get the returned amount after accounting for taxes and instant withdrawal fees
+    let instant_withdrawal_fee = if let Some(true) = instant {
+        Some(calculate_instant_withdrawal_fee(
+            &config,
+            read_deposit_age(deps.storage, &info.sender, env.block.time),
+            env.block.time,
+        ))
+    } else {
+        None
+    };
+
+    let WithdrawPayout {
+        return_amount,
+        withdrawal_fee,
+        net_return_amount,
+    } = calculate_withdraw_payout(
+        deps.as_ref(),
+        &config,
+        withdrawn_aust_value,
+        instant_withdrawal_fee,
+    )?;
 
     let mut msgs: Vec<CosmosMsg> = vec![];
 
@@ -992,38 +1666,51 @@ pub fn execute_withdraw(
     msgs.push(redeem_msg);
 
     // Instant withdrawal. The user incurs a fee and receive the funds with this operation
-    let mut withdrawal_fee = Uint256::zero();
-
     if let Some(true) = instant {
-        // Apply instant withdrawal fee
-        withdrawal_fee = return_amount * config.instant_withdrawal_fee;
-        return_amount = return_amount.sub(withdrawal_fee);
-
         // Add the withdrawal fee to the total_reserve
         state.total_reserve += withdrawal_fee;
-
-        // Get the amount of ust to return after tax
-        let net_coin_amount = deduct_tax(
-            deps.as_ref(),
-            coin(return_amount.into(), config.stable_denom),
-        )?;
+        state.withdrawal_fee_reserve += withdrawal_fee;
 
         msgs.push(CosmosMsg::Bank(BankMsg::Send {
             to_address: info.sender.to_string(),
-            amount: vec![net_coin_amount],
+            amount: vec![Coin {
+                denom: config.stable_denom,
+                amount: net_return_amount.into(),
+            }],
         }));
     } else {
-        // Check max unbonding_info concurrent claims is not bypassed
-        if depositor_info.unbonding_info.len() as u8 >= MAX_CLAIMS {
-            return Err(ContractError::MaxUnbondingClaims {});
-        }
-        // Place amount in unbonding state as a claim
-        depositor_info.unbonding_info.push(Claim {
-            amount: return_amount,
-            release_at: config.unbonding_period.after(&env.block),
-        });
+        // Place amount in unbonding state as a claim,
+        // merged with the other withdrawals released in the same epoch
+        let (release_epoch, release_at) = calculate_release_epoch(&config, &env.block)?;
+        add_unbonding_claim(
+            deps.storage,
+            &info.sender,
+            release_epoch,
+            release_at,
+            return_amount,
+        )?;
     }
 
+    let (kind, amount) = if let Some(true) = instant {
+        (DepositorActivityKind::InstantWithdraw, net_return_amount)
+    } else {
+        (DepositorActivityKind::Withdraw, return_amount)
+    };
+
+    append_depositor_history(
+        deps.storage,
+        &info.sender,
+        DepositorActivity {
+            kind,
+            amount,
+            shares: withdrawn_shares,
+            tickets: withdrawn_tickets as u64,
+            block_height: env.block.height,
+            lottery_id: None,
+        },
+        config.max_history_entries,
+    )?;
+
     store_depositor_info(deps.storage, &info.sender, depositor_info, env.block.height)?;
     STATE.save(deps.storage, &state)?;
     POOL.save(deps.storage, &pool)?;
@@ -1043,13 +1730,23 @@ pub fn execute_claim_unbonded(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let state = STATE.load(deps.storage)?;
 
     let mut depositor = read_depositor_info(deps.storage, &info.sender);
 
-    let to_send = claim_unbonded_withdrawals(&mut depositor, &env.block, None)?;
+    let (to_send, released_epochs) = claim_unbonded_withdrawals(
+        deps.storage,
+        &info.sender,
+        &mut depositor,
+        &env.block,
+        None,
+        start_after,
+        limit,
+    )?;
 
     let current_lottery = read_lottery_info(deps.storage, state.current_lottery);
     if current_lottery.rand_round != 0 {
@@ -1086,6 +1783,21 @@ pub fn execute_claim_unbonded(
         });
     }
 
+    append_depositor_history(
+        deps.storage,
+        &info.sender,
+        DepositorActivity {
+            kind: DepositorActivityKind::ClaimUnbonded,
+            amount: Uint256::from(net_send),
+            shares: Uint256::zero(),
+            tickets: 0,
+            block_height: env.block.height,
+            lottery_id: None,
+        },
+        config.max_history_entries,
+    )?;
+
+    remove_unbonding_claims(deps.storage, &info.sender, &released_epochs);
     store_depositor_info(deps.storage, &info.sender, depositor, env.block.height)?;
     STATE.save(deps.storage, &state)?;
 
@@ -1110,69 +1822,147 @@ pub fn execute_claim_lottery(
     env: Env,
     info: MessageInfo,
     lottery_ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    claim_lottery(deps, env, info.sender, lottery_ids)
+}
+
+pub fn execute_claim_lottery_for(
+    deps: DepsMut,
+    env: Env,
+    depositor: String,
+    lottery_ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    let depositor = deps.api.addr_validate(&depositor)?;
+
+    // Only prizes going to a charity can be claimed on behalf of the depositor
+    if read_prize_beneficiary(deps.storage, &depositor)?.is_none() {
+        return Err(ContractError::NotInDonationMode {});
+    }
+
+    claim_lottery(deps, env, depositor, lottery_ids)
+}
+
+/// Claims the prizes of depositor, which are sent to their prize beneficiary if any
+fn claim_lottery(
+    mut deps: DepsMut,
+    env: Env,
+    depositor: Addr,
+    lottery_ids: Vec<u64>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let state = STATE.load(deps.storage)?;
 
-    let mut ust_to_send = Uint128::zero();
-    let mut glow_to_send = Uint128::zero();
-
     let current_lottery = read_lottery_info(deps.storage, state.current_lottery);
     if current_lottery.rand_round != 0 {
         return Err(ContractError::LotteryAlreadyStarted {});
     }
 
-    for lottery_id in lottery_ids.clone() {
-        let lottery_info = read_lottery_info(deps.storage, lottery_id);
-        if !lottery_info.awarded {
-            return Err(ContractError::InvalidClaimLotteryNotAwarded(lottery_id));
-        }
-        //Calculate and add to to_send
-        let lottery_key: U64Key = U64Key::from(lottery_id);
-        let prize = PRIZES
-            .may_load(deps.storage, (lottery_key.clone(), &info.sender))
-            .unwrap();
-        if let Some(prize) = prize {
-            if prize.claimed {
-                return Err(ContractError::InvalidClaimPrizeAlreadyClaimed(lottery_id));
-            }
+    let ClaimablePrizes {
+        ust_to_send,
+        glow_to_send,
+        prizes,
+    } = calculate_claimable_prizes(deps.as_ref(), &config, &depositor, &lottery_ids)?;
+
+    // Mark the prizes as claimed
+    for (lottery_id, prize, prize_ust) in prizes {
+        PRIZES.save(
+            deps.storage,
+            (U64Key::from(lottery_id), &depositor),
+            &PrizeInfo {
+                claimed: true,
+                ..prize
+            },
+        )?;
 
-            let snapshotted_depositor_stats_info = read_depositor_stats_at_height(
-                deps.storage,
-                &info.sender,
-                lottery_info.block_height,
-            );
+        append_depositor_history(
+            deps.storage,
+            &depositor,
+            DepositorActivity {
+                kind: DepositorActivityKind::ClaimPrize,
+                amount: Uint256::from(prize_ust),
+                shares: Uint256::zero(),
+                tickets: 0,
+                block_height: env.block.height,
+                lottery_id: Some(lottery_id),
+            },
+            config.max_history_entries,
+        )?;
+    }
 
-            let (local_ust_to_send, local_glow_to_send): (Uint128, Uint128) =
-                calculate_winner_prize(
-                    &deps.querier,
-                    &config,
-                    &prize,
-                    &lottery_info,
-                    &snapshotted_depositor_stats_info,
-                    &info.sender,
-                )?;
+    let PrizePayout {
+        msgs,
+        recipient,
+        net_send,
+        operator_commission,
+    } = pay_prizes(
+        deps.branch(),
+        &env,
+        &config,
+        &depositor,
+        ust_to_send,
+        glow_to_send,
+    )?;
 
-            ust_to_send += local_ust_to_send;
-            glow_to_send += local_glow_to_send;
+    // Update storage
+    STATE.save(deps.storage, &state)?;
 
-            PRIZES.save(
-                deps.storage,
-                (lottery_key, &info.sender),
-                &PrizeInfo {
-                    claimed: true,
-                    ..prize
-                },
-            )?;
-        }
-    }
+    // Send response
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "claim_lottery"),
+        attr("lottery_ids", format!("{:?}", lottery_ids)),
+        attr("depositor", depositor.to_string()),
+        attr("recipient", recipient.to_string()),
+        attr("redeemed_ust", net_send),
+        attr("redeemed_glow", glow_to_send),
+        attr("operator_commission", operator_commission),
+    ]))
+}
+
+struct PrizePayout {
+    msgs: Vec<CosmosMsg>,
+    recipient: Addr,
+    // Amount of ust received by the recipient after the operator commission and taxes
+    net_send: Uint128,
+    operator_commission: Uint128,
+}
 
+/// Pays the prizes claimed by depositor: its operator accrues its commission on ust_to_send,
+/// and the rest is sent along with glow_to_send to the prize beneficiary of the depositor if any
+fn pay_prizes(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    depositor: &Addr,
+    ust_to_send: Uint128,
+    glow_to_send: Uint128,
+) -> Result<PrizePayout, ContractError> {
     // If ust_to_send is zero, don't send anything even if glow_to_send is positive.
     // It should never be the case that ust_to_send is 0 and glow_to_send is positive.
     if ust_to_send == Uint128::zero() {
         return Err(ContractError::InsufficientClaimableFunds {});
     }
 
+    // The operator of the depositor accrues its commission on the prizes
+    let depositor_info = read_depositor_info(deps.storage, depositor);
+    let mut operator_commission = Uint128::zero();
+    if depositor_info.operator_addr != Addr::unchecked("") {
+        let mut operator = read_operator_info(deps.storage, &depositor_info.operator_addr);
+        let agreed_rate = read_operator_commission_rate(deps.storage, depositor)?;
+        operator_commission =
+            calculate_operator_commission(config, &operator, agreed_rate, ust_to_send);
+
+        if !operator_commission.is_zero() {
+            operator.pending_commission += Uint256::from(operator_commission);
+            store_operator_info(deps.storage, &depositor_info.operator_addr, operator)?;
+        }
+    }
+    let ust_to_send = ust_to_send - operator_commission;
+
+    // Prizes of depositors in donation mode go to their charity
+    let beneficiary = read_prize_beneficiary(deps.storage, depositor)?;
+    let recipient = beneficiary.clone().unwrap_or_else(|| depositor.clone());
+
     let mut msgs: Vec<CosmosMsg> = vec![];
 
     // ust_to_send calculations
@@ -1198,13 +1988,16 @@ pub fn execute_claim_lottery(
         });
     }
 
-    msgs.push(CosmosMsg::Bank(BankMsg::Send {
-        to_address: info.sender.to_string(),
-        amount: vec![Coin {
-            denom: config.stable_denom,
-            amount: net_send,
-        }],
-    }));
+    // The whole prize can go to the operator commission, in which case there is nothing to send
+    if !net_send.is_zero() {
+        msgs.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: config.stable_denom.clone(),
+                amount: net_send,
+            }],
+        }));
+    }
 
     // glow_to_send calculations
 
@@ -1213,307 +2006,1176 @@ pub fn execute_claim_lottery(
             contract_addr: config.distributor_contract.to_string(),
             funds: vec![],
             msg: to_binary(&FaucetExecuteMsg::Spend {
-                recipient: info.sender.to_string(),
+                recipient: recipient.to_string(),
                 amount: glow_to_send,
             })?,
         }));
     }
 
-    // Update storage
-    STATE.save(deps.storage, &state)?;
+    // Keep track of the amounts donated to the charity
+    if let Some(beneficiary) = beneficiary {
+        CHARITIES.update(deps.storage, &beneficiary, |charity| -> StdResult<_> {
+            let mut charity = charity.ok_or_else(|| StdError::not_found("charity"))?;
+            charity.donated_ust += Uint256::from(net_send);
+            charity.donated_glow += Uint256::from(glow_to_send);
+            Ok(charity)
+        })?;
+    }
 
-    // Send response
+    Ok(PrizePayout {
+        msgs,
+        recipient,
+        net_send,
+        operator_commission,
+    })
+}
 
-    Ok(Response::new().add_messages(msgs).add_attributes(vec![
-        attr("action", "claim_lottery"),
-        attr("lottery_ids", format!("{:?}", lottery_ids)),
-        attr("depositor", info.sender.to_string()),
-        attr("redeemed_ust", net_send),
-        attr("redeemed_glow", glow_to_send),
+pub fn execute_create_syndicate(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+) -> Result<Response, ContractError> {
+    let syndicate_id = SYNDICATE_COUNT.may_load(deps.storage)?.unwrap_or_default();
+
+    SYNDICATES.save(
+        deps.storage,
+        U64Key::from(syndicate_id),
+        &Syndicate {
+            name,
+            creator: info.sender.clone(),
+        },
+    )?;
+    SYNDICATE_COUNT.save(deps.storage, &(syndicate_id + 1))?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "create_syndicate"),
+        attr("syndicate_id", syndicate_id.to_string()),
+        attr("creator", info.sender.to_string()),
+        attr("depositor", syndicate_address(syndicate_id).to_string()),
     ]))
 }
 
-pub fn execute_epoch_ops(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+pub fn execute_claim_syndicate_lottery(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    syndicate_id: u64,
+    lottery_ids: Vec<u64>,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let pool = POOL.load(deps.storage)?;
-    let mut state = STATE.load(deps.storage)?;
-
-    // Validate distributor contract has already been registered
-    if !config.contracts_registered() {
-        return Err(ContractError::NotRegistered {});
-    }
+    let state = STATE.load(deps.storage)?;
 
-    // Validate that executing epoch will follow rate limiting
-    if !state.next_epoch.is_expired(&env.block) {
-        return Err(ContractError::InvalidEpochExecution {});
+    if !SYNDICATES.has(deps.storage, U64Key::from(syndicate_id)) {
+        return Err(ContractError::InvalidSyndicate(syndicate_id));
     }
 
-    // Validate that the lottery is not in the process of running
-    // This helps avoid delaying the computing of the reward following lottery execution.
     let current_lottery = read_lottery_info(deps.storage, state.current_lottery);
     if current_lottery.rand_round != 0 {
         return Err(ContractError::LotteryAlreadyStarted {});
     }
 
-    // Compute global Glow rewards
-    compute_global_operator_reward(&mut state, &pool, env.block.height);
-    compute_global_sponsor_reward(&mut state, &pool, env.block.height);
+    let syndicate = syndicate_address(syndicate_id);
+    let mut ust_to_send = Uint128::zero();
+    let mut glow_to_send = Uint128::zero();
 
-    // Compute total_reserves to fund community contract
-    let total_reserves = state.total_reserve;
-    let messages: Vec<CosmosMsg> = if !total_reserves.is_zero() {
-        vec![CosmosMsg::Bank(BankMsg::Send {
-            to_address: config.community_contract.to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom,
-                    amount: total_reserves.into(),
-                },
-            )?],
-        })]
-    } else {
-        vec![]
-    };
+    for lottery_id in lottery_ids.iter() {
+        let lottery_info = read_lottery_info(deps.storage, *lottery_id);
+        if !lottery_info.awarded {
+            return Err(ContractError::InvalidClaimLotteryNotAwarded(*lottery_id));
+        }
 
-    // Update next_epoch based on epoch_interval
-    state.next_epoch = Expiration::AtTime(env.block.time).add(config.epoch_interval)?;
-    // Empty total reserve and store state
-    state.total_reserve = Uint256::zero();
-    STATE.save(deps.storage, &state)?;
+        let mut prize =
+            match PRIZES.may_load(deps.storage, (U64Key::from(*lottery_id), &syndicate))? {
+                Some(prize) => prize,
+                None => continue,
+            };
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "execute_epoch_operations"),
-        attr("total_reserves", total_reserves.to_string()),
-    ]))
-}
+        // Each member can claim their share of a syndicate prize once
+        let mut claimed_syndicates = SYNDICATE_PRIZE_CLAIMS
+            .may_load(deps.storage, (U64Key::from(*lottery_id), &info.sender))?
+            .unwrap_or_default();
+        if claimed_syndicates.contains(&syndicate_id) {
+            return Err(ContractError::InvalidClaimPrizeAlreadyClaimed(*lottery_id));
+        }
+        claimed_syndicates.push(syndicate_id);
+        SYNDICATE_PRIZE_CLAIMS.save(
+            deps.storage,
+            (U64Key::from(*lottery_id), &info.sender),
+            &claimed_syndicates,
+        )?;
 
-pub fn execute_claim_rewards(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    let pool = POOL.load(deps.storage)?;
-    let mut state = STATE.load(deps.storage)?;
+        // Split the prize pro rata to the shares of the syndicate at the lottery
+        let snapshotted_syndicate_stats_info = read_winner_stats(
+            deps.storage,
+            &syndicate,
+            *lottery_id,
+            lottery_info.block_height,
+        );
+        let member_shares = read_syndicate_member_shares_at_lottery(
+            deps.storage,
+            syndicate_id,
+            &info.sender,
+            *lottery_id,
+            lottery_info.block_height,
+        )?;
 
-    let depositor_address = info.sender.as_str();
-    let mut sponsor: SponsorInfo = read_sponsor_info(deps.storage, &info.sender);
-    let mut operator: OperatorInfo = read_operator_info(deps.storage, &info.sender);
+        if member_shares.is_zero() {
+            continue;
+        }
 
-    // Validate distributor contract has already been registered
-    if !config.contracts_registered() {
-        return Err(ContractError::NotRegistered {});
-    }
+        // The glow of the member is boosted by their own veGLOW balance, as if they
+        // had won the prize with their shares of the syndicate
+        let (prize_ust, prize_glow) = calculate_winner_prize(
+            &deps.querier,
+            &config,
+            &prize,
+            &lottery_info,
+            &DepositorStatsInfo {
+                shares: member_shares,
+                ..snapshotted_syndicate_stats_info.clone()
+            },
+            &info.sender,
+        )?;
 
-    // Compute Glow depositor rewards
-    compute_global_operator_reward(&mut state, &pool, env.block.height);
-    compute_global_sponsor_reward(&mut state, &pool, env.block.height);
-    compute_operator_reward(&state, &mut operator);
-    compute_sponsor_reward(&state, &mut sponsor);
+        let member_ust = Uint128::from(
+            Uint256::from(prize_ust)
+                .multiply_ratio(member_shares, snapshotted_syndicate_stats_info.shares),
+        );
+        let member_glow = Uint128::from(
+            Uint256::from(prize_glow)
+                .multiply_ratio(member_shares, snapshotted_syndicate_stats_info.shares),
+        );
 
-    let claim_amount = (operator.pending_rewards + sponsor.pending_rewards) * Uint256::one();
-    sponsor.pending_rewards = Decimal256::zero();
-    operator.pending_rewards = Decimal256::zero();
-    STATE.save(deps.storage, &state)?;
-    store_sponsor_info(deps.storage, &info.sender, sponsor)?;
-    store_operator_info(deps.storage, &info.sender, operator)?;
+        // The prize is claimed once all the syndicate shares have claimed their share of it
+        let claimed_shares = SYNDICATE_PRIZE_CLAIMED_SHARES
+            .may_load(
+                deps.storage,
+                (U64Key::from(*lottery_id), U64Key::from(syndicate_id)),
+            )?
+            .unwrap_or_else(Uint256::zero)
+            + member_shares;
+        SYNDICATE_PRIZE_CLAIMED_SHARES.save(
+            deps.storage,
+            (U64Key::from(*lottery_id), U64Key::from(syndicate_id)),
+            &claimed_shares,
+        )?;
+        if claimed_shares >= snapshotted_syndicate_stats_info.shares {
+            prize.claimed = true;
+            PRIZES.save(
+                deps.storage,
+                (U64Key::from(*lottery_id), &syndicate),
+                &prize,
+            )?;
+        }
 
-    let messages: Vec<CosmosMsg> = if !claim_amount.is_zero() {
-        vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.distributor_contract.to_string(),
-            funds: vec![],
-            msg: to_binary(&FaucetExecuteMsg::Spend {
-                recipient: depositor_address.to_string(),
-                amount: claim_amount.into(),
-            })?,
-        })]
-    } else {
-        vec![]
-    };
+        ust_to_send += member_ust;
+        glow_to_send += member_glow;
+
+        append_depositor_history(
+            deps.storage,
+            &info.sender,
+            DepositorActivity {
+                kind: DepositorActivityKind::ClaimPrize,
+                amount: Uint256::from(member_ust),
+                shares: Uint256::zero(),
+                tickets: 0,
+                block_height: env.block.height,
+                lottery_id: Some(*lottery_id),
+            },
+            config.max_history_entries,
+        )?;
+    }
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "claim_rewards"),
-        attr("claim_amount", claim_amount),
+    let PrizePayout {
+        msgs,
+        recipient,
+        net_send,
+        operator_commission,
+    } = pay_prizes(
+        deps.branch(),
+        &env,
+        &config,
+        &info.sender,
+        ust_to_send,
+        glow_to_send,
+    )?;
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "claim_syndicate_lottery"),
+        attr("syndicate_id", syndicate_id.to_string()),
+        attr("lottery_ids", format!("{:?}", lottery_ids)),
+        attr("member", info.sender.to_string()),
+        attr("recipient", recipient.to_string()),
+        attr("redeemed_ust", net_send),
+        attr("redeemed_glow", glow_to_send),
+        attr("operator_commission", operator_commission),
     ]))
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn execute_update_config(
-    deps: DepsMut,
+pub fn execute_exit_syndicate(
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    owner: Option<String>,
-    oracle_addr: Option<String>,
-    reserve_factor: Option<Decimal256>,
-    instant_withdrawal_fee: Option<Decimal256>,
-    unbonding_period: Option<u64>,
-    epoch_interval: Option<u64>,
-    max_holders: Option<u8>,
-    max_tickets_per_depositor: Option<u64>,
-    paused: Option<bool>,
-    lotto_winner_boost_config: Option<BoostConfig>,
-    operator_glow_emission_rate: Option<Decimal256>,
-    sponsor_glow_emission_rate: Option<Decimal256>,
+    syndicate_id: u64,
 ) -> Result<Response, ContractError> {
-    let mut config: Config = CONFIG.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    let mut pool = POOL.load(deps.storage)?;
 
-    // check permission
-    if info.sender != config.owner {
-        return Err(ContractError::Unauthorized {});
+    // Validate that there isn't a lottery in progress already
+    let current_lottery = read_lottery_info(deps.storage, state.current_lottery);
+    if current_lottery.rand_round != 0 {
+        return Err(ContractError::LotteryAlreadyStarted {});
     }
 
-    // change owner of Glow lotto contract
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_validate(owner.as_str())?;
+    let member_shares = read_syndicate_member_shares(deps.storage, syndicate_id, &info.sender)?;
+    if member_shares.is_zero() {
+        return Err(ContractError::NotSyndicateMember {});
     }
 
-    // change oracle contract addr
-    if let Some(oracle_addr) = oracle_addr {
-        config.owner = deps.api.addr_validate(oracle_addr.as_str())?;
-    }
+    let syndicate = syndicate_address(syndicate_id);
+    let mut syndicate_info = read_depositor_info(deps.storage, &syndicate);
+    let mut member_info = read_depositor_info(deps.storage, &info.sender);
 
-    if let Some(reserve_factor) = reserve_factor {
-        if reserve_factor > Decimal256::one() {
-            return Err(ContractError::InvalidReserveFactor {});
-        }
+    // Get the aust exchange rate
+    let aust_exchange_rate = query_exchange_rate(
+        deps.as_ref(),
+        config.anchor_contract.to_string(),
+        env.block.height,
+    )?
+    .exchange_rate;
 
-        config.reserve_factor = reserve_factor;
-    }
+    let member_shares_value = member_shares
+        .multiply_ratio(pool.total_user_aust, pool.total_user_shares)
+        * aust_exchange_rate;
 
-    if let Some(instant_withdrawal_fee) = instant_withdrawal_fee {
-        if instant_withdrawal_fee > Decimal256::one() {
-            return Err(ContractError::InvalidWithdrawalFee {});
-        }
-        config.instant_withdrawal_fee = instant_withdrawal_fee;
+    // Remove the tickets that the syndicate can't afford without the member shares
+    syndicate_info.shares = syndicate_info.shares - member_shares;
+    let syndicate_balance = syndicate_info
+        .shares
+        .multiply_ratio(pool.total_user_aust, pool.total_user_shares)
+        * aust_exchange_rate;
+    let max_syndicate_tickets =
+        Uint128::from(syndicate_balance / Decimal256::from_uint256(config.ticket_price)).u128()
+            as usize;
+    let removed_tickets = syndicate_info
+        .tickets
+        .len()
+        .saturating_sub(max_syndicate_tickets);
+
+    for seq in syndicate_info.tickets.drain(..removed_tickets) {
+        TICKETS.update(deps.storage, seq.as_bytes(), |tickets| -> StdResult<_> {
+            let mut new_tickets = tickets.unwrap();
+            let index = new_tickets.iter().position(|x| *x == syndicate).unwrap();
+            let _elem = new_tickets.remove(index);
+            Ok(new_tickets)
+        })?;
     }
+    state.total_tickets = state
+        .total_tickets
+        .sub(Uint256::from(removed_tickets as u128));
 
-    if let Some(unbonding_period) = unbonding_period {
-        config.unbonding_period = Duration::Time(unbonding_period);
-    }
+    // Age the member's deposits with the time the syndicate deposits were made
+    let syndicate_deposit_time = read_deposit_age(deps.storage, &syndicate, env.block.time);
+    update_deposit_age(
+        deps.storage,
+        &info.sender,
+        member_info.shares,
+        member_shares,
+        syndicate_deposit_time,
+    )?;
 
-    if let Some(epoch_interval) = epoch_interval {
-        // validate that epoch_interval is at least 30 minutes
-        if epoch_interval < THIRTY_MINUTE_TIME {
-            return Err(ContractError::InvalidEpochInterval {});
-        }
+    // Move the shares to the member's own position
+    compute_global_operator_reward(&mut state, &pool, env.block.height);
+    handle_depositor_operator_updates(
+        deps.branch(),
+        &mut state,
+        &mut pool,
+        &info.sender,
+        &mut member_info,
+        member_shares,
+        None,
+    )?;
+    member_info.shares = member_info.shares.add(member_shares);
 
-        config.epoch_interval = Duration::Time(epoch_interval);
-    }
+    // Exited members are kept without shares, so that the shares they had
+    // at past lotteries can still be exported
+    SYNDICATE_MEMBERS.save(
+        deps.storage,
+        (U64Key::from(syndicate_id), &info.sender),
+        &Uint256::zero(),
+        env.block.height,
+    )?;
 
-    if let Some(max_holders) = max_holders {
-        // Validate that max_holders is within the bounds
-        if max_holders < MAX_HOLDERS_FLOOR || MAX_HOLDERS_CAP < max_holders {
-            return Err(ContractError::InvalidMaxHoldersOutsideBounds {});
-        }
+    append_depositor_history(
+        deps.storage,
+        &info.sender,
+        DepositorActivity {
+            kind: DepositorActivityKind::ExitSyndicate,
+            amount: member_shares_value,
+            shares: member_shares,
+            tickets: 0,
+            block_height: env.block.height,
+            lottery_id: None,
+        },
+        config.max_history_entries,
+    )?;
 
-        // Validate that max_holders is increasing
-        if max_holders < config.max_holders {
-            return Err(ContractError::InvalidMaxHoldersAttemptedDecrease {});
+    store_depositor_info(deps.storage, &syndicate, syndicate_info, env.block.height)?;
+    store_depositor_info(deps.storage, &info.sender, member_info, env.block.height)?;
+    STATE.save(deps.storage, &state)?;
+    POOL.save(deps.storage, &pool)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "exit_syndicate"),
+        attr("syndicate_id", syndicate_id.to_string()),
+        attr("member", info.sender.to_string()),
+        attr("shares", member_shares.to_string()),
+        attr("removed_tickets", removed_tickets.to_string()),
+    ]))
+}
+
+pub fn execute_set_prize_beneficiary(
+    deps: DepsMut,
+    info: MessageInfo,
+    beneficiary: Option<String>,
+) -> Result<Response, ContractError> {
+    let beneficiary = if let Some(beneficiary) = beneficiary {
+        let beneficiary = deps.api.addr_validate(&beneficiary)?;
+
+        // Validate that the beneficiary is a verified charity
+        let verified = CHARITIES
+            .may_load(deps.storage, &beneficiary)?
+            .map(|charity| charity.verified)
+            .unwrap_or(false);
+        if !verified {
+            return Err(ContractError::CharityNotRegistered {});
         }
 
-        config.max_holders = max_holders;
-    }
+        PRIZE_BENEFICIARIES.save(deps.storage, &info.sender, &beneficiary)?;
+        beneficiary.to_string()
+    } else {
+        PRIZE_BENEFICIARIES.remove(deps.storage, &info.sender);
+        info.sender.to_string()
+    };
 
-    if let Some(max_tickets_per_depositor) = max_tickets_per_depositor {
-        config.max_tickets_per_depositor = max_tickets_per_depositor;
-    }
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_prize_beneficiary"),
+        attr("depositor", info.sender.to_string()),
+        attr("beneficiary", beneficiary),
+    ]))
+}
 
-    if let Some(paused) = paused {
-        if !paused {
-            // Make sure that there isn't any old data left if you are unpausing
+pub fn execute_register_charity(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    name: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
 
-            let old_depositors = old_read_depositors(deps.as_ref(), None, Some(1))?;
-            if !old_depositors.is_empty() {
-                return Err(ContractError::Std(StdError::generic_err(
-                    "Cannot unpause contract with old depositors",
-                )));
-            }
-        }
-        config.paused = paused;
+    // Permission check
+    if info.sender != config.gov_contract {
+        return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(lotto_winner_boost_config) = lotto_winner_boost_config {
-        if lotto_winner_boost_config.base_multiplier > lotto_winner_boost_config.max_multiplier {
-            return Err(ContractError::InvalidBoostConfig {});
-        }
-        config.lotto_winner_boost_config = lotto_winner_boost_config
+    let address = deps.api.addr_validate(&address)?;
+    CHARITIES.update(deps.storage, &address, |charity| -> StdResult<_> {
+        Ok(match charity {
+            Some(charity) => Charity {
+                name: name.clone(),
+                verified: true,
+                ..charity
+            },
+            None => Charity {
+                name: name.clone(),
+                verified: true,
+                donated_ust: Uint256::zero(),
+                donated_glow: Uint256::zero(),
+            },
+        })
+    })?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_charity"),
+        attr("charity", address.to_string()),
+        attr("name", name),
+    ]))
+}
+
+pub fn execute_unregister_charity(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.gov_contract {
+        return Err(ContractError::Unauthorized {});
     }
 
-    CONFIG.save(deps.storage, &config)?;
+    let address = deps.api.addr_validate(&address)?;
+    let mut charity = CHARITIES
+        .may_load(deps.storage, &address)?
+        .ok_or(ContractError::CharityNotRegistered {})?;
+    charity.verified = false;
+    CHARITIES.save(deps.storage, &address, &charity)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "unregister_charity"),
+        attr("charity", address.to_string()),
+    ]))
+}
 
+pub fn execute_epoch_ops(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
     let mut state = STATE.load(deps.storage)?;
 
-    if let Some(operator_glow_emission_rate) = operator_glow_emission_rate {
-        state.operator_reward_emission_index.glow_emission_rate = operator_glow_emission_rate;
+    // Validate distributor contract has already been registered
+    if !config.contracts_registered() {
+        return Err(ContractError::NotRegistered {});
     }
 
-    if let Some(sponsor_glow_emission_rate) = sponsor_glow_emission_rate {
-        state.sponsor_reward_emission_index.glow_emission_rate = sponsor_glow_emission_rate;
+    // Validate that executing epoch will follow rate limiting
+    if !state.next_epoch.is_expired(&env.block) {
+        return Err(ContractError::InvalidEpochExecution {});
+    }
+
+    // Validate that the lottery is not in the process of running
+    // This helps avoid delaying the computing of the reward following lottery execution.
+    let current_lottery = read_lottery_info(deps.storage, state.current_lottery);
+    if current_lottery.rand_round != 0 {
+        return Err(ContractError::LotteryAlreadyStarted {});
     }
 
+    // Compute global Glow rewards
+    compute_global_operator_reward(&mut state, &pool, env.block.height);
+    compute_global_sponsor_reward(&mut state, &pool, env.block.height);
+
+    // Split each reserve source among its destinations
+    let total_reserves = state.total_reserve;
+    let withdrawal_fee_reserve = state.withdrawal_fee_reserve;
+    let prize_reserve = state.prize_reserve;
+    let messages = calculate_reserve_transfers(&config, &state)
+        .into_iter()
+        .map(|(to_address, amount)| {
+            Ok(CosmosMsg::Bank(BankMsg::Send {
+                to_address,
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: config.stable_denom.clone(),
+                        amount: amount.into(),
+                    },
+                )?],
+            }))
+        })
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    // Update next_epoch based on epoch_interval
+    state.next_epoch = Expiration::AtTime(env.block.time).add(config.epoch_interval)?;
+    // Empty total reserve and store state
+    state.total_reserve = Uint256::zero();
+    state.withdrawal_fee_reserve = Uint256::zero();
+    state.prize_reserve = Uint256::zero();
     STATE.save(deps.storage, &state)?;
 
-    Ok(Response::new().add_attributes(vec![("action", "update_config")]))
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "execute_epoch_operations"),
+        attr("total_reserves", total_reserves.to_string()),
+        attr("withdrawal_fee_reserve", withdrawal_fee_reserve.to_string()),
+        attr("prize_reserve", prize_reserve.to_string()),
+    ]))
 }
 
-pub fn execute_update_lottery_config(
+pub fn execute_claim_rewards(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    lottery_interval: Option<u64>,
-    block_time: Option<u64>,
-    ticket_price: Option<Uint256>,
-    prize_distribution: Option<[Decimal256; NUM_PRIZE_BUCKETS]>,
-    round_delta: Option<u64>,
 ) -> Result<Response, ContractError> {
-    let mut config: Config = CONFIG.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
 
-    // check permission
-    if info.sender != config.owner {
-        return Err(ContractError::Unauthorized {});
-    }
+    let depositor_address = info.sender.as_str();
+    let mut sponsor: SponsorInfo = read_sponsor_info(deps.storage, &info.sender);
+    let mut operator: OperatorInfo = read_operator_info(deps.storage, &info.sender);
 
-    if let Some(lottery_interval) = lottery_interval {
-        config.lottery_interval = Duration::Time(lottery_interval);
+    // Validate distributor contract has already been registered
+    if !config.contracts_registered() {
+        return Err(ContractError::NotRegistered {});
     }
 
-    if let Some(block_time) = block_time {
-        config.block_time = Duration::Time(block_time);
-    }
+    // Compute Glow depositor rewards
+    compute_global_operator_reward(&mut state, &pool, env.block.height);
+    compute_global_sponsor_reward(&mut state, &pool, env.block.height);
+    compute_operator_reward(&state, &mut operator);
+    compute_sponsor_reward(&state, &mut sponsor);
 
-    if let Some(round_delta) = round_delta {
-        config.round_delta = round_delta;
-    }
+    let claim_amount = (operator.pending_rewards + sponsor.pending_rewards) * Uint256::one();
+    sponsor.pending_rewards = Decimal256::zero();
+    operator.pending_rewards = Decimal256::zero();
+    STATE.save(deps.storage, &state)?;
+    store_sponsor_info(deps.storage, &info.sender, sponsor)?;
+    store_operator_info(deps.storage, &info.sender, operator)?;
 
-    if let Some(ticket_price) = ticket_price {
-        config.ticket_price = ticket_price;
+    let messages: Vec<CosmosMsg> = if !claim_amount.is_zero() {
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.distributor_contract.to_string(),
+            funds: vec![],
+            msg: to_binary(&FaucetExecuteMsg::Spend {
+                recipient: depositor_address.to_string(),
+                amount: claim_amount.into(),
+            })?,
+        })]
+    } else {
+        vec![]
+    };
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "claim_rewards"),
+        attr("claim_amount", claim_amount),
+    ]))
+}
+
+pub fn execute_register_operator(
+    deps: DepsMut,
+    info: MessageInfo,
+    commission_rate: Decimal256,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if commission_rate > config.max_operator_commission {
+        return Err(ContractError::OperatorCommissionTooHigh {
+            commission_rate,
+            max_operator_commission: config.max_operator_commission,
+        });
     }
 
-    if let Some(prize_distribution) = prize_distribution {
-        if prize_distribution.len() != NUM_PRIZE_BUCKETS {
-            return Err(ContractError::InvalidPrizeDistribution {});
-        }
+    let mut operator = read_operator_info(deps.storage, &info.sender);
+    operator.commission_rate = commission_rate;
+    store_operator_info(deps.storage, &info.sender, operator)?;
 
-        let mut sum = Decimal256::zero();
-        for item in prize_distribution.iter() {
-            sum += *item;
-        }
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_operator"),
+        attr("operator", info.sender.to_string()),
+        attr("commission_rate", commission_rate.to_string()),
+    ]))
+}
 
-        if sum != Decimal256::one() {
-            return Err(ContractError::InvalidPrizeDistribution {});
-        }
+pub fn execute_claim_operator_commission(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut operator = read_operator_info(deps.storage, &info.sender);
 
-        config.prize_distribution = prize_distribution;
+    let commission = Uint128::from(operator.pending_commission);
+    if commission.is_zero() {
+        return Err(ContractError::InsufficientClaimableFunds {});
     }
 
-    CONFIG.save(deps.storage, &config)?;
+    // Double-check if there is enough balance to send in the contract
+    let balance = query_balance(
+        deps.as_ref(),
+        env.contract.address.to_string(),
+        config.stable_denom.clone(),
+    )?;
 
-    Ok(Response::new().add_attributes(vec![("action", "update_lottery_config")]))
-}
+    if commission > balance.into() {
+        return Err(ContractError::InsufficientFunds {
+            to_send: commission,
+            available_balance: balance,
+        });
+    }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+    // Deduct taxes on the claim
+    let net_send = deduct_tax(
+        deps.as_ref(),
+        coin(commission.into(), config.stable_denom.clone()),
+    )?
+    .amount;
+
+    operator.pending_commission = Uint256::zero();
+    store_operator_info(deps.storage, &info.sender, operator)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin {
+                denom: config.stable_denom,
+                amount: net_send,
+            }],
+        }))
+        .add_attributes(vec![
+            attr("action", "claim_operator_commission"),
+            attr("operator", info.sender.to_string()),
+            attr("commission", commission),
+            attr("redeemed_ust", net_send),
+        ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    oracle_addr: Option<String>,
+    reserve_factor: Option<Decimal256>,
+    instant_withdrawal_fee: Option<Decimal256>,
+    unbonding_period: Option<u64>,
+    epoch_interval: Option<u64>,
+    max_holders: Option<u8>,
+    max_tickets_per_depositor: Option<u64>,
+    paused: Option<bool>,
+    lotto_winner_boost_config: Option<BoostConfig>,
+    operator_glow_emission_rate: Option<Decimal256>,
+    sponsor_glow_emission_rate: Option<Decimal256>,
+    max_history_entries: Option<u64>,
+    instant_withdrawal_fee_decay: Option<InstantWithdrawalFeeDecay>,
+    max_operator_commission: Option<Decimal256>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // check permission
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // change owner of Glow lotto contract
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_validate(owner.as_str())?;
+    }
+
+    // change oracle contract addr
+    if let Some(oracle_addr) = oracle_addr {
+        config.owner = deps.api.addr_validate(oracle_addr.as_str())?;
+    }
+
+    if let Some(reserve_factor) = reserve_factor {
+        if reserve_factor > Decimal256::one() {
+            return Err(ContractError::InvalidReserveFactor {});
+        }
+
+        config.reserve_factor = reserve_factor;
+    }
+
+    if let Some(instant_withdrawal_fee) = instant_withdrawal_fee {
+        if instant_withdrawal_fee > Decimal256::one() {
+            return Err(ContractError::InvalidWithdrawalFee {});
+        }
+        config.instant_withdrawal_fee = instant_withdrawal_fee;
+    }
+
+    if let Some(unbonding_period) = unbonding_period {
+        config.unbonding_period = Duration::Time(unbonding_period);
+    }
+
+    if let Some(epoch_interval) = epoch_interval {
+        // validate that epoch_interval is at least 30 minutes
+        if epoch_interval < THIRTY_MINUTE_TIME {
+            return Err(ContractError::InvalidEpochInterval {});
+        }
+
+        config.epoch_interval = Duration::Time(epoch_interval);
+    }
+
+    // Epochs of the unbonding ledger can't mix time and height keys
+    validate_unbonding_units(&config)?;
+
+    if let Some(max_holders) = max_holders {
+        // Validate that max_holders is within the bounds
+        if max_holders < MAX_HOLDERS_FLOOR || MAX_HOLDERS_CAP < max_holders {
+            return Err(ContractError::InvalidMaxHoldersOutsideBounds {});
+        }
+
+        // Validate that max_holders is increasing
+        if max_holders < config.max_holders {
+            return Err(ContractError::InvalidMaxHoldersAttemptedDecrease {});
+        }
+
+        config.max_holders = max_holders;
+    }
+
+    if let Some(max_tickets_per_depositor) = max_tickets_per_depositor {
+        config.max_tickets_per_depositor = max_tickets_per_depositor;
+    }
+
+    if let Some(paused) = paused {
+        if !paused {
+            // Make sure that there isn't any old data left if you are unpausing
+            assert_old_depositors_migrated(deps.as_ref())?;
+        }
+        config.paused = PauseFlags::all(paused);
+    }
+
+    if let Some(lotto_winner_boost_config) = lotto_winner_boost_config {
+        if lotto_winner_boost_config.base_multiplier > lotto_winner_boost_config.max_multiplier {
+            return Err(ContractError::InvalidBoostConfig {});
+        }
+        config.lotto_winner_boost_config = lotto_winner_boost_config
+    }
+
+    if let Some(max_history_entries) = max_history_entries {
+        if max_history_entries > MAX_HISTORY_ENTRIES_CAP {
+            return Err(ContractError::InvalidMaxHistoryEntries {});
+        }
+        config.max_history_entries = max_history_entries;
+    }
+
+    if let Some(instant_withdrawal_fee_decay) = instant_withdrawal_fee_decay {
+        config.instant_withdrawal_fee_decay = Some(instant_withdrawal_fee_decay);
+    }
+
+    if let Some(max_operator_commission) = max_operator_commission {
+        if max_operator_commission > Decimal256::one() {
+            return Err(ContractError::InvalidMaxOperatorCommission {});
+        }
+        config.max_operator_commission = max_operator_commission;
+    }
+
+    // The decayed fee can't be higher than the fee it decays from
+    if let Some(instant_withdrawal_fee_decay) = &config.instant_withdrawal_fee_decay {
+        if instant_withdrawal_fee_decay.min_fee > config.instant_withdrawal_fee {
+            return Err(ContractError::InvalidWithdrawalFeeDecay {});
+        }
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    let mut state = STATE.load(deps.storage)?;
+
+    if let Some(operator_glow_emission_rate) = operator_glow_emission_rate {
+        state.operator_reward_emission_index.glow_emission_rate = operator_glow_emission_rate;
+    }
+
+    if let Some(sponsor_glow_emission_rate) = sponsor_glow_emission_rate {
+        state.sponsor_reward_emission_index.glow_emission_rate = sponsor_glow_emission_rate;
+    }
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "update_config")]))
+}
+
+pub fn execute_set_pause_flags(
+    deps: DepsMut,
+    info: MessageInfo,
+    flags: PauseFlags,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // Permission check, the guardian can only pause more actions
+    let unpauses = !config.paused.is_subset_of(&flags);
+    let is_guardian = config.guardian.as_ref() == Some(&info.sender);
+    if info.sender != config.owner && !(is_guardian && !unpauses) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Make sure that there isn't any old data left if you are unpausing
+    if unpauses {
+        assert_old_depositors_migrated(deps.as_ref())?;
+    }
+
+    config.paused = flags;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_pause_flags"),
+        attr("sender", info.sender.to_string()),
+    ]))
+}
+
+pub fn execute_update_guardian(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.guardian = guardian
+        .map(|guardian| deps.api.addr_validate(&guardian))
+        .transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_guardian"),
+        attr(
+            "guardian",
+            config
+                .guardian
+                .map(|guardian| guardian.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+pub fn execute_update_reserve_destinations(
+    deps: DepsMut,
+    info: MessageInfo,
+    reserve_destinations: ReserveDestinations,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.reserve_destinations =
+        validate_reserve_destinations(deps.as_ref(), reserve_destinations)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "update_reserve_destinations")]))
+}
+
+pub fn execute_import_state(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    version: u32,
+    records: Vec<StateRecord>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Importing state into a live contract would break its accounting
+    if !config.paused.is_fully_paused() {
+        return Err(ContractError::ImportStateNotPaused {});
+    }
+
+    if version != STATE_EXPORT_VERSION {
+        return Err(ContractError::UnsupportedStateVersion(version));
+    }
+
+    // Depositor addresses include syndicates, which aren't valid account addresses
+    let api = deps.api;
+    let num_records = records.len();
+    for record in records {
+        match record {
+            StateRecord::Totals(totals) => {
+                let mut state = STATE.load(deps.storage)?;
+                let mut pool = POOL.load(deps.storage)?;
+
+                state.total_tickets = totals.total_tickets;
+                state.total_reserve = totals.total_reserve;
+                state.withdrawal_fee_reserve = totals.withdrawal_fee_reserve;
+                state.prize_reserve = totals.prize_reserve;
+                state.prize_buckets = totals.prize_buckets;
+                state.current_lottery = totals.current_lottery;
+                state.next_lottery_time = totals.next_lottery_time;
+                state.next_lottery_exec_time = totals.next_lottery_exec_time;
+                state.next_epoch = totals.next_epoch;
+                state.operator_reward_emission_index = totals.operator_reward_emission_index;
+                state.sponsor_reward_emission_index = totals.sponsor_reward_emission_index;
+                state.last_lottery_execution_aust_exchange_rate =
+                    totals.last_lottery_execution_aust_exchange_rate;
+                pool.total_user_aust = totals.total_user_aust;
+                pool.total_user_shares = totals.total_user_shares;
+                pool.total_sponsor_lottery_deposits = totals.total_sponsor_lottery_deposits;
+                pool.total_operator_shares = totals.total_operator_shares;
+
+                STATE.save(deps.storage, &state)?;
+                POOL.save(deps.storage, &pool)?;
+                SYNDICATE_COUNT.save(deps.storage, &totals.syndicate_count)?;
+                if let Some(deposit_ages_start) = totals.deposit_ages_start {
+                    DEPOSIT_AGES_START.save(deps.storage, &deposit_ages_start)?;
+                }
+            }
+            StateRecord::Depositor(depositor) => {
+                let address = validate_depositor_addr(api, &depositor.address)?;
+                let operator_addr = if depositor.operator.is_empty() {
+                    Addr::unchecked("")
+                } else {
+                    api.addr_validate(&depositor.operator)?
+                };
+                store_depositor_info(
+                    deps.storage,
+                    &address,
+                    DepositorInfo {
+                        shares: depositor.shares,
+                        tickets: depositor.tickets,
+                        unbonding_info: depositor.unbonding_info,
+                        operator_addr,
+                    },
+                    env.block.height,
+                )?;
+                store_share_locks(deps.storage, &address, depositor.share_locks)?;
+                if let Some(deposit_time) = depositor.deposit_time {
+                    DEPOSIT_AGES.save(deps.storage, &address, &deposit_time)?;
+                }
+                if let Some(commission_rate) = depositor.operator_commission_rate {
+                    OPERATOR_COMMISSION_RATES.save(deps.storage, &address, &commission_rate)?;
+                }
+                if let Some(beneficiary) = depositor.prize_beneficiary {
+                    PRIZE_BENEFICIARIES.save(
+                        deps.storage,
+                        &address,
+                        &api.addr_validate(&beneficiary)?,
+                    )?;
+                }
+                if let Some(relay_nonce) = depositor.relay_nonce {
+                    RELAY_NONCES.save(deps.storage, &address, &relay_nonce)?;
+                }
+            }
+            StateRecord::Sponsor(sponsor) => {
+                store_sponsor_info(
+                    deps.storage,
+                    &api.addr_validate(&sponsor.address)?,
+                    SponsorInfo {
+                        lottery_deposit: sponsor.lottery_deposit,
+                        pending_rewards: sponsor.pending_rewards,
+                        reward_index: sponsor.reward_index,
+                    },
+                )?;
+            }
+            StateRecord::Operator(operator) => {
+                store_operator_info(
+                    deps.storage,
+                    &api.addr_validate(&operator.address)?,
+                    OperatorInfo {
+                        shares: operator.shares,
+                        pending_rewards: operator.pending_rewards,
+                        reward_index: operator.reward_index,
+                        commission_rate: operator.commission_rate,
+                        pending_commission: operator.pending_commission,
+                    },
+                )?;
+            }
+            StateRecord::Ticket(ticket) => {
+                let holders = ticket
+                    .holders
+                    .iter()
+                    .map(|holder| validate_depositor_addr(api, holder))
+                    .collect::<StdResult<Vec<Addr>>>()?;
+                TICKETS.save(deps.storage, ticket.sequence.as_bytes(), &holders)?;
+            }
+            StateRecord::Lottery(lottery) => {
+                store_lottery_info(
+                    deps.storage,
+                    lottery.lottery_id,
+                    &LotteryInfo {
+                        rand_round: lottery.rand_round,
+                        sequence: lottery.sequence,
+                        awarded: lottery.awarded,
+                        timestamp: lottery.timestamp,
+                        block_height: lottery.block_height,
+                        prize_buckets: lottery.prize_buckets,
+                        number_winners: lottery.number_winners,
+                        page: lottery.page,
+                        glow_prize_buckets: lottery.glow_prize_buckets,
+                        total_user_shares: lottery.total_user_shares,
+                        reserve: lottery.reserve,
+                    },
+                )?;
+            }
+            StateRecord::Prize(prize) => {
+                let address = validate_depositor_addr(api, &prize.address)?;
+                PRIZES.save(
+                    deps.storage,
+                    (U64Key::from(prize.lottery_id), &address),
+                    &PrizeInfo {
+                        claimed: prize.claimed,
+                        matches: prize.matches,
+                    },
+                )?;
+
+                let winner_operator = if prize.winner_stats.operator.is_empty() {
+                    Addr::unchecked("")
+                } else {
+                    api.addr_validate(&prize.winner_stats.operator)?
+                };
+                IMPORTED_WINNER_STATS.save(
+                    deps.storage,
+                    (U64Key::from(prize.lottery_id), &address),
+                    &DepositorStatsInfo {
+                        shares: prize.winner_stats.shares,
+                        num_tickets: prize.winner_stats.num_tickets as usize,
+                        operator_addr: winner_operator,
+                    },
+                )?;
+            }
+            StateRecord::GiftCode(gift_code) => {
+                GIFT_CODES.save(
+                    deps.storage,
+                    &decode_gift_code_hash(&gift_code.code_hash)?,
+                    &GiftCode {
+                        creator: api.addr_validate(&gift_code.creator)?,
+                        shares: gift_code.shares,
+                        encoded_tickets: gift_code.encoded_tickets,
+                        expires_at: gift_code.expires_at,
+                    },
+                )?;
+            }
+            StateRecord::Charity(charity) => {
+                CHARITIES.save(
+                    deps.storage,
+                    &api.addr_validate(&charity.address)?,
+                    &Charity {
+                        name: charity.name,
+                        verified: charity.verified,
+                        donated_ust: charity.donated_ust,
+                        donated_glow: charity.donated_glow,
+                    },
+                )?;
+            }
+            StateRecord::Syndicate(syndicate) => {
+                SYNDICATES.save(
+                    deps.storage,
+                    U64Key::from(syndicate.syndicate_id),
+                    &Syndicate {
+                        name: syndicate.name,
+                        creator: api.addr_validate(&syndicate.creator)?,
+                    },
+                )?;
+            }
+            StateRecord::SyndicateMember(member) => {
+                let address = api.addr_validate(&member.address)?;
+                SYNDICATE_MEMBERS.save(
+                    deps.storage,
+                    (U64Key::from(member.syndicate_id), &address),
+                    &member.shares,
+                    env.block.height,
+                )?;
+            }
+            StateRecord::SyndicatePrizeClaim(claim) => {
+                SYNDICATE_PRIZE_CLAIMS.save(
+                    deps.storage,
+                    (
+                        U64Key::from(claim.lottery_id),
+                        &api.addr_validate(&claim.address)?,
+                    ),
+                    &claim.syndicate_ids,
+                )?;
+            }
+            StateRecord::SyndicatePrizeShares(prize_shares) => {
+                SYNDICATE_PRIZE_CLAIMED_SHARES.save(
+                    deps.storage,
+                    (
+                        U64Key::from(prize_shares.lottery_id),
+                        U64Key::from(prize_shares.syndicate_id),
+                    ),
+                    &prize_shares.claimed_shares,
+                )?;
+                for (address, shares) in prize_shares.member_shares {
+                    IMPORTED_SYNDICATE_MEMBER_SHARES.save(
+                        deps.storage,
+                        (
+                            U64Key::from(prize_shares.syndicate_id),
+                            U64Key::from(prize_shares.lottery_id),
+                            &api.addr_validate(&address)?,
+                        ),
+                        &shares,
+                    )?;
+                }
+            }
+            StateRecord::UnbondingClaim(unbonding_claim) => {
+                UNBONDING_CLAIMS.save(
+                    deps.storage,
+                    (
+                        &validate_depositor_addr(api, &unbonding_claim.address)?,
+                        U64Key::from(unbonding_claim.release_epoch),
+                    ),
+                    &unbonding_claim.claim,
+                )?;
+            }
+            StateRecord::DepositorActivity(entry) => {
+                import_depositor_history(
+                    deps.storage,
+                    &validate_depositor_addr(api, &entry.address)?,
+                    entry.id,
+                    entry.activity,
+                )?;
+            }
+        }
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "import_state"),
+        attr("num_records", num_records.to_string()),
+    ]))
+}
+
+fn assert_old_depositors_migrated(deps: Deps) -> Result<(), ContractError> {
+    let old_depositors = old_read_depositors(deps, None, Some(1))?;
+    if !old_depositors.is_empty() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Cannot unpause contract with old depositors",
+        )));
+    }
+    Ok(())
+}
+
+pub fn execute_update_lottery_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    lottery_interval: Option<u64>,
+    block_time: Option<u64>,
+    ticket_price: Option<Uint256>,
+    prize_distribution: Option<[Decimal256; NUM_PRIZE_BUCKETS]>,
+    round_delta: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // check permission
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(lottery_interval) = lottery_interval {
+        config.lottery_interval = Duration::Time(lottery_interval);
+    }
+
+    if let Some(block_time) = block_time {
+        config.block_time = Duration::Time(block_time);
+    }
+
+    if let Some(round_delta) = round_delta {
+        config.round_delta = round_delta;
+    }
+
+    if let Some(ticket_price) = ticket_price {
+        config.ticket_price = ticket_price;
+    }
+
+    if let Some(prize_distribution) = prize_distribution {
+        if prize_distribution.len() != NUM_PRIZE_BUCKETS {
+            return Err(ContractError::InvalidPrizeDistribution {});
+        }
+
+        let mut sum = Decimal256::zero();
+        for item in prize_distribution.iter() {
+            sum += *item;
+        }
+
+        if sum != Decimal256::one() {
+            return Err(ContractError::InvalidPrizeDistribution {});
+        }
+
+        config.prize_distribution = prize_distribution;
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "update_lottery_config")]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::State { block_height } => to_binary(&query_state(deps, env, block_height)?),
         QueryMsg::Pool {} => to_binary(&query_pool(deps)?),
         QueryMsg::LotteryInfo { lottery_id } => {
@@ -1543,227 +3205,1091 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         }
         QueryMsg::Sponsor { address } => to_binary(&query_sponsor(deps, env, address)?),
         QueryMsg::Operator { address } => to_binary(&query_operator(deps, env, address)?),
+        QueryMsg::DepositorLocks { address } => {
+            to_binary(&query_depositor_locks(deps, env, address)?)
+        }
+        QueryMsg::GiftCode { code_hash } => to_binary(&query_gift_code(deps, env, code_hash)?),
         QueryMsg::LotteryBalance {} => to_binary(&query_lottery_balance(deps, env)?),
+        QueryMsg::SimulateDeposit { amount } => {
+            to_binary(&query_simulate_deposit(deps, env, amount)?)
+        }
+        QueryMsg::SimulateWithdraw {
+            address,
+            amount,
+            instant,
+        } => to_binary(&query_simulate_withdraw(
+            deps, env, address, amount, instant,
+        )?),
+        QueryMsg::SimulateClaimLottery {
+            address,
+            lottery_ids,
+        } => to_binary(&query_simulate_claim_lottery(deps, address, lottery_ids)?),
+        QueryMsg::Boost { address, at_time } => {
+            to_binary(&query_boost(deps, env, address, at_time)?)
+        }
+        QueryMsg::DepositorHistory {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_depositor_history(deps, address, start_after, limit)?),
+        QueryMsg::UnbondingClaims {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_unbonding_claims(
+            deps,
+            env,
+            address,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::InstantWithdrawalFee { address } => {
+            to_binary(&query_instant_withdrawal_fee(deps, env, address)?)
+        }
+        QueryMsg::PrizeBeneficiary { address } => {
+            to_binary(&query_prize_beneficiary(deps, address)?)
+        }
+        QueryMsg::Charity { address } => to_binary(&query_charity(deps, address)?),
+        QueryMsg::Syndicate { syndicate_id } => to_binary(&query_syndicate(deps, syndicate_id)?),
+        QueryMsg::SyndicateMember {
+            syndicate_id,
+            address,
+        } => to_binary(&query_syndicate_member(deps, syndicate_id, address)?),
+        QueryMsg::RelayNonce { address } => to_binary(&query_relay_nonce(deps, address)?),
+        QueryMsg::LotteryStats { from_id, to_id } => {
+            to_binary(&query_lottery_stats(deps, env, from_id, to_id)?)
+        }
+        QueryMsg::ExportTotals {} => to_binary(&query_export_totals(deps)?),
+        QueryMsg::ExportDepositors { start_after, limit } => {
+            to_binary(&query_export_depositors(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportSponsors { start_after, limit } => {
+            to_binary(&query_export_sponsors(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportOperators { start_after, limit } => {
+            to_binary(&query_export_operators(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportTickets { start_after, limit } => {
+            to_binary(&query_export_tickets(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportLotteries { start_after, limit } => {
+            to_binary(&query_export_lotteries(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportPrizes { start_after, limit } => {
+            to_binary(&query_export_prizes(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportGiftCodes { start_after, limit } => {
+            to_binary(&query_export_gift_codes(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportCharities { start_after, limit } => {
+            to_binary(&query_export_charities(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportSyndicates { start_after, limit } => {
+            to_binary(&query_export_syndicates(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportSyndicateMembers { start_after, limit } => {
+            to_binary(&query_export_syndicate_members(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportSyndicatePrizeClaims { start_after, limit } => to_binary(
+            &query_export_syndicate_prize_claims(deps, start_after, limit)?,
+        ),
+        QueryMsg::ExportSyndicatePrizeShares {
+            syndicate_id,
+            lottery_id,
+            start_after,
+            limit,
+        } => to_binary(&query_export_syndicate_prize_shares(
+            deps,
+            syndicate_id,
+            lottery_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ExportUnbondingClaims { start_after, limit } => {
+            to_binary(&query_export_unbonding_claims(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportDepositorHistory { start_after, limit } => {
+            to_binary(&query_export_depositor_history(deps, start_after, limit)?)
+        }
+        QueryMsg::Charities { start_after, limit } => {
+            to_binary(&query_charities(deps, start_after, limit)?)
+        }
+    }
+}
+
+pub fn query_ticket_info(deps: Deps, ticket: String) -> StdResult<TicketInfoResponse> {
+    let holders = TICKETS
+        .may_load(deps.storage, ticket.as_ref())?
+        .unwrap_or_default();
+    Ok(TicketInfoResponse { holders })
+}
+
+pub fn query_prizes(deps: Deps, address: String, lottery_id: u64) -> StdResult<PrizeInfoResponse> {
+    // Get config
+    let config = CONFIG.load(deps.storage)?;
+
+    // Get lottery info
+    let lottery_info = read_lottery_info(deps.storage, lottery_id);
+
+    // Get prize info
+    let lottery_key = U64Key::from(lottery_id);
+    let addr = deps.api.addr_validate(&address)?;
+    let prize_info =
+        if let Some(prize_info) = PRIZES.may_load(deps.storage, (lottery_key, &addr))? {
+            prize_info
+        } else {
+            return Err(StdError::generic_err(
+                "No prize with the specified address and lottery id.",
+            ));
+        };
+
+    // Get ust and glow to send
+    let snapshotted_depositor_stats_info =
+        read_winner_stats(deps.storage, &addr, lottery_id, lottery_info.block_height);
+
+    let (local_ust_to_send, local_glow_to_send): (Uint128, Uint128) = calculate_winner_prize(
+        &deps.querier,
+        &config,
+        &prize_info,
+        &lottery_info,
+        &snapshotted_depositor_stats_info,
+        &addr,
+    )?;
+
+    Ok(PrizeInfoResponse {
+        holder: addr,
+        lottery_id,
+        claimed: prize_info.claimed,
+        matches: prize_info.matches,
+        won_ust: local_ust_to_send,
+        won_glow: local_glow_to_send,
+    })
+}
+
+pub fn query_lottery_prizes(
+    deps: Deps,
+    lottery_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PrizeInfosResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let addr = if let Some(s) = start_after {
+        Some(deps.api.addr_validate(&s)?)
+    } else {
+        None
+    };
+
+    let lottery_info = read_lottery_info(deps.storage, lottery_id);
+
+    let prize_infos = read_lottery_prizes(deps, lottery_id, addr, limit)?;
+
+    let prize_info_responses = prize_infos
+        .into_iter()
+        .map(|(addr, prize_info)| {
+            let snapshotted_depositor_stats_info =
+                read_winner_stats(deps.storage, &addr, lottery_id, lottery_info.block_height);
+
+            let (local_ust_to_send, local_glow_to_send): (Uint128, Uint128) =
+                calculate_winner_prize(
+                    &deps.querier,
+                    &config,
+                    &prize_info,
+                    &lottery_info,
+                    &snapshotted_depositor_stats_info,
+                    &addr,
+                )?;
+
+            Ok(PrizeInfoResponse {
+                holder: addr,
+                lottery_id,
+                claimed: prize_info.claimed,
+                matches: prize_info.matches,
+                won_ust: local_ust_to_send,
+                won_glow: local_glow_to_send,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PrizeInfosResponse {
+        prize_infos: prize_info_responses,
+    })
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        owner: config.owner.to_string(),
+        stable_denom: config.stable_denom,
+        a_terra_contract: config.a_terra_contract.to_string(),
+        anchor_contract: config.anchor_contract.to_string(),
+        gov_contract: config.gov_contract.to_string(),
+        ve_contract: config.ve_contract.to_string(),
+        community_contract: config.community_contract.to_string(),
+        distributor_contract: config.distributor_contract.to_string(),
+        lottery_interval: config.lottery_interval,
+        epoch_interval: config.epoch_interval,
+        block_time: config.block_time,
+        round_delta: config.round_delta,
+        ticket_price: config.ticket_price,
+        max_holders: config.max_holders,
+        prize_distribution: config.prize_distribution,
+        target_award: config.target_award,
+        reserve_factor: config.reserve_factor,
+        split_factor: config.split_factor,
+        instant_withdrawal_fee: config.instant_withdrawal_fee,
+        unbonding_period: config.unbonding_period,
+        max_tickets_per_depositor: config.max_tickets_per_depositor,
+        paused: config.paused,
+        guardian: config.guardian.map(|guardian| guardian.to_string()),
+        max_history_entries: config.max_history_entries,
+        instant_withdrawal_fee_decay: config.instant_withdrawal_fee_decay,
+        max_operator_commission: config.max_operator_commission,
+        reserve_destinations: config.reserve_destinations,
+    })
+}
+
+pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult<StateResponse> {
+    let pool = POOL.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    let block_height = if let Some(block_height) = block_height {
+        block_height
+    } else {
+        env.block.height
+    };
+
+    if block_height < state.operator_reward_emission_index.last_reward_updated
+        || block_height < state.sponsor_reward_emission_index.last_reward_updated
+    {
+        return Err(StdError::generic_err(
+            "Block_height must be greater than both operator and sponsor last_reward_updated",
+        ));
     }
+
+    // Compute reward rate with given block height
+    compute_global_operator_reward(&mut state, &pool, block_height);
+    compute_global_sponsor_reward(&mut state, &pool, block_height);
+
+    Ok(StateResponse {
+        total_tickets: state.total_tickets,
+        total_reserve: state.total_reserve,
+        withdrawal_fee_reserve: state.withdrawal_fee_reserve,
+        prize_reserve: state.prize_reserve,
+        prize_buckets: state.prize_buckets,
+        current_lottery: state.current_lottery,
+        next_lottery_time: state.next_lottery_time,
+        next_lottery_exec_time: state.next_lottery_exec_time,
+        next_epoch: state.next_epoch,
+        operator_reward_emission_index: state.operator_reward_emission_index,
+        sponsor_reward_emission_index: state.sponsor_reward_emission_index,
+        last_lottery_execution_aust_exchange_rate: state.last_lottery_execution_aust_exchange_rate,
+    })
+}
+
+pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
+    let pool = POOL.load(deps.storage)?;
+
+    Ok(PoolResponse {
+        total_user_shares: pool.total_user_shares,
+        total_user_aust: pool.total_user_aust,
+        total_sponsor_lottery_deposits: pool.total_sponsor_lottery_deposits,
+        total_operator_shares: pool.total_operator_shares,
+    })
+}
+
+pub fn query_lottery_info(
+    deps: Deps,
+    env: Env,
+    lottery_id: Option<u64>,
+) -> StdResult<LotteryInfoResponse> {
+    let (lottery_id, lottery) = if let Some(lottery_id) = lottery_id {
+        (lottery_id, read_lottery_info(deps.storage, lottery_id))
+    } else {
+        let lottery_id = query_state(deps, env, None)?.current_lottery;
+        (lottery_id, read_lottery_info(deps.storage, lottery_id))
+    };
+    Ok(LotteryInfoResponse {
+        lottery_id,
+        rand_round: lottery.rand_round,
+        sequence: lottery.sequence,
+        awarded: lottery.awarded,
+        timestamp: lottery.timestamp,
+        block_height: lottery.block_height,
+        glow_prize_buckets: lottery.glow_prize_buckets,
+        prize_buckets: lottery.prize_buckets,
+        number_winners: lottery.number_winners,
+        page: lottery.page,
+        total_user_shares: lottery.total_user_shares,
+        reserve: lottery.reserve,
+    })
+}
+
+pub fn query_depositor_info(
+    deps: Deps,
+    _env: Env,
+    addr: String,
+) -> StdResult<DepositorInfoResponse> {
+    let address = deps.api.addr_validate(&addr)?;
+    let depositor = read_depositor_info(deps.storage, &address);
+
+    let (unbonding_info, unbonding_info_has_more) =
+        read_depositor_unbonding_info(deps.storage, &address, depositor.unbonding_info)?;
+
+    Ok(DepositorInfoResponse {
+        depositor: addr,
+        shares: depositor.shares,
+        tickets: depositor.tickets,
+        unbonding_info,
+        unbonding_info_has_more,
+    })
 }
 
-pub fn query_ticket_info(deps: Deps, ticket: String) -> StdResult<TicketInfoResponse> {
-    let holders = TICKETS
-        .may_load(deps.storage, ticket.as_ref())?
-        .unwrap_or_default();
-    Ok(TicketInfoResponse { holders })
+pub fn query_depositor_locks(
+    deps: Deps,
+    env: Env,
+    addr: String,
+) -> StdResult<DepositorLocksResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
+
+    let address = deps.api.addr_validate(&addr)?;
+    let depositor = read_depositor_info(deps.storage, &address);
+
+    // Only report the locks that haven't expired yet
+    let locks: Vec<ShareLock> = read_share_locks(deps.storage, &address)
+        .into_iter()
+        .filter(|share_lock| share_lock.unlock_at > env.block.time)
+        .collect();
+
+    let locked_shares = std::cmp::min(
+        calculate_locked_shares(&locks, env.block.time),
+        depositor.shares,
+    );
+    let unlocked_shares = depositor.shares - locked_shares;
+
+    // Get the aust exchange rate
+    let aust_exchange_rate =
+        query_exchange_rate(deps, config.anchor_contract.to_string(), env.block.height)?
+            .exchange_rate;
+
+    let locked_balance = pool.total_user_aust
+        * decimal_from_ratio_or_one(locked_shares, pool.total_user_shares)
+        * aust_exchange_rate;
+    let unlocked_balance = pool.total_user_aust
+        * decimal_from_ratio_or_one(unlocked_shares, pool.total_user_shares)
+        * aust_exchange_rate;
+
+    Ok(DepositorLocksResponse {
+        depositor: addr,
+        locked_shares,
+        unlocked_shares,
+        locked_balance,
+        unlocked_balance,
+        locks,
+    })
 }
 
-pub fn query_prizes(deps: Deps, address: String, lottery_id: u64) -> StdResult<PrizeInfoResponse> {
-    // Get config
+pub fn query_gift_code(deps: Deps, env: Env, code_hash: String) -> StdResult<GiftCodeResponse> {
     let config = CONFIG.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
 
-    // Get lottery info
-    let lottery_info = read_lottery_info(deps.storage, lottery_id);
+    let code_hash_bytes =
+        hex::decode(&code_hash).map_err(|_| StdError::generic_err("Invalid gift code hash"))?;
+    let gift_code = GIFT_CODES.load(deps.storage, &code_hash_bytes)?;
 
-    // Get prize info
-    let lottery_key = U64Key::from(lottery_id);
-    let addr = deps.api.addr_validate(&address)?;
-    let prize_info =
-        if let Some(prize_info) = PRIZES.may_load(deps.storage, (lottery_key, &addr))? {
-            prize_info
-        } else {
-            return Err(StdError::generic_err(
-                "No prize with the specified address and lottery id.",
-            ));
-        };
+    // Get the aust exchange rate
+    let aust_exchange_rate =
+        query_exchange_rate(deps, config.anchor_contract.to_string(), env.block.height)?
+            .exchange_rate;
 
-    // Get ust and glow to send
-    let snapshotted_depositor_stats_info =
-        read_depositor_stats_at_height(deps.storage, &addr, lottery_info.block_height);
+    let value = gift_code
+        .shares
+        .multiply_ratio(pool.total_user_aust, pool.total_user_shares)
+        * aust_exchange_rate;
 
-    let (local_ust_to_send, local_glow_to_send): (Uint128, Uint128) = calculate_winner_prize(
-        &deps.querier,
-        &config,
-        &prize_info,
-        &lottery_info,
-        &snapshotted_depositor_stats_info,
-        &addr,
-    )?;
+    Ok(GiftCodeResponse {
+        code_hash,
+        creator: gift_code.creator.to_string(),
+        shares: gift_code.shares,
+        value,
+        encoded_tickets: gift_code.encoded_tickets,
+        expires_at: gift_code.expires_at,
+    })
+}
 
-    Ok(PrizeInfoResponse {
-        holder: addr,
-        lottery_id,
-        claimed: prize_info.claimed,
-        matches: prize_info.matches,
-        won_ust: local_ust_to_send,
-        won_glow: local_glow_to_send,
+pub fn query_depositor_history(
+    deps: Deps,
+    addr: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<DepositorHistoryResponse> {
+    let address = deps.api.addr_validate(&addr)?;
+
+    let history = read_depositor_history(deps.storage, &address, start_after, limit)?
+        .into_iter()
+        .map(|(id, activity)| DepositorActivityResponse {
+            id,
+            kind: activity.kind,
+            amount: activity.amount,
+            shares: activity.shares,
+            tickets: activity.tickets,
+            block_height: activity.block_height,
+            lottery_id: activity.lottery_id,
+        })
+        .collect();
+
+    Ok(DepositorHistoryResponse {
+        depositor: addr,
+        history,
     })
 }
 
-pub fn query_lottery_prizes(
+pub fn query_unbonding_claims(
     deps: Deps,
-    lottery_id: u64,
-    start_after: Option<String>,
+    env: Env,
+    addr: String,
+    start_after: Option<u64>,
     limit: Option<u32>,
-) -> StdResult<PrizeInfosResponse> {
+) -> StdResult<UnbondingClaimsResponse> {
+    let address = deps.api.addr_validate(&addr)?;
+
+    let mut pending_amount = Uint256::zero();
+    let mut releasable_amount = Uint256::zero();
+
+    let claims = read_unbonding_claims(deps.storage, &address, start_after, limit)?
+        .into_iter()
+        .map(|(release_epoch, claim)| {
+            let releasable = claim.release_at.is_expired(&env.block);
+            if releasable {
+                releasable_amount += claim.amount;
+            } else {
+                pending_amount += claim.amount;
+            }
+
+            UnbondingClaimResponse {
+                release_epoch,
+                amount: claim.amount,
+                release_at: claim.release_at,
+                releasable,
+            }
+        })
+        .collect();
+
+    Ok(UnbondingClaimsResponse {
+        depositor: addr,
+        claims,
+        pending_amount,
+        releasable_amount,
+    })
+}
+
+pub fn query_instant_withdrawal_fee(
+    deps: Deps,
+    env: Env,
+    addr: String,
+) -> StdResult<InstantWithdrawalFeeResponse> {
     let config = CONFIG.load(deps.storage)?;
+    let address = deps.api.addr_validate(&addr)?;
+    let deposit_time = read_deposit_age(deps.storage, &address, env.block.time);
 
-    let addr = if let Some(s) = start_after {
-        Some(deps.api.addr_validate(&s)?)
+    Ok(InstantWithdrawalFeeResponse {
+        depositor: addr,
+        instant_withdrawal_fee: calculate_instant_withdrawal_fee(
+            &config,
+            deposit_time,
+            env.block.time,
+        ),
+        average_deposit_time: deposit_time.seconds(),
+    })
+}
+
+pub fn query_syndicate(deps: Deps, syndicate_id: u64) -> StdResult<SyndicateResponse> {
+    let syndicate = SYNDICATES.load(deps.storage, U64Key::from(syndicate_id))?;
+    let depositor = syndicate_address(syndicate_id);
+    let depositor_info = read_depositor_info(deps.storage, &depositor);
+
+    Ok(SyndicateResponse {
+        syndicate_id,
+        name: syndicate.name,
+        creator: syndicate.creator.to_string(),
+        depositor: depositor.to_string(),
+        shares: depositor_info.shares,
+        tickets: depositor_info.tickets,
+    })
+}
+
+pub fn query_syndicate_member(
+    deps: Deps,
+    syndicate_id: u64,
+    addr: String,
+) -> StdResult<SyndicateMemberResponse> {
+    let address = deps.api.addr_validate(&addr)?;
+
+    Ok(SyndicateMemberResponse {
+        syndicate_id,
+        member: addr,
+        shares: read_syndicate_member_shares(deps.storage, syndicate_id, &address)?,
+    })
+}
+
+pub fn query_relay_nonce(deps: Deps, addr: String) -> StdResult<RelayNonceResponse> {
+    let address = deps.api.addr_validate(&addr)?;
+
+    Ok(RelayNonceResponse {
+        address: addr,
+        nonce: RELAY_NONCES
+            .may_load(deps.storage, &address)?
+            .unwrap_or_default(),
+    })
+}
+
+pub fn query_lottery_stats(
+    deps: Deps,
+    env: Env,
+    from_id: u64,
+    to_id: u64,
+) -> StdResult<LotteryStatsResponse> {
+    if to_id < from_id {
+        return Err(StdError::generic_err(
+            "to_id must not be lower than from_id",
+        ));
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
+
+    let mut lotteries = vec![];
+    let mut total_prizes = Uint256::zero();
+    let mut total_glow_prizes = Uint256::zero();
+    let mut number_winners = [0u32; NUM_PRIZE_BUCKETS];
+    let mut total_reserve = Uint256::zero();
+    let mut total_user_shares = Uint256::zero();
+
+    for (lottery_id, lottery_info) in read_awarded_lotteries(deps.storage, from_id, to_id)? {
+        // Prize buckets are only set for the tiers with winners
+        let prizes = lottery_info
+            .prize_buckets
+            .iter()
+            .fold(Uint256::zero(), |sum, prize| sum + *prize);
+        let glow_prizes = lottery_info
+            .glow_prize_buckets
+            .iter()
+            .fold(Uint256::zero(), |sum, prize| sum + *prize);
+
+        total_prizes = total_prizes + prizes;
+        total_glow_prizes = total_glow_prizes + glow_prizes;
+        for (index, winners) in lottery_info.number_winners.iter().enumerate() {
+            number_winners[index] += winners;
+        }
+        total_reserve = total_reserve + lottery_info.reserve;
+        total_user_shares = total_user_shares + lottery_info.total_user_shares;
+
+        lotteries.push(LotteryStats {
+            lottery_id,
+            timestamp: lottery_info.timestamp,
+            prizes,
+            glow_prizes,
+            number_winners: lottery_info.number_winners,
+            reserve: lottery_info.reserve,
+            total_user_shares: lottery_info.total_user_shares,
+        });
+    }
+
+    // Value the deposits at draw time at the current share price
+    let aust_exchange_rate =
+        query_exchange_rate(deps, config.anchor_contract.to_string(), env.block.height)?
+            .exchange_rate;
+    let total_deposits = total_user_shares
+        * decimal_from_ratio_or_one(pool.total_user_aust, pool.total_user_shares)
+        * aust_exchange_rate;
+
+    // Annualize the prize rate of a lottery with the lottery interval
+    let lottery_interval = if let Duration::Time(time) = config.lottery_interval {
+        time
     } else {
-        None
+        return Err(StdError::generic_err("Lottery interval is not a time"));
+    };
+    let prize_apr = if total_deposits.is_zero() {
+        Decimal256::zero()
+    } else {
+        Decimal256::from_ratio(total_prizes, total_deposits)
+            * Decimal256::from_ratio(
+                Uint256::from(SECONDS_PER_YEAR),
+                Uint256::from(lottery_interval),
+            )
     };
 
-    let lottery_info = read_lottery_info(deps.storage, lottery_id);
+    Ok(LotteryStatsResponse {
+        lotteries,
+        total_prizes,
+        total_glow_prizes,
+        number_winners,
+        total_reserve,
+        prize_apr,
+    })
+}
 
-    let prize_infos = read_lottery_prizes(deps, lottery_id, addr, limit)?;
+pub fn query_export_totals(deps: Deps) -> StdResult<StateExportResponse> {
+    let state = STATE.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
 
-    let prize_info_responses = prize_infos
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records: vec![StateRecord::Totals(ExportedTotals {
+            total_tickets: state.total_tickets,
+            total_reserve: state.total_reserve,
+            withdrawal_fee_reserve: state.withdrawal_fee_reserve,
+            prize_reserve: state.prize_reserve,
+            prize_buckets: state.prize_buckets,
+            current_lottery: state.current_lottery,
+            next_lottery_time: state.next_lottery_time,
+            next_lottery_exec_time: state.next_lottery_exec_time,
+            next_epoch: state.next_epoch,
+            operator_reward_emission_index: state.operator_reward_emission_index,
+            sponsor_reward_emission_index: state.sponsor_reward_emission_index,
+            last_lottery_execution_aust_exchange_rate: state
+                .last_lottery_execution_aust_exchange_rate,
+            total_user_aust: pool.total_user_aust,
+            total_user_shares: pool.total_user_shares,
+            total_sponsor_lottery_deposits: pool.total_sponsor_lottery_deposits,
+            total_operator_shares: pool.total_operator_shares,
+            syndicate_count: SYNDICATE_COUNT.may_load(deps.storage)?.unwrap_or_default(),
+            deposit_ages_start: DEPOSIT_AGES_START.may_load(deps.storage)?,
+        })],
+    })
+}
+
+// Export cursors are not validated, as exported depositors include syndicates
+pub fn query_export_depositors(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let records = read_depositors(deps.storage, start_after.map(Addr::unchecked), limit)?
         .into_iter()
-        .map(|(addr, prize_info)| {
-            let snapshotted_depositor_stats_info =
-                read_depositor_stats_at_height(deps.storage, &addr, lottery_info.block_height);
+        .map(|address| {
+            let depositor_info = read_depositor_info(deps.storage, &address);
+            Ok(StateRecord::Depositor(ExportedDepositor {
+                address: address.to_string(),
+                shares: depositor_info.shares,
+                tickets: depositor_info.tickets,
+                operator: depositor_info.operator_addr.to_string(),
+                unbonding_info: depositor_info.unbonding_info,
+                share_locks: read_share_locks(deps.storage, &address),
+                deposit_time: DEPOSIT_AGES.may_load(deps.storage, &address)?,
+                operator_commission_rate: OPERATOR_COMMISSION_RATES
+                    .may_load(deps.storage, &address)?,
+                prize_beneficiary: PRIZE_BENEFICIARIES
+                    .may_load(deps.storage, &address)?
+                    .map(|beneficiary| beneficiary.to_string()),
+                relay_nonce: RELAY_NONCES.may_load(deps.storage, &address)?,
+            }))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
 
-            let (local_ust_to_send, local_glow_to_send): (Uint128, Uint128) =
-                calculate_winner_prize(
-                    &deps.querier,
-                    &config,
-                    &prize_info,
-                    &lottery_info,
-                    &snapshotted_depositor_stats_info,
-                    &addr,
-                )?;
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
+    })
+}
 
-            Ok(PrizeInfoResponse {
-                holder: addr,
+pub fn query_export_sponsors(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let records = read_sponsors(deps.storage, start_after.map(Addr::unchecked), limit)?
+        .into_iter()
+        .map(|(address, sponsor_info)| {
+            StateRecord::Sponsor(ExportedSponsor {
+                address: address.to_string(),
+                lottery_deposit: sponsor_info.lottery_deposit,
+                pending_rewards: sponsor_info.pending_rewards,
+                reward_index: sponsor_info.reward_index,
+            })
+        })
+        .collect();
+
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
+    })
+}
+
+pub fn query_export_operators(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let records = read_operators(deps.storage, start_after.map(Addr::unchecked), limit)?
+        .into_iter()
+        .map(|(address, operator_info)| {
+            StateRecord::Operator(ExportedOperator {
+                address: address.to_string(),
+                shares: operator_info.shares,
+                pending_rewards: operator_info.pending_rewards,
+                reward_index: operator_info.reward_index,
+                commission_rate: operator_info.commission_rate,
+                pending_commission: operator_info.pending_commission,
+            })
+        })
+        .collect();
+
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
+    })
+}
+
+pub fn query_export_tickets(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let records = read_tickets(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(sequence, holders)| {
+            StateRecord::Ticket(ExportedTicket {
+                sequence,
+                holders: holders.iter().map(|holder| holder.to_string()).collect(),
+            })
+        })
+        .collect();
+
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
+    })
+}
+
+pub fn query_export_lotteries(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let records = read_lotteries(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(lottery_id, lottery_info)| {
+            StateRecord::Lottery(ExportedLottery {
+                lottery_id,
+                rand_round: lottery_info.rand_round,
+                sequence: lottery_info.sequence,
+                awarded: lottery_info.awarded,
+                timestamp: lottery_info.timestamp,
+                block_height: lottery_info.block_height,
+                prize_buckets: lottery_info.prize_buckets,
+                number_winners: lottery_info.number_winners,
+                page: lottery_info.page,
+                glow_prize_buckets: lottery_info.glow_prize_buckets,
+                total_user_shares: lottery_info.total_user_shares,
+                reserve: lottery_info.reserve,
+            })
+        })
+        .collect();
+
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
+    })
+}
+
+pub fn query_export_prizes(
+    deps: Deps,
+    start_after: Option<(u64, String)>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let start_after = start_after.map(|(lottery_id, addr)| (lottery_id, Addr::unchecked(addr)));
+    let records = read_prizes(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(lottery_id, address, prize_info)| {
+            let lottery_info = read_lottery_info(deps.storage, lottery_id);
+            let winner_stats = read_winner_stats(
+                deps.storage,
+                &address,
                 lottery_id,
+                lottery_info.block_height,
+            );
+            StateRecord::Prize(ExportedPrize {
+                lottery_id,
+                address: address.to_string(),
                 claimed: prize_info.claimed,
                 matches: prize_info.matches,
-                won_ust: local_ust_to_send,
-                won_glow: local_glow_to_send,
+                winner_stats: ExportedDepositorStats {
+                    shares: winner_stats.shares,
+                    num_tickets: winner_stats.num_tickets as u64,
+                    operator: winner_stats.operator_addr.to_string(),
+                },
+            })
+        })
+        .collect();
+
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
+    })
+}
+
+pub fn query_export_gift_codes(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let start_after = start_after
+        .map(|code_hash| hex::decode(code_hash).map_err(|e| StdError::generic_err(e.to_string())))
+        .transpose()?;
+    let records = read_gift_codes(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(code_hash, gift_code)| {
+            StateRecord::GiftCode(ExportedGiftCode {
+                code_hash: hex::encode(code_hash),
+                creator: gift_code.creator.to_string(),
+                shares: gift_code.shares,
+                encoded_tickets: gift_code.encoded_tickets,
+                expires_at: gift_code.expires_at,
+            })
+        })
+        .collect();
+
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
+    })
+}
+
+pub fn query_export_charities(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let records = read_charities(deps.storage, start_after.map(Addr::unchecked), limit)?
+        .into_iter()
+        .map(|(address, charity)| {
+            StateRecord::Charity(ExportedCharity {
+                address: address.to_string(),
+                name: charity.name,
+                verified: charity.verified,
+                donated_ust: charity.donated_ust,
+                donated_glow: charity.donated_glow,
+            })
+        })
+        .collect();
+
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
+    })
+}
+
+pub fn query_export_syndicates(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let records = read_syndicates(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(syndicate_id, syndicate)| {
+            StateRecord::Syndicate(ExportedSyndicate {
+                syndicate_id,
+                name: syndicate.name,
+                creator: syndicate.creator.to_string(),
+            })
+        })
+        .collect();
+
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
+    })
+}
+
+pub fn query_export_syndicate_members(
+    deps: Deps,
+    start_after: Option<(u64, String)>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let start_after = start_after.map(|(id, addr)| (id, Addr::unchecked(addr)));
+    let records = read_syndicate_members(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(syndicate_id, address, shares)| {
+            StateRecord::SyndicateMember(ExportedSyndicateMember {
+                syndicate_id,
+                address: address.to_string(),
+                shares,
             })
         })
-        .collect::<StdResult<Vec<_>>>()?;
+        .collect();
 
-    Ok(PrizeInfosResponse {
-        prize_infos: prize_info_responses,
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
     })
 }
 
-pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
-    let config = CONFIG.load(deps.storage)?;
+pub fn query_export_syndicate_prize_claims(
+    deps: Deps,
+    start_after: Option<(u64, String)>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let start_after = start_after.map(|(id, addr)| (id, Addr::unchecked(addr)));
+    let records = read_syndicate_prize_claims(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(lottery_id, address, syndicate_ids)| {
+            StateRecord::SyndicatePrizeClaim(ExportedSyndicatePrizeClaim {
+                lottery_id,
+                address: address.to_string(),
+                syndicate_ids,
+            })
+        })
+        .collect();
 
-    Ok(ConfigResponse {
-        owner: config.owner.to_string(),
-        stable_denom: config.stable_denom,
-        a_terra_contract: config.a_terra_contract.to_string(),
-        anchor_contract: config.anchor_contract.to_string(),
-        gov_contract: config.gov_contract.to_string(),
-        ve_contract: config.ve_contract.to_string(),
-        community_contract: config.community_contract.to_string(),
-        distributor_contract: config.distributor_contract.to_string(),
-        lottery_interval: config.lottery_interval,
-        epoch_interval: config.epoch_interval,
-        block_time: config.block_time,
-        round_delta: config.round_delta,
-        ticket_price: config.ticket_price,
-        max_holders: config.max_holders,
-        prize_distribution: config.prize_distribution,
-        target_award: config.target_award,
-        reserve_factor: config.reserve_factor,
-        split_factor: config.split_factor,
-        instant_withdrawal_fee: config.instant_withdrawal_fee,
-        unbonding_period: config.unbonding_period,
-        max_tickets_per_depositor: config.max_tickets_per_depositor,
-        paused: config.paused,
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
     })
 }
 
-pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult<StateResponse> {
-    let pool = POOL.load(deps.storage)?;
-    let mut state = STATE.load(deps.storage)?;
+/// Exports the shares as of the lottery of a page of the members of a syndicate that won
+/// a prize at the lottery, which the shares of the member are split by when claiming.
+/// The first page is returned even without members, as it carries the claimed shares
+pub fn query_export_syndicate_prize_shares(
+    deps: Deps,
+    syndicate_id: u64,
+    lottery_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let lottery_info = read_lottery_info(deps.storage, lottery_id);
+    let member_shares = read_syndicate_prize_shares(
+        deps.storage,
+        syndicate_id,
+        lottery_id,
+        lottery_info.block_height,
+        start_after.clone().map(Addr::unchecked),
+        limit,
+    )?;
 
-    let block_height = if let Some(block_height) = block_height {
-        block_height
+    let records = if start_after.is_some() && member_shares.is_empty() {
+        vec![]
     } else {
-        env.block.height
+        vec![StateRecord::SyndicatePrizeShares(
+            ExportedSyndicatePrizeShares {
+                syndicate_id,
+                lottery_id,
+                claimed_shares: SYNDICATE_PRIZE_CLAIMED_SHARES
+                    .may_load(
+                        deps.storage,
+                        (U64Key::from(lottery_id), U64Key::from(syndicate_id)),
+                    )?
+                    .unwrap_or_else(Uint256::zero),
+                member_shares: member_shares
+                    .into_iter()
+                    .map(|(address, shares)| (address.to_string(), shares))
+                    .collect(),
+            },
+        )]
     };
 
-    if block_height < state.operator_reward_emission_index.last_reward_updated
-        || block_height < state.sponsor_reward_emission_index.last_reward_updated
-    {
-        return Err(StdError::generic_err(
-            "Block_height must be greater than both operator and sponsor last_reward_updated",
-        ));
-    }
-
-    // Compute reward rate with given block height
-    compute_global_operator_reward(&mut state, &pool, block_height);
-    compute_global_sponsor_reward(&mut state, &pool, block_height);
-
-    Ok(StateResponse {
-        total_tickets: state.total_tickets,
-        total_reserve: state.total_reserve,
-        prize_buckets: state.prize_buckets,
-        current_lottery: state.current_lottery,
-        next_lottery_time: state.next_lottery_time,
-        next_lottery_exec_time: state.next_lottery_exec_time,
-        next_epoch: state.next_epoch,
-        operator_reward_emission_index: state.operator_reward_emission_index,
-        sponsor_reward_emission_index: state.sponsor_reward_emission_index,
-        last_lottery_execution_aust_exchange_rate: state.last_lottery_execution_aust_exchange_rate,
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
     })
 }
 
-pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
-    let pool = POOL.load(deps.storage)?;
+pub fn query_export_unbonding_claims(
+    deps: Deps,
+    start_after: Option<(String, u64)>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let start_after = start_after.map(|(addr, epoch)| (Addr::unchecked(addr), epoch));
+    let records = read_all_unbonding_claims(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(address, release_epoch, claim)| {
+            StateRecord::UnbondingClaim(ExportedUnbondingClaim {
+                address: address.to_string(),
+                release_epoch,
+                claim,
+            })
+        })
+        .collect();
 
-    Ok(PoolResponse {
-        total_user_shares: pool.total_user_shares,
-        total_user_aust: pool.total_user_aust,
-        total_sponsor_lottery_deposits: pool.total_sponsor_lottery_deposits,
-        total_operator_shares: pool.total_operator_shares,
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
     })
 }
 
-pub fn query_lottery_info(
+pub fn query_export_depositor_history(
     deps: Deps,
-    env: Env,
-    lottery_id: Option<u64>,
-) -> StdResult<LotteryInfoResponse> {
-    let (lottery_id, lottery) = if let Some(lottery_id) = lottery_id {
-        (lottery_id, read_lottery_info(deps.storage, lottery_id))
-    } else {
-        let lottery_id = query_state(deps, env, None)?.current_lottery;
-        (lottery_id, read_lottery_info(deps.storage, lottery_id))
-    };
-    Ok(LotteryInfoResponse {
-        lottery_id,
-        rand_round: lottery.rand_round,
-        sequence: lottery.sequence,
-        awarded: lottery.awarded,
-        timestamp: lottery.timestamp,
-        block_height: lottery.block_height,
-        glow_prize_buckets: lottery.glow_prize_buckets,
-        prize_buckets: lottery.prize_buckets,
-        number_winners: lottery.number_winners,
-        page: lottery.page,
-        total_user_shares: lottery.total_user_shares,
+    start_after: Option<(String, u64)>,
+    limit: Option<u32>,
+) -> StdResult<StateExportResponse> {
+    let start_after = start_after.map(|(addr, id)| (Addr::unchecked(addr), id));
+    let records = read_all_depositor_history(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(address, id, activity)| {
+            StateRecord::DepositorActivity(ExportedDepositorActivity {
+                address: address.to_string(),
+                id,
+                activity,
+            })
+        })
+        .collect();
+
+    Ok(StateExportResponse {
+        version: STATE_EXPORT_VERSION,
+        records,
     })
 }
 
-pub fn query_depositor_info(
-    deps: Deps,
-    _env: Env,
-    addr: String,
-) -> StdResult<DepositorInfoResponse> {
+pub fn query_prize_beneficiary(deps: Deps, addr: String) -> StdResult<PrizeBeneficiaryResponse> {
     let address = deps.api.addr_validate(&addr)?;
-    let depositor = read_depositor_info(deps.storage, &address);
 
-    Ok(DepositorInfoResponse {
+    Ok(PrizeBeneficiaryResponse {
         depositor: addr,
-        shares: depositor.shares,
-        tickets: depositor.tickets,
-        unbonding_info: depositor.unbonding_info,
+        beneficiary: read_prize_beneficiary(deps.storage, &address)?.map(|a| a.to_string()),
+    })
+}
+
+pub fn query_charity(deps: Deps, addr: String) -> StdResult<CharityResponse> {
+    let address = deps.api.addr_validate(&addr)?;
+    let charity = CHARITIES.load(deps.storage, &address)?;
+
+    Ok(CharityResponse {
+        address: addr,
+        name: charity.name,
+        verified: charity.verified,
+        donated_ust: charity.donated_ust,
+        donated_glow: charity.donated_glow,
     })
 }
 
+pub fn query_charities(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<CharitiesResponse> {
+    let start_after = if let Some(s) = start_after {
+        Some(deps.api.addr_validate(&s)?)
+    } else {
+        None
+    };
+
+    let charities = read_charities(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(address, charity)| CharityResponse {
+            address: address.to_string(),
+            name: charity.name,
+            verified: charity.verified,
+            donated_ust: charity.donated_ust,
+            donated_glow: charity.donated_glow,
+        })
+        .collect();
+
+    Ok(CharitiesResponse { charities })
+}
+
 pub fn query_depositor_stats(
     deps: Deps,
     _env: Env,
@@ -1814,6 +4340,8 @@ pub fn query_operator(deps: Deps, env: Env, addr: String) -> StdResult<OperatorI
         shares: operator.shares,
         reward_index: operator.reward_index,
         pending_rewards: operator.pending_rewards,
+        commission_rate: operator.commission_rate,
+        pending_commission: operator.pending_commission,
     })
 }
 
@@ -1890,6 +4418,239 @@ pub fn query_lottery_balance(deps: Deps, env: Env) -> StdResult<LotteryBalanceRe
     })
 }
 
+pub fn query_simulate_deposit(
+    deps: Deps,
+    env: Env,
+    amount: Uint128,
+) -> StdResult<SimulateDepositResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
+
+    // Get the aust exchange rate
+    let aust_exchange_rate =
+        query_exchange_rate(deps, config.anchor_contract.to_string(), env.block.height)?
+            .exchange_rate;
+
+    let deposit_amount = Uint256::from(amount);
+
+    let DepositMintedShares {
+        post_tax_deposit_amount,
+        minted_aust,
+        minted_shares,
+    } = calculate_deposit_minted_shares(deps, &config, &pool, deposit_amount, aust_exchange_rate)?;
+
+    let deposit_value = minted_aust * aust_exchange_rate;
+
+    Ok(SimulateDepositResponse {
+        deposit_amount,
+        tax: deposit_amount - post_tax_deposit_amount,
+        minted_aust,
+        minted_shares,
+        deposit_value,
+        max_tickets: calculate_max_affordable_tickets(&config, deposit_value),
+    })
+}
+
+pub fn query_simulate_withdraw(
+    deps: Deps,
+    env: Env,
+    addr: String,
+    amount: Option<Uint128>,
+    instant: Option<bool>,
+) -> StdResult<SimulateWithdrawResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
+
+    let address = deps.api.addr_validate(&addr)?;
+    let depositor_info = read_depositor_info(deps.storage, &address);
+
+    if depositor_info.shares.is_zero() {
+        return Err(StdError::generic_err(
+            ContractError::NoDepositorSavingsAustToWithdraw {}.to_string(),
+        ));
+    }
+
+    if (amount.is_some()) && (amount.unwrap().is_zero()) {
+        return Err(StdError::generic_err(
+            ContractError::SpecifiedWithdrawAmountIsZero {}.to_string(),
+        ));
+    }
+
+    // Get the shares that aren't locked by a gift
+    let locks = read_share_locks(deps.storage, &address);
+    let unlocked_shares = depositor_info.shares
+        - std::cmp::min(
+            calculate_locked_shares(&locks, env.block.time),
+            depositor_info.shares,
+        );
+
+    if unlocked_shares.is_zero() {
+        return Err(StdError::generic_err(
+            ContractError::DepositorSharesLocked {}.to_string(),
+        ));
+    }
+
+    // Get the aust exchange rate
+    let aust_exchange_rate =
+        query_exchange_rate(deps, config.anchor_contract.to_string(), env.block.height)?
+            .exchange_rate;
+
+    let WithdrawAmounts {
+        withdrawn_shares,
+        withdrawn_aust,
+        withdrawn_aust_value,
+        withdrawn_tickets,
+        post_transaction_depositor_balance,
+    } = calculate_withdraw_amounts(
+        &config,
+        &pool,
+        &depositor_info,
+        amount,
+        unlocked_shares,
+        aust_exchange_rate,
+    )
+    .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let instant_withdrawal_fee = if let Some(true) = instant {
+        Some(calculate_instant_withdrawal_fee(
+            &config,
+            read_deposit_age(deps.storage, &address, env.block.time),
+            env.block.time,
+        ))
+    } else {
+        None
+    };
+
+    let WithdrawPayout {
+        return_amount,
+        withdrawal_fee,
+        net_return_amount,
+    } = calculate_withdraw_payout(deps, &config, withdrawn_aust_value, instant_withdrawal_fee)?;
+
+    // Instant withdrawals are sent right away, otherwise the depositor gets an unbonding claim
+    let return_amount = if instant_withdrawal_fee.is_some() {
+        net_return_amount
+    } else {
+        return_amount
+    };
+
+    Ok(SimulateWithdrawResponse {
+        withdrawn_shares,
+        withdrawn_aust,
+        withdrawn_aust_value,
+        withdrawn_tickets: withdrawn_tickets as u64,
+        instant_withdrawal_fee: withdrawal_fee,
+        tax: withdrawn_aust_value - withdrawal_fee - return_amount,
+        return_amount,
+        post_transaction_shares: depositor_info.shares - withdrawn_shares,
+        post_transaction_balance: post_transaction_depositor_balance,
+    })
+}
+
+pub fn query_simulate_claim_lottery(
+    deps: Deps,
+    addr: String,
+    lottery_ids: Vec<u64>,
+) -> StdResult<SimulateClaimLotteryResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let address = deps.api.addr_validate(&addr)?;
+
+    let ClaimablePrizes {
+        ust_to_send,
+        glow_to_send,
+        ..
+    } = calculate_claimable_prizes(deps, &config, &address, &lottery_ids)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let depositor_info = read_depositor_info(deps.storage, &address);
+    let operator_commission = if depositor_info.operator_addr != Addr::unchecked("") {
+        let operator = read_operator_info(deps.storage, &depositor_info.operator_addr);
+        let agreed_rate = read_operator_commission_rate(deps.storage, &address)?;
+        calculate_operator_commission(&config, &operator, agreed_rate, ust_to_send)
+    } else {
+        Uint128::zero()
+    };
+
+    // Deduct taxes on the claim
+    let received_ust = ust_to_send - operator_commission;
+    let net_ust = deduct_tax(deps, coin(received_ust.into(), config.stable_denom))?.amount;
+
+    Ok(SimulateClaimLotteryResponse {
+        won_ust: ust_to_send,
+        operator_commission,
+        tax: received_ust - net_ust,
+        net_ust,
+        won_glow: glow_to_send,
+    })
+}
+
+pub fn query_boost(
+    deps: Deps,
+    env: Env,
+    addr: String,
+    at_time: Option<u64>,
+) -> StdResult<BoostResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
+
+    let address = deps.api.addr_validate(&addr)?;
+    let depositor_stats = read_depositor_stats(deps.storage, &address);
+
+    let at_time = at_time.unwrap_or_else(|| env.block.time.seconds());
+
+    // User and total voting balances
+    let voting_balance = query_address_voting_balance_at_timestamp(
+        &deps.querier,
+        &config.ve_contract,
+        at_time,
+        &address,
+    )?;
+    let total_voting_balance =
+        query_total_voting_balance_at_timestamp(&deps.querier, &config.ve_contract, at_time)?;
+
+    let share_ratio = if pool.total_user_shares.is_zero() {
+        Decimal256::zero()
+    } else {
+        Decimal256::from_ratio(depositor_stats.shares, pool.total_user_shares)
+    };
+
+    let voting_ratio = if total_voting_balance.is_zero() {
+        Decimal256::zero()
+    } else {
+        Decimal256::from_ratio(
+            Uint256::from(voting_balance),
+            Uint256::from(total_voting_balance),
+        )
+    };
+
+    let multiplier = calculate_boost_multiplier(
+        config.lotto_winner_boost_config.clone(),
+        depositor_stats.shares,
+        pool.total_user_shares,
+        voting_balance,
+        total_voting_balance,
+    );
+
+    let additional_voting_balance_needed = calculate_boost_voting_balance_needed(
+        config.lotto_winner_boost_config,
+        depositor_stats.shares,
+        pool.total_user_shares,
+        voting_balance,
+        total_voting_balance,
+    );
+
+    Ok(BoostResponse {
+        depositor: addr,
+        multiplier,
+        share_ratio,
+        voting_ratio,
+        voting_balance,
+        total_voting_balance,
+        additional_voting_balance_needed,
+    })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
     // Migration Notes
@@ -1900,6 +4661,7 @@ pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response>
     // - DEPOSITORS (new storage key, paginated migration)
     // - STATE (reuses storage key)
     // - POOL (reuses storage key)
+    // - DEPOSIT_AGES_START (new storage key)
 
     let default_lotto_winner_boost_config: BoostConfig = BoostConfig {
         base_multiplier: Decimal256::from_ratio(40u64, 100u64),
@@ -1947,12 +4709,23 @@ pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response>
         unbonding_period: old_config.unbonding_period,
         max_tickets_per_depositor: msg.max_tickets_per_depositor,
         glow_prize_buckets: msg.glow_prize_buckets,
-        paused: true,
+        paused: PauseFlags::all(true),
+        guardian: None,
         lotto_winner_boost_config,
+        max_history_entries: DEFAULT_MAX_HISTORY_ENTRIES,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: Decimal256::zero(),
+        reserve_destinations: ReserveDestinations::default(),
     };
+    validate_unbonding_units(&new_config).map_err(|e| StdError::generic_err(e.to_string()))?;
 
     CONFIG.save(deps.storage, &new_config)?;
 
+    // Existing deposits are aged from the upgrade
+    if DEPOSIT_AGES_START.may_load(deps.storage)?.is_none() {
+        DEPOSIT_AGES_START.save(deps.storage, &env.block.time)?;
+    }
+
     // Query exchange_rate from anchor money market
     let aust_exchange_rate: Decimal256 = query_exchange_rate(
         deps.as_ref(),
@@ -1968,6 +4741,8 @@ pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response>
     let state = State {
         total_tickets: old_state.total_tickets,
         total_reserve: old_state.total_reserve,
+        withdrawal_fee_reserve: Uint256::zero(),
+        prize_reserve: Uint256::zero(),
         prize_buckets: old_state.prize_buckets,
         current_lottery: old_state.current_lottery,
         next_lottery_time: old_state.next_lottery_time,
@@ -2112,6 +4887,7 @@ pub fn migrate_old_depositors(
                 glow_prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
                 block_height: old_lottery_info.timestamp,
                 total_user_shares: pool.total_user_shares,
+                reserve: Uint256::zero(),
             };
 
             store_lottery_info(deps.storage, i, &new_lottery_info)?;
@@ -2119,8 +4895,8 @@ pub fn migrate_old_depositors(
             old_remove_lottery_info(deps.storage, i);
         }
 
-        // Set paused to false and save
-        config.paused = false;
+        // Unpause all actions and save
+        config.paused = PauseFlags::default();
         CONFIG.save(deps.storage, &config)?;
     }
 
@@ -1,4 +1,4 @@
-use cosmwasm_bignumber::Uint256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{StdError, Uint128};
 use cw0::Expiration;
 use thiserror::Error;
@@ -44,6 +44,42 @@ pub enum ContractError {
     #[error("Gift ticket amount must be greater than zero")]
     ZeroGiftAmount {},
 
+    #[error("Invalid unlock time, it must be in the future and within the maximum lock duration")]
+    InvalidUnlockTime {},
+
+    #[error("The depositor already has the maximum number of share locks, all unlocking earlier")]
+    TooManyShareLocks {},
+
+    #[error("Invalid gift code hash, it must be a hex encoded keccak256 hash")]
+    InvalidGiftCodeHash {},
+
+    #[error("A gift code with the same hash already exists")]
+    GiftCodeAlreadyExists {},
+
+    #[error("Gift code not found")]
+    GiftCodeNotFound {},
+
+    #[error("Invalid gift code expiry, it must be in the future and within the maximum duration")]
+    InvalidGiftCodeExpiry {},
+
+    #[error("The gift code has expired")]
+    GiftCodeExpired {},
+
+    #[error("The gift code hasn't expired yet")]
+    GiftCodeNotExpired {},
+
+    #[error("The gift code redemption must be committed in an earlier block")]
+    GiftCodeNotCommitted {},
+
+    #[error("The depositor's shares are locked and can't be withdrawn yet")]
+    DepositorSharesLocked {},
+
+    #[error("The depositor specified to withdraw more shares ({withdrawn_shares}) than are unlocked ({unlocked_shares})")]
+    WithdrawingLockedShares {
+        withdrawn_shares: Uint256,
+        unlocked_shares: Uint256,
+    },
+
     #[error("Insufficient post transaction depositor balance ({post_transaction_depositor_balance}) for post transaction num tickets ({post_transaction_num_depositor_tickets}). Max Post transaction max depositor tickets: {post_transaction_max_depositor_tickets}.")]
     InsufficientPostTransactionDepositorBalance {
         post_transaction_depositor_balance: Uint256,
@@ -96,9 +132,6 @@ pub enum ContractError {
     #[error("There are not enough funds to run the lottery")]
     InsufficientLotteryFunds {},
 
-    #[error("Max number of concurrent unbonding claims for this users has been reached")]
-    MaxUnbondingClaims {},
-
     #[error("Lottery claim is invalid, as lottery #{0} has not being awarded yet")]
     InvalidClaimLotteryNotAwarded(u64),
 
@@ -120,6 +153,18 @@ pub enum ContractError {
     #[error("Invalid instant withdrawal fee config")]
     InvalidWithdrawalFee {},
 
+    #[error("Invalid instant withdrawal fee decay. Min fee must be less than or equal to the instant withdrawal fee")]
+    InvalidWithdrawalFeeDecay {},
+
+    #[error("Invalid max operator commission config")]
+    InvalidMaxOperatorCommission {},
+
+    #[error("The operator commission rate ({commission_rate}) is higher than the max operator commission ({max_operator_commission})")]
+    OperatorCommissionTooHigh {
+        commission_rate: Decimal256,
+        max_operator_commission: Decimal256,
+    },
+
     #[error("Invalid unbonding period config")]
     InvalidUnbondingPeriod {},
 
@@ -129,12 +174,18 @@ pub enum ContractError {
     #[error("Invalid epoch interval config")]
     InvalidEpochInterval {},
 
+    #[error("The unbonding period and the epoch interval must use the same duration unit")]
+    MixedUnbondingUnits {},
+
     #[error("Invalid max holders config, outside bounds")]
     InvalidMaxHoldersOutsideBounds {},
 
     #[error("Invalid max holders config, can only increase max holders, not decrease")]
     InvalidMaxHoldersAttemptedDecrease {},
 
+    #[error("Invalid max history entries config")]
+    InvalidMaxHistoryEntries {},
+
     #[error("Invalid lottery interval config")]
     InvalidLotteryInterval {},
 
@@ -167,4 +218,45 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("The prize beneficiary is not a registered charity")]
+    CharityNotRegistered {},
+
+    #[error("The depositor is not donating their prizes")]
+    NotInDonationMode {},
+
+    #[error("Syndicate {0} does not exist")]
+    InvalidSyndicate(u64),
+
+    #[error("Syndicate deposits can't set an operator")]
+    SyndicateOperatorNotAllowed {},
+
+    #[error("The sender has no shares in the syndicate")]
+    NotSyndicateMember {},
+
+    #[error("The deposit intent is meant for another contract or chain")]
+    InvalidRelayIntent {},
+
+    #[error("The deposit intent has expired")]
+    RelayIntentExpired {},
+
+    #[error("Invalid deposit intent signature or public key")]
+    InvalidRelaySignature {},
+
+    #[error("Invalid deposit intent nonce. Expected: {expected}, got: {got}")]
+    InvalidRelayNonce { expected: u64, got: u64 },
+
+    #[error(
+        "The relayer must send the amount of the deposit intent. Expected: {expected}, got: {got}"
+    )]
+    InvalidRelayFunds { expected: Uint128, got: Uint128 },
+
+    #[error("State can only be imported while every action is paused")]
+    ImportStateNotPaused {},
+
+    #[error("Unsupported state export version: {0}")]
+    UnsupportedStateVersion(u32),
+
+    #[error("Reserve destination shares must be positive and add up to one")]
+    InvalidReserveDestinations {},
 }
@@ -1,9 +1,21 @@
 use std::convert::TryInto;
 use std::ops::Add;
 
+use bech32::{ToBase32, Variant};
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{Addr, BlockInfo, DepsMut, Env, QuerierWrapper, StdError, StdResult, Uint128};
-use glow_protocol::lotto::{BoostConfig, RewardEmissionsIndex, NUM_PRIZE_BUCKETS, TICKET_LENGTH};
+use cosmwasm_std::{
+    coin, Addr, Api, BlockInfo, Deps, DepsMut, Env, QuerierWrapper, StdError, StdResult, Storage,
+    Timestamp, Uint128,
+};
+use cw0::{Duration, Expiration};
+use cw_storage_plus::U64Key;
+use glow_protocol::lotto::{
+    BoostConfig, DepositIntent, ExecuteMsg, PauseFlags, ReserveDestination, ReserveDestinations,
+    RewardEmissionsIndex, ShareLock, DEPOSIT_INTENT_DOMAIN, NUM_PRIZE_BUCKETS, TICKET_LENGTH,
+};
+use glow_protocol::querier::deduct_tax;
+use ripemd160::Ripemd160;
+use sha2::Sha256;
 use sha3::{Digest, Keccak256};
 
 use crate::error::ContractError;
@@ -12,8 +24,10 @@ use crate::querier::{
 };
 
 use crate::state::{
-    read_operator_info, store_operator_info, Config, DepositorInfo, DepositorStatsInfo,
-    LotteryInfo, OperatorInfo, Pool, PrizeInfo, SponsorInfo, State, TICKETS,
+    read_lottery_info, read_operator_info, read_released_unbonding_claims, read_share_locks,
+    read_winner_stats, store_operator_info, store_share_locks, syndicate_address, Config,
+    DepositorInfo, DepositorStatsInfo, LotteryInfo, OperatorInfo, Pool, PrizeInfo, SponsorInfo,
+    State, OPERATOR_COMMISSION_RATES, PRIZES, TICKETS,
 };
 
 /// Compute distributed reward and update global reward index for operators
@@ -71,6 +85,51 @@ pub fn compute_sponsor_reward(state: &State, sponsor: &mut SponsorInfo) {
     sponsor.reward_index = state.sponsor_reward_emission_index.global_reward_index;
 }
 
+pub struct DepositMintedShares {
+    pub post_tax_deposit_amount: Uint256,
+    pub minted_aust: Uint256,
+    pub minted_shares: Uint256,
+}
+
+/// Compute the aust and shares minted by depositing deposit_amount into anchor
+pub fn calculate_deposit_minted_shares(
+    deps: Deps,
+    config: &Config,
+    pool: &Pool,
+    deposit_amount: Uint256,
+    aust_exchange_rate: Decimal256,
+) -> StdResult<DepositMintedShares> {
+    // Deduct tx taxes when calculating the net deposited amount in anchor
+    let net_coin_amount = deduct_tax(
+        deps,
+        coin(deposit_amount.into(), config.stable_denom.clone()),
+    )?;
+
+    let post_tax_deposit_amount = Uint256::from(net_coin_amount.amount);
+
+    // Get the number of minted aust
+    let minted_aust = post_tax_deposit_amount / aust_exchange_rate;
+
+    // Get the amount of minted_shares
+    let minted_shares =
+        minted_aust * decimal_from_ratio_or_one(pool.total_user_shares, pool.total_user_aust);
+
+    Ok(DepositMintedShares {
+        post_tax_deposit_amount,
+        minted_aust,
+        minted_shares,
+    })
+}
+
+/// Number of tickets that a depositor with the given balance can hold
+pub fn calculate_max_affordable_tickets(config: &Config, balance: Uint256) -> u64 {
+    // Subtract 10^-5 in order to offset rounding problems
+    // relies on ticket price being at least 10^-5 UST
+    let ticket_price = Decimal256::from_uint256(config.ticket_price - Uint256::from(10u128));
+
+    Uint128::from(balance / ticket_price).u128() as u64
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn handle_depositor_ticket_updates(
     deps: DepsMut,
@@ -103,16 +162,8 @@ pub fn handle_depositor_ticket_updates(
         )
         * aust_exchange_rate;
 
-    let post_transaction_max_depositor_tickets = Uint128::from(
-        post_transaction_depositor_balance
-            / Decimal256::from_uint256(
-                config.ticket_price
-            // Subtract 10^-5 in order to offset rounding problems
-            // relies on ticket price being at least 10^-5 UST
-                - Uint256::from(10u128),
-            ),
-    )
-    .u128() as u64;
+    let post_transaction_max_depositor_tickets =
+        calculate_max_affordable_tickets(config, post_transaction_depositor_balance);
 
     // Get the amount of requested tickets
     let mut number_of_new_tickets = combinations.len() as u64;
@@ -228,6 +279,9 @@ pub fn handle_depositor_operator_updates(
         // Read the new operator in question
         let mut new_operator = read_operator_info(deps.storage, &depositor_info.operator_addr);
 
+        // Snapshot the commission rate agreed to by the depositor
+        OPERATOR_COMMISSION_RATES.save(deps.storage, depositor, &new_operator.commission_rate)?;
+
         // Update the reward index for the new operator
         compute_operator_reward(state, &mut new_operator);
 
@@ -246,37 +300,386 @@ pub fn handle_depositor_operator_updates(
     Ok(())
 }
 
-/// This iterates over all mature claims for the address, and removes them, up to an optional cap.
-/// it removes the finished claims and returns the total amount of tokens to be released.
+pub struct WithdrawAmounts {
+    pub withdrawn_shares: Uint256,
+    pub withdrawn_aust: Uint256,
+    pub withdrawn_aust_value: Uint256,
+    pub withdrawn_tickets: u128,
+    pub post_transaction_depositor_balance: Uint256,
+}
+
+/// Compute the shares, aust and tickets removed from a depositor withdrawing amount
+/// (or all of its unlocked shares if amount is None)
+pub fn calculate_withdraw_amounts(
+    config: &Config,
+    pool: &Pool,
+    depositor_info: &DepositorInfo,
+    amount: Option<Uint128>,
+    unlocked_shares: Uint256,
+    aust_exchange_rate: Decimal256,
+) -> Result<WithdrawAmounts, ContractError> {
+    // Get the number of withdrawn shares
+    let withdrawn_shares = amount
+        .map(|amount| {
+            std::cmp::max(
+                (Uint256::from(amount) / aust_exchange_rate)
+                    .multiply_ratio(pool.total_user_shares, pool.total_user_aust),
+                // Always withdraw at least one share
+                Uint256::one(),
+            )
+        })
+        .unwrap_or(unlocked_shares);
+
+    // Get the withdrawn amount
+    let withdrawn_aust =
+        withdrawn_shares.multiply_ratio(pool.total_user_aust, pool.total_user_shares);
+
+    let withdrawn_aust_value = withdrawn_aust * aust_exchange_rate;
+
+    // Calculate the depositor's balance from their aust balance
+    let depositor_balance = pool.total_user_aust
+        * Decimal256::from_ratio(depositor_info.shares, pool.total_user_shares)
+        * aust_exchange_rate;
+
+    if withdrawn_aust_value > depositor_balance {
+        return Err(ContractError::SpecifiedWithdrawAmountTooBig {
+            amount: Uint128::from(withdrawn_aust_value),
+            depositor_balance,
+        });
+    }
+
+    // Validate that the withdrawn shares aren't locked
+    if withdrawn_shares > unlocked_shares {
+        return Err(ContractError::WithdrawingLockedShares {
+            withdrawn_shares,
+            unlocked_shares,
+        });
+    }
+
+    // Get the depositor's balance post withdraw
+    let post_transaction_depositor_balance = (pool.total_user_aust - withdrawn_aust)
+        * decimal_from_ratio_or_one(
+            depositor_info.shares - withdrawn_shares,
+            pool.total_user_shares - withdrawn_shares,
+        )
+        * aust_exchange_rate;
+
+    let post_transaction_max_depositor_tickets = Uint128::from(
+        post_transaction_depositor_balance / Decimal256::from_uint256(config.ticket_price),
+    )
+    .u128();
+
+    // Calculate how many tickets to remove
+    let num_depositor_tickets = depositor_info.tickets.len() as u128;
+
+    // Get the number of tickets to withdraw
+    let withdrawn_tickets: u128 = num_depositor_tickets
+        .checked_sub(post_transaction_max_depositor_tickets)
+        .unwrap_or_default();
+
+    if withdrawn_tickets > num_depositor_tickets {
+        return Err(ContractError::WithdrawingTooManyTickets {
+            withdrawn_tickets,
+            num_depositor_tickets,
+        });
+    }
+
+    Ok(WithdrawAmounts {
+        withdrawn_shares,
+        withdrawn_aust,
+        withdrawn_aust_value,
+        withdrawn_tickets,
+        post_transaction_depositor_balance,
+    })
+}
+
+pub struct WithdrawPayout {
+    // Amount left after the anchor redemption tax and the instant withdrawal fee
+    pub return_amount: Uint256,
+    pub withdrawal_fee: Uint256,
+    // Amount received by the depositor after the transfer tax
+    pub net_return_amount: Uint256,
+}
+
+/// Compute the amounts returned to a depositor for withdrawn_aust_value.
+/// instant_withdrawal_fee is only set for instant withdrawals.
+pub fn calculate_withdraw_payout(
+    deps: Deps,
+    config: &Config,
+    withdrawn_aust_value: Uint256,
+    instant_withdrawal_fee: Option<Decimal256>,
+) -> StdResult<WithdrawPayout> {
+    // Get the value of the returned amount after accounting for taxes.
+    let mut return_amount = Uint256::from(
+        deduct_tax(
+            deps,
+            coin(withdrawn_aust_value.into(), config.stable_denom.clone()),
+        )?
+        .amount,
+    );
+
+    // Apply instant withdrawal fee
+    let mut withdrawal_fee = Uint256::zero();
+    if let Some(instant_withdrawal_fee) = instant_withdrawal_fee {
+        withdrawal_fee = return_amount * instant_withdrawal_fee;
+        return_amount = return_amount - withdrawal_fee;
+    }
+
+    // Get the amount of ust to return after tax
+    let net_return_amount = Uint256::from(
+        deduct_tax(
+            deps,
+            coin(return_amount.into(), config.stable_denom.clone()),
+        )?
+        .amount,
+    );
+
+    Ok(WithdrawPayout {
+        return_amount,
+        withdrawal_fee,
+        net_return_amount,
+    })
+}
+
+/// Commission taken by an operator on won_ust, the prizes won by one of its referred depositors.
+/// The rate is the lowest of the rate agreed to by the depositor when setting the operator,
+/// the operator's current rate and the current max operator commission.
+pub fn calculate_operator_commission(
+    config: &Config,
+    operator_info: &OperatorInfo,
+    agreed_rate: Decimal256,
+    won_ust: Uint128,
+) -> Uint128 {
+    let mut commission_rate = if operator_info.commission_rate > agreed_rate {
+        agreed_rate
+    } else {
+        operator_info.commission_rate
+    };
+    if commission_rate > config.max_operator_commission {
+        commission_rate = config.max_operator_commission;
+    }
+
+    (Uint256::from(won_ust) * commission_rate).into()
+}
+
+/// Instant withdrawal fee for shares deposited on average at deposit_time.
+/// Without a decay schedule the fee is the flat config.instant_withdrawal_fee,
+/// otherwise it decays from it to min_fee over the decay period.
+pub fn calculate_instant_withdrawal_fee(
+    config: &Config,
+    deposit_time: Timestamp,
+    time: Timestamp,
+) -> Decimal256 {
+    let max_fee = config.instant_withdrawal_fee;
+    let decay = match &config.instant_withdrawal_fee_decay {
+        Some(decay) => decay,
+        None => return max_fee,
+    };
+
+    let age = time.seconds().saturating_sub(deposit_time.seconds());
+    if age >= decay.decay_period {
+        return decay.min_fee;
+    }
+
+    // Only count whole steps for stepwise decay
+    let age = match decay.step {
+        Some(step) if step > 0 => age - age % step,
+        _ => age,
+    };
+
+    max_fee - (max_fee - decay.min_fee) * Decimal256::from_ratio(age, decay.decay_period)
+}
+
+/// Compute the release epoch of a withdrawal unbonding from block.
+/// The end of the unbonding period is rounded up to a multiple of the epoch interval,
+/// so that withdrawals made during the same epoch are merged into a single claim.
+/// Returns the epoch, which is the time (or height) at which it is released, and its expiration.
+/// Both durations must use the same unit, so that epochs are all keyed by time or all by height.
+pub fn calculate_release_epoch(
+    config: &Config,
+    block: &BlockInfo,
+) -> Result<(u64, Expiration), ContractError> {
+    match (config.unbonding_period, config.epoch_interval) {
+        (Duration::Time(period), Duration::Time(interval)) => {
+            let release_epoch =
+                round_up_to_multiple(block.time.seconds() + period, std::cmp::max(interval, 1));
+            Ok((
+                release_epoch,
+                Expiration::AtTime(Timestamp::from_seconds(release_epoch)),
+            ))
+        }
+        (Duration::Height(period), Duration::Height(interval)) => {
+            let release_epoch =
+                round_up_to_multiple(block.height + period, std::cmp::max(interval, 1));
+            Ok((release_epoch, Expiration::AtHeight(release_epoch)))
+        }
+        _ => Err(ContractError::MixedUnbondingUnits {}),
+    }
+}
+
+/// Validate that the unbonding period and the epoch interval use the same duration unit
+pub fn validate_unbonding_units(config: &Config) -> Result<(), ContractError> {
+    match (config.unbonding_period, config.epoch_interval) {
+        (Duration::Time(_), Duration::Time(_)) | (Duration::Height(_), Duration::Height(_)) => {
+            Ok(())
+        }
+        _ => Err(ContractError::MixedUnbondingUnits {}),
+    }
+}
+
+fn round_up_to_multiple(value: u64, multiple: u64) -> u64 {
+    (value + multiple - 1) / multiple * multiple
+}
+
+/// This iterates over the mature claims for the address, up to an optional cap.
+/// Released epochs of the unbonding ledger are read after start_after, up to limit epochs.
+/// It removes the finished legacy claims from depositor and returns the total amount of tokens
+/// to be released, along with the released epochs of the unbonding ledger.
+/// The released epochs have to be removed with remove_unbonding_claims by the caller.
 pub fn claim_unbonded_withdrawals(
+    storage: &dyn Storage,
+    depositor_addr: &Addr,
     depositor: &mut DepositorInfo,
     block: &BlockInfo,
     cap: Option<Uint128>,
-) -> StdResult<Uint128> {
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<(Uint128, Vec<u64>)> {
     let mut to_send = Uint128::zero();
 
-    if depositor.unbonding_info.is_empty() {
-        return Ok(to_send);
+    // Legacy claims, made before the unbonding ledger
+    if !depositor.unbonding_info.is_empty() {
+        let (_send, waiting): (Vec<_>, _) =
+            depositor.unbonding_info.iter().cloned().partition(|c| {
+                // if mature and we can pay fully, then include in _send
+                if c.release_at.is_expired(block) {
+                    let new_amount = c.amount;
+                    if let Some(limit) = cap {
+                        if to_send + Uint128::from(new_amount) > limit {
+                            return false;
+                        }
+                    }
+                    to_send += Uint128::from(new_amount);
+                    true
+                } else {
+                    //nothing to send, leave all claims in waiting status
+                    false
+                }
+            });
+        depositor.unbonding_info = waiting;
     }
 
-    let (_send, waiting): (Vec<_>, _) = depositor.unbonding_info.iter().cloned().partition(|c| {
-        // if mature and we can pay fully, then include in _send
-        if c.release_at.is_expired(block) {
-            let new_amount = c.amount;
-            if let Some(limit) = cap {
-                if to_send + Uint128::from(new_amount) > limit {
-                    return false;
-                }
+    // Released epochs of the unbonding ledger, oldest first
+    let mut released_epochs: Vec<u64> = vec![];
+    for (release_epoch, claim) in
+        read_released_unbonding_claims(storage, depositor_addr, block, start_after, limit)?
+    {
+        let amount = Uint128::from(claim.amount);
+        if let Some(limit) = cap {
+            if to_send + amount > limit {
+                break;
             }
-            to_send += Uint128::from(new_amount);
-            true
-        } else {
-            //nothing to send, leave all claims in waiting status
-            false
         }
-    });
-    depositor.unbonding_info = waiting;
-    Ok(to_send)
+        to_send += amount;
+        released_epochs.push(release_epoch);
+    }
+
+    Ok((to_send, released_epochs))
+}
+
+/// Sum of the shares that are still locked at the given time
+pub fn calculate_locked_shares(share_locks: &[ShareLock], time: Timestamp) -> Uint256 {
+    share_locks
+        .iter()
+        .filter(|share_lock| share_lock.unlock_at > time)
+        .fold(Uint256::zero(), |sum, share_lock| sum + share_lock.shares)
+}
+
+/// Removes the expired share locks of a depositor and returns the amount of shares
+/// that can leave the depositor's position at the given time.
+/// Every path that withdraws or transfers depositor shares must respect this amount.
+pub fn prune_share_locks_and_get_unlocked_shares(
+    storage: &mut dyn Storage,
+    depositor: &Addr,
+    depositor_shares: Uint256,
+    time: Timestamp,
+) -> StdResult<Uint256> {
+    let share_locks: Vec<ShareLock> = read_share_locks(storage, depositor)
+        .into_iter()
+        .filter(|share_lock| share_lock.unlock_at > time)
+        .collect();
+
+    let locked_shares = calculate_locked_shares(&share_locks, time);
+
+    store_share_locks(storage, depositor, share_locks)?;
+
+    if depositor_shares > locked_shares {
+        Ok(depositor_shares - locked_shares)
+    } else {
+        Ok(Uint256::zero())
+    }
+}
+
+pub struct ClaimablePrizes {
+    pub ust_to_send: Uint128,
+    pub glow_to_send: Uint128,
+    // The claimed prizes and their ust amount by lottery id
+    pub prizes: Vec<(u64, PrizeInfo, Uint128)>,
+}
+
+/// Compute the prizes that a depositor can claim for the given lottery ids
+pub fn calculate_claimable_prizes(
+    deps: Deps,
+    config: &Config,
+    depositor: &Addr,
+    lottery_ids: &[u64],
+) -> Result<ClaimablePrizes, ContractError> {
+    let mut ust_to_send = Uint128::zero();
+    let mut glow_to_send = Uint128::zero();
+    let mut prizes: Vec<(u64, PrizeInfo, Uint128)> = vec![];
+
+    for lottery_id in lottery_ids.iter() {
+        let lottery_info = read_lottery_info(deps.storage, *lottery_id);
+        if !lottery_info.awarded {
+            return Err(ContractError::InvalidClaimLotteryNotAwarded(*lottery_id));
+        }
+
+        let prize = PRIZES.may_load(deps.storage, (U64Key::from(*lottery_id), depositor))?;
+        if let Some(prize) = prize {
+            // A prize can only be claimed once, even if its lottery id is repeated
+            if prize.claimed || prizes.iter().any(|(id, _, _)| id == lottery_id) {
+                return Err(ContractError::InvalidClaimPrizeAlreadyClaimed(*lottery_id));
+            }
+
+            let snapshotted_depositor_stats_info = read_winner_stats(
+                deps.storage,
+                depositor,
+                *lottery_id,
+                lottery_info.block_height,
+            );
+
+            let (local_ust_to_send, local_glow_to_send): (Uint128, Uint128) =
+                calculate_winner_prize(
+                    &deps.querier,
+                    config,
+                    &prize,
+                    &lottery_info,
+                    &snapshotted_depositor_stats_info,
+                    depositor,
+                )?;
+
+            ust_to_send += local_ust_to_send;
+            glow_to_send += local_glow_to_send;
+
+            prizes.push((*lottery_id, prize, local_ust_to_send));
+        }
+    }
+
+    Ok(ClaimablePrizes {
+        ust_to_send,
+        glow_to_send,
+        prizes,
+    })
 }
 
 pub fn calculate_winner_prize(
@@ -410,6 +813,64 @@ pub fn calculate_boost_multiplier(
     glow_multiplier
 }
 
+/// Compute the additional voting balance that a depositor needs to reach the max boost multiplier.
+/// Returns None if it can't be reached by only adding voting balance.
+pub fn calculate_boost_voting_balance_needed(
+    boost_config: BoostConfig,
+    user_shares: Uint256,
+    total_user_shares: Uint256,
+    user_voting_balance: Uint128,
+    total_voting_balance: Uint128,
+) -> Option<Uint128> {
+    let max_multiplier = boost_config.max_multiplier;
+
+    let multiplier = calculate_boost_multiplier(
+        boost_config.clone(),
+        user_shares,
+        total_user_shares,
+        user_voting_balance,
+        total_voting_balance,
+    );
+
+    if multiplier >= max_multiplier {
+        return Some(Uint128::zero());
+    }
+
+    // Depositors without shares don't get any boost
+    if user_shares.is_zero() {
+        return None;
+    }
+
+    // The max multiplier is reached when
+    //   UserVotingBalance >= weight * (UserDeposited / TotalDeposited) * TotalVotingBalance
+    // Adding x to the user voting balance also adds x to the total voting balance, so
+    //   x >= (weight * share_ratio * TotalVotingBalance - UserVotingBalance)
+    //        / (1 - weight * share_ratio)
+    let weighted_share_ratio = boost_config.total_voting_power_weight
+        * Decimal256::from_ratio(user_shares, total_user_shares);
+
+    if weighted_share_ratio >= Decimal256::one() {
+        return None;
+    }
+
+    let target_voting_balance =
+        Decimal256::from_uint256(Uint256::from(total_voting_balance)) * weighted_share_ratio;
+    let current_voting_balance = Decimal256::from_uint256(Uint256::from(user_voting_balance));
+
+    let needed = if target_voting_balance > current_voting_balance {
+        (target_voting_balance - current_voting_balance)
+            / (Decimal256::one() - weighted_share_ratio)
+    } else {
+        Decimal256::zero()
+    };
+
+    // Round up, and require at least one unit as the max multiplier isn't reached yet
+    Some(Uint128::from(std::cmp::max(
+        uint256_times_decimal256_ceil(Uint256::one(), needed),
+        Uint256::one(),
+    )))
+}
+
 // Get max bounds
 pub fn calculate_max_bound(min_bound: &str, minimum_matches_for_winning_ticket: usize) -> String {
     format!(
@@ -430,6 +891,219 @@ pub fn pseudo_random_seq(sender_addr: String, tickets: u64, time: u64) -> String
     pseudo_random_hash.to_string()
 }
 
+/// Hash a gift code preimage the same way gift code hashes are expected to be computed
+pub fn hash_gift_code_preimage(preimage: &str) -> Vec<u8> {
+    let mut hasher = Keccak256::new();
+    hasher.update(preimage.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Hash a gift code preimage followed by the address of its redeemer, as committed
+/// before redeeming the gift code
+pub fn hash_gift_code_commitment(preimage: &str, redeemer: &Addr) -> Vec<u8> {
+    let mut hasher = Keccak256::new();
+    hasher.update(preimage.as_bytes());
+    hasher.update(redeemer.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Whether msg is blocked by the pause flags. When every action is paused,
+/// messages without a flag are blocked as well, except the ones updating
+/// the flags and importing state
+pub fn is_action_paused(paused: &PauseFlags, msg: &ExecuteMsg) -> bool {
+    match msg {
+        ExecuteMsg::SetPauseFlags { .. }
+        | ExecuteMsg::UpdateGuardian { .. }
+        | ExecuteMsg::ImportState { .. } => false,
+        ExecuteMsg::Deposit { .. }
+        | ExecuteMsg::RelayDeposit { .. }
+        | ExecuteMsg::ClaimTickets { .. } => paused.deposit,
+        ExecuteMsg::Gift { .. }
+        | ExecuteMsg::CreateGiftCode { .. }
+        | ExecuteMsg::CommitGiftCodeRedemption { .. }
+        | ExecuteMsg::RedeemGiftCode { .. } => paused.gift,
+        ExecuteMsg::Sponsor { .. } => paused.sponsor,
+        ExecuteMsg::Withdraw { .. }
+        | ExecuteMsg::SponsorWithdraw {}
+        | ExecuteMsg::RefundGiftCode { .. }
+        | ExecuteMsg::ExitSyndicate { .. } => paused.withdraw,
+        ExecuteMsg::Claim { .. }
+        | ExecuteMsg::ClaimLottery { .. }
+        | ExecuteMsg::ClaimLotteryFor { .. }
+        | ExecuteMsg::ClaimSyndicateLottery { .. }
+        | ExecuteMsg::ClaimRewards {}
+        | ExecuteMsg::ClaimOperatorCommission {} => paused.claim,
+        ExecuteMsg::ExecuteLottery {} | ExecuteMsg::ExecutePrize { .. } => paused.lottery,
+        ExecuteMsg::ExecuteEpochOps {} => paused.epoch_ops,
+        _ => paused.is_fully_paused(),
+    }
+}
+
+/// Hash of a deposit intent, as signed by its signer. See DepositIntent for its encoding
+pub fn hash_deposit_intent(intent: &DepositIntent) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(DEPOSIT_INTENT_DOMAIN);
+    for field in [
+        &intent.contract,
+        &intent.chain_id,
+        &intent.stable_token,
+        &intent.encoded_tickets,
+    ]
+    .iter()
+    {
+        hasher.update((field.len() as u32).to_be_bytes());
+        hasher.update(field.as_bytes());
+    }
+    match &intent.operator {
+        Some(operator) => {
+            hasher.update([1u8]);
+            hasher.update((operator.len() as u32).to_be_bytes());
+            hasher.update(operator.as_bytes());
+        }
+        None => hasher.update([0u8]),
+    }
+    hasher.update(intent.amount.u128().to_be_bytes());
+    hasher.update(intent.nonce.to_be_bytes());
+    hasher.update(intent.expires_at.to_be_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Account address with the given bech32 prefix of a compressed secp256k1 public key
+pub fn pubkey_to_address(deps: Deps, pubkey: &[u8], prefix: &str) -> Result<Addr, ContractError> {
+    if pubkey.len() != 33 {
+        return Err(ContractError::InvalidRelaySignature {});
+    }
+
+    let mut hasher = Ripemd160::new();
+    hasher.update(Sha256::digest(pubkey));
+    let address = bech32::encode(prefix, hasher.finalize().to_base32(), Variant::Bech32)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(deps.api.addr_validate(&address)?)
+}
+
+/// Validate the address of a depositor, which is either an account or a syndicate
+pub fn validate_depositor_addr(api: &dyn Api, address: &str) -> StdResult<Addr> {
+    if let Some(syndicate_id) = address.strip_prefix("syndicate/") {
+        let syndicate_id: u64 = syndicate_id
+            .parse()
+            .map_err(|_| StdError::generic_err(format!("Invalid syndicate: {}", address)))?;
+        let syndicate = syndicate_address(syndicate_id);
+        if syndicate.as_str() == address {
+            return Ok(syndicate);
+        }
+    }
+
+    api.addr_validate(address)
+}
+
+/// Validates the destinations of each reserve source: every share must be positive
+/// and the shares of a source with destinations must add up to one
+pub fn validate_reserve_destinations(
+    deps: Deps,
+    reserve_destinations: ReserveDestinations,
+) -> Result<ReserveDestinations, ContractError> {
+    let validate = |destinations: Vec<ReserveDestination>| {
+        if destinations.is_empty() {
+            return Ok(destinations);
+        }
+
+        let mut total_share = Decimal256::zero();
+        for destination in destinations.iter() {
+            if destination.share.is_zero() {
+                return Err(ContractError::InvalidReserveDestinations {});
+            }
+            total_share = total_share + destination.share;
+        }
+        if total_share != Decimal256::one() {
+            return Err(ContractError::InvalidReserveDestinations {});
+        }
+
+        destinations
+            .into_iter()
+            .map(|destination| {
+                Ok(ReserveDestination {
+                    address: deps.api.addr_validate(&destination.address)?.to_string(),
+                    share: destination.share,
+                })
+            })
+            .collect::<Result<Vec<ReserveDestination>, ContractError>>()
+    };
+
+    Ok(ReserveDestinations {
+        withdrawal_fees: validate(reserve_destinations.withdrawal_fees)?,
+        prize_reserve: validate(reserve_destinations.prize_reserve)?,
+    })
+}
+
+/// Splits a reserve amount among its destinations, sending it to the community
+/// contract when there are none. The rounding dust goes to the last destination
+pub fn split_reserve(
+    reserve: Uint256,
+    destinations: &[ReserveDestination],
+    community_contract: &Addr,
+) -> Vec<(String, Uint256)> {
+    if destinations.is_empty() {
+        return vec![(community_contract.to_string(), reserve)];
+    }
+
+    let mut remaining = reserve;
+    destinations
+        .iter()
+        .enumerate()
+        .map(|(index, destination)| {
+            let amount = if index == destinations.len() - 1 {
+                remaining
+            } else {
+                reserve * destination.share
+            };
+            remaining = remaining - amount;
+            (destination.address.clone(), amount)
+        })
+        .collect()
+}
+
+/// Amount of reserve to send to each address on the next epoch operation.
+/// Reserve accrued before the sources were tracked goes to the community contract
+pub fn calculate_reserve_transfers(config: &Config, state: &State) -> Vec<(String, Uint256)> {
+    let untracked_reserve =
+        state.total_reserve - state.withdrawal_fee_reserve - state.prize_reserve;
+
+    let splits = split_reserve(
+        state.withdrawal_fee_reserve,
+        &config.reserve_destinations.withdrawal_fees,
+        &config.community_contract,
+    )
+    .into_iter()
+    .chain(split_reserve(
+        state.prize_reserve,
+        &config.reserve_destinations.prize_reserve,
+        &config.community_contract,
+    ))
+    .chain(split_reserve(
+        untracked_reserve,
+        &[],
+        &config.community_contract,
+    ));
+
+    // Merge the transfers to the same address
+    let mut transfers: Vec<(String, Uint256)> = vec![];
+    for (address, amount) in splits {
+        if amount.is_zero() {
+            continue;
+        }
+        match transfers
+            .iter_mut()
+            .find(|(recipient, _)| *recipient == address)
+        {
+            Some((_, total)) => *total += amount,
+            None => transfers.push((address, amount)),
+        }
+    }
+
+    transfers
+}
+
 pub fn is_valid_sequence(sequence: &str, len: usize) -> bool {
     sequence.len() == len
         && sequence
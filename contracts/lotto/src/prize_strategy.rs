@@ -94,6 +94,7 @@ pub fn execute_lottery(
         block_height: env.block.height,
         timestamp: env.block.time,
         total_user_shares: pool.total_user_shares,
+        reserve: Uint256::zero(),
     };
 
     store_lottery_info(deps.storage, state.current_lottery, &lottery_info)?;
@@ -315,6 +316,10 @@ pub fn execute_prize(
 
                 // Increase the total reserve by the reserve fee
                 state.total_reserve += local_reserve_fee;
+                state.prize_reserve += local_reserve_fee;
+
+                // Record the reserve fee taken from this lottery
+                lottery_info.reserve += local_reserve_fee;
 
                 // Increase total_awarded_prize by the prize to be distributed
                 total_awarded_prize += awarded_prize_bucket;
@@ -5,12 +5,15 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{Addr, Deps, Order, StdError, StdResult, Storage, Timestamp};
+use cosmwasm_std::{
+    Addr, BlockInfo, Deps, Order, StdError, StdResult, Storage, Timestamp, Uint128,
+};
 use cosmwasm_storage::{bucket, bucket_read, ReadonlyBucket};
 use cw0::{Duration, Expiration};
-use cw_storage_plus::{Bound, Item, Map, SnapshotMap, U64Key};
+use cw_storage_plus::{Bound, Item, Map, PrimaryKey, SnapshotMap, U64Key};
 use glow_protocol::lotto::{
-    BoostConfig, Claim, DepositorInfoResponse, DepositorStatsResponse, RewardEmissionsIndex,
+    BoostConfig, Claim, DepositorActivity, DepositorInfoResponse, DepositorStatsResponse,
+    InstantWithdrawalFeeDecay, PauseFlags, ReserveDestinations, RewardEmissionsIndex, ShareLock,
 };
 
 use glow_protocol::lotto::NUM_PRIZE_BUCKETS;
@@ -40,12 +43,82 @@ pub const DEPOSITOR_STATS: SnapshotMap<&Addr, DepositorStatsInfo> = SnapshotMap:
 
 pub const LOTTERIES: Map<U64Key, LotteryInfo> = Map::new("lo_v2");
 
+// Shares received through locked gifts, which can't be withdrawn until unlock_at
+pub const LOCKED_SHARES: Map<&Addr, Vec<ShareLock>> = Map::new("locked_shares");
+
+// Escrowed gift deposits keyed by the keccak256 hash of their code
+pub const GIFT_CODES: Map<&[u8], GiftCode> = Map::new("gift_codes");
+
+// Height at which a gift code redemption was committed, keyed by the redeemer and the commitment
+pub const GIFT_CODE_COMMITMENTS: Map<(&Addr, &[u8]), u64> = Map::new("gift_code_commitments");
+
+// Activity log of each depositor, keyed by an increasing id per depositor
+pub const DEPOSITOR_HISTORY: Map<(&Addr, U64Key), DepositorActivity> =
+    Map::new("depositor_history");
+pub const DEPOSITOR_HISTORY_INDEX: Map<&Addr, DepositorHistoryIndex> =
+    Map::new("depositor_history_index");
+
+// Unbonding withdrawals of each depositor, merged by release epoch.
+// Epochs are keyed by the time (or height) at which they are released.
+pub const UNBONDING_CLAIMS: Map<(&Addr, U64Key), Claim> = Map::new("unbonding_claims");
+
+// Share-weighted average time at which each depositor's shares were deposited
+pub const DEPOSIT_AGES: Map<&Addr, Timestamp> = Map::new("deposit_ages");
+// Time at which deposit ages started being tracked
+pub const DEPOSIT_AGES_START: Item<Timestamp> = Item::new("deposit_ages_start");
+
+// Commission rate of the operator of each depositor, as of the time the
+// depositor set the operator. Later increases of the rate don't apply to them
+pub const OPERATOR_COMMISSION_RATES: Map<&Addr, Decimal256> = Map::new("operator_commission_rates");
+
+// Charities that depositors can donate their prizes to, managed by gov
+pub const CHARITIES: Map<&Addr, Charity> = Map::new("charities");
+
+// Charity receiving the prizes of each depositor in donation mode
+pub const PRIZE_BENEFICIARIES: Map<&Addr, Addr> = Map::new("prize_beneficiaries");
+
+// Syndicates pooling the deposits of their members, keyed by id
+pub const SYNDICATES: Map<U64Key, Syndicate> = Map::new("syndicates");
+pub const SYNDICATE_COUNT: Item<u64> = Item::new("syndicate_count");
+// Shares of the syndicate depositor owned by each member
+pub const SYNDICATE_MEMBERS: SnapshotMap<(U64Key, &Addr), Uint256> = SnapshotMap::new(
+    "syndicate_members",
+    "syndicate_members__checkpoint",
+    "syndicate_members__changelog",
+    cw_storage_plus::Strategy::EveryBlock,
+);
+// Ids of the syndicates whose prize of a lottery has been claimed by a member
+pub const SYNDICATE_PRIZE_CLAIMS: Map<(U64Key, &Addr), Vec<u64>> =
+    Map::new("syndicate_prize_claims");
+// Syndicate shares whose part of the syndicate prize of a lottery has been claimed,
+// keyed by lottery id and syndicate id
+pub const SYNDICATE_PRIZE_CLAIMED_SHARES: Map<(U64Key, U64Key), Uint256> =
+    Map::new("syndicate_prize_claimed_shares");
+
+// Next nonce expected in the deposit intents relayed for each signer
+pub const RELAY_NONCES: Map<&Addr, u64> = Map::new("relay_nonces");
+
+// Snapshot maps can only be written at the current height, so the snapshots of lotteries
+// drawn before a state import can't be imported into them. The stats and shares as of
+// those lotteries are imported into the maps below instead, which are only written by
+// ImportState. They take precedence over the snapshots when computing prizes, for as long
+// as the prizes of the imported lotteries can be claimed and exported, so they are never
+// pruned. Lotteries drawn after the import only use the snapshots.
+
+// Stats of the winners of imported lotteries as of each lottery, keyed by lottery id
+pub const IMPORTED_WINNER_STATS: Map<(U64Key, &Addr), DepositorStatsInfo> =
+    Map::new("imported_winner_stats");
+// Shares of syndicate members as of imported lotteries, keyed by syndicate and lottery id
+pub const IMPORTED_SYNDICATE_MEMBER_SHARES: Map<(U64Key, U64Key, &Addr), Uint256> =
+    Map::new("imported_syndicate_member_shares");
+
 use crate::helpers::{
     vec_binary_tickets_to_vec_string_tickets, vec_string_tickets_to_vec_binary_tickets,
 };
 
 // settings for pagination
 const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -72,8 +145,13 @@ pub struct Config {
     pub unbonding_period: Duration,
     pub max_tickets_per_depositor: u64,
     pub glow_prize_buckets: [Uint256; NUM_PRIZE_BUCKETS],
-    pub paused: bool,
+    pub paused: PauseFlags,
+    pub guardian: Option<Addr>,
     pub lotto_winner_boost_config: BoostConfig,
+    pub max_history_entries: u64,
+    pub instant_withdrawal_fee_decay: Option<InstantWithdrawalFeeDecay>,
+    pub max_operator_commission: Decimal256,
+    pub reserve_destinations: ReserveDestinations,
 }
 
 impl Config {
@@ -116,7 +194,12 @@ impl OldConfig {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub total_tickets: Uint256,
+    // Sum of the reserve sources below, plus any reserve accrued before they were tracked
     pub total_reserve: Uint256,
+    #[serde(default)]
+    pub withdrawal_fee_reserve: Uint256,
+    #[serde(default)]
+    pub prize_reserve: Uint256,
     pub prize_buckets: [Uint256; NUM_PRIZE_BUCKETS],
     pub current_lottery: u64,
     pub next_lottery_time: Expiration,
@@ -232,6 +315,18 @@ impl DepositorInfo {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GiftCode {
+    // Address that created the gift code and can get a refund after it expires
+    pub creator: Addr,
+    // Escrowed shares, which are part of total_user_shares but have no depositor
+    pub shares: Uint256,
+    // Tickets given to the depositor redeeming the gift code
+    pub encoded_tickets: String,
+    // Time after which the gift code can no longer be redeemed
+    pub expires_at: Timestamp,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct SponsorInfo {
     // Cumulative value of the sponsor's deposits.
@@ -256,6 +351,12 @@ pub struct OperatorInfo {
     pub pending_rewards: Decimal256,
     // Reward index is used for tracking and calculating the operator's rewards
     pub reward_index: Decimal256,
+    // Share of the prizes won by referred depositors taken by the operator
+    #[serde(default)]
+    pub commission_rate: Decimal256,
+    // Commission accrued by the operator, available to claim.
+    #[serde(default)]
+    pub pending_commission: Uint256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -270,6 +371,9 @@ pub struct LotteryInfo {
     pub page: String,
     pub glow_prize_buckets: [Uint256; NUM_PRIZE_BUCKETS],
     pub total_user_shares: Uint256,
+    // Reserve fees taken from the awarded prize buckets
+    #[serde(default)]
+    pub reserve: Uint256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -311,6 +415,7 @@ pub fn read_lottery_info(storage: &dyn Storage, lottery_id: u64) -> LotteryInfo
             glow_prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             block_height: 0,
             total_user_shares: Uint256::zero(),
+            reserve: Uint256::zero(),
         },
     }
 }
@@ -468,6 +573,19 @@ pub fn read_depositor_stats_at_height(
     }
 }
 
+/// Stats of the winner of a prize as of the lottery, from the imported stats if any
+pub fn read_winner_stats(
+    storage: &dyn Storage,
+    depositor: &Addr,
+    lottery_id: u64,
+    height: u64,
+) -> DepositorStatsInfo {
+    match IMPORTED_WINNER_STATS.may_load(storage, (U64Key::from(lottery_id), depositor)) {
+        Ok(Some(v)) => v,
+        _ => read_depositor_stats_at_height(storage, depositor, height),
+    }
+}
+
 pub fn read_depositor_data(storage: &dyn Storage, depositor: &Addr) -> DepositorData {
     match DEPOSITOR_DATA.load(storage, depositor) {
         Ok(v) => v,
@@ -478,6 +596,488 @@ pub fn read_depositor_data(storage: &dyn Storage, depositor: &Addr) -> Depositor
     }
 }
 
+/// Read the gift codes, ordered by code hash
+pub fn read_gift_codes(
+    storage: &dyn Storage,
+    start_after: Option<Vec<u8>>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Vec<u8>, GiftCode)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start = start_after.map(Bound::Exclusive);
+
+    GIFT_CODES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+pub fn read_share_locks(storage: &dyn Storage, depositor: &Addr) -> Vec<ShareLock> {
+    LOCKED_SHARES
+        .may_load(storage, depositor)
+        .unwrap_or_default()
+        .unwrap_or_default()
+}
+
+/// Store the share locks of a depositor, removing the entry once there are none left
+pub fn store_share_locks(
+    storage: &mut dyn Storage,
+    depositor: &Addr,
+    share_locks: Vec<ShareLock>,
+) -> StdResult<()> {
+    if share_locks.is_empty() {
+        LOCKED_SHARES.remove(storage, depositor);
+        Ok(())
+    } else {
+        LOCKED_SHARES.save(storage, depositor, &share_locks)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Syndicate {
+    pub name: String,
+    pub creator: Addr,
+}
+
+/// Address under which a syndicate holds its deposits and tickets.
+/// It can't collide with a real account, as it isn't a valid bech32 address.
+pub fn syndicate_address(syndicate_id: u64) -> Addr {
+    Addr::unchecked(format!("syndicate/{}", syndicate_id))
+}
+
+pub fn read_syndicate_member_shares(
+    storage: &dyn Storage,
+    syndicate_id: u64,
+    member: &Addr,
+) -> StdResult<Uint256> {
+    Ok(SYNDICATE_MEMBERS
+        .may_load(storage, (U64Key::from(syndicate_id), member))?
+        .unwrap_or_else(Uint256::zero))
+}
+
+/// Shares of a syndicate member as of a lottery, from the imported shares if any
+pub fn read_syndicate_member_shares_at_lottery(
+    storage: &dyn Storage,
+    syndicate_id: u64,
+    member: &Addr,
+    lottery_id: u64,
+    height: u64,
+) -> StdResult<Uint256> {
+    let imported = IMPORTED_SYNDICATE_MEMBER_SHARES.may_load(
+        storage,
+        (U64Key::from(syndicate_id), U64Key::from(lottery_id), member),
+    )?;
+    if let Some(shares) = imported {
+        return Ok(shares);
+    }
+
+    Ok(SYNDICATE_MEMBERS
+        .may_load_at_height(storage, (U64Key::from(syndicate_id), member), height)?
+        .unwrap_or_else(Uint256::zero))
+}
+
+pub fn read_syndicates(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Syndicate)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::Exclusive(id.to_be_bytes().to_vec()));
+
+    SYNDICATES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((parse_u64_key(&k)?, v))
+        })
+        .collect()
+}
+
+/// Read the members of all syndicates, ordered by syndicate id and then by address
+pub fn read_syndicate_members(
+    storage: &dyn Storage,
+    start_after: Option<(u64, Addr)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Addr, Uint256)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start =
+        start_after.map(|(id, addr)| Bound::Exclusive((U64Key::from(id), &addr).joined_key()));
+
+    SYNDICATE_MEMBERS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            let (syndicate_id, addr) = parse_u64_addr_key(&k)?;
+            Ok((syndicate_id, addr, v))
+        })
+        .collect()
+}
+
+/// Read the syndicate prizes claimed by each member, ordered by lottery id and then by address
+pub fn read_syndicate_prize_claims(
+    storage: &dyn Storage,
+    start_after: Option<(u64, Addr)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Addr, Vec<u64>)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start =
+        start_after.map(|(id, addr)| Bound::Exclusive((U64Key::from(id), &addr).joined_key()));
+
+    SYNDICATE_PRIZE_CLAIMS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            let (lottery_id, addr) = parse_u64_addr_key(&k)?;
+            Ok((lottery_id, addr, v))
+        })
+        .collect()
+}
+
+/// Read the shares of the members of a syndicate as of a lottery, ordered by address
+pub fn read_syndicate_prize_shares(
+    storage: &dyn Storage,
+    syndicate_id: u64,
+    lottery_id: u64,
+    height: u64,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, Uint256)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::Exclusive(addr.as_bytes().to_vec()));
+
+    SYNDICATE_MEMBERS
+        .prefix(U64Key::from(syndicate_id))
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, _) = item?;
+            let member = Addr::unchecked(from_utf8(&k)?);
+            let shares = read_syndicate_member_shares_at_lottery(
+                storage,
+                syndicate_id,
+                &member,
+                lottery_id,
+                height,
+            )?;
+            Ok((member, shares))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Charity {
+    pub name: String,
+    // Unregistered charities are kept to preserve their donated amounts
+    pub verified: bool,
+    pub donated_ust: Uint256,
+    pub donated_glow: Uint256,
+}
+
+/// Charity receiving the prizes of a depositor.
+/// Returns None if the depositor is not in donation mode or their
+/// beneficiary is no longer a verified charity.
+pub fn read_prize_beneficiary(storage: &dyn Storage, depositor: &Addr) -> StdResult<Option<Addr>> {
+    let beneficiary = match PRIZE_BENEFICIARIES.may_load(storage, depositor)? {
+        Some(beneficiary) => beneficiary,
+        None => return Ok(None),
+    };
+
+    let verified = CHARITIES
+        .may_load(storage, &beneficiary)?
+        .map(|charity| charity.verified)
+        .unwrap_or(false);
+
+    Ok(if verified { Some(beneficiary) } else { None })
+}
+
+pub fn read_charities(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, Charity)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start = start_after.map(|a| Bound::Exclusive(a.as_bytes().to_vec()));
+
+    CHARITIES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((Addr::unchecked(String::from_utf8(k).unwrap()), v))
+        })
+        .collect()
+}
+
+/// Average deposit time of a depositor.
+/// Depositors without a record only hold shares deposited before deposit ages were
+/// tracked, so their shares are aged from the time tracking started, or from time
+/// if it is unknown.
+pub fn read_deposit_age(storage: &dyn Storage, depositor: &Addr, time: Timestamp) -> Timestamp {
+    DEPOSIT_AGES
+        .may_load(storage, depositor)
+        .unwrap_or_default()
+        .or_else(|| DEPOSIT_AGES_START.may_load(storage).unwrap_or_default())
+        .unwrap_or(time)
+}
+
+/// Move the average deposit time of a depositor holding current_shares
+/// towards time, weighted by the newly credited_shares
+pub fn update_deposit_age(
+    storage: &mut dyn Storage,
+    depositor: &Addr,
+    current_shares: Uint256,
+    credited_shares: Uint256,
+    time: Timestamp,
+) -> StdResult<()> {
+    let total_shares = current_shares + credited_shares;
+    if total_shares.is_zero() {
+        return Ok(());
+    }
+
+    let deposit_time = Uint256::from(read_deposit_age(storage, depositor, time).seconds())
+        .multiply_ratio(current_shares, total_shares)
+        + Uint256::from(time.seconds()).multiply_ratio(credited_shares, total_shares);
+
+    DEPOSIT_AGES.save(
+        storage,
+        depositor,
+        &Timestamp::from_seconds(Uint128::from(deposit_time).u128() as u64),
+    )
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+pub struct DepositorHistoryIndex {
+    pub first_id: u64, // id of the oldest entry kept
+    pub next_id: u64,  // id of the next entry
+}
+
+/// Append an entry to the history of a depositor,
+/// pruning the oldest entries over max_history_entries
+pub fn append_depositor_history(
+    storage: &mut dyn Storage,
+    depositor: &Addr,
+    activity: DepositorActivity,
+    max_history_entries: u64,
+) -> StdResult<()> {
+    let mut index = DEPOSITOR_HISTORY_INDEX
+        .may_load(storage, depositor)?
+        .unwrap_or_default();
+
+    DEPOSITOR_HISTORY.save(storage, (depositor, U64Key::from(index.next_id)), &activity)?;
+    index.next_id += 1;
+
+    while index.next_id - index.first_id > max_history_entries {
+        DEPOSITOR_HISTORY.remove(storage, (depositor, U64Key::from(index.first_id)));
+        index.first_id += 1;
+    }
+
+    DEPOSITOR_HISTORY_INDEX.save(storage, depositor, &index)
+}
+
+/// Commission rate agreed to by depositor when setting its operator. Depositors without
+/// a snapshot set their operator before operators could take a commission
+pub fn read_operator_commission_rate(
+    storage: &dyn Storage,
+    depositor: &Addr,
+) -> StdResult<Decimal256> {
+    Ok(OPERATOR_COMMISSION_RATES
+        .may_load(storage, depositor)?
+        .unwrap_or_else(Decimal256::zero))
+}
+
+pub fn read_depositor_history(
+    storage: &dyn Storage,
+    depositor: &Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, DepositorActivity)>> {
+    let start = start_after.map(|id| Bound::Exclusive(id.to_be_bytes().to_vec()));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+
+    DEPOSITOR_HISTORY
+        .prefix(depositor)
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((parse_u64_key(&k)?, v))
+        })
+        .collect()
+}
+
+/// Read the history entries of all depositors, ordered by address and then by id
+pub fn read_all_depositor_history(
+    storage: &dyn Storage,
+    start_after: Option<(Addr, u64)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, u64, DepositorActivity)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start =
+        start_after.map(|(addr, id)| Bound::Exclusive((&addr, U64Key::from(id)).joined_key()));
+
+    DEPOSITOR_HISTORY
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            let (addr, id) = parse_addr_u64_key(&k)?;
+            Ok((addr, id, v))
+        })
+        .collect()
+}
+
+/// Write an exported history entry of a depositor, extending the
+/// indexed range of entries to cover it
+pub fn import_depositor_history(
+    storage: &mut dyn Storage,
+    depositor: &Addr,
+    id: u64,
+    activity: DepositorActivity,
+) -> StdResult<()> {
+    let index = match DEPOSITOR_HISTORY_INDEX.may_load(storage, depositor)? {
+        Some(index) => DepositorHistoryIndex {
+            first_id: index.first_id.min(id),
+            next_id: index.next_id.max(id + 1),
+        },
+        None => DepositorHistoryIndex {
+            first_id: id,
+            next_id: id + 1,
+        },
+    };
+
+    DEPOSITOR_HISTORY.save(storage, (depositor, U64Key::from(id)), &activity)?;
+    DEPOSITOR_HISTORY_INDEX.save(storage, depositor, &index)
+}
+
+/// Add amount to the unbonding claim of a depositor for release_epoch,
+/// creating the claim if it is the first withdrawal of the epoch
+pub fn add_unbonding_claim(
+    storage: &mut dyn Storage,
+    depositor: &Addr,
+    release_epoch: u64,
+    release_at: Expiration,
+    amount: Uint256,
+) -> StdResult<()> {
+    UNBONDING_CLAIMS.update(
+        storage,
+        (depositor, U64Key::from(release_epoch)),
+        |claim| -> StdResult<_> {
+            Ok(match claim {
+                Some(claim) => Claim {
+                    amount: claim.amount + amount,
+                    release_at: claim.release_at,
+                },
+                None => Claim { amount, release_at },
+            })
+        },
+    )?;
+
+    Ok(())
+}
+
+pub fn remove_unbonding_claims(
+    storage: &mut dyn Storage,
+    depositor: &Addr,
+    release_epochs: &[u64],
+) {
+    for release_epoch in release_epochs {
+        UNBONDING_CLAIMS.remove(storage, (depositor, U64Key::from(*release_epoch)));
+    }
+}
+
+pub fn read_unbonding_claims(
+    storage: &dyn Storage,
+    depositor: &Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Claim)>> {
+    let start = start_after.map(|epoch| Bound::Exclusive(epoch.to_be_bytes().to_vec()));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    UNBONDING_CLAIMS
+        .prefix(depositor)
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((parse_u64_key(&k)?, v))
+        })
+        .collect()
+}
+
+/// Read the unbonding claims of all depositors, ordered by address and then by release epoch
+pub fn read_all_unbonding_claims(
+    storage: &dyn Storage,
+    start_after: Option<(Addr, u64)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, u64, Claim)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start = start_after
+        .map(|(addr, epoch)| Bound::Exclusive((&addr, U64Key::from(epoch)).joined_key()));
+
+    UNBONDING_CLAIMS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            let (addr, epoch) = parse_addr_u64_key(&k)?;
+            Ok((addr, epoch, v))
+        })
+        .collect()
+}
+
+/// Read the unbonding claims of a depositor that are released at block, oldest epoch first,
+/// starting after the release epoch start_after and up to limit epochs
+pub fn read_released_unbonding_claims(
+    storage: &dyn Storage,
+    depositor: &Addr,
+    block: &BlockInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Claim)>> {
+    let start = start_after.map(|epoch| Bound::Exclusive(epoch.to_be_bytes().to_vec()));
+    let limit = limit.unwrap_or(MAX_LIMIT).min(MAX_LIMIT) as usize;
+
+    UNBONDING_CLAIMS
+        .prefix(depositor)
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((parse_u64_key(&k)?, v))
+        })
+        .take_while(|item: &StdResult<(u64, Claim)>| match item {
+            Ok((_, claim)) => claim.release_at.is_expired(block),
+            Err(_) => true,
+        })
+        .collect()
+}
+
+/// Legacy unbonding claims of a depositor followed by the oldest claims of the unbonding ledger,
+/// and whether the ledger has more claims. The full ledger can be paginated with the
+/// UnbondingClaims query
+pub fn read_depositor_unbonding_info(
+    storage: &dyn Storage,
+    depositor: &Addr,
+    legacy_unbonding_info: Vec<Claim>,
+) -> StdResult<(Vec<Claim>, bool)> {
+    let mut unbonding_info = legacy_unbonding_info;
+    let mut claims = UNBONDING_CLAIMS
+        .prefix(depositor)
+        .range(storage, None, None, Order::Ascending)
+        .take(MAX_LIMIT as usize + 1)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<Vec<Claim>>>()?;
+
+    let has_more = claims.len() > MAX_LIMIT as usize;
+    claims.truncate(MAX_LIMIT as usize);
+    unbonding_info.extend(claims);
+
+    Ok((unbonding_info, has_more))
+}
+
 pub fn store_sponsor_info(
     storage: &mut dyn Storage,
     sponsor: &Addr,
@@ -512,6 +1112,8 @@ pub fn read_operator_info(storage: &dyn Storage, operator: &Addr) -> OperatorInf
             shares: Uint256::zero(),
             pending_rewards: Decimal256::zero(),
             reward_index: Decimal256::zero(),
+            commission_rate: Decimal256::zero(),
+            pending_commission: Uint256::zero(),
         },
     }
 }
@@ -534,11 +1136,17 @@ pub fn read_depositors_info(
             let depositor_data = read_depositor_data(deps.storage, &depositor_addr);
             let vec_string_tickets =
                 vec_binary_tickets_to_vec_string_tickets(depositor_data.vec_binary_tickets);
+            let (unbonding_info, unbonding_info_has_more) = read_depositor_unbonding_info(
+                deps.storage,
+                &depositor_addr,
+                depositor_data.unbonding_info,
+            )?;
             Ok(DepositorInfoResponse {
                 depositor,
                 shares: v.shares,
                 tickets: vec_string_tickets,
-                unbonding_info: depositor_data.unbonding_info,
+                unbonding_info,
+                unbonding_info_has_more,
             })
         })
         .collect()
@@ -567,6 +1175,141 @@ pub fn read_depositors_stats(
         .collect()
 }
 
+pub fn read_depositors(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start = start_after.map(|v| Bound::Exclusive(v.as_bytes().to_vec()));
+
+    DEPOSITOR_STATS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            Ok(Addr::unchecked(String::from_utf8(k)?))
+        })
+        .collect()
+}
+
+pub fn read_sponsors(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, SponsorInfo)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start = old_calc_range_start(start_after);
+
+    bucket_read::<SponsorInfo>(storage, PREFIX_SPONSOR)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((Addr::unchecked(String::from_utf8(k)?), v))
+        })
+        .collect()
+}
+
+pub fn read_operators(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, OperatorInfo)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start = old_calc_range_start(start_after);
+
+    bucket_read::<OperatorInfo>(storage, PREFIX_OPERATOR)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((Addr::unchecked(String::from_utf8(k)?), v))
+        })
+        .collect()
+}
+
+pub fn read_tickets(
+    storage: &dyn Storage,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, Vec<Addr>)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start = start_after.map(|sequence| Bound::Exclusive(sequence.into_bytes()));
+
+    TICKETS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((String::from_utf8(k)?, v))
+        })
+        .collect()
+}
+
+pub fn read_lotteries(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, LotteryInfo)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::Exclusive(id.to_be_bytes().to_vec()));
+
+    LOTTERIES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((parse_u64_key(&k)?, v))
+        })
+        .collect()
+}
+
+/// Read the awarded lotteries with ids from from_id to to_id (inclusive),
+/// spanning at most MAX_LIMIT lottery ids
+pub fn read_awarded_lotteries(
+    storage: &dyn Storage,
+    from_id: u64,
+    to_id: u64,
+) -> StdResult<Vec<(u64, LotteryInfo)>> {
+    let to_id = to_id.min(from_id.saturating_add(MAX_LIMIT as u64 - 1));
+    let start = Some(Bound::Inclusive(from_id.to_be_bytes().to_vec()));
+    let end = Some(Bound::Inclusive(to_id.to_be_bytes().to_vec()));
+
+    LOTTERIES
+        .range(storage, start, end, Order::Ascending)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((parse_u64_key(&k)?, v))
+        })
+        .filter(|elem: &StdResult<(u64, LotteryInfo)>| match elem {
+            Ok((_, lottery_info)) => lottery_info.awarded,
+            Err(_) => true,
+        })
+        .collect()
+}
+
+/// Read the prizes of all lotteries, ordered by lottery id and then by address
+pub fn read_prizes(
+    storage: &dyn Storage,
+    start_after: Option<(u64, Addr)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Addr, PrizeInfo)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let start =
+        start_after.map(|(id, addr)| Bound::Exclusive((U64Key::from(id), &addr).joined_key()));
+
+    PRIZES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let (lottery_id, addr) = parse_u64_addr_key(&k)?;
+            Ok((lottery_id, addr, v))
+        })
+        .collect()
+}
+
 pub fn old_read_depositors(
     deps: Deps,
     start_after: Option<Addr>,
@@ -639,6 +1382,37 @@ pub fn read_lottery_prizes(
         .collect::<StdResult<Vec<_>>>()
 }
 
+// helper to deserialize u64 keys
+fn parse_u64_key(value: &[u8]) -> StdResult<u64> {
+    Ok(u64::from_be_bytes(value.try_into().map_err(|_| {
+        StdError::generic_err("Could not read 8 byte key")
+    })?))
+}
+
+// Keys are the length prefixed id followed by the address
+fn parse_u64_addr_key(value: &[u8]) -> StdResult<(u64, Addr)> {
+    if value.len() < 10 {
+        return Err(StdError::generic_err("Could not read id and address key"));
+    }
+    let id = parse_u64_key(&value[2..10])?;
+    let addr = Addr::unchecked(from_utf8(&value[10..])?);
+    Ok((id, addr))
+}
+
+// Keys are the length prefixed address followed by the id
+fn parse_addr_u64_key(value: &[u8]) -> StdResult<(Addr, u64)> {
+    if value.len() < 2 {
+        return Err(StdError::generic_err("Could not read address and id key"));
+    }
+    let addr_end = 2 + parse_length(&value[0..2])?;
+    if value.len() != addr_end + 8 {
+        return Err(StdError::generic_err("Could not read address and id key"));
+    }
+    let addr = Addr::unchecked(from_utf8(&value[2..addr_end])?);
+    let id = parse_u64_key(&value[addr_end..])?;
+    Ok((addr, id))
+}
+
 // helper to deserialize the length
 pub fn parse_length(value: &[u8]) -> StdResult<usize> {
     Ok(u16::from_be_bytes(
@@ -1,22 +1,31 @@
 use crate::contract::{
-    execute, instantiate, migrate, query, query_config, query_pool, query_state, query_ticket_info,
-    INITIAL_DEPOSIT_AMOUNT,
+    execute, instantiate, migrate, query, query_config, query_export_charities,
+    query_export_depositor_history, query_export_depositors, query_export_gift_codes,
+    query_export_lotteries, query_export_operators, query_export_prizes, query_export_sponsors,
+    query_export_syndicate_members, query_export_syndicate_prize_claims, query_export_syndicates,
+    query_export_tickets, query_export_totals, query_export_unbonding_claims, query_lottery_stats,
+    query_operator, query_pool, query_state, query_ticket_info, DEFAULT_MAX_HISTORY_ENTRIES,
+    INITIAL_DEPOSIT_AMOUNT, MAX_GIFT_CODE_DURATION, MAX_HISTORY_ENTRIES_CAP, MAX_SHARE_LOCKS,
+    MAX_SHARE_LOCK_DURATION, SECONDS_PER_YEAR, THIRTY_MINUTE_TIME,
 };
 use crate::helpers::{
-    base64_encoded_tickets_to_vec_string_tickets, calculate_boost_multiplier, calculate_max_bound,
+    base64_encoded_tickets_to_vec_string_tickets, calculate_boost_multiplier,
+    calculate_boost_voting_balance_needed, calculate_max_bound, calculate_release_epoch,
     calculate_value_of_aust_to_be_redeemed_for_lottery, calculate_winner_prize,
-    get_minimum_matches_for_winning_ticket, uint256_times_decimal256_ceil,
-    ExecuteLotteryRedeemedAustInfo,
+    get_minimum_matches_for_winning_ticket, hash_gift_code_commitment, hash_gift_code_preimage,
+    uint256_times_decimal256_ceil, ExecuteLotteryRedeemedAustInfo,
 };
 use crate::mock_querier::{
     mock_dependencies, mock_env, mock_info, WasmMockQuerier, MOCK_CONTRACT_ADDR,
 };
 use crate::state::{
-    old_read_depositor_info, old_read_lottery_info, old_remove_depositor_info, read_depositor_info,
-    read_depositor_stats_at_height, read_lottery_info, read_lottery_prizes, read_prize,
-    read_sponsor_info, store_depositor_info, store_depositor_stats, Config, DepositorInfo,
-    DepositorStatsInfo, LotteryInfo, OldConfig, OldDepositorInfo, OldPool, OldState, Pool,
-    PrizeInfo, State, CONFIG, OLDCONFIG, OLDPOOL, OLDSTATE, OLD_PRIZES, POOL, PRIZES, STATE,
+    add_unbonding_claim, old_read_depositor_info, old_read_lottery_info, old_remove_depositor_info,
+    read_depositor_info, read_depositor_stats_at_height, read_lottery_info, read_lottery_prizes,
+    read_prize, read_sponsor_info, read_unbonding_claims, store_depositor_info,
+    store_depositor_stats, store_lottery_info, Config, DepositorInfo, DepositorStatsInfo,
+    LotteryInfo, OldConfig, OldDepositorInfo, OldPool, OldState, Pool, PrizeInfo, State, CONFIG,
+    DEPOSIT_AGES, DEPOSIT_AGES_START, OLDCONFIG, OLDPOOL, OLDSTATE, OLD_PRIZES, POOL, PRIZES,
+    STATE,
 };
 use crate::test_helpers::{
     calculate_lottery_prize_buckets, calculate_prize_buckets,
@@ -26,16 +35,24 @@ use crate::test_helpers::{
 use cosmwasm_storage::bucket;
 use cw_storage_plus::U64Key;
 use glow_protocol::lotto::{
-    BoostConfig, MigrateMsg, OperatorInfoResponse, PrizeInfoResponse, RewardEmissionsIndex,
-    NUM_PRIZE_BUCKETS, TICKET_LENGTH,
+    BoostConfig, BoostResponse, CharitiesResponse, CharityResponse, DepositIntent,
+    DepositorActivityKind, DepositorActivityResponse, DepositorHistoryResponse,
+    DepositorInfoResponse, DepositorLocksResponse, ExportedDepositorStats, ExportedPrize,
+    GiftCodeResponse, InstantWithdrawalFeeDecay, InstantWithdrawalFeeResponse, MigrateMsg,
+    OperatorInfoResponse, PauseFlags, PrizeBeneficiaryResponse, PrizeInfoResponse,
+    RelayNonceResponse, ReserveDestination, ReserveDestinations, RewardEmissionsIndex, ShareLock,
+    SimulateClaimLotteryResponse, SimulateDepositResponse, SimulateWithdrawResponse, StateRecord,
+    SyndicateMemberResponse, SyndicateResponse, UnbondingClaimsResponse, NUM_PRIZE_BUCKETS,
+    STATE_EXPORT_VERSION, TICKET_LENGTH,
 };
 use lazy_static::lazy_static;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::MockApi;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal, DepsMut, Env,
-    MemoryStorage, OwnedDeps, Response, StdError, SubMsg, Timestamp, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Api, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, MemoryStorage, OwnedDeps, Response, StdError, SubMsg, Timestamp, Uint128,
+    WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 use glow_protocol::distributor::ExecuteMsg as FaucetExecuteMsg;
@@ -283,7 +300,12 @@ fn proper_initialization() {
             instant_withdrawal_fee: Decimal256::percent(INSTANT_WITHDRAWAL_FEE),
             unbonding_period: WEEK,
             max_tickets_per_depositor: MAX_TICKETS_PER_DEPOSITOR,
-            paused: false
+            paused: PauseFlags::default(),
+            guardian: None,
+            max_history_entries: DEFAULT_MAX_HISTORY_ENTRIES,
+            instant_withdrawal_fee_decay: None,
+            max_operator_commission: Decimal256::zero(),
+            reserve_destinations: ReserveDestinations::default(),
         }
     );
 
@@ -326,6 +348,8 @@ fn proper_initialization() {
         StateResponse {
             total_tickets: Uint256::zero(),
             total_reserve: Uint256::zero(),
+            withdrawal_fee_reserve: Uint256::zero(),
+            prize_reserve: Uint256::zero(),
             prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             current_lottery: 0,
             next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
@@ -388,6 +412,9 @@ fn update_config() {
         lotto_winner_boost_config: None,
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
@@ -432,6 +459,9 @@ fn update_config() {
 
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -459,6 +489,9 @@ fn update_config() {
 
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -487,6 +520,9 @@ fn update_config() {
 
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -515,6 +551,9 @@ fn update_config() {
 
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -540,6 +579,9 @@ fn update_config() {
 
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -567,6 +609,9 @@ fn update_config() {
 
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -592,6 +637,9 @@ fn update_config() {
 
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -615,6 +663,9 @@ fn update_config() {
         lotto_winner_boost_config: None,
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -640,6 +691,9 @@ fn update_config() {
         lotto_winner_boost_config: None,
         operator_glow_emission_rate: Some(Decimal256::percent(10000)),
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -675,6 +729,9 @@ fn update_config() {
         lotto_winner_boost_config: None,
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: Some(Decimal256::percent(1000)),
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -711,6 +768,9 @@ fn update_config() {
 
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -719,7 +779,7 @@ fn update_config() {
     // check paused has changed
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_response: ConfigResponse = from_binary(&res).unwrap();
-    assert!(config_response.paused);
+    assert!(config_response.paused.is_fully_paused());
 
     // check only owner can update config
     let info = mock_info("owner2", &[]);
@@ -738,6 +798,9 @@ fn update_config() {
 
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -745,6 +808,34 @@ fn update_config() {
         Err(ContractError::Unauthorized {}) => {}
         _ => panic!("Must return unauthorized error"),
     }
+
+    // The unbonding period and the epoch interval can't mix time and height
+    let mut config = CONFIG.load(deps.as_ref().storage).unwrap();
+    config.unbonding_period = Duration::Height(1000);
+    CONFIG.save(deps.as_mut().storage, &config).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_addr: None,
+        reserve_factor: None,
+        instant_withdrawal_fee: None,
+        unbonding_period: None,
+        epoch_interval: Some(THIRTY_MINUTE_TIME),
+        max_holders: None,
+        max_tickets_per_depositor: None,
+        paused: None,
+        lotto_winner_boost_config: None,
+        operator_glow_emission_rate: None,
+        sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), msg);
+    match res {
+        Err(ContractError::MixedUnbondingUnits {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 }
 
 #[test]
@@ -769,6 +860,7 @@ fn test_max_tickets_per_depositor() {
     let msg = ExecuteMsg::Deposit {
         encoded_tickets: vec_string_tickets_to_encoded_tickets(too_many_combinations),
         operator: None,
+        syndicate: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
@@ -793,6 +885,7 @@ fn test_max_tickets_per_depositor() {
     let msg = ExecuteMsg::Deposit {
         encoded_tickets: vec_string_tickets_to_encoded_tickets(too_many_combinations),
         operator: None,
+        syndicate: None,
     };
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -810,6 +903,7 @@ fn test_max_tickets_per_depositor() {
     let msg = ExecuteMsg::Deposit {
         encoded_tickets: vec_string_tickets_to_encoded_tickets(too_many_combinations),
         operator: None,
+        syndicate: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
 
@@ -841,6 +935,9 @@ fn test_max_tickets_per_depositor() {
 
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -859,6 +956,7 @@ fn test_max_tickets_per_depositor() {
     let msg = ExecuteMsg::Deposit {
         encoded_tickets: vec_string_tickets_to_encoded_tickets(too_many_combinations),
         operator: None,
+        syndicate: None,
     };
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 }
@@ -878,6 +976,7 @@ fn deposit() {
             String::from(ZERO_MATCH_SEQUENCE),
         ]),
         operator: None,
+        syndicate: None,
     };
     let info = mock_info(
         "addr0000",
@@ -972,6 +1071,7 @@ fn deposit() {
             String::from(ONE_MATCH_SEQUENCE),
         ]),
         operator: None,
+        syndicate: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1018,6 +1118,8 @@ fn deposit() {
         StateResponse {
             total_tickets: Uint256::from(2u64),
             total_reserve: Uint256::zero(),
+            withdrawal_fee_reserve: Uint256::zero(),
+            prize_reserve: Uint256::zero(),
             prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             current_lottery: 0,
             next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
@@ -1093,6 +1195,7 @@ fn deposit() {
             TWO_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1110,6 +1213,7 @@ fn deposit() {
             THREE_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1126,6 +1230,7 @@ fn deposit() {
             ZERO_MATCH_SEQUENCE_2,
         )]),
         operator: None,
+        syndicate: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1142,6 +1247,7 @@ fn deposit() {
             ZERO_MATCH_SEQUENCE_3,
         )]),
         operator: None,
+        syndicate: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1159,6 +1265,7 @@ fn deposit() {
             FOUR_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1168,6 +1275,7 @@ fn deposit() {
             FOUR_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
 
     // We let users have a repeated ticket
@@ -1187,6 +1295,7 @@ fn deposit() {
                 ZERO_MATCH_SEQUENCE_4,
             )]),
             operator: None,
+            syndicate: None,
         };
         let info = mock_info(
             address.as_str(),
@@ -1211,6 +1320,7 @@ fn deposit() {
             ZERO_MATCH_SEQUENCE_4,
         )]),
         operator: None,
+        syndicate: None,
     };
     let info = mock_info(
         "addr1111",
@@ -1244,6 +1354,7 @@ fn gift_tickets() {
         ]),
         recipient: "addr1111".to_string(),
         operator: None,
+        unlock_at: None,
     };
     let info = mock_info(
         "addr0000",
@@ -1308,6 +1419,7 @@ fn gift_tickets() {
         ]),
         recipient: "addr0000".to_string(),
         operator: None,
+        unlock_at: None,
     };
     let info = mock_info(
         "addr0000",
@@ -1396,6 +1508,7 @@ fn gift_tickets() {
         ]),
         recipient: "addr1111".to_string(),
         operator: None,
+        unlock_at: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1442,6 +1555,8 @@ fn gift_tickets() {
         StateResponse {
             total_tickets: Uint256::from(2u64),
             total_reserve: Uint256::zero(),
+            withdrawal_fee_reserve: Uint256::zero(),
+            prize_reserve: Uint256::zero(),
             prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             current_lottery: 0,
             next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
@@ -1503,538 +1618,1065 @@ fn gift_tickets() {
 }
 
 #[test]
-fn sponsor() {
+fn locked_gift() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    let sponsor_amount = 100_000_000u128;
-
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1_000_000u128))],
-    );
+    let unlock_at = mock_env().block.time.seconds() + WEEK_TIME;
 
-    // Address sponsor
     let info = mock_info(
-        "addr0001",
+        "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(sponsor_amount),
+            amount: Uint256::from(2 * TICKET_PRICE).into(),
         }],
     );
 
-    let msg = ExecuteMsg::Sponsor {
-        award: None,
-        prize_distribution: None,
+    // Unlock time must be in the future
+    let msg = ExecuteMsg::Gift {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
+            String::from(ZERO_MATCH_SEQUENCE),
+            String::from(ONE_MATCH_SEQUENCE),
+        ]),
+        recipient: "addr1111".to_string(),
+        operator: None,
+        unlock_at: Some(mock_env().block.time.seconds()),
     };
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg);
-    println!("{:?}", _res);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidUnlockTime {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let net_amount = Uint256::from(
-        deduct_tax(
+    // Correct locked gift - gifts two tickets
+    let msg = ExecuteMsg::Gift {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
+            String::from(ZERO_MATCH_SEQUENCE),
+            String::from(ONE_MATCH_SEQUENCE),
+        ]),
+        recipient: "addr1111".to_string(),
+        operator: None,
+        unlock_at: Some(unlock_at),
+    };
+
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let minted_shares = Uint256::from(2 * TICKET_PRICE) / Decimal256::permille(RATE);
+
+    // Tickets play immediately
+    assert_eq!(
+        query_ticket_info(deps.as_ref(), String::from(ZERO_MATCH_SEQUENCE))
+            .unwrap()
+            .holders,
+        vec![deps.api.addr_validate("addr1111").unwrap()]
+    );
+
+    let res: DepositorLocksResponse = from_binary(
+        &query(
             deps.as_ref(),
-            Coin {
-                denom: String::from("uusd"),
-                amount: Uint128::from(sponsor_amount),
+            mock_env(),
+            QueryMsg::DepositorLocks {
+                address: "addr1111".to_string(),
             },
         )
-        .unwrap()
-        .amount,
+        .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        res,
+        DepositorLocksResponse {
+            depositor: "addr1111".to_string(),
+            locked_shares: minted_shares,
+            unlocked_shares: Uint256::zero(),
+            locked_balance: minted_shares * Decimal256::permille(RATE),
+            unlocked_balance: Uint256::zero(),
+            locks: vec![ShareLock {
+                shares: minted_shares,
+                unlock_at: Timestamp::from_seconds(unlock_at),
+            }],
+        }
     );
 
-    let minted_aust = net_amount / Decimal256::permille(RATE);
+    // The recipient can't withdraw the locked shares
+    let msg = ExecuteMsg::Withdraw {
+        amount: None,
+        instant: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr1111", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::DepositorSharesLocked {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    deps.querier.with_token_balances(&[(
-        &A_UST.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &minted_aust.into())],
-    )]);
+    // The recipient deposits on their own
+    let deposit_msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            ZERO_MATCH_SEQUENCE_2,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let info = mock_info(
+        "addr1111",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, deposit_msg).unwrap();
 
-    let info = mock_info("addr0001", &[]);
-    let msg = ExecuteMsg::SponsorWithdraw {};
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    // Withdrawing more than the unlocked shares fails
+    let withdraw_msg = ExecuteMsg::Withdraw {
+        amount: Some(Uint256::from(2 * TICKET_PRICE).into()),
+        instant: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr1111", &[]),
+        withdraw_msg,
+    );
+    match res {
+        Err(ContractError::WithdrawingLockedShares { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let sponsor_info = read_sponsor_info(
-        deps.as_ref().storage,
-        &deps.api.addr_validate("addr0001").unwrap(),
+    // Withdrawing everything only withdraws the unlocked shares
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr1111", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        read_depositor_info(
+            deps.as_ref().storage,
+            &deps.api.addr_validate("addr1111").unwrap()
+        )
+        .shares,
+        minted_shares
     );
 
-    let pool = query_pool(deps.as_ref()).unwrap();
+    // After unlock_at, the gifted shares can be withdrawn
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(unlock_at);
 
-    assert_eq!(sponsor_info.lottery_deposit, Uint256::zero());
-    assert_eq!(pool.total_sponsor_lottery_deposits, Uint256::zero());
+    execute(deps.as_mut(), env.clone(), mock_info("addr1111", &[]), msg).unwrap();
+
+    assert_eq!(
+        read_depositor_info(
+            deps.as_ref().storage,
+            &deps.api.addr_validate("addr1111").unwrap()
+        )
+        .shares,
+        Uint256::zero()
+    );
+
+    let res: DepositorLocksResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::DepositorLocks {
+                address: "addr1111".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(res.locks, vec![]);
+    assert_eq!(res.locked_shares, Uint256::zero());
 }
 
 #[test]
-fn instant_sponsor() {
+fn gift_codes() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    let sponsor_amount = 100_000_000u128;
-
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1_000_000u128))],
-    );
+    let preimage = "happy birthday";
+    let code_hash = hex::encode(hash_gift_code_preimage(preimage));
+    let expires_at = mock_env().block.time.seconds() + WEEK_TIME;
 
-    // Address sponsor
     let info = mock_info(
-        "addr0001",
+        "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(sponsor_amount),
+            amount: Uint256::from(2 * TICKET_PRICE).into(),
         }],
     );
 
-    // Test sponsoring with the default prize distribution
-
-    let msg = ExecuteMsg::Sponsor {
-        award: Some(true),
-        prize_distribution: None,
+    // Code hash must be a hex encoded keccak256 hash
+    let msg = ExecuteMsg::CreateGiftCode {
+        code_hash: "happy birthday".to_string(),
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            ZERO_MATCH_SEQUENCE,
+        )]),
+        expires_at,
     };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidGiftCodeHash {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
-    println!("{:?}", _res);
+    // Expiry must be within the maximum gift code duration
+    for expires_at in [
+        mock_env().block.time.seconds() + MAX_GIFT_CODE_DURATION + 1,
+        u64::MAX,
+    ] {
+        let msg = ExecuteMsg::CreateGiftCode {
+            code_hash: code_hash.clone(),
+            encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+                ZERO_MATCH_SEQUENCE,
+            )]),
+            expires_at,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::InvalidGiftCodeExpiry {}) => {}
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
 
-    // Check that the prize buckets were updated
+    // Can't request more tickets than the deposit affords
+    let msg = ExecuteMsg::CreateGiftCode {
+        code_hash: code_hash.clone(),
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
+            String::from(ZERO_MATCH_SEQUENCE),
+            String::from(ONE_MATCH_SEQUENCE),
+            String::from(TWO_MATCH_SEQUENCE),
+        ]),
+        expires_at,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InsufficientPostTransactionDepositorBalance { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let mut prize_buckets = [Uint256::zero(); NUM_PRIZE_BUCKETS];
+    // Correct gift code with two tickets
+    let encoded_tickets = vec_string_tickets_to_encoded_tickets(vec![
+        String::from(ZERO_MATCH_SEQUENCE),
+        String::from(ONE_MATCH_SEQUENCE),
+    ]);
+    let msg = ExecuteMsg::CreateGiftCode {
+        code_hash: code_hash.clone(),
+        encoded_tickets: encoded_tickets.clone(),
+        expires_at,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
 
-    // Distribute the sponsorship to the prize buckets according to the prize distribution
-    for (index, fraction_of_prize) in PRIZE_DISTRIBUTION.iter().enumerate() {
-        // Add the proportional amount of the net redeemed amount to the relevant award bucket.
-        prize_buckets[index] += Uint256::from(sponsor_amount) * *fraction_of_prize
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: ANCHOR.to_string(),
+            funds: vec![Coin {
+                denom: DENOM.to_string(),
+                amount: Uint256::from(2 * TICKET_PRICE).into(),
+            }],
+            msg: to_binary(&AnchorMsg::DepositStable {}).unwrap(),
+        }))]
+    );
+
+    // The same code hash can't be used twice
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::GiftCodeAlreadyExists {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.prize_buckets, prize_buckets);
+    let minted_shares = Uint256::from(2 * TICKET_PRICE) / Decimal256::permille(RATE);
 
-    // Check that the sponsor doesn't exist in the db
+    // The shares are escrowed in the pool
+    assert_eq!(
+        query_pool(deps.as_ref()).unwrap().total_user_shares,
+        minted_shares
+    );
 
-    let sponsor_info = read_sponsor_info(
-        deps.as_ref().storage,
-        &deps.api.addr_validate("addr0001").unwrap(),
+    let res: GiftCodeResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GiftCode {
+                code_hash: code_hash.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        GiftCodeResponse {
+            code_hash: code_hash.clone(),
+            creator: "addr0000".to_string(),
+            shares: minted_shares,
+            value: minted_shares * Decimal256::permille(RATE),
+            encoded_tickets,
+            expires_at: Timestamp::from_seconds(expires_at),
+        }
     );
 
-    assert_eq!(sponsor_info.lottery_deposit, Uint256::zero());
+    // Wrong preimage
+    let msg = ExecuteMsg::RedeemGiftCode {
+        preimage: "happy new year".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr1111", &[]), msg);
+    match res {
+        Err(ContractError::GiftCodeNotFound {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // Check that the pool sponsor deposits are zero
+    // The redemption must be committed first
+    let msg = ExecuteMsg::RedeemGiftCode {
+        preimage: preimage.to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr1111", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::GiftCodeNotCommitted {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let pool = query_pool(deps.as_ref()).unwrap();
-    assert_eq!(pool.total_sponsor_lottery_deposits, Uint256::zero());
+    let commitment = hex::encode(hash_gift_code_commitment(
+        preimage,
+        &Addr::unchecked("addr1111"),
+    ));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr1111", &[]),
+        ExecuteMsg::CommitGiftCodeRedemption { commitment },
+    )
+    .unwrap();
 
-    // Test sponsoring with a custom prize distribution
+    // In an earlier block
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr1111", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::GiftCodeNotCommitted {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let custom_prize_distribution = [
-        Decimal256::zero(),
-        Decimal256::percent(5),
-        Decimal256::percent(5),
-        Decimal256::percent(15),
-        Decimal256::percent(25),
-        Decimal256::percent(30),
-        Decimal256::percent(20),
-    ];
-    let msg = ExecuteMsg::Sponsor {
-        award: Some(true),
-        prize_distribution: Some(custom_prize_distribution),
-    };
-
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
-    println!("{:?}", _res);
-
-    // Check that the prize buckets were updated
-
-    // Distribute the sponsorship to the prize buckets according to the prize distribution
-    for (index, fraction_of_prize) in custom_prize_distribution.iter().enumerate() {
-        // Add the proportional amount of the net redeemed amount to the relevant award bucket.
-        prize_buckets[index] += Uint256::from(sponsor_amount) * *fraction_of_prize
+    // Someone else can't redeem with the revealed preimage
+    let mut env = mock_env();
+    env.block.height += 1;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr2222", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::GiftCodeNotCommitted {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.prize_buckets, prize_buckets);
-
-    // Check that the sponsor doesn't exist in the db
+    // Correct redemption
+    execute(deps.as_mut(), env, mock_info("addr1111", &[]), msg.clone()).unwrap();
 
-    let sponsor_info = read_sponsor_info(
-        deps.as_ref().storage,
-        &deps.api.addr_validate("addr0001").unwrap(),
+    assert_eq!(
+        read_depositor_info(
+            deps.as_ref().storage,
+            &deps.api.addr_validate("addr1111").unwrap()
+        ),
+        DepositorInfo {
+            shares: minted_shares,
+            tickets: vec![
+                String::from(ZERO_MATCH_SEQUENCE),
+                String::from(ONE_MATCH_SEQUENCE)
+            ],
+            unbonding_info: vec![],
+            operator_addr: Addr::unchecked("")
+        }
+    );
+    assert_eq!(
+        query_state(deps.as_ref(), mock_env(), None)
+            .unwrap()
+            .total_tickets,
+        Uint256::from(2u64)
+    );
+    assert_eq!(
+        query_pool(deps.as_ref()).unwrap().total_user_shares,
+        minted_shares
     );
 
-    assert_eq!(sponsor_info.lottery_deposit, Uint256::zero());
+    // A gift code can only be redeemed once
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr2222", &[]), msg);
+    match res {
+        Err(ContractError::GiftCodeNotFound {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // Check that the pool sponsor deposits are zero
+    // Refund path for a gift code that is never redeemed
+    let code_hash = hex::encode(hash_gift_code_preimage("never redeemed"));
+    let msg = ExecuteMsg::CreateGiftCode {
+        code_hash: code_hash.clone(),
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            ZERO_MATCH_SEQUENCE_2,
+        )]),
+        expires_at,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let pool = query_pool(deps.as_ref()).unwrap();
-    assert_eq!(pool.total_sponsor_lottery_deposits, Uint256::zero());
+    let msg = ExecuteMsg::RefundGiftCode {
+        code_hash: code_hash.clone(),
+    };
 
-    // Test sponsoring with a prize distribution that doesn't sum to 1
+    // Can't refund before expiry
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::GiftCodeNotExpired {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let custom_prize_distribution = [
-        Decimal256::zero(),
-        Decimal256::percent(10),
-        Decimal256::percent(10),
-        Decimal256::percent(15),
-        Decimal256::percent(25),
-        Decimal256::percent(30),
-        Decimal256::percent(20),
-    ];
-    let msg = ExecuteMsg::Sponsor {
-        award: Some(true),
-        prize_distribution: Some(custom_prize_distribution),
-    };
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(expires_at);
+
+    // Can't redeem after expiry
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr1111", &[]),
+        ExecuteMsg::RedeemGiftCode {
+            preimage: "never redeemed".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::GiftCodeExpired {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    // Only the creator can get a refund
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr1111", &[]),
+        msg.clone(),
+    );
     match res {
-        Err(ContractError::InvalidPrizeDistribution {}) => {}
+        Err(ContractError::Unauthorized {}) => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
+
+    execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg).unwrap();
+
+    let creator_info = read_depositor_info(
+        deps.as_ref().storage,
+        &deps.api.addr_validate("addr0000").unwrap(),
+    );
+    assert_eq!(
+        creator_info.shares,
+        Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE)
+    );
+    assert_eq!(creator_info.tickets.len(), 0);
 }
 
 #[test]
-fn withdraw() {
+fn depositor_history() {
     // Initialize contract
-    let mut deps = mock_dependencies(&[Coin {
-        denom: DENOM.to_string(),
-        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-    }]);
+    let mut deps = mock_dependencies(&[]);
 
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    let deposit_amount = Uint256::from(TICKET_PRICE).into();
+    let query_history = |deps: &OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                         address: &str,
+                         start_after: Option<u64>,
+                         limit: Option<u32>|
+     -> DepositorHistoryResponse {
+        from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::DepositorHistory {
+                    address: address.to_string(),
+                    start_after,
+                    limit,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
 
-    // Address buys one ticket
+    // Deposit two tickets
     let info = mock_info(
-        "addr0001",
+        "addr0000",
         &[Coin {
-            denom: "uusd".to_string(),
-            amount: deposit_amount,
+            denom: DENOM.to_string(),
+            amount: Uint256::from(2 * TICKET_PRICE).into(),
         }],
     );
-
     let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
+            String::from(ZERO_MATCH_SEQUENCE),
+            String::from(ONE_MATCH_SEQUENCE),
+        ]),
+        operator: None,
+        syndicate: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Gift one ticket
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+    let msg = ExecuteMsg::Gift {
         encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            ONE_MATCH_SEQUENCE,
+            TWO_MATCH_SEQUENCE,
         )]),
+        recipient: "addr0001".to_string(),
         operator: None,
+        unlock_at: None,
     };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
-
-    let info = mock_info("addr0001", &[]);
-
+    // Withdraw everything instantly
+    let info = mock_info("addr0000", &[]);
     let msg = ExecuteMsg::Withdraw {
         amount: None,
-        instant: None,
+        instant: Some(true),
     };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: deposit_amount,
-        }],
-    );
+    let deposited_shares = Uint256::from(2 * TICKET_PRICE) / Decimal256::permille(RATE);
+    let gifted_shares = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
 
-    deps.querier.with_token_balances(&[(
-        &A_UST.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &minted_aust.into())],
-    )]);
+    let withdrawn_value = deposited_shares * Decimal256::permille(RATE);
+    let withdrawal_fee = withdrawn_value * Decimal256::percent(INSTANT_WITHDRAWAL_FEE);
 
-    // Correct withdraw, user has 1 ticket to be withdrawn
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let block_height = mock_env().block.height;
 
-    // Get the sent_amount
-    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
-        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
-        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
-            amount
-        } else {
-            panic!("DO NOT ENTER HERE")
+    assert_eq!(
+        query_history(&deps, "addr0000", None, None),
+        DepositorHistoryResponse {
+            depositor: "addr0000".to_string(),
+            history: vec![
+                DepositorActivityResponse {
+                    id: 0,
+                    kind: DepositorActivityKind::Deposit,
+                    amount: Uint256::from(2 * TICKET_PRICE),
+                    shares: deposited_shares,
+                    tickets: 2,
+                    block_height,
+                    lottery_id: None,
+                },
+                DepositorActivityResponse {
+                    id: 1,
+                    kind: DepositorActivityKind::GiftSent,
+                    amount: Uint256::from(TICKET_PRICE),
+                    shares: gifted_shares,
+                    tickets: 1,
+                    block_height,
+                    lottery_id: None,
+                },
+                DepositorActivityResponse {
+                    id: 2,
+                    kind: DepositorActivityKind::InstantWithdraw,
+                    amount: withdrawn_value - withdrawal_fee,
+                    shares: deposited_shares,
+                    tickets: 2,
+                    block_height,
+                    lottery_id: None,
+                },
+            ],
         }
-    } else {
-        panic!("DO NOT ENTER HERE");
+    );
+
+    // The recipient of the gift has its own history
+    let history = query_history(&deps, "addr0001", None, None).history;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].kind, DepositorActivityKind::GiftReceived);
+    assert_eq!(history[0].shares, gifted_shares);
+
+    // Pagination
+    let history = query_history(&deps, "addr0000", Some(0), Some(1)).history;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].id, 1);
+
+    // Lower the retention cap
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_addr: None,
+        reserve_factor: None,
+        instant_withdrawal_fee: None,
+        unbonding_period: None,
+        epoch_interval: None,
+        max_holders: None,
+        max_tickets_per_depositor: None,
+        paused: None,
+        lotto_winner_boost_config: None,
+        operator_glow_emission_rate: None,
+        sponsor_glow_emission_rate: None,
+        max_history_entries: Some(2),
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
 
-    println!("Shares vs sent_amount: {}, {}", minted_aust, sent_amount);
+    // The retention cap is bounded
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            owner: None,
+            oracle_addr: None,
+            reserve_factor: None,
+            instant_withdrawal_fee: None,
+            unbonding_period: None,
+            epoch_interval: None,
+            max_holders: None,
+            max_tickets_per_depositor: None,
+            paused: None,
+            lotto_winner_boost_config: None,
+            operator_glow_emission_rate: None,
+            sponsor_glow_emission_rate: None,
+            max_history_entries: Some(MAX_HISTORY_ENTRIES_CAP + 1),
+            instant_withdrawal_fee_decay: None,
+            max_operator_commission: None,
+        },
+    );
+    match res {
+        Err(ContractError::InvalidMaxHistoryEntries {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let empty_addr: Vec<Addr> = vec![];
-    // Check address of sender was removed correctly in the sequence bucket
-    assert_eq!(
-        query_ticket_info(deps.as_ref(), String::from("234567"))
-            .unwrap()
-            .holders,
-        empty_addr
+    // Deposit again, the oldest entries are pruned
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
     );
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            ZERO_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // Check depositor info was updated correctly
+    let history = query_history(&deps, "addr0000", None, None).history;
     assert_eq!(
-        read_depositor_info(
-            deps.as_ref().storage,
-            &deps.api.addr_validate("addr0001").unwrap()
-        ),
-        DepositorInfo {
-            shares: Uint256::zero(),
-            tickets: vec![],
-            unbonding_info: vec![Claim {
-                amount: Uint256::from(sent_amount) * Decimal256::permille(RATE),
-                release_at: WEEK.after(&mock_env().block),
-            }],
-            operator_addr: Addr::unchecked("")
-        }
+        history.iter().map(|entry| entry.id).collect::<Vec<u64>>(),
+        vec![2, 3]
     );
+    assert_eq!(history[1].kind, DepositorActivityKind::Deposit);
+}
 
-    assert_eq!(
-        query_state(deps.as_ref(), mock_env(), None).unwrap(),
-        StateResponse {
-            total_tickets: Uint256::zero(),
-            total_reserve: Uint256::zero(),
-            prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
-            current_lottery: 0,
-            next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
-            next_lottery_exec_time: Expiration::Never {},
-            next_epoch: HOUR.mul(3).after(&mock_env().block),
-            operator_reward_emission_index: RewardEmissionsIndex {
-                last_reward_updated: 12345,
-                global_reward_index: Decimal256::zero(),
-                glow_emission_rate: Decimal256::zero(),
-            },
-            sponsor_reward_emission_index: RewardEmissionsIndex {
-                last_reward_updated: 12345,
-                global_reward_index: Decimal256::zero(),
-                glow_emission_rate: Decimal256::zero(),
-            },
-            last_lottery_execution_aust_exchange_rate: Decimal256::permille(RATE)
-        }
-    );
+#[test]
+fn sponsor() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
 
-    assert_eq!(
-        query_pool(deps.as_ref()).unwrap(),
-        PoolResponse {
-            total_user_shares: Uint256::zero(),
-            total_user_aust: Uint256::zero(),
-            total_sponsor_lottery_deposits: Uint256::zero(),
-            total_operator_shares: Uint256::zero(),
-        }
-    );
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: A_UST.to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Send {
-                contract: ANCHOR.to_string(),
-                amount: sent_amount,
-                msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
-            })
-            .unwrap(),
-        }))]
-    );
+    let sponsor_amount = 100_000_000u128;
 
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "withdraw_ticket"),
-            attr("depositor", "addr0001"),
-            attr("tickets_amount", 1u64.to_string()),
-            attr("redeem_amount_anchor", sent_amount.to_string()),
-            attr(
-                "redeem_stable_amount",
-                (Uint256::from(sent_amount) * Decimal256::permille(RATE)).to_string()
-            ),
-            attr("instant_withdrawal_fee", Uint256::zero().to_string())
-        ]
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1_000_000u128))],
     );
 
-    // Deposit one ticket 10 times
-    for index in 0..10 {
-        let msg = ExecuteMsg::Deposit {
-            encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![format!(
-                "{:0length$}",
-                index,
-                length = TICKET_LENGTH
-            )]),
-            operator: None,
-        };
-        let info = mock_info(
-            "addr2222",
-            &[Coin {
-                denom: "uusd".to_string(),
-                amount: Uint256::from(TICKET_PRICE).into(),
-            }],
-        );
-
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    }
-
-    let dep = read_depositor_info(
-        deps.as_ref().storage,
-        &deps.api.addr_validate("addr2222").unwrap(),
+    // Address sponsor
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(sponsor_amount),
+        }],
     );
 
-    println!("depositor: {:?}", dep);
-    let minted_aust = Uint256::from(10 * TICKET_PRICE) / Decimal256::permille(RATE);
-
-    let info = mock_info("addr2222", &[]);
-
-    // Withdraws half of its tickets
-    let msg = ExecuteMsg::Withdraw {
-        // Withdraw amount - 1 to avoid rounding issues
-        amount: Some(Uint256::from(5 * TICKET_PRICE - 1).into()),
-        instant: None,
+    let msg = ExecuteMsg::Sponsor {
+        award: None,
+        prize_distribution: None,
     };
 
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: deposit_amount,
-        }],
+    let _res = execute(deps.as_mut(), mock_env(), info, msg);
+    println!("{:?}", _res);
+
+    let net_amount = Uint256::from(
+        deduct_tax(
+            deps.as_ref(),
+            Coin {
+                denom: String::from("uusd"),
+                amount: Uint128::from(sponsor_amount),
+            },
+        )
+        .unwrap()
+        .amount,
     );
 
+    let minted_aust = net_amount / Decimal256::permille(RATE);
+
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
         &[(&MOCK_CONTRACT_ADDR.to_string(), &minted_aust.into())],
     )]);
 
-    // Correct withdraw, user withdraws 5 tickets
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::SponsorWithdraw {};
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let dep = read_depositor_info(
+    let sponsor_info = read_sponsor_info(
         deps.as_ref().storage,
-        &deps.api.addr_validate("addr2222").unwrap(),
+        &deps.api.addr_validate("addr0001").unwrap(),
     );
 
-    println!("depositor: {:?}", dep);
+    let pool = query_pool(deps.as_ref()).unwrap();
 
-    // Check depositor info was updated correctly
-    assert_eq!(
-        read_depositor_info(
-            deps.as_ref().storage,
-            &deps.api.addr_validate("addr2222").unwrap()
-        )
-        .tickets,
-        vec![
-            format!("{:0length$}", 5, length = TICKET_LENGTH),
-            format!("{:0length$}", 6, length = TICKET_LENGTH),
-            format!("{:0length$}", 7, length = TICKET_LENGTH),
-            format!("{:0length$}", 8, length = TICKET_LENGTH),
-            format!("{:0length$}", 9, length = TICKET_LENGTH)
-        ]
-    );
+    assert_eq!(sponsor_info.lottery_deposit, Uint256::zero());
+    assert_eq!(pool.total_sponsor_lottery_deposits, Uint256::zero());
+}
 
-    assert_eq!(
-        query_state(deps.as_ref(), mock_env(), None)
-            .unwrap()
-            .total_tickets,
-        Uint256::from(5u64)
-    );
+#[test]
+fn locked_gift_limits() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
 
-    // Check ticket map is updated correctly
-    assert_eq!(
-        query_ticket_info(
-            deps.as_ref(),
-            format!("{:0length$}", 2, length = TICKET_LENGTH)
-        )
-        .unwrap()
-        .holders,
-        empty_addr
-    );
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
-    assert_eq!(
-        query_ticket_info(
-            deps.as_ref(),
-            format!("{:0length$}", 6, length = TICKET_LENGTH)
-        )
-        .unwrap()
-        .holders,
-        vec![Addr::unchecked("addr2222")]
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
     );
 
-    // Withdraws a very small amount, burns a ticket as rounding
-    let msg = ExecuteMsg::Withdraw {
-        amount: Some(Uint128::from(1u128)),
-        instant: None,
+    let combinations = generate_sequential_ticket_combinations(MAX_SHARE_LOCKS as u64 + 3);
+    let gift_msg = |combination: &String, unlock_at: u64| ExecuteMsg::Gift {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![combination.clone()]),
+        recipient: "addr1111".to_string(),
+        operator: None,
+        unlock_at: Some(unlock_at),
     };
 
-    // Correct withdraw, one ticket gets withdrawn
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    // Unlock time can't be past the maximum lock duration
+    for unlock_at in [
+        mock_env().block.time.seconds() + MAX_SHARE_LOCK_DURATION + 1,
+        u64::MAX,
+    ] {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            gift_msg(&combinations[0], unlock_at),
+        );
+        match res {
+            Err(ContractError::InvalidUnlockTime {}) => {}
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
 
-    // Check depositor info was updated correctly
-    assert_eq!(
-        read_depositor_info(
-            deps.as_ref().storage,
-            &deps.api.addr_validate("addr2222").unwrap()
+    // Gifts unlocking at the same time share a lock
+    let unlock_at = mock_env().block.time.seconds() + WEEK_TIME;
+    for combination in combinations.iter().take(2) {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            gift_msg(combination, unlock_at),
         )
-        .tickets,
-        vec![
-            format!("{:0length$}", 6, length = TICKET_LENGTH),
-            format!("{:0length$}", 7, length = TICKET_LENGTH),
-            format!("{:0length$}", 8, length = TICKET_LENGTH),
-            format!("{:0length$}", 9, length = TICKET_LENGTH)
-        ]
-    );
+        .unwrap();
+    }
 
+    let minted_shares = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+    let res: DepositorLocksResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DepositorLocks {
+                address: "addr1111".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
     assert_eq!(
-        query_state(deps.as_ref(), mock_env(), None)
-            .unwrap()
-            .total_tickets,
-        Uint256::from(4u64)
+        res.locks,
+        vec![ShareLock {
+            shares: minted_shares + minted_shares,
+            unlock_at: Timestamp::from_seconds(unlock_at),
+        }]
+    );
+
+    // The number of locks of a depositor is capped
+    for (i, combination) in combinations
+        .iter()
+        .enumerate()
+        .skip(2)
+        .take(MAX_SHARE_LOCKS - 1)
+    {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            gift_msg(combination, unlock_at + i as u64),
+        )
+        .unwrap();
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        gift_msg(&combinations[MAX_SHARE_LOCKS + 1], unlock_at + WEEK_TIME),
     );
-    // Check ticket map is updated correctly
-    assert_eq!(
-        query_ticket_info(
+    match res {
+        Err(ContractError::TooManyShareLocks {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Unless a lock unlocks later, which the gift then joins
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        gift_msg(&combinations[MAX_SHARE_LOCKS + 2], unlock_at + 1),
+    )
+    .unwrap();
+
+    let res: DepositorLocksResponse = from_binary(
+        &query(
             deps.as_ref(),
-            format!("{:0length$}", 5, length = TICKET_LENGTH)
+            mock_env(),
+            QueryMsg::DepositorLocks {
+                address: "addr1111".to_string(),
+            },
         )
-        .unwrap()
-        .holders,
-        empty_addr
-    );
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.locks.len(), MAX_SHARE_LOCKS);
+    assert!(res.locks.contains(&ShareLock {
+        shares: minted_shares + minted_shares,
+        unlock_at: Timestamp::from_seconds(unlock_at + 2),
+    }));
+
+    // Expired locks are pruned and make room for new ones
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(unlock_at);
+    execute(
+        deps.as_mut(),
+        env,
+        info,
+        gift_msg(&combinations[MAX_SHARE_LOCKS + 1], unlock_at + WEEK_TIME),
+    )
+    .unwrap();
 }
 
 #[test]
-fn instant_withdraw() {
+fn instant_sponsor() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    let deposit_amount = Uint256::from(TICKET_PRICE).into();
+    let sponsor_amount = 100_000_000u128;
 
-    // Address buys one ticket
-    let info = mock_info(
-        "addr0001",
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1_000_000u128))],
+    );
+
+    // Address sponsor
+    let info = mock_info(
+        "addr0001",
         &[Coin {
-            denom: DENOM.to_string(),
+            denom: "uusd".to_string(),
+            amount: Uint128::from(sponsor_amount),
+        }],
+    );
+
+    // Test sponsoring with the default prize distribution
+
+    let msg = ExecuteMsg::Sponsor {
+        award: Some(true),
+        prize_distribution: None,
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    println!("{:?}", _res);
+
+    // Check that the prize buckets were updated
+
+    let mut prize_buckets = [Uint256::zero(); NUM_PRIZE_BUCKETS];
+
+    // Distribute the sponsorship to the prize buckets according to the prize distribution
+    for (index, fraction_of_prize) in PRIZE_DISTRIBUTION.iter().enumerate() {
+        // Add the proportional amount of the net redeemed amount to the relevant award bucket.
+        prize_buckets[index] += Uint256::from(sponsor_amount) * *fraction_of_prize
+    }
+
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.prize_buckets, prize_buckets);
+
+    // Check that the sponsor doesn't exist in the db
+
+    let sponsor_info = read_sponsor_info(
+        deps.as_ref().storage,
+        &deps.api.addr_validate("addr0001").unwrap(),
+    );
+
+    assert_eq!(sponsor_info.lottery_deposit, Uint256::zero());
+
+    // Check that the pool sponsor deposits are zero
+
+    let pool = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(pool.total_sponsor_lottery_deposits, Uint256::zero());
+
+    // Test sponsoring with a custom prize distribution
+
+    let custom_prize_distribution = [
+        Decimal256::zero(),
+        Decimal256::percent(5),
+        Decimal256::percent(5),
+        Decimal256::percent(15),
+        Decimal256::percent(25),
+        Decimal256::percent(30),
+        Decimal256::percent(20),
+    ];
+    let msg = ExecuteMsg::Sponsor {
+        award: Some(true),
+        prize_distribution: Some(custom_prize_distribution),
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    println!("{:?}", _res);
+
+    // Check that the prize buckets were updated
+
+    // Distribute the sponsorship to the prize buckets according to the prize distribution
+    for (index, fraction_of_prize) in custom_prize_distribution.iter().enumerate() {
+        // Add the proportional amount of the net redeemed amount to the relevant award bucket.
+        prize_buckets[index] += Uint256::from(sponsor_amount) * *fraction_of_prize
+    }
+
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.prize_buckets, prize_buckets);
+
+    // Check that the sponsor doesn't exist in the db
+
+    let sponsor_info = read_sponsor_info(
+        deps.as_ref().storage,
+        &deps.api.addr_validate("addr0001").unwrap(),
+    );
+
+    assert_eq!(sponsor_info.lottery_deposit, Uint256::zero());
+
+    // Check that the pool sponsor deposits are zero
+
+    let pool = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(pool.total_sponsor_lottery_deposits, Uint256::zero());
+
+    // Test sponsoring with a prize distribution that doesn't sum to 1
+
+    let custom_prize_distribution = [
+        Decimal256::zero(),
+        Decimal256::percent(10),
+        Decimal256::percent(10),
+        Decimal256::percent(15),
+        Decimal256::percent(25),
+        Decimal256::percent(30),
+        Decimal256::percent(20),
+    ];
+    let msg = ExecuteMsg::Sponsor {
+        award: Some(true),
+        prize_distribution: Some(custom_prize_distribution),
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidPrizeDistribution {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn withdraw() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[Coin {
+        denom: DENOM.to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
+
+    let deposit_amount = Uint256::from(TICKET_PRICE).into();
+
+    // Address buys one ticket
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
             amount: deposit_amount,
         }],
     );
 
     let msg = ExecuteMsg::Deposit {
         encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            ZERO_MATCH_SEQUENCE,
+            ONE_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+
     let info = mock_info("addr0001", &[]);
 
     let msg = ExecuteMsg::Withdraw {
         amount: None,
-        instant: Some(true),
+        instant: None,
     };
 
     deps.querier.update_balance(
@@ -2045,26 +2687,29 @@ fn instant_withdraw() {
         }],
     );
 
-    // Get the number of minted aust
-    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
-
-    // Shares equals aust in this case
-    let aust_to_redeem = minted_aust;
-    let mut return_amount = aust_to_redeem * Decimal256::permille(RATE);
-
-    let withdrawal_fee = return_amount * Decimal256::percent(INSTANT_WITHDRAWAL_FEE);
-    return_amount = return_amount.sub(withdrawal_fee);
-
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
         &[(&MOCK_CONTRACT_ADDR.to_string(), &minted_aust.into())],
     )]);
 
     // Correct withdraw, user has 1 ticket to be withdrawn
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let empty_addr: Vec<Addr> = vec![];
+    // Get the sent_amount
+    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+            amount
+        } else {
+            panic!("DO NOT ENTER HERE")
+        }
+    } else {
+        panic!("DO NOT ENTER HERE");
+    };
+
+    println!("Shares vs sent_amount: {}, {}", minted_aust, sent_amount);
 
+    let empty_addr: Vec<Addr> = vec![];
     // Check address of sender was removed correctly in the sequence bucket
     assert_eq!(
         query_ticket_info(deps.as_ref(), String::from("234567"))
@@ -2075,7 +2720,10 @@ fn instant_withdraw() {
 
     // Check depositor info was updated correctly
     assert_eq!(
-        read_depositor_info(&deps.storage, &deps.api.addr_validate("addr0001").unwrap()),
+        read_depositor_info(
+            deps.as_ref().storage,
+            &deps.api.addr_validate("addr0001").unwrap()
+        ),
         DepositorInfo {
             shares: Uint256::zero(),
             tickets: vec![],
@@ -2084,11 +2732,33 @@ fn instant_withdraw() {
         }
     );
 
+    // Check the withdrawal was placed in the unbonding ledger
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    let (release_epoch, release_at) = calculate_release_epoch(&config, &mock_env().block).unwrap();
+    assert_eq!(
+        read_unbonding_claims(
+            deps.as_ref().storage,
+            &deps.api.addr_validate("addr0001").unwrap(),
+            None,
+            None
+        )
+        .unwrap(),
+        vec![(
+            release_epoch,
+            Claim {
+                amount: Uint256::from(sent_amount) * Decimal256::permille(RATE),
+                release_at,
+            }
+        )]
+    );
+
     assert_eq!(
         query_state(deps.as_ref(), mock_env(), None).unwrap(),
         StateResponse {
             total_tickets: Uint256::zero(),
-            total_reserve: withdrawal_fee,
+            total_reserve: Uint256::zero(),
+            withdrawal_fee_reserve: Uint256::zero(),
+            prize_reserve: Uint256::zero(),
             prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             current_lottery: 0,
             next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
@@ -2120,25 +2790,16 @@ fn instant_withdraw() {
 
     assert_eq!(
         res.messages,
-        vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: A_UST.to_string(),
-                funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Send {
-                    contract: ANCHOR.to_string(),
-                    amount: aust_to_redeem.into(),
-                    msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
-                })
-                .unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uusd".to_string(),
-                    amount: return_amount.into()
-                }],
-            }))
-        ]
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: A_UST.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: ANCHOR.to_string(),
+                amount: sent_amount,
+                msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
+            })
+            .unwrap(),
+        }))]
     );
 
     assert_eq!(
@@ -2147,411 +2808,301 @@ fn instant_withdraw() {
             attr("action", "withdraw_ticket"),
             attr("depositor", "addr0001"),
             attr("tickets_amount", 1u64.to_string()),
-            attr("redeem_amount_anchor", aust_to_redeem.to_string()),
-            attr("redeem_stable_amount", return_amount.to_string()),
-            attr("instant_withdrawal_fee", withdrawal_fee.to_string())
+            attr("redeem_amount_anchor", sent_amount.to_string()),
+            attr(
+                "redeem_stable_amount",
+                (Uint256::from(sent_amount) * Decimal256::permille(RATE)).to_string()
+            ),
+            attr("instant_withdrawal_fee", Uint256::zero().to_string())
         ]
-    )
-}
+    );
 
-#[test]
-fn claim() {
-    // Initialize contract
-    let mut deps = mock_dependencies(&[]);
+    // Deposit one ticket 10 times
+    for index in 0..10 {
+        let msg = ExecuteMsg::Deposit {
+            encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![format!(
+                "{:0length$}",
+                index,
+                length = TICKET_LENGTH
+            )]),
+            operator: None,
+            syndicate: None,
+        };
+        let info = mock_info(
+            "addr2222",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint256::from(TICKET_PRICE).into(),
+            }],
+        );
 
-    mock_instantiate(&mut deps);
-    mock_register_contracts(deps.as_mut());
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
 
-    // Address buys one ticket
-    let info = mock_info(
-        "addr0001",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
+    let dep = read_depositor_info(
+        deps.as_ref().storage,
+        &deps.api.addr_validate("addr2222").unwrap(),
     );
 
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            ZERO_MATCH_SEQUENCE,
-        )]),
-        operator: None,
-    };
+    println!("depositor: {:?}", dep);
+    let minted_aust = Uint256::from(10 * TICKET_PRICE) / Decimal256::permille(RATE);
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("addr2222", &[]);
 
-    // Address withdraws one ticket
-    let info = mock_info("addr0001", &[]);
+    // Withdraws half of its tickets
     let msg = ExecuteMsg::Withdraw {
-        amount: None,
+        // Withdraw amount - 1 to avoid rounding issues
+        amount: Some(Uint256::from(5 * TICKET_PRICE - 1).into()),
         instant: None,
     };
 
-    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: deposit_amount,
+        }],
+    );
 
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
         &[(&MOCK_CONTRACT_ADDR.to_string(), &minted_aust.into())],
     )]);
 
-    // Get the number of minted aust
-    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
-
-    // Calculate the depositor's aust balance
-    let depositor_aust_balance = minted_aust;
-
-    // Calculate the depositor's balance from their aust balance
-    let depositor_balance = depositor_aust_balance * Decimal256::permille(RATE);
-
-    let redeemed_amount = depositor_balance;
-
-    // Correct withdraw, user has 1 ticket to be withdrawn
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    // Get the amount to redeem and it's corresponding value
-    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
-        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
-        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
-            amount
-        } else {
-            panic!("DO NOT ENTER HERE")
-        }
-    } else {
-        panic!("DO NOT ENTER HERE");
-    };
-
-    // Claim amount that you don't have, should fail
-    let info = mock_info("addr0002", &[]);
-    let msg = ExecuteMsg::Claim {};
-
-    let res = execute(deps.as_mut(), mock_env(), info, msg);
-    match res {
-        Err(ContractError::InsufficientClaimableFunds {}) => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    // Claim amount that you have, but still in unbonding state, should fail
-    let info = mock_info("addr0001", &[]);
-    let msg = ExecuteMsg::Claim {};
-
-    let mut env = mock_env();
-
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    match res {
-        Err(ContractError::InsufficientClaimableFunds {}) => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    // Correct withdraw, user withdraws 5 tickets
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
-    println!("Block time 1: {}", env.block.time);
+    let dep = read_depositor_info(
+        deps.as_ref().storage,
+        &deps.api.addr_validate("addr2222").unwrap(),
+    );
 
-    // Advance one week in time
-    if let Duration::Time(time) = WEEK {
-        env.block.time = env.block.time.plus_seconds(time * 2);
-    }
-    println!("Block time 2: {}", env.block.time);
+    println!("depositor: {:?}", dep);
 
-    // Read the depositor info
-    let dep = read_depositor_info(&deps.storage, &deps.api.addr_validate("addr0001").unwrap());
+    // Check depositor info was updated correctly
+    assert_eq!(
+        read_depositor_info(
+            deps.as_ref().storage,
+            &deps.api.addr_validate("addr2222").unwrap()
+        )
+        .tickets,
+        vec![
+            format!("{:0length$}", 5, length = TICKET_LENGTH),
+            format!("{:0length$}", 6, length = TICKET_LENGTH),
+            format!("{:0length$}", 7, length = TICKET_LENGTH),
+            format!("{:0length$}", 8, length = TICKET_LENGTH),
+            format!("{:0length$}", 9, length = TICKET_LENGTH)
+        ]
+    );
 
-    // Fail because not enough funds in the contract
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    match res {
-        Err(ContractError::InsufficientFunds {
-            to_send,
-            available_balance,
-        }) => {
-            if available_balance == Uint256::zero() && Uint256::from(to_send) == redeemed_amount {}
-        }
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    assert_eq!(
+        query_state(deps.as_ref(), mock_env(), None)
+            .unwrap()
+            .total_tickets,
+        Uint256::from(5u64)
+    );
 
-    // Revert the state of the depositor
-    store_depositor_info(
-        &mut deps.storage,
-        &deps.api.addr_validate("addr0001").unwrap(),
-        dep,
-        env.block.height,
-    )
-    .unwrap();
+    // Check ticket map is updated correctly
+    assert_eq!(
+        query_ticket_info(
+            deps.as_ref(),
+            format!("{:0length$}", 2, length = TICKET_LENGTH)
+        )
+        .unwrap()
+        .holders,
+        empty_addr
+    );
 
-    // Update the contract balance to include the withdrawn funds
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR,
-        vec![Coin {
-            denom: DENOM.to_string(),
-            amount: Uint128::from(Uint256::from(sent_amount) * Decimal256::permille(RATE)),
-        }],
+    assert_eq!(
+        query_ticket_info(
+            deps.as_ref(),
+            format!("{:0length$}", 6, length = TICKET_LENGTH)
+        )
+        .unwrap()
+        .holders,
+        vec![Addr::unchecked("addr2222")]
     );
 
-    // Claim amount is already unbonded, so claim execution should work
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    // Withdraws a very small amount, burns a ticket as rounding
+    let msg = ExecuteMsg::Withdraw {
+        amount: Some(Uint128::from(1u128)),
+        instant: None,
+    };
+
+    // Correct withdraw, one ticket gets withdrawn
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // Check depositor info was updated correctly
     assert_eq!(
-        read_depositor_info(&deps.storage, &deps.api.addr_validate("addr0001").unwrap()),
-        DepositorInfo {
-            shares: Uint256::zero(),
-            tickets: vec![],
-            unbonding_info: vec![],
-            operator_addr: Addr::unchecked("")
-        }
+        read_depositor_info(
+            deps.as_ref().storage,
+            &deps.api.addr_validate("addr2222").unwrap()
+        )
+        .tickets,
+        vec![
+            format!("{:0length$}", 6, length = TICKET_LENGTH),
+            format!("{:0length$}", 7, length = TICKET_LENGTH),
+            format!("{:0length$}", 8, length = TICKET_LENGTH),
+            format!("{:0length$}", 9, length = TICKET_LENGTH)
+        ]
     );
 
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0001".to_string(),
-            amount: vec![Coin {
-                denom: String::from("uusd"),
-                amount: redeemed_amount.into()
-            }],
-        }))]
+        query_state(deps.as_ref(), mock_env(), None)
+            .unwrap()
+            .total_tickets,
+        Uint256::from(4u64)
     );
-
+    // Check ticket map is updated correctly
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "claim_unbonded"),
-            attr("depositor", "addr0001"),
-            attr("redeemed_amount", redeemed_amount.to_string()),
-        ]
+        query_ticket_info(
+            deps.as_ref(),
+            format!("{:0length$}", 5, length = TICKET_LENGTH)
+        )
+        .unwrap()
+        .holders,
+        empty_addr
     );
 }
 
 #[test]
-fn claim_lottery_single_winner() {
+fn instant_withdraw() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    // Users buys winning ticket
+    let deposit_amount = Uint256::from(TICKET_PRICE).into();
+
+    // Address buys one ticket
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: deposit_amount,
+        }],
+    );
+
     let msg = ExecuteMsg::Deposit {
         encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            SIX_MATCH_SEQUENCE,
+            ZERO_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
-    );
 
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let address_raw = deps.api.addr_validate("addr0000").unwrap();
+    let info = mock_info("addr0001", &[]);
+
+    let msg = ExecuteMsg::Withdraw {
+        amount: None,
+        instant: Some(true),
+    };
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: deposit_amount,
+        }],
+    );
 
     // Get the number of minted aust
     let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
 
-    // Get the amount of minted_shares
-    let minted_shares = minted_aust;
+    // Shares equals aust in this case
+    let aust_to_redeem = minted_aust;
+    let mut return_amount = aust_to_redeem * Decimal256::permille(RATE);
 
-    // Check depositor info was updated correctly
-    assert_eq!(
-        read_depositor_info(deps.as_ref().storage, &address_raw),
-        DepositorInfo {
-            shares: minted_shares,
-            tickets: vec![String::from(SIX_MATCH_SEQUENCE)],
-            unbonding_info: vec![],
-            operator_addr: Addr::unchecked("")
-        }
-    );
-
-    // Run lottery, one winner (5 hits) - should run correctly
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-
-    //Advance time one week
-    let mut env = mock_env();
-    // Advance one week in time
-    if let Duration::Time(time) = WEEK {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
+    let withdrawal_fee = return_amount * Decimal256::percent(INSTANT_WITHDRAWAL_FEE);
+    return_amount = return_amount.sub(withdrawal_fee);
 
-    //Add aterra balance
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(20_000_000u128),
-        )],
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &minted_aust.into())],
     )]);
 
-    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
-
-    // Execute Lottery
-    let msg = ExecuteMsg::ExecuteLottery {};
-
-    let execute_lottery_block = env.block.clone();
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    // Check that state equals calculated prize
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.prize_buckets, state_prize_buckets);
-
-    // Get the amount of aust that is being redeemed
-    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
-        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
-        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
-            amount
-        } else {
-            panic!("DO NOT ENTER HERE")
-        }
-    } else {
-        panic!("DO NOT ENTER HERE");
-    };
-
-    // Update contract_balance based on the amount of redeemed aust --
+    // Correct withdraw, user has 1 ticket to be withdrawn
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
-    // Increase the uusd balance by the value of the aust
+    let empty_addr: Vec<Addr> = vec![];
 
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR,
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(Uint256::from(sent_amount) * Decimal256::permille(RATE)),
-        }],
+    // Check address of sender was removed correctly in the sequence bucket
+    assert_eq!(
+        query_ticket_info(deps.as_ref(), String::from("234567"))
+            .unwrap()
+            .holders,
+        empty_addr
     );
 
-    // Decrease the aust balance by the amount of redeemed aust
-    deps.querier.with_token_balances(&[(
-        &A_UST.to_string(),
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &(Uint128::from(20_000_000u128) - sent_amount),
-        )],
-    )]);
-
-    // Advance block_time in time
-    if let Duration::Time(time) = HOUR {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
-
-    let msg = ExecuteMsg::ExecutePrize { limit: None };
-    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-
-    let number_winners = [0, 0, 0, 0, 0, 0, 1];
-    let (lottery_prize_buckets, total_reserve) =
-        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
-    let (glow_prize_buckets, _) =
-        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
-
-    let lottery = read_lottery_info(deps.as_ref().storage, 0u64);
+    // Check depositor info was updated correctly
     assert_eq!(
-        lottery,
-        LotteryInfo {
-            rand_round: 20170,
-            sequence: SIX_MATCH_SEQUENCE.to_string(),
-            awarded: true,
-            timestamp: execute_lottery_block.time,
-            prize_buckets: lottery_prize_buckets,
-            number_winners,
-            page: "".to_string(),
-            glow_prize_buckets,
-            block_height: execute_lottery_block.height,
-            total_user_shares: minted_shares
+        read_depositor_info(&deps.storage, &deps.api.addr_validate("addr0001").unwrap()),
+        DepositorInfo {
+            shares: Uint256::zero(),
+            tickets: vec![],
+            unbonding_info: vec![],
+            operator_addr: Addr::unchecked("")
         }
     );
 
-    let prize_info = read_prize(deps.as_ref(), &address_raw, 0u64).unwrap();
     assert_eq!(
-        prize_info,
-        PrizeInfo {
-            claimed: false,
-            matches: number_winners,
+        query_state(deps.as_ref(), mock_env(), None).unwrap(),
+        StateResponse {
+            total_tickets: Uint256::zero(),
+            total_reserve: withdrawal_fee,
+            withdrawal_fee_reserve: withdrawal_fee,
+            prize_reserve: Uint256::zero(),
+            prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
+            current_lottery: 0,
+            next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
+            next_lottery_exec_time: Expiration::Never {},
+            next_epoch: HOUR.mul(3).after(&mock_env().block),
+            operator_reward_emission_index: RewardEmissionsIndex {
+                last_reward_updated: 12345,
+                global_reward_index: Decimal256::zero(),
+                glow_emission_rate: Decimal256::zero(),
+            },
+            sponsor_reward_emission_index: RewardEmissionsIndex {
+                last_reward_updated: 12345,
+                global_reward_index: Decimal256::zero(),
+                glow_emission_rate: Decimal256::zero(),
+            },
+            last_lottery_execution_aust_exchange_rate: Decimal256::permille(RATE)
         }
     );
 
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-
-    assert_eq!(state.current_lottery, 1u64);
-    assert_eq!(state.total_reserve, total_reserve);
-
-    let remaining_state_prize_buckets =
-        calculate_remaining_state_prize_buckets(state_prize_buckets, number_winners);
-
-    // From the initialization of the contract
-    assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
-
-    let info = mock_info("addr0000", &[]);
-    let msg = ExecuteMsg::ClaimLottery {
-        lottery_ids: Vec::from([0u64]),
-    };
-
-    // Claim lottery should work, even if there are no unbonded claims
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    let config = CONFIG.load(deps.as_ref().storage).unwrap();
-    let lottery_info = read_lottery_info(deps.as_ref().storage, 0u64);
-    let snapshotted_depositor_stats_info = read_depositor_stats_at_height(
-        deps.as_ref().storage,
-        &info.sender,
-        lottery_info.block_height,
-    );
-
-    let winner_address = info.sender;
-
-    let (ust_to_send, glow_to_send): (Uint128, Uint128) = calculate_winner_prize(
-        &deps.as_mut().querier,
-        &config,
-        &prize_info,
-        &lottery_info,
-        &snapshotted_depositor_stats_info,
-        &winner_address,
-    )
-    .unwrap();
-
-    let prizes = read_prize(deps.as_ref(), &address_raw, 0u64).unwrap();
     assert_eq!(
-        prizes,
-        PrizeInfo {
-            claimed: true,
-            matches: [0, 0, 0, 0, 0, 0, 1],
+        query_pool(deps.as_ref()).unwrap(),
+        PoolResponse {
+            total_user_shares: Uint256::zero(),
+            total_user_aust: Uint256::zero(),
+            total_sponsor_lottery_deposits: Uint256::zero(),
+            total_operator_shares: Uint256::zero(),
         }
     );
 
-    let prize_response: PrizeInfoResponse = from_binary(
-        &query(
-            deps.as_ref(),
-            env,
-            QueryMsg::PrizeInfo {
-                address: "addr0000".to_string(),
-                lottery_id: 0,
-            },
-        )
-        .unwrap(),
-    )
-    .unwrap();
-
-    assert_eq!(prize_response.won_ust, ust_to_send);
-    assert_eq!(prize_response.won_glow, glow_to_send);
-
-    //check total_reserve
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.total_reserve, total_reserve);
-
     assert_eq!(
         res.messages,
         vec![
-            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-                to_address: "addr0000".to_string(),
-                amount: vec![Coin {
-                    denom: String::from("uusd"),
-                    amount: ust_to_send,
-                }],
-            })),
             SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: config.distributor_contract.to_string(),
+                contract_addr: A_UST.to_string(),
                 funds: vec![],
-                msg: to_binary(&FaucetExecuteMsg::Spend {
-                    recipient: "addr0000".to_string(),
-                    amount: glow_to_send,
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: ANCHOR.to_string(),
+                    amount: aust_to_redeem.into(),
+                    msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
                 })
                 .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: return_amount.into()
+                }],
             }))
         ]
     );
@@ -2559,567 +3110,766 @@ fn claim_lottery_single_winner() {
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "claim_lottery"),
-            attr("lottery_ids", "[0]"),
-            attr("depositor", "addr0000"),
-            attr("redeemed_ust", ust_to_send.to_string()),
-            attr("redeemed_glow", glow_to_send.to_string()),
+            attr("action", "withdraw_ticket"),
+            attr("depositor", "addr0001"),
+            attr("tickets_amount", 1u64.to_string()),
+            attr("redeem_amount_anchor", aust_to_redeem.to_string()),
+            attr("redeem_stable_amount", return_amount.to_string()),
+            attr("instant_withdrawal_fee", withdrawal_fee.to_string())
         ]
-    );
+    )
 }
 
 #[test]
-fn execute_lottery() {
+fn instant_withdrawal_fee_decay() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
-    //Add aterra balance
-    deps.querier.with_token_balances(&[(
-        &A_UST.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
-    )]);
-
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    let info = mock_info(
-        "addr0001",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(1u64),
-        }],
-    );
-
-    let msg = ExecuteMsg::ExecuteLottery {};
+    let update_decay = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                        decay: InstantWithdrawalFeeDecay|
+     -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::UpdateConfig {
+            owner: None,
+            oracle_addr: None,
+            instant_withdrawal_fee: None,
+            unbonding_period: None,
+            reserve_factor: None,
+            epoch_interval: None,
+            max_holders: None,
+            max_tickets_per_depositor: None,
+            paused: None,
+            lotto_winner_boost_config: None,
+            operator_glow_emission_rate: None,
+            sponsor_glow_emission_rate: None,
+            max_history_entries: None,
+            instant_withdrawal_fee_decay: Some(decay),
+            max_operator_commission: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg)
+    };
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
-
-    match res {
-        Err(ContractError::InvalidLotteryExecutionFunds {}) => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    let query_fee = |deps: &OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                     env: Env|
+     -> InstantWithdrawalFeeResponse {
+        from_binary(
+            &query(
+                deps.as_ref(),
+                env,
+                QueryMsg::InstantWithdrawalFee {
+                    address: "addr0001".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
 
-    let mut env = mock_env();
-    let info = mock_info("addr0001", &[]);
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    // The min fee can't be higher than the instant withdrawal fee
+    let res = update_decay(
+        &mut deps,
+        InstantWithdrawalFeeDecay {
+            min_fee: Decimal256::percent(INSTANT_WITHDRAWAL_FEE + 1),
+            decay_period: 4 * WEEK_TIME,
+            step: None,
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidWithdrawalFeeDecay {}));
 
-    match res {
-        Err(ContractError::LotteryNotReady { next_lottery_time })
-            if next_lottery_time
-                == Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)) => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    // Decay linearly from 10% to 2% over 4 weeks
+    update_decay(
+        &mut deps,
+        InstantWithdrawalFeeDecay {
+            min_fee: Decimal256::percent(2),
+            decay_period: 4 * WEEK_TIME,
+            step: None,
+        },
+    )
+    .unwrap();
 
-    // Advance one week in time
-    if let Duration::Time(time) = WEEK {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(1_600_000_000);
+    let deposit_time = env.block.time.seconds();
 
-    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    let deposit = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>, env: Env| {
+        let info = mock_info(
+            "addr0001",
+            &[Coin {
+                denom: DENOM.to_string(),
+                amount: Uint128::from(TICKET_PRICE),
+            }],
+        );
+        let msg = ExecuteMsg::Deposit {
+            encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+                ZERO_MATCH_SEQUENCE,
+            )]),
+            operator: None,
+            syndicate: None,
+        };
+        execute(deps.as_mut(), env, info, msg).unwrap();
+    };
 
-    // Lottery cannot be run with 0 tickets participating
-    match res {
-        Err(ContractError::InvalidLotteryExecutionTickets {}) => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    deposit(&mut deps, env.clone());
 
-    // Correct deposit - buys two tickets
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint256::from(2 * TICKET_PRICE).into(),
-        }],
+    // Fresh deposits pay the full fee
+    let res = query_fee(&deps, env.clone());
+    assert_eq!(
+        res.instant_withdrawal_fee,
+        Decimal256::percent(INSTANT_WITHDRAWAL_FEE)
     );
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
-            String::from(ZERO_MATCH_SEQUENCE),
-            String::from(ONE_MATCH_SEQUENCE),
-        ]),
-        operator: None,
-    };
-    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(res.average_deposit_time, deposit_time);
 
-    // Calculate the number of minted_aust
-    let minted_aust = Uint256::from(2 * TICKET_PRICE) / Decimal256::permille(RATE);
-
-    // Add minted_aust to our contract balance
-    deps.querier.increment_token_balance(
-        A_UST.to_string(),
-        MOCK_CONTRACT_ADDR.to_string(),
-        minted_aust.into(),
+    // Halfway through the decay period
+    env.block.time = env.block.time.plus_seconds(2 * WEEK_TIME);
+    assert_eq!(
+        query_fee(&deps, env.clone()).instant_withdrawal_fee,
+        Decimal256::percent(6)
     );
 
-    // Execute lottery, now with tickets
-    let lottery_msg = ExecuteMsg::ExecuteLottery {};
-    let info = mock_info("addr0001", &[]);
+    // Depositing the same amount again averages the deposit time
+    deposit(&mut deps, env.clone());
 
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+    let res = query_fee(&deps, env.clone());
+    assert_eq!(res.average_deposit_time, deposit_time + WEEK_TIME);
+    assert_eq!(res.instant_withdrawal_fee, Decimal256::percent(8));
 
-    // Get the sent_amount
-    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
-        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
-        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
-            amount
-        } else {
-            panic!("DO NOT ENTER HERE")
-        }
-    } else {
-        panic!("DO NOT ENTER HERE");
-    };
+    // Decay in steps of 2 weeks
+    update_decay(
+        &mut deps,
+        InstantWithdrawalFeeDecay {
+            min_fee: Decimal256::percent(2),
+            decay_period: 4 * WEEK_TIME,
+            step: Some(2 * WEEK_TIME),
+        },
+    )
+    .unwrap();
 
-    // Messages is empty because aust hasn't appreciated so there is nothing to redeem
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "execute_lottery"),
-            attr("redeemed_amount", sent_amount.to_string()),
-        ]
+        query_fee(&deps, env.clone()).instant_withdrawal_fee,
+        Decimal256::percent(INSTANT_WITHDRAWAL_FEE)
     );
 
-    deps.querier.decrement_token_balance(
-        A_UST.to_string(),
-        MOCK_CONTRACT_ADDR.to_string(),
-        sent_amount,
+    env.block.time = env.block.time.plus_seconds(WEEK_TIME);
+    assert_eq!(
+        query_fee(&deps, env.clone()).instant_withdrawal_fee,
+        Decimal256::percent(6)
     );
 
-    // Directly check next_lottery_exec_time has been set up
-    let next_lottery_exec_time = query_state(deps.as_ref(), mock_env(), None)
-        .unwrap()
-        .next_lottery_exec_time;
-
+    // Past the decay period only the min fee is paid
+    let mut later_env = env.clone();
+    later_env.block.time = later_env.block.time.plus_seconds(2 * WEEK_TIME);
     assert_eq!(
-        next_lottery_exec_time,
-        Expiration::AtTime(env.block.time).add(HOUR).unwrap()
+        query_fee(&deps, later_env).instant_withdrawal_fee,
+        Decimal256::percent(2)
     );
 
-    // Advance block_time in time
-    if let Duration::Time(time) = HOUR {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
-
-    // Execute prize
-    let execute_prize_msg = ExecuteMsg::ExecutePrize { limit: None };
-    let res = execute(deps.as_mut(), env.clone(), info, execute_prize_msg.clone()).unwrap();
-
-    // Directly check next_lottery_time has been set up for next week
-    let next_lottery_time = query_state(deps.as_ref(), mock_env(), None)
-        .unwrap()
-        .next_lottery_time;
+    // Instant withdrawals pay the decayed fee
+    let minted_aust = Uint256::from(2 * TICKET_PRICE) / Decimal256::permille(RATE);
 
-    assert_eq!(
-        next_lottery_time,
-        Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME))
-            .add(WEEK)
-            .unwrap()
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: DENOM.to_string(),
+            amount: Uint128::from(2 * TICKET_PRICE),
+        }],
     );
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &minted_aust.into())],
+    )]);
 
-    // Directly check next_lottery_exec_time has been set up to Never
-    let next_lottery_exec_time = query_state(deps.as_ref(), mock_env(), None)
-        .unwrap()
-        .next_lottery_exec_time;
-
-    assert_eq!(next_lottery_exec_time, Expiration::Never {});
+    let msg = ExecuteMsg::Withdraw {
+        amount: Some(Uint128::from(TICKET_PRICE)),
+        instant: Some(true),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
 
-    assert_eq!(res.messages, vec![]);
+    let withdrawn_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+    let withdrawal_fee = withdrawn_aust * Decimal256::permille(RATE) * Decimal256::percent(6);
 
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "execute_prize"),
-            attr("total_awarded_prize", "0"),
-        ]
+        query_state(deps.as_ref(), env.clone(), None)
+            .unwrap()
+            .total_reserve,
+        withdrawal_fee
     );
 
-    // Increase the aust exchange rate
-    let new_rate = Decimal256::permille(RATE * 2);
-    deps.querier.with_exchange_rate(new_rate);
+    // Depositors with shares but without a deposit age record,
+    // are aged from when deposit ages started being tracked
+    let upgrade_time = env.block.time;
+    DEPOSIT_AGES.remove(deps.as_mut().storage, &Addr::unchecked("addr0001"));
+    DEPOSIT_AGES_START
+        .save(deps.as_mut().storage, &upgrade_time)
+        .unwrap();
 
-    // Advance one week in time
-    if let Duration::Time(time) = WEEK {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
+    env.block.time = env.block.time.plus_seconds(2 * WEEK_TIME);
+    let res = query_fee(&deps, env.clone());
+    assert_eq!(res.instant_withdrawal_fee, Decimal256::percent(6));
+    assert_eq!(res.average_deposit_time, upgrade_time.seconds());
+
+    // And their first deposit doesn't reset the age of their existing shares,
+    // about as many as the deposited ones after the withdrawal
+    deposit(&mut deps, env.clone());
+    let average_deposit_time = query_fee(&deps, env).average_deposit_time;
+    assert!(average_deposit_time + 1 >= upgrade_time.seconds() + WEEK_TIME);
+    assert!(average_deposit_time <= upgrade_time.seconds() + WEEK_TIME + 1);
+}
 
-    // Execute 2nd lottery
-    let lottery_msg = ExecuteMsg::ExecuteLottery {};
-    let info = mock_info("addr0001", &[]);
+#[test]
+fn simulate_deposit_and_withdraw() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
 
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
-    // Amount of aust to redeem
+    let deposit_amount = Uint128::from(2 * TICKET_PRICE);
 
-    // Get this contracts aust balance
-    let contract_a_balance = query_token_balance(
-        deps.as_ref(),
-        Addr::unchecked(A_UST),
-        Addr::unchecked(MOCK_CONTRACT_ADDR),
+    // Simulate a deposit for two tickets
+    let simulation: SimulateDepositResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateDeposit {
+                amount: deposit_amount,
+            },
+        )
+        .unwrap(),
     )
     .unwrap();
 
-    let config = CONFIG.load(deps.as_ref().storage).unwrap();
-    let pool = POOL.load(deps.as_ref().storage).unwrap();
-    let state = STATE.load(deps.as_ref().storage).unwrap();
-    let ExecuteLotteryRedeemedAustInfo { aust_to_redeem, .. } =
-        calculate_value_of_aust_to_be_redeemed_for_lottery(
-            &state,
-            &pool,
-            &config,
-            contract_a_balance,
-            new_rate,
-        );
+    let minted_aust = Uint256::from(2 * TICKET_PRICE) / Decimal256::permille(RATE);
 
-    // Verify amount to redeem for the lottery
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: A_UST.to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Send {
-                contract: ANCHOR.to_string(),
-                amount: Uint128::from(aust_to_redeem),
-                msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
-            })
-            .unwrap(),
-        }))]
+        simulation,
+        SimulateDepositResponse {
+            deposit_amount: Uint256::from(deposit_amount),
+            tax: Uint256::zero(),
+            minted_aust,
+            minted_shares: minted_aust,
+            deposit_value: minted_aust * Decimal256::permille(RATE),
+            max_tickets: 2,
+        }
     );
 
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "execute_lottery"),
-            attr("redeemed_amount", aust_to_redeem.to_string()),
-        ]
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: deposit_amount,
+        }],
     );
 
-    // Advance block_time in time
-    if let Duration::Time(time) = HOUR {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
+            String::from(ZERO_MATCH_SEQUENCE),
+            String::from(ONE_MATCH_SEQUENCE),
+        ]),
+        operator: None,
+        syndicate: None,
+    };
 
-    // Execute prize
-    let _res = execute(deps.as_mut(), env.clone(), info, execute_prize_msg.clone()).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // Directly check next_lottery_time has been set up for next week
-    let next_lottery_time = query_state(deps.as_ref(), mock_env(), None)
-        .unwrap()
-        .next_lottery_time;
+    // The simulated shares match the minted shares
+    let depositor_info =
+        read_depositor_info(&deps.storage, &deps.api.addr_validate("addr0001").unwrap());
+    assert_eq!(depositor_info.shares, simulation.minted_shares);
 
-    assert_eq!(
-        next_lottery_time,
-        Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME))
-            .add(WEEK)
-            .unwrap()
-            .add(WEEK)
-            .unwrap()
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: deposit_amount,
+        }],
     );
 
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    println!("state: {:?}", state);
-
-    // Advance three weeks in time
-    if let Duration::Time(time) = WEEK {
-        env.block.time = env.block.time.plus_seconds(time * 3);
-    }
-
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(20_000_000u128),
-        )],
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &minted_aust.into())],
     )]);
 
-    // Get this contracts aust balance
-    let contract_a_balance = query_token_balance(
+    // Simulating a withdrawal with a zero amount fails like the withdrawal itself
+    let res = query(
         deps.as_ref(),
-        Addr::unchecked(A_UST),
-        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        mock_env(),
+        QueryMsg::SimulateWithdraw {
+            address: "addr0001".to_string(),
+            amount: Some(Uint128::zero()),
+            instant: None,
+        },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err(
+            ContractError::SpecifiedWithdrawAmountIsZero {}.to_string()
+        ))
+    );
+
+    // Simulate withdrawing half a ticket worth of funds, which removes one ticket
+    let withdraw_amount = Uint128::from(TICKET_PRICE / 2);
+
+    let regular_simulation: SimulateWithdrawResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateWithdraw {
+                address: "addr0001".to_string(),
+                amount: Some(withdraw_amount),
+                instant: None,
+            },
+        )
+        .unwrap(),
     )
     .unwrap();
 
-    // Execute 3rd lottery
-    let lottery_msg = ExecuteMsg::ExecuteLottery {};
-    let info = mock_info("addr0001", &[]);
+    let instant_simulation: SimulateWithdrawResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateWithdraw {
+                address: "addr0001".to_string(),
+                amount: Some(withdraw_amount),
+                instant: Some(true),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
 
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+    assert_eq!(regular_simulation.withdrawn_tickets, 1);
+    assert_eq!(regular_simulation.instant_withdrawal_fee, Uint256::zero());
+    assert_eq!(
+        instant_simulation.instant_withdrawal_fee,
+        regular_simulation.return_amount * Decimal256::percent(INSTANT_WITHDRAWAL_FEE)
+    );
+    assert_eq!(
+        instant_simulation.return_amount,
+        regular_simulation.return_amount - instant_simulation.instant_withdrawal_fee
+    );
 
-    let config = CONFIG.load(deps.as_ref().storage).unwrap();
-    let pool = POOL.load(deps.as_ref().storage).unwrap();
-    let state = STATE.load(deps.as_ref().storage).unwrap();
-    let ExecuteLotteryRedeemedAustInfo { aust_to_redeem, .. } =
-        calculate_value_of_aust_to_be_redeemed_for_lottery(
-            &state,
-            &pool,
-            &config,
-            contract_a_balance,
-            new_rate,
-        );
+    // Execute the instant withdrawal and compare with the simulation
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::Withdraw {
+        amount: Some(withdraw_amount),
+        instant: Some(true),
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // Check the attributes
     assert_eq!(
-        res.attributes,
+        res.messages,
         vec![
-            attr("action", "execute_lottery"),
-            attr("redeemed_amount", aust_to_redeem.to_string()),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: A_UST.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: ANCHOR.to_string(),
+                    amount: instant_simulation.withdrawn_aust.into(),
+                    msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: instant_simulation.return_amount.into()
+                }],
+            }))
         ]
     );
 
-    // Verify amount to redeem for the lottery
+    let depositor_info =
+        read_depositor_info(&deps.storage, &deps.api.addr_validate("addr0001").unwrap());
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: A_UST.to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Send {
-                contract: ANCHOR.to_string(),
-                amount: Uint128::from(aust_to_redeem),
-                msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
-            })
-            .unwrap(),
-        }))]
+        depositor_info.shares,
+        instant_simulation.post_transaction_shares
     );
+    assert_eq!(depositor_info.tickets.len(), 1);
 
-    // Advance block_time in time
-    if let Duration::Time(time) = HOUR {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
-
-    // Execute prize
-    let _res = execute(deps.as_mut(), env.clone(), info, execute_prize_msg.clone()).unwrap();
-
-    // Directly check next_lottery_time has been set up for three weeks from the last lottery
-    // This checks the functionality of ensuring that the next_lottery_time is always
-    // set to a time in the future
-    let next_lottery_time = query_state(deps.as_ref(), mock_env(), None)
-        .unwrap()
-        .next_lottery_time;
-
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
     assert_eq!(
-        next_lottery_time,
-        Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME))
-            .add(WEEK)
-            .unwrap()
-            .add(WEEK)
-            .unwrap()
-            .add(WEEK)
-            .unwrap()
-            .add(WEEK)
-            .unwrap()
-            .add(WEEK)
-            .unwrap()
+        state.total_reserve,
+        instant_simulation.instant_withdrawal_fee
     );
+}
 
-    // Advance to the next lottery time
-    if let Expiration::AtTime(next_lottery_time_seconds) = next_lottery_time {
-        env.block.time = next_lottery_time_seconds;
-    };
-
-    // Execute 4th lottery
-    // Confirm that you can run the lottery right at the next execution time
-    let lottery_msg = ExecuteMsg::ExecuteLottery {};
-    let info = mock_info("addr0001", &[]);
+#[test]
+fn claim() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
 
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
-    // Advance block_time in time
-    if let Duration::Time(time) = HOUR {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
+    // Address buys one ticket
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
 
-    // Execute prize
-    let _res = execute(deps.as_mut(), env, info, execute_prize_msg).unwrap();
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            ZERO_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
 
-    // Directly check next_lottery_time has been set up one week from the last execution time
-    let next_lottery_time = query_state(deps.as_ref(), mock_env(), None)
-        .unwrap()
-        .next_lottery_time;
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    assert_eq!(
-        next_lottery_time,
-        Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME))
-            .add(WEEK)
-            .unwrap()
-            .add(WEEK)
-            .unwrap()
-            .add(WEEK)
-            .unwrap()
-            .add(WEEK)
-            .unwrap()
-            .add(WEEK)
-            .unwrap()
-            .add(WEEK)
-            .unwrap()
-    );
-}
+    // Address withdraws one ticket
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::Withdraw {
+        amount: None,
+        instant: None,
+    };
 
-#[test]
-fn execute_lottery_no_tickets() {
-    // Initialize contract
-    let mut deps = mock_dependencies(&[]);
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
 
-    //Add aterra balance
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &minted_aust.into())],
     )]);
 
-    mock_instantiate(&mut deps);
-    mock_register_contracts(deps.as_mut());
+    // Get the number of minted aust
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
 
-    let info = mock_info("addr0001", &[]);
+    // Calculate the depositor's aust balance
+    let depositor_aust_balance = minted_aust;
 
-    let mut env = mock_env();
-    // Advance one week in time
-    if let Duration::Time(time) = WEEK {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
+    // Calculate the depositor's balance from their aust balance
+    let depositor_balance = depositor_aust_balance * Decimal256::permille(RATE);
 
-    // Execute Lottery
-    let msg = ExecuteMsg::ExecuteLottery {};
+    let redeemed_amount = depositor_balance;
 
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    // Correct withdraw, user has 1 ticket to be withdrawn
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    println!("res: {:?}", res);
+    // Get the amount to redeem and it's corresponding value
+    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+            amount
+        } else {
+            panic!("DO NOT ENTER HERE")
+        }
+    } else {
+        panic!("DO NOT ENTER HERE");
+    };
+
+    // Claim amount that you don't have, should fail
+    let info = mock_info("addr0002", &[]);
+    let msg = ExecuteMsg::Claim {
+        start_after: None,
+        limit: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(ContractError::InvalidLotteryExecutionTickets {}) => {}
+        Err(ContractError::InsufficientClaimableFunds {}) => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // Advance block_time in time
-    if let Duration::Time(time) = HOUR {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
+    // Claim amount that you have, but still in unbonding state, should fail
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::Claim {
+        start_after: None,
+        limit: None,
+    };
 
-    let msg = ExecuteMsg::ExecutePrize { limit: None };
-    // Run lottery, no winners - should run correctly
-    let res = execute(deps.as_mut(), env, info, msg);
+    let mut env = mock_env();
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
     match res {
-        Err(ContractError::InvalidLotteryPrizeExecution {}) => {}
+        Err(ContractError::InsufficientClaimableFunds {}) => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
-}
-
-#[test]
-fn execute_prize_no_winners() {
-    // Initialize contract
-    let mut deps = mock_dependencies(&[]);
 
-    mock_instantiate(&mut deps);
-    mock_register_contracts(deps.as_mut());
+    println!("Block time 1: {}", env.block.time);
 
-    // Users buys a non-winning ticket
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            ZERO_MATCH_SEQUENCE,
-        )]),
-        operator: None,
-    };
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
-    );
+    // Advance one week in time
+    if let Duration::Time(time) = WEEK {
+        env.block.time = env.block.time.plus_seconds(time * 2);
+    }
+    println!("Block time 2: {}", env.block.time);
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    // Read the depositor info
+    let dep = read_depositor_info(&deps.storage, &deps.api.addr_validate("addr0001").unwrap());
 
-    let address_raw = deps.api.addr_validate("addr0000").unwrap();
+    // Fail because not enough funds in the contract
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::InsufficientFunds {
+            to_send,
+            available_balance,
+        }) => {
+            if available_balance == Uint256::zero() && Uint256::from(to_send) == redeemed_amount {}
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // Get the number of minted aust
-    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+    // Revert the state of the depositor
+    store_depositor_info(
+        &mut deps.storage,
+        &deps.api.addr_validate("addr0001").unwrap(),
+        dep,
+        env.block.height,
+    )
+    .unwrap();
 
-    // Get the amount of minted_shares
-    let minted_shares = minted_aust;
+    // Update the contract balance to include the withdrawn funds
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: DENOM.to_string(),
+            amount: Uint128::from(Uint256::from(sent_amount) * Decimal256::permille(RATE)),
+        }],
+    );
+
+    // Claim amount is already unbonded, so claim execution should work
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
 
     // Check depositor info was updated correctly
     assert_eq!(
-        read_depositor_info(deps.as_ref().storage, &address_raw),
+        read_depositor_info(&deps.storage, &deps.api.addr_validate("addr0001").unwrap()),
         DepositorInfo {
-            shares: minted_shares,
-            tickets: vec![String::from(ZERO_MATCH_SEQUENCE)],
+            shares: Uint256::zero(),
+            tickets: vec![],
             unbonding_info: vec![],
             operator_addr: Addr::unchecked("")
         }
     );
 
-    //Advance time one week
-    let mut env = mock_env();
-    // Advance one week in time
-    if let Duration::Time(time) = WEEK {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: String::from("uusd"),
+                amount: redeemed_amount.into()
+            }],
+        }))]
+    );
 
-    //Add aterra balance
-    deps.querier.with_token_balances(&[(
-        &A_UST.to_string(),
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(20_000_000u128),
-        )],
-    )]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_unbonded"),
+            attr("depositor", "addr0001"),
+            attr("redeemed_amount", redeemed_amount.to_string()),
+        ]
+    );
+}
 
-    // Calculate the prize buckets
-    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
+#[test]
+fn unbonding_claims() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
 
-    // Execute lottery - should run correctly
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-    let msg = ExecuteMsg::ExecuteLottery {};
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
-    let execute_lottery_block = env.block.clone();
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint256::from(2 * TICKET_PRICE).into(),
+        }],
+    );
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
+            String::from(ZERO_MATCH_SEQUENCE),
+            String::from(ONE_MATCH_SEQUENCE),
+        ]),
+        operator: None,
+        syndicate: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // Check that state equals calculated prize
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.prize_buckets, state_prize_buckets);
+    // Withdraw twice per epoch, over more epochs than the former limit of concurrent claims
+    let mut env = mock_env();
+    for _ in 0..16 {
+        for _ in 0..2 {
+            let msg = ExecuteMsg::Withdraw {
+                amount: Some(Uint128::from(1_000u128)),
+                instant: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+        }
 
-    // Advance block_time in time
-    if let Duration::Time(time) = HOUR {
-        env.block.time = env.block.time.plus_seconds(time);
+        if let Duration::Time(time) = HOUR.mul(3) {
+            env.block.time = env.block.time.plus_seconds(time);
+        }
     }
 
-    let msg = ExecuteMsg::ExecutePrize { limit: None };
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    // Each withdrawal returns the value of the aust of 1000 uusd
+    let withdrawn_aust = Uint256::from(1_000u128) / Decimal256::permille(RATE);
+    let epoch_amount = withdrawn_aust * Decimal256::permille(RATE) * Uint256::from(2u128);
+
+    let query_claims = |deps: &OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                        env: Env,
+                        start_after: Option<u64>|
+     -> UnbondingClaimsResponse {
+        from_binary(
+            &query(
+                deps.as_ref(),
+                env,
+                QueryMsg::UnbondingClaims {
+                    address: "addr0001".to_string(),
+                    start_after,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
 
-    // Check lottery info was updated correctly
-    let awarded_prize = Uint256::zero();
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    // Withdrawals of the same epoch are merged
+    let first_page = query_claims(&deps, env.clone(), None);
+    assert_eq!(first_page.claims.len(), 10);
+    assert!(first_page
+        .claims
+        .iter()
+        .all(|claim| claim.amount == epoch_amount && !claim.releasable));
+    assert_eq!(
+        first_page.pending_amount,
+        epoch_amount * Uint256::from(10u128)
+    );
+    assert_eq!(first_page.releasable_amount, Uint256::zero());
+
+    let second_page = query_claims(&deps, env.clone(), Some(first_page.claims[9].release_epoch));
+    assert_eq!(second_page.claims.len(), 6);
+
+    // Move to the release of the first epoch
+    let mut env = mock_env();
+    if let Expiration::AtTime(release_time) = first_page.claims[0].release_at {
+        env.block.time = release_time;
+    }
+
+    let claims = query_claims(&deps, env.clone(), None);
+    assert!(claims.claims[0].releasable);
+    assert!(!claims.claims[1].releasable);
+    assert_eq!(claims.releasable_amount, epoch_amount);
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: DENOM.to_string(),
+            amount: Uint128::from(1_000_000u128),
+        }],
+    );
+
+    // Only the released epoch is claimed
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::Claim {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
 
     assert_eq!(
-        read_lottery_info(deps.as_ref().storage, 0u64),
-        LotteryInfo {
-            rand_round: 20170,
-            sequence: SIX_MATCH_SEQUENCE.to_string(),
-            awarded: true,
-            prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
-            number_winners: [0; NUM_PRIZE_BUCKETS],
-            page: "".to_string(),
-            glow_prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
-            timestamp: execute_lottery_block.time,
-            block_height: execute_lottery_block.height,
-            total_user_shares: minted_shares
-        }
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: DENOM.to_string(),
+                amount: epoch_amount.into(),
+            }],
+        }))]
     );
 
-    assert_eq!(state.current_lottery, 1u64);
-    assert_eq!(state.total_reserve, Uint256::zero());
+    let claims = query_claims(&deps, env, None);
+    assert_eq!(claims.claims[0], first_page.claims[1]);
+    assert_eq!(claims.releasable_amount, Uint256::zero());
 
-    // After executing the lottery, the prize buckets remain unchanged because there were no winning tickets
-    assert_eq!(state.prize_buckets, state_prize_buckets);
+    // Once every epoch is released, claims can be limited to a number of epochs
+    let mut env = mock_env();
+    if let Expiration::AtTime(release_time) = second_page.claims[5].release_at {
+        env.block.time = release_time;
+    }
 
-    assert_eq!(res.messages, vec![]);
+    let claim_msg = |amount: Uint256| {
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: DENOM.to_string(),
+                amount: amount.into(),
+            }],
+        }))]
+    };
 
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::Claim {
+            start_after: None,
+            limit: Some(2),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages, claim_msg(epoch_amount * Uint256::from(2u128)));
+
+    // The remaining epochs fit in the default limit
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::Claim {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "execute_prize"),
-            attr("total_awarded_prize", awarded_prize.to_string()),
-        ]
+        res.messages,
+        claim_msg(epoch_amount * Uint256::from(13u128))
     );
+    assert_eq!(query_claims(&deps, env.clone(), None).claims, vec![]);
+
+    // The depositor info lists the oldest claims, and tells when there are more
+    let mut env = env;
+    for _ in 0..31 {
+        let msg = ExecuteMsg::Withdraw {
+            amount: Some(Uint128::from(1_000u128)),
+            instant: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+
+        if let Duration::Time(time) = HOUR.mul(3) {
+            env.block.time = env.block.time.plus_seconds(time);
+        }
+    }
+
+    let res: DepositorInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::DepositorInfo {
+                address: "addr0001".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.unbonding_info.len(), 30);
+    assert!(res.unbonding_info_has_more);
 }
 
 #[test]
-fn execute_prize_one_winner() {
+fn claim_lottery_single_winner() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
@@ -3132,6 +3882,7 @@ fn execute_prize_one_winner() {
             SIX_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
     let info = mock_info(
         "addr0000",
@@ -3167,7 +3918,6 @@ fn execute_prize_one_winner() {
 
     //Advance time one week
     let mut env = mock_env();
-
     // Advance one week in time
     if let Duration::Time(time) = WEEK {
         env.block.time = env.block.time.plus_seconds(time);
@@ -3182,47 +3932,91 @@ fn execute_prize_one_winner() {
         )],
     )]);
 
-    // Check lottery info was updated correctly
     let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
 
     // Execute Lottery
     let msg = ExecuteMsg::ExecuteLottery {};
 
     let execute_lottery_block = env.block.clone();
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    // Advance block_time in time
-    if let Duration::Time(time) = HOUR {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
-    let msg = ExecuteMsg::ExecutePrize { limit: None };
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    // Check that state equals calculated prize
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.prize_buckets, state_prize_buckets);
 
-    let number_winners = [0, 0, 0, 0, 0, 0, 1];
-    let (lottery_prize_buckets, total_reserve) =
-        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
+    // Get the amount of aust that is being redeemed
+    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+            amount
+        } else {
+            panic!("DO NOT ENTER HERE")
+        }
+    } else {
+        panic!("DO NOT ENTER HERE");
+    };
 
-    let (glow_prize_buckets, _) =
-        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
+    // Update contract_balance based on the amount of redeemed aust --
 
-    assert_eq!(
-        read_lottery_info(deps.as_ref().storage, 0u64),
-        LotteryInfo {
+    // Increase the uusd balance by the value of the aust
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(Uint256::from(sent_amount) * Decimal256::permille(RATE)),
+        }],
+    );
+
+    // Decrease the aust balance by the amount of redeemed aust
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &(Uint128::from(20_000_000u128) - sent_amount),
+        )],
+    )]);
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    let msg = ExecuteMsg::ExecutePrize { limit: None };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let number_winners = [0, 0, 0, 0, 0, 0, 1];
+    let (lottery_prize_buckets, total_reserve) =
+        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
+    let (glow_prize_buckets, _) =
+        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
+
+    let lottery = read_lottery_info(deps.as_ref().storage, 0u64);
+    assert_eq!(
+        lottery,
+        LotteryInfo {
             rand_round: 20170,
             sequence: SIX_MATCH_SEQUENCE.to_string(),
             awarded: true,
+            timestamp: execute_lottery_block.time,
             prize_buckets: lottery_prize_buckets,
             number_winners,
             page: "".to_string(),
             glow_prize_buckets,
-            timestamp: execute_lottery_block.time,
             block_height: execute_lottery_block.height,
             total_user_shares: minted_shares,
+            reserve: total_reserve,
         }
     );
 
-    let prizes = read_prize(deps.as_ref(), &address_raw, 0u64).unwrap();
-    assert_eq!(prizes.matches, number_winners);
+    let prize_info = read_prize(deps.as_ref(), &address_raw, 0u64).unwrap();
+    assert_eq!(
+        prize_info,
+        PrizeInfo {
+            claimed: false,
+            matches: number_winners,
+        }
+    );
 
     let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
 
@@ -3235,688 +4029,2492 @@ fn execute_prize_one_winner() {
     // From the initialization of the contract
     assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
 
-    assert_eq!(res.messages, vec![]);
+    // Repeated lottery ids can't be simulated
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SimulateClaimLottery {
+            address: "addr0000".to_string(),
+            lottery_ids: vec![0u64, 0u64],
+        },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err(
+            ContractError::InvalidClaimPrizeAlreadyClaimed(0).to_string()
+        ))
+    );
+
+    let simulation: SimulateClaimLotteryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateClaimLottery {
+                address: "addr0000".to_string(),
+                lottery_ids: vec![0u64],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::ClaimLottery {
+        lottery_ids: Vec::from([0u64]),
+    };
+
+    // Claim lottery should work, even if there are no unbonded claims
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    let lottery_info = read_lottery_info(deps.as_ref().storage, 0u64);
+    let snapshotted_depositor_stats_info = read_depositor_stats_at_height(
+        deps.as_ref().storage,
+        &info.sender,
+        lottery_info.block_height,
+    );
+
+    let winner_address = info.sender;
+
+    let (ust_to_send, glow_to_send): (Uint128, Uint128) = calculate_winner_prize(
+        &deps.as_mut().querier,
+        &config,
+        &prize_info,
+        &lottery_info,
+        &snapshotted_depositor_stats_info,
+        &winner_address,
+    )
+    .unwrap();
+
+    let prizes = read_prize(deps.as_ref(), &address_raw, 0u64).unwrap();
+    assert_eq!(
+        prizes,
+        PrizeInfo {
+            claimed: true,
+            matches: [0, 0, 0, 0, 0, 0, 1],
+        }
+    );
+
+    let prize_response: PrizeInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::PrizeInfo {
+                address: "addr0000".to_string(),
+                lottery_id: 0,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(prize_response.won_ust, ust_to_send);
+    assert_eq!(prize_response.won_glow, glow_to_send);
+
+    // The simulation matches the claimed prize
+    assert_eq!(
+        simulation,
+        SimulateClaimLotteryResponse {
+            won_ust: ust_to_send,
+            operator_commission: Uint128::zero(),
+            tax: Uint128::zero(),
+            net_ust: ust_to_send,
+            won_glow: glow_to_send,
+        }
+    );
+
+    //check total_reserve
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.total_reserve, total_reserve);
+
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: String::from("uusd"),
+                    amount: ust_to_send,
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: config.distributor_contract.to_string(),
+                funds: vec![],
+                msg: to_binary(&FaucetExecuteMsg::Spend {
+                    recipient: "addr0000".to_string(),
+                    amount: glow_to_send,
+                })
+                .unwrap(),
+            }))
+        ]
+    );
 
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "execute_prize"),
-            attr(
-                "total_awarded_prize",
-                lottery_prize_buckets[NUM_PRIZE_BUCKETS - 1].to_string()
-            ),
+            attr("action", "claim_lottery"),
+            attr("lottery_ids", "[0]"),
+            attr("depositor", "addr0000"),
+            attr("recipient", "addr0000"),
+            attr("redeemed_ust", ust_to_send.to_string()),
+            attr("redeemed_glow", glow_to_send.to_string()),
+            attr("operator_commission", Uint128::zero().to_string()),
         ]
     );
 }
 
 #[test]
-fn execute_prize_winners_diff_ranks() {
+fn operator_commission() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    // Users buys winning ticket - 5 hits
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            SIX_MATCH_SEQUENCE,
-        )]),
-        operator: None,
-    };
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
-    );
+    let update_max_operator_commission =
+        |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+         max_operator_commission: Decimal256|
+         -> Result<Response, ContractError> {
+            let msg = ExecuteMsg::UpdateConfig {
+                owner: None,
+                oracle_addr: None,
+                instant_withdrawal_fee: None,
+                unbonding_period: None,
+                reserve_factor: None,
+                epoch_interval: None,
+                max_holders: None,
+                max_tickets_per_depositor: None,
+                paused: None,
+                lotto_winner_boost_config: None,
+                operator_glow_emission_rate: None,
+                sponsor_glow_emission_rate: None,
+                max_history_entries: None,
+                instant_withdrawal_fee_decay: None,
+                max_operator_commission: Some(max_operator_commission),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg)
+        };
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let register_operator = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                             commission_rate: Decimal256|
+     -> Result<Response, ContractError> {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("operator", &[]),
+            ExecuteMsg::RegisterOperator { commission_rate },
+        )
+    };
 
-    let address_raw_0 = deps.api.addr_validate("addr0000").unwrap();
+    // Operators can't take any commission by default
+    let res = register_operator(&mut deps, Decimal256::percent(5));
+    assert_eq!(
+        res,
+        Err(ContractError::OperatorCommissionTooHigh {
+            commission_rate: Decimal256::percent(5),
+            max_operator_commission: Decimal256::zero(),
+        })
+    );
 
-    // Get the number of minted aust
-    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+    let res = update_max_operator_commission(&mut deps, Decimal256::percent(101));
+    assert_eq!(res, Err(ContractError::InvalidMaxOperatorCommission {}));
 
-    // Get the amount of minted_shares
-    let minted_shares = minted_aust;
+    update_max_operator_commission(&mut deps, Decimal256::percent(10)).unwrap();
 
-    // Check depositor info was updated correctly
+    let res = register_operator(&mut deps, Decimal256::percent(20));
     assert_eq!(
-        read_depositor_info(deps.as_ref().storage, &address_raw_0),
-        DepositorInfo {
-            shares: minted_shares,
-            tickets: vec![String::from(SIX_MATCH_SEQUENCE)],
-            unbonding_info: vec![],
-            operator_addr: Addr::unchecked("")
-        }
+        res,
+        Err(ContractError::OperatorCommissionTooHigh {
+            commission_rate: Decimal256::percent(20),
+            max_operator_commission: Decimal256::percent(10),
+        })
     );
 
-    // Users buys winning ticket - 2 hits
+    register_operator(&mut deps, Decimal256::percent(5)).unwrap();
+
+    let operator = query_operator(deps.as_ref(), mock_env(), "operator".to_string()).unwrap();
+    assert_eq!(operator.commission_rate, Decimal256::percent(5));
+    assert_eq!(operator.pending_commission, Uint256::zero());
+
+    // User referred by the operator buys the winning ticket
     let msg = ExecuteMsg::Deposit {
         encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            TWO_MATCH_SEQUENCE,
+            SIX_MATCH_SEQUENCE,
         )]),
-        operator: None,
+        operator: Some("operator".to_string()),
+        syndicate: None,
     };
     let info = mock_info(
-        "addr0001",
+        "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
             amount: Uint256::from(TICKET_PRICE).into(),
         }],
     );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    let address_raw_1 = deps.api.addr_validate("addr0001").unwrap();
-
-    // Check depositor info was updated correctly
-    assert_eq!(
-        read_depositor_info(deps.as_ref().storage, &address_raw_1),
-        DepositorInfo {
-            shares: minted_shares,
-            tickets: vec![String::from(TWO_MATCH_SEQUENCE)],
-            unbonding_info: vec![],
-            operator_addr: Addr::unchecked("")
-        }
-    );
+    // Raising the rate afterwards doesn't apply to the depositor
+    register_operator(&mut deps, Decimal256::percent(10)).unwrap();
 
-    // Run lottery, one winner (6 hits), one winner (2 hits) - should run correctly
+    // Run the lottery a week later
     let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-    //Advance time one week
     let mut env = mock_env();
-    // Advance one week in time
     if let Duration::Time(time) = WEEK {
         env.block.time = env.block.time.plus_seconds(time);
     }
 
-    //Add aterra balance
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(30_000_000u128),
+            &Uint128::from(20_000_000u128),
         )],
     )]);
 
-    // Get the prize buckets
-    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
-
-    // Execute Lottery
-    let msg = ExecuteMsg::ExecuteLottery {};
-
-    let execute_lottery_block = env.block.clone();
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    // Advance block_time in time
-    if let Duration::Time(time) = HOUR {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ExecuteLottery {},
+    )
+    .unwrap();
 
-    let msg = ExecuteMsg::ExecutePrize { limit: None };
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+            amount
+        } else {
+            panic!("DO NOT ENTER HERE")
+        }
+    } else {
+        panic!("DO NOT ENTER HERE");
+    };
 
-    let number_winners = [0, 0, 1, 0, 0, 0, 1];
-    let (lottery_prize_buckets, _total_reserve) =
-        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
-    let (glow_prize_buckets, _) =
-        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
-
-    // Get the number of minted aust
-    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
-
-    let each_shares_amount = minted_aust;
-
-    // calculate the total minted_aust_value
-    let total_minted_shares = Uint256::from(2u128) * each_shares_amount;
-
-    assert_eq!(
-        read_lottery_info(deps.as_ref().storage, 0u64),
-        LotteryInfo {
-            rand_round: 20170,
-            sequence: SIX_MATCH_SEQUENCE.to_string(),
-            awarded: true,
-            prize_buckets: lottery_prize_buckets,
-            number_winners,
-            page: "".to_string(),
-            glow_prize_buckets,
-            timestamp: execute_lottery_block.time,
-            block_height: execute_lottery_block.height,
-            total_user_shares: total_minted_shares,
-        }
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(Uint256::from(sent_amount) * Decimal256::permille(RATE)),
+        }],
     );
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &(Uint128::from(20_000_000u128) - sent_amount),
+        )],
+    )]);
 
-    let prizes = read_prize(deps.as_ref(), &address_raw_0, 0u64).unwrap();
-    assert_eq!(prizes.matches, [0, 0, 0, 0, 0, 0, 1]);
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
 
-    let prizes = read_prize(deps.as_ref(), &address_raw_1, 0u64).unwrap();
-    assert_eq!(prizes.matches, [0, 0, 1, 0, 0, 0, 0]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ExecutePrize { limit: None },
+    )
+    .unwrap();
 
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    let simulation: SimulateClaimLotteryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateClaimLottery {
+                address: "addr0000".to_string(),
+                lottery_ids: vec![0u64],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
 
-    assert_eq!(state.current_lottery, 1u64);
+    let won_ust = simulation.won_ust;
+    let commission = Uint128::from(Uint256::from(won_ust) * Decimal256::percent(5));
+    assert!(!commission.is_zero());
+    assert_eq!(simulation.operator_commission, commission);
+    assert_eq!(simulation.net_ust, won_ust - commission);
+
+    // The depositor receives the prize minus the commission
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ClaimLottery {
+            lottery_ids: vec![0u64],
+        },
+    )
+    .unwrap();
 
-    let remaining_state_prize_buckets =
-        calculate_remaining_state_prize_buckets(state_prize_buckets, number_winners);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: String::from("uusd"),
+                amount: won_ust - commission,
+            }],
+        }))
+    );
 
-    // From the initialization of the contract
-    assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
+    let operator = query_operator(deps.as_ref(), env.clone(), "operator".to_string()).unwrap();
+    assert_eq!(operator.pending_commission, Uint256::from(commission));
 
-    assert_eq!(res.messages, vec![]);
+    // The operator claims its commission
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("operator", &[]),
+        ExecuteMsg::ClaimOperatorCommission {},
+    )
+    .unwrap();
 
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "execute_prize"),
-            attr(
-                "total_awarded_prize",
-                lottery_prize_buckets
-                    .iter()
-                    .fold(Uint256::zero(), |sum, val| sum + *val)
-                    .to_string()
-            ),
-        ]
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "operator".to_string(),
+            amount: vec![Coin {
+                denom: String::from("uusd"),
+                amount: commission,
+            }],
+        }))]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("operator", &[]),
+        ExecuteMsg::ClaimOperatorCommission {},
     );
+    assert_eq!(res, Err(ContractError::InsufficientClaimableFunds {}));
 }
 
 #[test]
-fn execute_prize_winners_same_rank() {
+fn donation_mode() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    // Users buys winning ticket - 4 hits
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            FOUR_MATCH_SEQUENCE,
-        )]),
-        operator: None,
+    // Only gov can register charities
+    let msg = ExecuteMsg::RegisterCharity {
+        address: "charity".to_string(),
+        name: "Charity".to_string(),
     };
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: DENOM.to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        msg.clone(),
     );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    let address_raw_0 = deps.api.addr_validate("addr0000").unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info(GOV_ADDR, &[]), msg).unwrap();
 
-    // Get the number of minted aust
-    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+    // Prizes can only be donated to registered charities
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::SetPrizeBeneficiary {
+            beneficiary: Some("charity2".to_string()),
+        },
+    );
+    assert_eq!(res, Err(ContractError::CharityNotRegistered {}));
 
-    // Get the amount of minted_shares
-    let minted_shares = minted_aust;
+    // User in donation mode buys the winning ticket
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::SetPrizeBeneficiary {
+            beneficiary: Some("charity".to_string()),
+        },
+    )
+    .unwrap();
 
-    // Check depositor info was updated correctly
-    assert_eq!(
-        read_depositor_info(deps.as_ref().storage, &address_raw_0),
-        DepositorInfo {
-            shares: minted_shares,
-            tickets: vec![String::from(FOUR_MATCH_SEQUENCE)],
-            unbonding_info: vec![],
-            operator_addr: Addr::unchecked("")
-        }
-    );
+    let beneficiary: PrizeBeneficiaryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PrizeBeneficiary {
+                address: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(beneficiary.beneficiary, Some("charity".to_string()));
 
-    // Users buys winning ticket - 4 hits
     let msg = ExecuteMsg::Deposit {
         encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            FOUR_MATCH_SEQUENCE,
+            SIX_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
     let info = mock_info(
-        "addr0001",
+        "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
             amount: Uint256::from(TICKET_PRICE).into(),
         }],
     );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    let address_raw_1 = deps.api.addr_validate("addr0001").unwrap();
-
-    // Check depositor info was updated correctly
-    assert_eq!(
-        read_depositor_info(deps.as_ref().storage, &address_raw_1),
-        DepositorInfo {
-            shares: minted_shares,
-            tickets: vec![String::from(FOUR_MATCH_SEQUENCE)],
-            unbonding_info: vec![],
-            operator_addr: Addr::unchecked("")
-        }
-    );
-
-    // Run lottery, 2 winners (4 hits) - should run correctly
+    // Run the lottery a week later
     let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-
     let mut env = mock_env();
-    // Advance one week in time
     if let Duration::Time(time) = WEEK {
         env.block.time = env.block.time.plus_seconds(time);
     }
 
-    //Add aterra balance
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(30_000_000u128),
+            &Uint128::from(20_000_000u128),
         )],
     )]);
 
-    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
-
-    // Execute Lottery
-    let msg = ExecuteMsg::ExecuteLottery {};
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ExecuteLottery {},
+    )
+    .unwrap();
 
-    let execute_lottery_block = env.block.clone();
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+            amount
+        } else {
+            panic!("DO NOT ENTER HERE")
+        }
+    } else {
+        panic!("DO NOT ENTER HERE");
+    };
 
-    // Check that state equals calculated prize
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.prize_buckets, state_prize_buckets);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(Uint256::from(sent_amount) * Decimal256::permille(RATE)),
+        }],
+    );
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &(Uint128::from(20_000_000u128) - sent_amount),
+        )],
+    )]);
 
-    // Advance block_time in time
     if let Duration::Time(time) = HOUR {
         env.block.time = env.block.time.plus_seconds(time);
     }
 
-    let msg = ExecuteMsg::ExecutePrize { limit: None };
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-
-    let number_winners = [0, 0, 0, 0, 2, 0, 0];
-    let (lottery_prize_buckets, total_reserve) =
-        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
-
-    let (glow_prize_buckets, _) =
-        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
-
-    // calculate the value of each deposit accounting for rounding errors
-    let each_minted_shares = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
-
-    // calculate the total minted_aust_value
-    let total_minted_shares = Uint256::from(2u128) * each_minted_shares;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ExecutePrize { limit: None },
+    )
+    .unwrap();
 
-    assert_eq!(
-        read_lottery_info(deps.as_ref().storage, 0u64),
-        LotteryInfo {
-            rand_round: 20170,
-            sequence: SIX_MATCH_SEQUENCE.to_string(),
-            awarded: true,
-            timestamp: execute_lottery_block.time,
-            block_height: execute_lottery_block.height,
-            prize_buckets: lottery_prize_buckets,
-            number_winners,
-            page: "".to_string(),
-            glow_prize_buckets,
-            total_user_shares: total_minted_shares
-        }
+    // Prizes of depositors not in donation mode can't be claimed by others
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::ClaimLotteryFor {
+            depositor: "addr0001".to_string(),
+            lottery_ids: vec![0u64],
+        },
     );
+    assert_eq!(res, Err(ContractError::NotInDonationMode {}));
 
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.current_lottery, 1u64);
-    assert_eq!(state.total_reserve, total_reserve);
-
-    let remaining_state_prize_buckets =
-        calculate_remaining_state_prize_buckets(state_prize_buckets, number_winners);
-
-    // Check award_available
-    assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
+    let simulation: SimulateClaimLotteryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateClaimLottery {
+                address: "addr0000".to_string(),
+                lottery_ids: vec![0u64],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
 
-    assert_eq!(res.messages, vec![]);
+    // Anyone can send the prizes to the charity
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::ClaimLotteryFor {
+            depositor: "addr0000".to_string(),
+            lottery_ids: vec![0u64],
+        },
+    )
+    .unwrap();
 
     assert_eq!(
-        res.attributes,
+        res.messages,
         vec![
-            attr("action", "execute_prize"),
-            attr(
-                "total_awarded_prize",
-                lottery_prize_buckets
-                    .iter()
-                    .fold(Uint256::zero(), |sum, val| sum + *val)
-                    .to_string()
-            ),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "charity".to_string(),
+                amount: vec![Coin {
+                    denom: String::from("uusd"),
+                    amount: simulation.net_ust,
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: DISTRIBUTOR_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&FaucetExecuteMsg::Spend {
+                    recipient: "charity".to_string(),
+                    amount: simulation.won_glow,
+                })
+                .unwrap(),
+            }))
         ]
     );
+
+    let charity: CharityResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Charity {
+                address: "charity".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        charity,
+        CharityResponse {
+            address: "charity".to_string(),
+            name: "Charity".to_string(),
+            verified: true,
+            donated_ust: Uint256::from(simulation.net_ust),
+            donated_glow: Uint256::from(simulation.won_glow),
+        }
+    );
+
+    // Unregistered charities keep their donated amounts, but don't receive prizes anymore
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(GOV_ADDR, &[]),
+        ExecuteMsg::UnregisterCharity {
+            address: "charity".to_string(),
+        },
+    )
+    .unwrap();
+
+    let charities: CharitiesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Charities {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        charities.charities,
+        vec![CharityResponse {
+            verified: false,
+            ..charity
+        }]
+    );
+
+    let beneficiary: PrizeBeneficiaryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::PrizeBeneficiary {
+                address: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(beneficiary.beneficiary, None);
 }
 
 #[test]
-fn execute_prize_one_winner_multiple_ranks() {
+fn syndicates() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    // Users buys winning ticket - 6 hits
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            SIX_MATCH_SEQUENCE,
-        )]),
-        operator: None,
-    };
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: DENOM.to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
-    );
-
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            ONE_MATCH_SEQUENCE,
-        )]),
-        operator: None,
-    };
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            FOUR_MATCH_SEQUENCE,
-        )]),
-        operator: None,
-    };
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            FOUR_MATCH_SEQUENCE_2,
-        )]),
-        operator: None,
-    };
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CreateSyndicate {
+            name: "Friends".to_string(),
+        },
+    )
+    .unwrap();
 
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            FOUR_MATCH_SEQUENCE_3,
-        )]),
-        operator: None,
+    let syndicate: SyndicateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Syndicate { syndicate_id: 0 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(syndicate.name, "Friends".to_string());
+    assert_eq!(syndicate.creator, "addr0000".to_string());
+    assert_eq!(syndicate.depositor, "syndicate/0".to_string());
+
+    let syndicate_deposit = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                             sender: &str,
+                             amount: u64,
+                             sequence: &str,
+                             operator: Option<String>,
+                             syndicate: u64|
+     -> Result<Response, ContractError> {
+        let info = mock_info(
+            sender,
+            &[Coin {
+                denom: DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        let msg = ExecuteMsg::Deposit {
+            encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(sequence)]),
+            operator,
+            syndicate: Some(syndicate),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg)
     };
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let address_raw = deps.api.addr_validate("addr0000").unwrap();
+    let res = syndicate_deposit(
+        &mut deps,
+        "addr0000",
+        TICKET_PRICE,
+        SIX_MATCH_SEQUENCE,
+        None,
+        1,
+    );
+    assert_eq!(res, Err(ContractError::InvalidSyndicate(1)));
 
-    // Get the number of minted aust
-    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+    let res = syndicate_deposit(
+        &mut deps,
+        "addr0000",
+        TICKET_PRICE,
+        SIX_MATCH_SEQUENCE,
+        Some("operator".to_string()),
+        0,
+    );
+    assert_eq!(res, Err(ContractError::SyndicateOperatorNotAllowed {}));
 
-    // Get the amount of minted_shares
-    let minted_shares = minted_aust * Uint256::from(5u128);
+    // Members deposit 2:3 in the syndicate, which holds the winning ticket
+    syndicate_deposit(
+        &mut deps,
+        "addr0000",
+        2 * TICKET_PRICE,
+        SIX_MATCH_SEQUENCE,
+        None,
+        0,
+    )
+    .unwrap();
+    syndicate_deposit(
+        &mut deps,
+        "addr0001",
+        3 * TICKET_PRICE,
+        ZERO_MATCH_SEQUENCE,
+        None,
+        0,
+    )
+    .unwrap();
 
-    // Check depositor info was updated correctly
+    let syndicate_addr = Addr::unchecked("syndicate/0");
+    let syndicate_info = read_depositor_info(deps.as_ref().storage, &syndicate_addr);
     assert_eq!(
-        read_depositor_info(deps.as_ref().storage, &address_raw),
-        DepositorInfo {
-            shares: minted_shares,
-            tickets: vec![
-                String::from(SIX_MATCH_SEQUENCE),
-                String::from(ONE_MATCH_SEQUENCE),
-                String::from(FOUR_MATCH_SEQUENCE),
-                String::from(FOUR_MATCH_SEQUENCE_2),
-                String::from(FOUR_MATCH_SEQUENCE_3),
-            ],
-            unbonding_info: vec![],
-            operator_addr: Addr::unchecked("")
-        }
+        syndicate_info.tickets,
+        vec![
+            String::from(SIX_MATCH_SEQUENCE),
+            String::from(ZERO_MATCH_SEQUENCE)
+        ]
     );
 
+    let query_member =
+        |deps: &OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>, address: &str| -> Uint256 {
+            let res: SyndicateMemberResponse = from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::SyndicateMember {
+                        syndicate_id: 0,
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            res.shares
+        };
+
+    let member_shares = query_member(&deps, "addr0000");
+    let other_member_shares = query_member(&deps, "addr0001");
+    assert_eq!(member_shares + other_member_shares, syndicate_info.shares);
+
+    // Run the lottery a week later
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
     let mut env = mock_env();
-    // Advance one week in time
     if let Duration::Time(time) = WEEK {
         env.block.time = env.block.time.plus_seconds(time);
     }
+    // Member shares are snapshotted before the lottery block
+    env.block.height += 100;
 
-    //Add aterra balance
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(55_000_000u128),
+            &Uint128::from(60_000_000u128),
         )],
     )]);
 
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-
-    // Get state prize buckets
-    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ExecuteLottery {},
+    )
+    .unwrap();
 
-    // Execute Lottery
-    let msg = ExecuteMsg::ExecuteLottery {};
-    let execute_lottery_block = env.block.clone();
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+            amount
+        } else {
+            panic!("DO NOT ENTER HERE")
+        }
+    } else {
+        panic!("DO NOT ENTER HERE");
+    };
 
-    // Check that state equals calculated prize
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.prize_buckets, state_prize_buckets);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(Uint256::from(sent_amount) * Decimal256::permille(RATE)),
+        }],
+    );
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &(Uint128::from(60_000_000u128) - sent_amount),
+        )],
+    )]);
 
-    // Advance block_time in time
     if let Duration::Time(time) = HOUR {
         env.block.time = env.block.time.plus_seconds(time);
     }
 
-    let msg = ExecuteMsg::ExecutePrize { limit: None };
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ExecutePrize { limit: None },
+    )
+    .unwrap();
 
-    let number_winners = [0, 0, 0, 0, 3, 0, 1];
-    let (lottery_prize_buckets, total_reserve) =
-        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
+    // Prize won by the syndicate
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    let lottery_info = read_lottery_info(deps.as_ref().storage, 0u64);
+    let prize_info = read_prize(deps.as_ref(), &syndicate_addr, 0u64).unwrap();
 
-    let (glow_prize_buckets, _) =
-        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
+    // The glow of the member is boosted as if they had won with their syndicate shares
+    let (prize_ust, prize_glow) = calculate_winner_prize(
+        &deps.as_mut().querier,
+        &config,
+        &prize_info,
+        &lottery_info,
+        &DepositorStatsInfo {
+            shares: member_shares,
+            ..read_depositor_stats_at_height(
+                deps.as_ref().storage,
+                &syndicate_addr,
+                lottery_info.block_height,
+            )
+        },
+        &deps.api.addr_validate("addr0000").unwrap(),
+    )
+    .unwrap();
 
-    println!(
-        "lottery_info: {:x?}",
-        read_lottery_info(deps.as_ref().storage, 0u64)
+    let member_ust = Uint128::from(
+        Uint256::from(prize_ust).multiply_ratio(member_shares, syndicate_info.shares),
+    );
+    let member_glow = Uint128::from(
+        Uint256::from(prize_glow).multiply_ratio(member_shares, syndicate_info.shares),
     );
 
+    // Members claim their share of the prize
+    let claim_msg = ExecuteMsg::ClaimSyndicateLottery {
+        syndicate_id: 0,
+        lottery_ids: vec![0u64],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        claim_msg.clone(),
+    )
+    .unwrap();
+
     assert_eq!(
-        read_lottery_info(deps.as_ref().storage, 0u64),
-        LotteryInfo {
-            rand_round: 20170,
-            sequence: SIX_MATCH_SEQUENCE.to_string(),
-            awarded: true,
-            timestamp: execute_lottery_block.time,
-            block_height: execute_lottery_block.height,
-            prize_buckets: lottery_prize_buckets,
-            number_winners,
-            page: "".to_string(),
-            glow_prize_buckets,
-            total_user_shares: minted_shares
-        }
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: String::from("uusd"),
+                    amount: member_ust,
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: DISTRIBUTOR_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&FaucetExecuteMsg::Spend {
+                    recipient: "addr0000".to_string(),
+                    amount: member_glow,
+                })
+                .unwrap(),
+            }))
+        ]
     );
 
-    let prizes = read_prize(deps.as_ref(), &address_raw, 0u64).unwrap();
-    assert_eq!(prizes.matches, number_winners);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        claim_msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::InvalidClaimPrizeAlreadyClaimed(0)));
 
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.current_lottery, 1u64);
-    assert_eq!(state.total_reserve, total_reserve);
+    // The prize is only claimed once all the members have claimed their share
+    let prize_info = read_prize(deps.as_ref(), &syndicate_addr, 0u64).unwrap();
+    assert!(!prize_info.claimed);
 
-    let remaining_state_prize_buckets =
-        calculate_remaining_state_prize_buckets(state_prize_buckets, number_winners);
+    // Non members don't get anything
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0002", &[]),
+        claim_msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::InsufficientClaimableFunds {}));
 
-    // From the initialization of the contract
-    assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        claim_msg,
+    )
+    .unwrap();
+    let prize_info = read_prize(deps.as_ref(), &syndicate_addr, 0u64).unwrap();
+    assert!(prize_info.claimed);
+
+    // A member exits to their own position, and the syndicate
+    // keeps the tickets it can still afford
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::ExitSyndicate { syndicate_id: 0 },
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("removed_tickets", "1")));
 
-    assert_eq!(res.messages, vec![]);
+    let syndicate_info = read_depositor_info(deps.as_ref().storage, &syndicate_addr);
+    assert_eq!(syndicate_info.shares, member_shares);
+    assert_eq!(syndicate_info.tickets.len(), 1);
 
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "execute_prize"),
-            attr(
-                "total_awarded_prize",
-                lottery_prize_buckets
-                    .iter()
-                    .fold(Uint256::zero(), |sum, val| sum + *val)
-                    .to_string()
-            ),
-        ]
+    let member_info = read_depositor_info(
+        deps.as_ref().storage,
+        &deps.api.addr_validate("addr0001").unwrap(),
+    );
+    assert_eq!(member_info.shares, other_member_shares);
+    assert_eq!(query_member(&deps, "addr0001"), Uint256::zero());
+
+    // The exit is recorded in the member's history
+    let history: DepositorHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::DepositorHistory {
+                address: "addr0001".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let exit = history
+        .history
+        .iter()
+        .find(|activity| activity.kind == DepositorActivityKind::ExitSyndicate)
+        .unwrap();
+    assert_eq!(exit.shares, other_member_shares);
+    assert_eq!(exit.tickets, 0);
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0001", &[]),
+        ExecuteMsg::ExitSyndicate { syndicate_id: 0 },
     );
+    assert_eq!(res, Err(ContractError::NotSyndicateMember {}));
 }
 
 #[test]
-fn execute_prize_multiple_winners_one_ticket() {
+fn relay_deposit() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            SIX_MATCH_SEQUENCE,
-        )]),
+    // The address of the signer has the prefix of the contract address
+    const CONTRACT: &str = "terra1s403t2q7pjfzghnu6vswdq3n38d9ju7dcvjprw";
+    let mut relay_env = mock_env();
+    relay_env.contract.address = Addr::unchecked(CONTRACT);
+
+    // Deposit intent signed off-chain by the holder of PUBKEY
+    const PUBKEY: &str = "AhJFzdc8qIYpe3eGHFOFFtbvhIkimke8Z0pk24ksqxEH";
+    const SIGNER: &str = "terra175aspnzmer83xhmxurmh768gzle3fc92f7ymsy";
+    const SIGNATURE: &str =
+        "BhCR18l3UBf7AatxqocgXMA+XRhEaqbt1bGZHofIDiJI+byKhTI1ESnMye7MPHO2lRIljxmZY5s4Hy6y2jSFTw==";
+
+    let intent = DepositIntent {
+        contract: CONTRACT.to_string(),
+        chain_id: mock_env().block.chain_id,
+        stable_token: "stable_token".to_string(),
+        amount: Uint128::from(TICKET_PRICE),
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from("abcdef")]),
         operator: None,
+        nonce: 0,
+        expires_at: 1_571_800_000,
     };
 
-    // User 0 buys winning ticket - 5 hits
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: DENOM.to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
-    );
-
-    let _res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    let relay_deposit = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                         env: Env,
+                         intent: DepositIntent|
+     -> Result<Response, ContractError> {
+        let amount = intent.amount;
+        execute(
+            deps.as_mut(),
+            env,
+            mock_info(
+                "relayer",
+                &[Coin {
+                    denom: "uusd".to_string(),
+                    amount,
+                }],
+            ),
+            ExecuteMsg::RelayDeposit {
+                intent,
+                signature: Binary::from_base64(SIGNATURE).unwrap(),
+                pubkey: Binary::from_base64(PUBKEY).unwrap(),
+            },
+        )
+    };
 
-    // User 1 buys winning ticket - 5 hits
-    let info = mock_info(
-        "addr1111",
-        &[Coin {
-            denom: DENOM.to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
+    // The signature doesn't cover a tampered intent
+    let res = relay_deposit(
+        &mut deps,
+        relay_env.clone(),
+        DepositIntent {
+            amount: Uint128::from(2 * TICKET_PRICE),
+            ..intent.clone()
+        },
     );
+    assert_eq!(res, Err(ContractError::InvalidRelaySignature {}));
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
-
-    // User 2 buys winning ticket - 5 hits
-    let info = mock_info(
-        "addr2222",
-        &[Coin {
-            denom: DENOM.to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
+    // Intents can't be relayed on other chains
+    let res = relay_deposit(
+        &mut deps,
+        relay_env.clone(),
+        DepositIntent {
+            chain_id: "columbus-5".to_string(),
+            ..intent.clone()
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidRelayIntent {}));
+
+    // Intents can't be relayed after they expire
+    let mut env = relay_env.clone();
+    env.block.time = Timestamp::from_seconds(intent.expires_at);
+    let res = relay_deposit(&mut deps, env, intent.clone());
+    assert_eq!(res, Err(ContractError::RelayIntentExpired {}));
+
+    // The relayer must front the amount of the intent
+    let res = execute(
+        deps.as_mut(),
+        relay_env.clone(),
+        mock_info("relayer", &[]),
+        ExecuteMsg::RelayDeposit {
+            intent: intent.clone(),
+            signature: Binary::from_base64(SIGNATURE).unwrap(),
+            pubkey: Binary::from_base64(PUBKEY).unwrap(),
+        },
+    );
+    assert_eq!(
+        res,
+        Err(ContractError::InvalidRelayFunds {
+            expected: Uint128::from(TICKET_PRICE),
+            got: Uint128::zero(),
+        })
     );
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    // Get the number of minted aust
-    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
-
-    // calculate the value of each deposit accounting for rounding errors
-    let each_lottery_shares = minted_aust;
-
-    // calculate the total minted_aust_value
-    let total_minted_shares = Uint256::from(3u128) * each_lottery_shares;
+    // The fronted funds are deposited for the signer, and the relayer
+    // is repaid with the stable token of the signer
+    let res = relay_deposit(&mut deps, relay_env.clone(), intent.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: ANCHOR.to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(TICKET_PRICE),
+                }],
+                msg: to_binary(&AnchorMsg::DepositStable {}).unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "stable_token".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: SIGNER.to_string(),
+                    recipient: "relayer".to_string(),
+                    amount: Uint128::from(TICKET_PRICE),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+    assert!(res.attributes.contains(&attr("depositor", SIGNER)));
+    assert!(res.attributes.contains(&attr("relayer", "relayer")));
 
-    let address_0 = deps.api.addr_validate("addr0000").unwrap();
-    let address_1 = deps.api.addr_validate("addr1111").unwrap();
-    let address_2 = deps.api.addr_validate("addr2222").unwrap();
+    let depositor_info = read_depositor_info(deps.as_ref().storage, &Addr::unchecked(SIGNER));
+    assert_eq!(
+        depositor_info.shares,
+        Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE)
+    );
+    assert_eq!(depositor_info.tickets, vec![String::from("abcdef")]);
 
-    let ticket = query_ticket_info(deps.as_ref(), String::from(SIX_MATCH_SEQUENCE)).unwrap();
+    let nonce: RelayNonceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RelayNonce {
+                address: SIGNER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(nonce.nonce, 1);
 
+    // The intent can't be replayed
+    let res = relay_deposit(&mut deps, relay_env, intent);
     assert_eq!(
-        ticket.holders,
-        vec![address_0.clone(), address_1, address_2]
+        res,
+        Err(ContractError::InvalidRelayNonce {
+            expected: 1,
+            got: 0
+        })
     );
+}
 
-    let mut env = mock_env();
-    // Advance one week in time
-    if let Duration::Time(time) = WEEK {
-        env.block.time = env.block.time.plus_seconds(time);
-    }
+#[test]
+fn execute_lottery() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
 
     //Add aterra balance
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(31_000_000u128),
-        )],
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
     )]);
 
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
-    // Get state prize buckets
-    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1u64),
+        }],
+    );
 
-    // Execute Lottery
     let msg = ExecuteMsg::ExecuteLottery {};
-    let execute_lottery_block = env.block.clone();
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    // Check that state equals calculated prize
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.prize_buckets, state_prize_buckets);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
 
-    // Advance block_time in time
-    if let Duration::Time(time) = HOUR {
+    match res {
+        Err(ContractError::InvalidLotteryExecutionFunds {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut env = mock_env();
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+
+    match res {
+        Err(ContractError::LotteryNotReady { next_lottery_time })
+            if next_lottery_time
+                == Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Advance one week in time
+    if let Duration::Time(time) = WEEK {
         env.block.time = env.block.time.plus_seconds(time);
     }
 
-    let msg = ExecuteMsg::ExecutePrize { limit: None };
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
 
-    let number_winners = [0, 0, 0, 0, 0, 0, 3];
-    let (lottery_prize_buckets, total_reserve) =
-        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
-    let (glow_prize_buckets, _) =
-        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
+    // Lottery cannot be run with 0 tickets participating
+    match res {
+        Err(ContractError::InvalidLotteryExecutionTickets {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    assert_eq!(
-        read_lottery_info(deps.as_ref().storage, 0u64),
-        LotteryInfo {
-            rand_round: 20170,
-            sequence: SIX_MATCH_SEQUENCE.to_string(),
-            awarded: true,
-            timestamp: execute_lottery_block.time,
-            block_height: execute_lottery_block.height,
-            prize_buckets: lottery_prize_buckets,
-            number_winners,
-            page: "".to_string(),
-            glow_prize_buckets,
-            total_user_shares: total_minted_shares
-        }
+    // Correct deposit - buys two tickets
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(2 * TICKET_PRICE).into(),
+        }],
     );
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
+            String::from(ZERO_MATCH_SEQUENCE),
+            String::from(ONE_MATCH_SEQUENCE),
+        ]),
+        operator: None,
+        syndicate: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-    let prizes = read_prize(deps.as_ref(), &address_0, 0u64).unwrap();
-    assert_eq!(prizes.matches, [0, 0, 0, 0, 0, 0, 1]);
+    // Calculate the number of minted_aust
+    let minted_aust = Uint256::from(2 * TICKET_PRICE) / Decimal256::permille(RATE);
 
-    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
-    assert_eq!(state.current_lottery, 1u64);
-    assert_eq!(state.total_reserve, total_reserve);
+    // Add minted_aust to our contract balance
+    deps.querier.increment_token_balance(
+        A_UST.to_string(),
+        MOCK_CONTRACT_ADDR.to_string(),
+        minted_aust.into(),
+    );
 
-    let remaining_state_prize_buckets =
-        calculate_remaining_state_prize_buckets(state_prize_buckets, number_winners);
+    // Execute lottery, now with tickets
+    let lottery_msg = ExecuteMsg::ExecuteLottery {};
+    let info = mock_info("addr0001", &[]);
 
-    // From the initialization of the contract
-    assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
 
-    assert_eq!(res.messages, vec![]);
+    // Get the sent_amount
+    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+            amount
+        } else {
+            panic!("DO NOT ENTER HERE")
+        }
+    } else {
+        panic!("DO NOT ENTER HERE");
+    };
 
+    // Messages is empty because aust hasn't appreciated so there is nothing to redeem
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "execute_prize"),
-            attr(
-                "total_awarded_prize",
-                lottery_prize_buckets
-                    .iter()
-                    .fold(Uint256::zero(), |sum, val| sum + *val)
+            attr("action", "execute_lottery"),
+            attr("redeemed_amount", sent_amount.to_string()),
+        ]
+    );
+
+    deps.querier.decrement_token_balance(
+        A_UST.to_string(),
+        MOCK_CONTRACT_ADDR.to_string(),
+        sent_amount,
+    );
+
+    // Directly check next_lottery_exec_time has been set up
+    let next_lottery_exec_time = query_state(deps.as_ref(), mock_env(), None)
+        .unwrap()
+        .next_lottery_exec_time;
+
+    assert_eq!(
+        next_lottery_exec_time,
+        Expiration::AtTime(env.block.time).add(HOUR).unwrap()
+    );
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    // Execute prize
+    let execute_prize_msg = ExecuteMsg::ExecutePrize { limit: None };
+    let res = execute(deps.as_mut(), env.clone(), info, execute_prize_msg.clone()).unwrap();
+
+    // Directly check next_lottery_time has been set up for next week
+    let next_lottery_time = query_state(deps.as_ref(), mock_env(), None)
+        .unwrap()
+        .next_lottery_time;
+
+    assert_eq!(
+        next_lottery_time,
+        Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME))
+            .add(WEEK)
+            .unwrap()
+    );
+
+    // Directly check next_lottery_exec_time has been set up to Never
+    let next_lottery_exec_time = query_state(deps.as_ref(), mock_env(), None)
+        .unwrap()
+        .next_lottery_exec_time;
+
+    assert_eq!(next_lottery_exec_time, Expiration::Never {});
+
+    assert_eq!(res.messages, vec![]);
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_prize"),
+            attr("total_awarded_prize", "0"),
+        ]
+    );
+
+    // Increase the aust exchange rate
+    let new_rate = Decimal256::permille(RATE * 2);
+    deps.querier.with_exchange_rate(new_rate);
+
+    // Advance one week in time
+    if let Duration::Time(time) = WEEK {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    // Execute 2nd lottery
+    let lottery_msg = ExecuteMsg::ExecuteLottery {};
+    let info = mock_info("addr0001", &[]);
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+
+    // Amount of aust to redeem
+
+    // Get this contracts aust balance
+    let contract_a_balance = query_token_balance(
+        deps.as_ref(),
+        Addr::unchecked(A_UST),
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+    )
+    .unwrap();
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    let pool = POOL.load(deps.as_ref().storage).unwrap();
+    let state = STATE.load(deps.as_ref().storage).unwrap();
+    let ExecuteLotteryRedeemedAustInfo { aust_to_redeem, .. } =
+        calculate_value_of_aust_to_be_redeemed_for_lottery(
+            &state,
+            &pool,
+            &config,
+            contract_a_balance,
+            new_rate,
+        );
+
+    // Verify amount to redeem for the lottery
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: A_UST.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: ANCHOR.to_string(),
+                amount: Uint128::from(aust_to_redeem),
+                msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_lottery"),
+            attr("redeemed_amount", aust_to_redeem.to_string()),
+        ]
+    );
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    // Execute prize
+    let _res = execute(deps.as_mut(), env.clone(), info, execute_prize_msg.clone()).unwrap();
+
+    // Directly check next_lottery_time has been set up for next week
+    let next_lottery_time = query_state(deps.as_ref(), mock_env(), None)
+        .unwrap()
+        .next_lottery_time;
+
+    assert_eq!(
+        next_lottery_time,
+        Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME))
+            .add(WEEK)
+            .unwrap()
+            .add(WEEK)
+            .unwrap()
+    );
+
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    println!("state: {:?}", state);
+
+    // Advance three weeks in time
+    if let Duration::Time(time) = WEEK {
+        env.block.time = env.block.time.plus_seconds(time * 3);
+    }
+
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(20_000_000u128),
+        )],
+    )]);
+
+    // Get this contracts aust balance
+    let contract_a_balance = query_token_balance(
+        deps.as_ref(),
+        Addr::unchecked(A_UST),
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+    )
+    .unwrap();
+
+    // Execute 3rd lottery
+    let lottery_msg = ExecuteMsg::ExecuteLottery {};
+    let info = mock_info("addr0001", &[]);
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    let pool = POOL.load(deps.as_ref().storage).unwrap();
+    let state = STATE.load(deps.as_ref().storage).unwrap();
+    let ExecuteLotteryRedeemedAustInfo { aust_to_redeem, .. } =
+        calculate_value_of_aust_to_be_redeemed_for_lottery(
+            &state,
+            &pool,
+            &config,
+            contract_a_balance,
+            new_rate,
+        );
+
+    // Check the attributes
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_lottery"),
+            attr("redeemed_amount", aust_to_redeem.to_string()),
+        ]
+    );
+
+    // Verify amount to redeem for the lottery
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: A_UST.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: ANCHOR.to_string(),
+                amount: Uint128::from(aust_to_redeem),
+                msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    // Execute prize
+    let _res = execute(deps.as_mut(), env.clone(), info, execute_prize_msg.clone()).unwrap();
+
+    // Directly check next_lottery_time has been set up for three weeks from the last lottery
+    // This checks the functionality of ensuring that the next_lottery_time is always
+    // set to a time in the future
+    let next_lottery_time = query_state(deps.as_ref(), mock_env(), None)
+        .unwrap()
+        .next_lottery_time;
+
+    assert_eq!(
+        next_lottery_time,
+        Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME))
+            .add(WEEK)
+            .unwrap()
+            .add(WEEK)
+            .unwrap()
+            .add(WEEK)
+            .unwrap()
+            .add(WEEK)
+            .unwrap()
+            .add(WEEK)
+            .unwrap()
+    );
+
+    // Advance to the next lottery time
+    if let Expiration::AtTime(next_lottery_time_seconds) = next_lottery_time {
+        env.block.time = next_lottery_time_seconds;
+    };
+
+    // Execute 4th lottery
+    // Confirm that you can run the lottery right at the next execution time
+    let lottery_msg = ExecuteMsg::ExecuteLottery {};
+    let info = mock_info("addr0001", &[]);
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    // Execute prize
+    let _res = execute(deps.as_mut(), env, info, execute_prize_msg).unwrap();
+
+    // Directly check next_lottery_time has been set up one week from the last execution time
+    let next_lottery_time = query_state(deps.as_ref(), mock_env(), None)
+        .unwrap()
+        .next_lottery_time;
+
+    assert_eq!(
+        next_lottery_time,
+        Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME))
+            .add(WEEK)
+            .unwrap()
+            .add(WEEK)
+            .unwrap()
+            .add(WEEK)
+            .unwrap()
+            .add(WEEK)
+            .unwrap()
+            .add(WEEK)
+            .unwrap()
+            .add(WEEK)
+            .unwrap()
+    );
+}
+
+#[test]
+fn execute_lottery_no_tickets() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
+
+    //Add aterra balance
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+    )]);
+
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
+
+    let info = mock_info("addr0001", &[]);
+
+    let mut env = mock_env();
+    // Advance one week in time
+    if let Duration::Time(time) = WEEK {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    // Execute Lottery
+    let msg = ExecuteMsg::ExecuteLottery {};
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    println!("res: {:?}", res);
+    match res {
+        Err(ContractError::InvalidLotteryExecutionTickets {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    let msg = ExecuteMsg::ExecutePrize { limit: None };
+    // Run lottery, no winners - should run correctly
+    let res = execute(deps.as_mut(), env, info, msg);
+    match res {
+        Err(ContractError::InvalidLotteryPrizeExecution {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn execute_prize_no_winners() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
+
+    // Users buys a non-winning ticket
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            ZERO_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let address_raw = deps.api.addr_validate("addr0000").unwrap();
+
+    // Get the number of minted aust
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+
+    // Get the amount of minted_shares
+    let minted_shares = minted_aust;
+
+    // Check depositor info was updated correctly
+    assert_eq!(
+        read_depositor_info(deps.as_ref().storage, &address_raw),
+        DepositorInfo {
+            shares: minted_shares,
+            tickets: vec![String::from(ZERO_MATCH_SEQUENCE)],
+            unbonding_info: vec![],
+            operator_addr: Addr::unchecked("")
+        }
+    );
+
+    //Advance time one week
+    let mut env = mock_env();
+    // Advance one week in time
+    if let Duration::Time(time) = WEEK {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    //Add aterra balance
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(20_000_000u128),
+        )],
+    )]);
+
+    // Calculate the prize buckets
+    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
+
+    // Execute lottery - should run correctly
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let msg = ExecuteMsg::ExecuteLottery {};
+
+    let execute_lottery_block = env.block.clone();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Check that state equals calculated prize
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.prize_buckets, state_prize_buckets);
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    let msg = ExecuteMsg::ExecutePrize { limit: None };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // Check lottery info was updated correctly
+    let awarded_prize = Uint256::zero();
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+
+    assert_eq!(
+        read_lottery_info(deps.as_ref().storage, 0u64),
+        LotteryInfo {
+            rand_round: 20170,
+            sequence: SIX_MATCH_SEQUENCE.to_string(),
+            awarded: true,
+            prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
+            number_winners: [0; NUM_PRIZE_BUCKETS],
+            page: "".to_string(),
+            glow_prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
+            timestamp: execute_lottery_block.time,
+            block_height: execute_lottery_block.height,
+            total_user_shares: minted_shares,
+            reserve: Uint256::zero(),
+        }
+    );
+
+    assert_eq!(state.current_lottery, 1u64);
+    assert_eq!(state.total_reserve, Uint256::zero());
+
+    // After executing the lottery, the prize buckets remain unchanged because there were no winning tickets
+    assert_eq!(state.prize_buckets, state_prize_buckets);
+
+    assert_eq!(res.messages, vec![]);
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_prize"),
+            attr("total_awarded_prize", awarded_prize.to_string()),
+        ]
+    );
+}
+
+#[test]
+fn execute_prize_one_winner() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
+
+    // Users buys winning ticket
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            SIX_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let address_raw = deps.api.addr_validate("addr0000").unwrap();
+
+    // Get the number of minted aust
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+
+    // Get the amount of minted_shares
+    let minted_shares = minted_aust;
+
+    // Check depositor info was updated correctly
+    assert_eq!(
+        read_depositor_info(deps.as_ref().storage, &address_raw),
+        DepositorInfo {
+            shares: minted_shares,
+            tickets: vec![String::from(SIX_MATCH_SEQUENCE)],
+            unbonding_info: vec![],
+            operator_addr: Addr::unchecked("")
+        }
+    );
+
+    // Run lottery, one winner (5 hits) - should run correctly
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+    //Advance time one week
+    let mut env = mock_env();
+
+    // Advance one week in time
+    if let Duration::Time(time) = WEEK {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    //Add aterra balance
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(20_000_000u128),
+        )],
+    )]);
+
+    // Check lottery info was updated correctly
+    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
+
+    // Execute Lottery
+    let msg = ExecuteMsg::ExecuteLottery {};
+
+    let execute_lottery_block = env.block.clone();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+    let msg = ExecuteMsg::ExecutePrize { limit: None };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let number_winners = [0, 0, 0, 0, 0, 0, 1];
+    let (lottery_prize_buckets, total_reserve) =
+        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
+
+    let (glow_prize_buckets, _) =
+        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
+
+    assert_eq!(
+        read_lottery_info(deps.as_ref().storage, 0u64),
+        LotteryInfo {
+            rand_round: 20170,
+            sequence: SIX_MATCH_SEQUENCE.to_string(),
+            awarded: true,
+            prize_buckets: lottery_prize_buckets,
+            number_winners,
+            page: "".to_string(),
+            glow_prize_buckets,
+            timestamp: execute_lottery_block.time,
+            block_height: execute_lottery_block.height,
+            total_user_shares: minted_shares,
+            reserve: total_reserve,
+        }
+    );
+
+    let prizes = read_prize(deps.as_ref(), &address_raw, 0u64).unwrap();
+    assert_eq!(prizes.matches, number_winners);
+
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+
+    assert_eq!(state.current_lottery, 1u64);
+    assert_eq!(state.total_reserve, total_reserve);
+
+    let remaining_state_prize_buckets =
+        calculate_remaining_state_prize_buckets(state_prize_buckets, number_winners);
+
+    // From the initialization of the contract
+    assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
+
+    assert_eq!(res.messages, vec![]);
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_prize"),
+            attr(
+                "total_awarded_prize",
+                lottery_prize_buckets[NUM_PRIZE_BUCKETS - 1].to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn execute_prize_winners_diff_ranks() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
+
+    // Users buys winning ticket - 5 hits
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            SIX_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let address_raw_0 = deps.api.addr_validate("addr0000").unwrap();
+
+    // Get the number of minted aust
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+
+    // Get the amount of minted_shares
+    let minted_shares = minted_aust;
+
+    // Check depositor info was updated correctly
+    assert_eq!(
+        read_depositor_info(deps.as_ref().storage, &address_raw_0),
+        DepositorInfo {
+            shares: minted_shares,
+            tickets: vec![String::from(SIX_MATCH_SEQUENCE)],
+            unbonding_info: vec![],
+            operator_addr: Addr::unchecked("")
+        }
+    );
+
+    // Users buys winning ticket - 2 hits
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            TWO_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let address_raw_1 = deps.api.addr_validate("addr0001").unwrap();
+
+    // Check depositor info was updated correctly
+    assert_eq!(
+        read_depositor_info(deps.as_ref().storage, &address_raw_1),
+        DepositorInfo {
+            shares: minted_shares,
+            tickets: vec![String::from(TWO_MATCH_SEQUENCE)],
+            unbonding_info: vec![],
+            operator_addr: Addr::unchecked("")
+        }
+    );
+
+    // Run lottery, one winner (6 hits), one winner (2 hits) - should run correctly
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    //Advance time one week
+    let mut env = mock_env();
+    // Advance one week in time
+    if let Duration::Time(time) = WEEK {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    //Add aterra balance
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(30_000_000u128),
+        )],
+    )]);
+
+    // Get the prize buckets
+    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
+
+    // Execute Lottery
+    let msg = ExecuteMsg::ExecuteLottery {};
+
+    let execute_lottery_block = env.block.clone();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    let msg = ExecuteMsg::ExecutePrize { limit: None };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let number_winners = [0, 0, 1, 0, 0, 0, 1];
+    let (lottery_prize_buckets, total_reserve) =
+        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
+    let (glow_prize_buckets, _) =
+        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
+
+    // Get the number of minted aust
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+
+    let each_shares_amount = minted_aust;
+
+    // calculate the total minted_aust_value
+    let total_minted_shares = Uint256::from(2u128) * each_shares_amount;
+
+    assert_eq!(
+        read_lottery_info(deps.as_ref().storage, 0u64),
+        LotteryInfo {
+            rand_round: 20170,
+            sequence: SIX_MATCH_SEQUENCE.to_string(),
+            awarded: true,
+            prize_buckets: lottery_prize_buckets,
+            number_winners,
+            page: "".to_string(),
+            glow_prize_buckets,
+            timestamp: execute_lottery_block.time,
+            block_height: execute_lottery_block.height,
+            total_user_shares: total_minted_shares,
+            reserve: total_reserve,
+        }
+    );
+
+    let prizes = read_prize(deps.as_ref(), &address_raw_0, 0u64).unwrap();
+    assert_eq!(prizes.matches, [0, 0, 0, 0, 0, 0, 1]);
+
+    let prizes = read_prize(deps.as_ref(), &address_raw_1, 0u64).unwrap();
+    assert_eq!(prizes.matches, [0, 0, 1, 0, 0, 0, 0]);
+
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+
+    assert_eq!(state.current_lottery, 1u64);
+
+    let remaining_state_prize_buckets =
+        calculate_remaining_state_prize_buckets(state_prize_buckets, number_winners);
+
+    // From the initialization of the contract
+    assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
+
+    assert_eq!(res.messages, vec![]);
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_prize"),
+            attr(
+                "total_awarded_prize",
+                lottery_prize_buckets
+                    .iter()
+                    .fold(Uint256::zero(), |sum, val| sum + *val)
+                    .to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn execute_prize_winners_same_rank() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
+
+    // Users buys winning ticket - 4 hits
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            FOUR_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let address_raw_0 = deps.api.addr_validate("addr0000").unwrap();
+
+    // Get the number of minted aust
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+
+    // Get the amount of minted_shares
+    let minted_shares = minted_aust;
+
+    // Check depositor info was updated correctly
+    assert_eq!(
+        read_depositor_info(deps.as_ref().storage, &address_raw_0),
+        DepositorInfo {
+            shares: minted_shares,
+            tickets: vec![String::from(FOUR_MATCH_SEQUENCE)],
+            unbonding_info: vec![],
+            operator_addr: Addr::unchecked("")
+        }
+    );
+
+    // Users buys winning ticket - 4 hits
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            FOUR_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let address_raw_1 = deps.api.addr_validate("addr0001").unwrap();
+
+    // Check depositor info was updated correctly
+    assert_eq!(
+        read_depositor_info(deps.as_ref().storage, &address_raw_1),
+        DepositorInfo {
+            shares: minted_shares,
+            tickets: vec![String::from(FOUR_MATCH_SEQUENCE)],
+            unbonding_info: vec![],
+            operator_addr: Addr::unchecked("")
+        }
+    );
+
+    // Run lottery, 2 winners (4 hits) - should run correctly
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+    let mut env = mock_env();
+    // Advance one week in time
+    if let Duration::Time(time) = WEEK {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    //Add aterra balance
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(30_000_000u128),
+        )],
+    )]);
+
+    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
+
+    // Execute Lottery
+    let msg = ExecuteMsg::ExecuteLottery {};
+
+    let execute_lottery_block = env.block.clone();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Check that state equals calculated prize
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.prize_buckets, state_prize_buckets);
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    let msg = ExecuteMsg::ExecutePrize { limit: None };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let number_winners = [0, 0, 0, 0, 2, 0, 0];
+    let (lottery_prize_buckets, total_reserve) =
+        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
+
+    let (glow_prize_buckets, _) =
+        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
+
+    // calculate the value of each deposit accounting for rounding errors
+    let each_minted_shares = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+
+    // calculate the total minted_aust_value
+    let total_minted_shares = Uint256::from(2u128) * each_minted_shares;
+
+    assert_eq!(
+        read_lottery_info(deps.as_ref().storage, 0u64),
+        LotteryInfo {
+            rand_round: 20170,
+            sequence: SIX_MATCH_SEQUENCE.to_string(),
+            awarded: true,
+            timestamp: execute_lottery_block.time,
+            block_height: execute_lottery_block.height,
+            prize_buckets: lottery_prize_buckets,
+            number_winners,
+            page: "".to_string(),
+            glow_prize_buckets,
+            total_user_shares: total_minted_shares,
+            reserve: total_reserve,
+        }
+    );
+
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.current_lottery, 1u64);
+    assert_eq!(state.total_reserve, total_reserve);
+
+    let remaining_state_prize_buckets =
+        calculate_remaining_state_prize_buckets(state_prize_buckets, number_winners);
+
+    // Check award_available
+    assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
+
+    assert_eq!(res.messages, vec![]);
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_prize"),
+            attr(
+                "total_awarded_prize",
+                lottery_prize_buckets
+                    .iter()
+                    .fold(Uint256::zero(), |sum, val| sum + *val)
+                    .to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn execute_prize_one_winner_multiple_ranks() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
+
+    // Users buys winning ticket - 6 hits
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            SIX_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            ONE_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            FOUR_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            FOUR_MATCH_SEQUENCE_2,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            FOUR_MATCH_SEQUENCE_3,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let address_raw = deps.api.addr_validate("addr0000").unwrap();
+
+    // Get the number of minted aust
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+
+    // Get the amount of minted_shares
+    let minted_shares = minted_aust * Uint256::from(5u128);
+
+    // Check depositor info was updated correctly
+    assert_eq!(
+        read_depositor_info(deps.as_ref().storage, &address_raw),
+        DepositorInfo {
+            shares: minted_shares,
+            tickets: vec![
+                String::from(SIX_MATCH_SEQUENCE),
+                String::from(ONE_MATCH_SEQUENCE),
+                String::from(FOUR_MATCH_SEQUENCE),
+                String::from(FOUR_MATCH_SEQUENCE_2),
+                String::from(FOUR_MATCH_SEQUENCE_3),
+            ],
+            unbonding_info: vec![],
+            operator_addr: Addr::unchecked("")
+        }
+    );
+
+    let mut env = mock_env();
+    // Advance one week in time
+    if let Duration::Time(time) = WEEK {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    //Add aterra balance
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(55_000_000u128),
+        )],
+    )]);
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+    // Get state prize buckets
+    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
+
+    // Execute Lottery
+    let msg = ExecuteMsg::ExecuteLottery {};
+    let execute_lottery_block = env.block.clone();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Check that state equals calculated prize
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.prize_buckets, state_prize_buckets);
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    let msg = ExecuteMsg::ExecutePrize { limit: None };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let number_winners = [0, 0, 0, 0, 3, 0, 1];
+    let (lottery_prize_buckets, total_reserve) =
+        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
+
+    let (glow_prize_buckets, _) =
+        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
+
+    println!(
+        "lottery_info: {:x?}",
+        read_lottery_info(deps.as_ref().storage, 0u64)
+    );
+
+    assert_eq!(
+        read_lottery_info(deps.as_ref().storage, 0u64),
+        LotteryInfo {
+            rand_round: 20170,
+            sequence: SIX_MATCH_SEQUENCE.to_string(),
+            awarded: true,
+            timestamp: execute_lottery_block.time,
+            block_height: execute_lottery_block.height,
+            prize_buckets: lottery_prize_buckets,
+            number_winners,
+            page: "".to_string(),
+            glow_prize_buckets,
+            total_user_shares: minted_shares,
+            reserve: total_reserve,
+        }
+    );
+
+    let prizes = read_prize(deps.as_ref(), &address_raw, 0u64).unwrap();
+    assert_eq!(prizes.matches, number_winners);
+
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.current_lottery, 1u64);
+    assert_eq!(state.total_reserve, total_reserve);
+
+    let remaining_state_prize_buckets =
+        calculate_remaining_state_prize_buckets(state_prize_buckets, number_winners);
+
+    // From the initialization of the contract
+    assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
+
+    assert_eq!(res.messages, vec![]);
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_prize"),
+            attr(
+                "total_awarded_prize",
+                lottery_prize_buckets
+                    .iter()
+                    .fold(Uint256::zero(), |sum, val| sum + *val)
+                    .to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn execute_prize_multiple_winners_one_ticket() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
+
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            SIX_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+
+    // User 0 buys winning ticket - 5 hits
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+
+    // User 1 buys winning ticket - 5 hits
+    let info = mock_info(
+        "addr1111",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+
+    // User 2 buys winning ticket - 5 hits
+    let info = mock_info(
+        "addr2222",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Get the number of minted aust
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+
+    // calculate the value of each deposit accounting for rounding errors
+    let each_lottery_shares = minted_aust;
+
+    // calculate the total minted_aust_value
+    let total_minted_shares = Uint256::from(3u128) * each_lottery_shares;
+
+    let address_0 = deps.api.addr_validate("addr0000").unwrap();
+    let address_1 = deps.api.addr_validate("addr1111").unwrap();
+    let address_2 = deps.api.addr_validate("addr2222").unwrap();
+
+    let ticket = query_ticket_info(deps.as_ref(), String::from(SIX_MATCH_SEQUENCE)).unwrap();
+
+    assert_eq!(
+        ticket.holders,
+        vec![address_0.clone(), address_1, address_2]
+    );
+
+    let mut env = mock_env();
+    // Advance one week in time
+    if let Duration::Time(time) = WEEK {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    //Add aterra balance
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(31_000_000u128),
+        )],
+    )]);
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+    // Get state prize buckets
+    let state_prize_buckets = calculate_prize_buckets(deps.as_ref());
+
+    // Execute Lottery
+    let msg = ExecuteMsg::ExecuteLottery {};
+    let execute_lottery_block = env.block.clone();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Check that state equals calculated prize
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.prize_buckets, state_prize_buckets);
+
+    // Advance block_time in time
+    if let Duration::Time(time) = HOUR {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+
+    let msg = ExecuteMsg::ExecutePrize { limit: None };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let number_winners = [0, 0, 0, 0, 0, 0, 3];
+    let (lottery_prize_buckets, total_reserve) =
+        calculate_lottery_prize_buckets(state_prize_buckets, number_winners, RESERVE_FACTOR);
+    let (glow_prize_buckets, _) =
+        calculate_lottery_prize_buckets(*GLOW_PRIZE_BUCKETS, number_winners, 0);
+
+    assert_eq!(
+        read_lottery_info(deps.as_ref().storage, 0u64),
+        LotteryInfo {
+            rand_round: 20170,
+            sequence: SIX_MATCH_SEQUENCE.to_string(),
+            awarded: true,
+            timestamp: execute_lottery_block.time,
+            block_height: execute_lottery_block.height,
+            prize_buckets: lottery_prize_buckets,
+            number_winners,
+            page: "".to_string(),
+            glow_prize_buckets,
+            total_user_shares: total_minted_shares,
+            reserve: total_reserve,
+        }
+    );
+
+    let prizes = read_prize(deps.as_ref(), &address_0, 0u64).unwrap();
+    assert_eq!(prizes.matches, [0, 0, 0, 0, 0, 0, 1]);
+
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.current_lottery, 1u64);
+    assert_eq!(state.total_reserve, total_reserve);
+
+    let remaining_state_prize_buckets =
+        calculate_remaining_state_prize_buckets(state_prize_buckets, number_winners);
+
+    // From the initialization of the contract
+    assert_eq!(state.prize_buckets, remaining_state_prize_buckets);
+
+    assert_eq!(res.messages, vec![]);
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_prize"),
+            attr(
+                "total_awarded_prize",
+                lottery_prize_buckets
+                    .iter()
+                    .fold(Uint256::zero(), |sum, val| sum + *val)
                     .to_string()
             ),
         ]
@@ -3946,6 +6544,7 @@ fn execute_prize_pagination() {
                 length = TICKET_LENGTH - 2
             )]),
             operator: None,
+            syndicate: None,
         };
         let info = mock_info(
             address.as_str(),
@@ -4318,6 +6917,7 @@ fn claim_rewards_one_referrer() {
             String::from(ZERO_MATCH_SEQUENCE),
         ]),
         operator: Some(String::from("operator")),
+        syndicate: None,
     };
 
     let deposit_amount = Uint256::from(2 * TICKET_PRICE).into();
@@ -4467,6 +7067,8 @@ fn execute_epoch_operations() {
         StateResponse {
             total_tickets: Uint256::zero(),
             total_reserve: Uint256::zero(),
+            withdrawal_fee_reserve: Uint256::zero(),
+            prize_reserve: Uint256::zero(),
             prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             current_lottery: 0,
             next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
@@ -4515,6 +7117,7 @@ fn small_withdraw() {
             ONE_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
     let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -4563,6 +7166,8 @@ fn small_withdraw() {
         StateResponse {
             total_tickets: Uint256::from(1u64),
             total_reserve: Uint256::zero(),
+            withdrawal_fee_reserve: Uint256::zero(),
+            prize_reserve: Uint256::zero(),
             prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             current_lottery: 0,
             next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
@@ -4639,19 +7244,38 @@ fn small_withdraw() {
         DepositorInfo {
             shares: minted_shares - withdrawn_shares,
             tickets: vec![],
-            unbonding_info: vec![Claim {
-                amount: Uint256::from(sent_amount) * Decimal256::permille(RATE),
-                release_at: WEEK.after(&env.block),
-            }],
+            unbonding_info: vec![],
             operator_addr: Addr::unchecked("")
         }
     );
 
+    // Check the withdrawal was placed in the unbonding ledger
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    let (release_epoch, release_at) = calculate_release_epoch(&config, &env.block).unwrap();
+    assert_eq!(
+        read_unbonding_claims(
+            deps.as_ref().storage,
+            &deps.api.addr_validate("addr0001").unwrap(),
+            None,
+            None
+        )
+        .unwrap(),
+        vec![(
+            release_epoch,
+            Claim {
+                amount: Uint256::from(sent_amount) * Decimal256::permille(RATE),
+                release_at,
+            }
+        )]
+    );
+
     assert_eq!(
         query_state(deps.as_ref(), mock_env(), None).unwrap(),
         StateResponse {
             total_tickets: Uint256::from(0u64),
             total_reserve: Uint256::zero(),
+            withdrawal_fee_reserve: Uint256::zero(),
+            prize_reserve: Uint256::zero(),
             prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             current_lottery: 0,
             next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
@@ -4712,6 +7336,7 @@ pub fn lottery_deposit_floor_edge_case() {
             ONE_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
     let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -4728,6 +7353,7 @@ pub fn lottery_deposit_floor_edge_case() {
             TWO_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
     let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -4780,6 +7406,7 @@ pub fn lottery_pool_solvency_edge_case() {
             ONE_MATCH_SEQUENCE,
         )]),
         operator: None,
+        syndicate: None,
     };
     let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -4828,6 +7455,8 @@ pub fn lottery_pool_solvency_edge_case() {
         StateResponse {
             total_tickets: Uint256::from(1u64),
             total_reserve: Uint256::zero(),
+            withdrawal_fee_reserve: Uint256::zero(),
+            prize_reserve: Uint256::zero(),
             prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             current_lottery: 0,
             next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
@@ -4909,7 +7538,264 @@ pub fn lottery_pool_solvency_edge_case() {
 }
 
 #[test]
-pub fn simulate_many_lotteries_with_one_depositor() {
+pub fn simulate_many_lotteries_with_one_depositor() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
+
+    let mut exchange_rate = Decimal256::from_str("1").unwrap();
+    deps.querier.with_exchange_rate(exchange_rate);
+
+    let num_weeks = 52;
+
+    let weekly_rate_multiplier =
+        Decimal256::from_str(&(1.2f64).powf(1.0 / (num_weeks as f64)).to_string()).unwrap();
+
+    // Mock aUST-UST exchange rate
+
+    // get env
+    let mut env = mock_env();
+
+    // mock instantiate the contracts
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
+
+    // User deposits and buys one ticket -------------------
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            TWO_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // Calculate the number of minted_aust
+    let minted_aust = Uint256::from(TICKET_PRICE) / exchange_rate;
+
+    let mut contract_balance = minted_aust;
+
+    let mut amount_distributed_through_lottery = Uint256::zero();
+
+    // Add the funds to the contract address -------------------
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &contract_balance.into())],
+    )]);
+
+    for i in 0..num_weeks {
+        // Get the pool size appreciation
+        let pool_size_appreciation = contract_balance * (exchange_rate * weekly_rate_multiplier)
+            - contract_balance * exchange_rate;
+
+        // Update the exchange rate
+        exchange_rate = exchange_rate * weekly_rate_multiplier;
+
+        // Mock aUST-UST exchange rate
+        deps.querier.with_exchange_rate(exchange_rate);
+
+        // Advance one week in time
+        if let Duration::Time(time) = WEEK {
+            env.block.time = env.block.time.plus_seconds(time);
+        }
+
+        // Execute the lottery
+
+        let lottery_msg = ExecuteMsg::ExecuteLottery {};
+        let info = mock_info("addr0001", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+
+        // Check how much aust was redeemed
+        let sent_amount =
+            if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+                let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+                if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+                    amount
+                } else {
+                    panic!("DO NOT ENTER HERE")
+                }
+            } else {
+                panic!("DO NOT ENTER HERE");
+            };
+
+        // Update the contract balance
+        contract_balance = contract_balance - Uint256::from(sent_amount);
+
+        // Add the funds to the contract address -------------------
+        deps.querier.with_token_balances(&[(
+            &A_UST.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &contract_balance.into())],
+        )]);
+
+        // Advance block_time in time
+        if let Duration::Time(time) = HOUR {
+            env.block.time = env.block.time.plus_seconds(time);
+        }
+
+        // Execute prize
+        let execute_prize_msg = ExecuteMsg::ExecutePrize { limit: None };
+        let _res = execute(deps.as_mut(), env.clone(), info, execute_prize_msg).unwrap();
+
+        amount_distributed_through_lottery += Uint256::from(sent_amount) * exchange_rate;
+
+        let percent_appreciation_towards_lottery =
+            Decimal256::from_uint256(Uint256::from(sent_amount)) * exchange_rate
+                / Decimal256::from_uint256(pool_size_appreciation);
+
+        assert!(
+            percent_appreciation_towards_lottery
+                >= Decimal256::percent(SPLIT_FACTOR) - Decimal256::permille(100)
+                && percent_appreciation_towards_lottery <= Decimal256::percent(SPLIT_FACTOR)
+        );
+
+        if i % 5 == 0 {
+            println!(
+                "Percent appreciation towards lottery after week {}: {}",
+                i, percent_appreciation_towards_lottery
+            );
+        }
+    }
+
+    println!("Initial pool size value: {}", minted_aust);
+    println!(
+        "Final pool size value: {}",
+        contract_balance * exchange_rate
+    );
+    println!(
+        "Total appreciation: {}",
+        Decimal256::from_uint256(contract_balance) * exchange_rate
+            / Decimal256::from_uint256(minted_aust)
+    );
+    println!(
+        "Total spent on lottery: {}",
+        amount_distributed_through_lottery
+    );
+    println!(
+        "Percent of total appreciation towards lottery: {}",
+        Decimal256::from_uint256(amount_distributed_through_lottery)
+            / Decimal256::from_uint256(
+                contract_balance * exchange_rate - minted_aust + amount_distributed_through_lottery
+            )
+    );
+
+    println!("Withdrawing half of user deposits");
+
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::Withdraw {
+        amount: Some((TICKET_PRICE / 2).into()),
+        instant: Some(true),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // Add a dummy ticket in order to pass validation
+
+    let mut state = STATE.load(deps.as_mut().storage).unwrap();
+    state.total_tickets = Uint256::one();
+    STATE.save(deps.as_mut().storage, &state).unwrap();
+
+    // Get the sent_amount
+    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+            amount
+        } else {
+            panic!("DO NOT ENTER HERE")
+        }
+    } else {
+        panic!("DO NOT ENTER HERE");
+    };
+
+    // Update the contract balance
+    contract_balance = contract_balance - Uint256::from(sent_amount);
+
+    // Add the funds to the contract address -------------------
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &contract_balance.into())],
+    )]);
+
+    for i in 0..num_weeks {
+        // Get the pool size appreciation
+        let pool_size_appreciation = contract_balance * (exchange_rate * weekly_rate_multiplier)
+            - contract_balance * exchange_rate;
+
+        // Update the exchange rate
+        exchange_rate = exchange_rate * weekly_rate_multiplier;
+
+        // Mock aUST-UST exchange rate
+        deps.querier.with_exchange_rate(exchange_rate);
+
+        // Advance one week in time
+        if let Duration::Time(time) = WEEK {
+            env.block.time = env.block.time.plus_seconds(time);
+        }
+
+        // Execute the lottery
+
+        let lottery_msg = ExecuteMsg::ExecuteLottery {};
+        let info = mock_info("addr0001", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+
+        // Check how much aust was redeemed
+        let sent_amount =
+            if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+                let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+                if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+                    amount
+                } else {
+                    panic!("DO NOT ENTER HERE")
+                }
+            } else {
+                panic!("DO NOT ENTER HERE");
+            };
+
+        // Update the contract balance
+        contract_balance = contract_balance - Uint256::from(sent_amount);
+
+        // Add the funds to the contract address -------------------
+        deps.querier.with_token_balances(&[(
+            &A_UST.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &contract_balance.into())],
+        )]);
+
+        // Advance block_time in time
+        if let Duration::Time(time) = HOUR {
+            env.block.time = env.block.time.plus_seconds(time);
+        }
+
+        // Execute prize
+        let execute_prize_msg = ExecuteMsg::ExecutePrize { limit: None };
+        let _res = execute(deps.as_mut(), env.clone(), info, execute_prize_msg).unwrap();
+
+        amount_distributed_through_lottery += Uint256::from(sent_amount) * exchange_rate;
+
+        let percent_appreciation_towards_lottery =
+            Decimal256::from_uint256(Uint256::from(sent_amount)) * exchange_rate
+                / Decimal256::from_uint256(pool_size_appreciation);
+
+        assert!(
+            percent_appreciation_towards_lottery
+                >= Decimal256::percent(SPLIT_FACTOR) - Decimal256::permille(100)
+                && percent_appreciation_towards_lottery <= Decimal256::percent(SPLIT_FACTOR)
+        );
+
+        if i % 5 == 0 {
+            println!(
+                "Percent appreciation towards lottery after week {}: {}",
+                i, percent_appreciation_towards_lottery
+            );
+        }
+    }
+}
+
+#[test]
+pub fn simulate_many_lotteries_with_one_sponsor() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
@@ -4930,6 +7816,12 @@ pub fn simulate_many_lotteries_with_one_depositor() {
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
+    // Add a dummy ticket in order to pass validation
+
+    let mut state = STATE.load(deps.as_mut().storage).unwrap();
+    state.total_tickets = Uint256::one();
+    STATE.save(deps.as_mut().storage, &state).unwrap();
+
     // User deposits and buys one ticket -------------------
     let info = mock_info(
         "addr0001",
@@ -4938,11 +7830,9 @@ pub fn simulate_many_lotteries_with_one_depositor() {
             amount: Uint256::from(TICKET_PRICE).into(),
         }],
     );
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            TWO_MATCH_SEQUENCE,
-        )]),
-        operator: None,
+    let msg = ExecuteMsg::Sponsor {
+        award: None,
+        prize_distribution: None,
     };
     let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -5018,11 +7908,7 @@ pub fn simulate_many_lotteries_with_one_depositor() {
             Decimal256::from_uint256(Uint256::from(sent_amount)) * exchange_rate
                 / Decimal256::from_uint256(pool_size_appreciation);
 
-        assert!(
-            percent_appreciation_towards_lottery
-                >= Decimal256::percent(SPLIT_FACTOR) - Decimal256::permille(100)
-                && percent_appreciation_towards_lottery <= Decimal256::percent(SPLIT_FACTOR)
-        );
+        assert!(percent_appreciation_towards_lottery >= Decimal256::percent(99));
 
         if i % 5 == 0 {
             println!(
@@ -5053,15 +7939,29 @@ pub fn simulate_many_lotteries_with_one_depositor() {
                 contract_balance * exchange_rate - minted_aust + amount_distributed_through_lottery
             )
     );
+}
 
-    println!("Withdrawing half of user deposits");
+#[test]
+pub fn simulate_many_lotteries_with_one_depositor_and_sponsor() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
 
-    let info = mock_info("addr0001", &[]);
-    let msg = ExecuteMsg::Withdraw {
-        amount: Some((TICKET_PRICE / 2).into()),
-        instant: Some(true),
-    };
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let mut exchange_rate = Decimal256::from_str("1").unwrap();
+    deps.querier.with_exchange_rate(exchange_rate);
+
+    let num_weeks = 52;
+
+    let weekly_rate_multiplier =
+        Decimal256::from_str(&(1.2f64).powf(1.0 / (num_weeks as f64)).to_string()).unwrap();
+
+    // Mock aUST-UST exchange rate
+
+    // get env
+    let mut env = mock_env();
+
+    // mock instantiate the contracts
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
     // Add a dummy ticket in order to pass validation
 
@@ -5069,20 +7969,46 @@ pub fn simulate_many_lotteries_with_one_depositor() {
     state.total_tickets = Uint256::one();
     STATE.save(deps.as_mut().storage, &state).unwrap();
 
-    // Get the sent_amount
-    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
-        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
-        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
-            amount
-        } else {
-            panic!("DO NOT ENTER HERE")
-        }
-    } else {
-        panic!("DO NOT ENTER HERE");
+    // User deposits and buys one ticket -------------------
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+    let msg = ExecuteMsg::Sponsor {
+        award: None,
+        prize_distribution: None,
     };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-    // Update the contract balance
-    contract_balance = contract_balance - Uint256::from(sent_amount);
+    // Calculate the number of minted_aust
+    let mut minted_aust = Uint256::from(TICKET_PRICE) / exchange_rate;
+
+    // User deposits and buys one ticket -------------------
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint256::from(TICKET_PRICE).into(),
+        }],
+    );
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            TWO_MATCH_SEQUENCE,
+        )]),
+        operator: None,
+        syndicate: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // Calculate the number of minted_aust
+    minted_aust += Uint256::from(TICKET_PRICE) / exchange_rate;
+
+    let mut contract_balance = minted_aust;
+
+    let mut amount_distributed_through_lottery = Uint256::zero();
 
     // Add the funds to the contract address -------------------
     deps.querier.with_token_balances(&[(
@@ -5149,11 +8075,7 @@ pub fn simulate_many_lotteries_with_one_depositor() {
             Decimal256::from_uint256(Uint256::from(sent_amount)) * exchange_rate
                 / Decimal256::from_uint256(pool_size_appreciation);
 
-        assert!(
-            percent_appreciation_towards_lottery
-                >= Decimal256::percent(SPLIT_FACTOR) - Decimal256::permille(100)
-                && percent_appreciation_towards_lottery <= Decimal256::percent(SPLIT_FACTOR)
-        );
+        assert!(percent_appreciation_towards_lottery <= Decimal256::percent(88));
 
         if i % 5 == 0 {
             println!(
@@ -5162,29 +8084,37 @@ pub fn simulate_many_lotteries_with_one_depositor() {
             );
         }
     }
-}
-
-#[test]
-pub fn simulate_many_lotteries_with_one_sponsor() {
-    // Initialize contract
-    let mut deps = mock_dependencies(&[]);
-
-    let mut exchange_rate = Decimal256::from_str("1").unwrap();
-    deps.querier.with_exchange_rate(exchange_rate);
-
-    let num_weeks = 52;
-
-    let weekly_rate_multiplier =
-        Decimal256::from_str(&(1.2f64).powf(1.0 / (num_weeks as f64)).to_string()).unwrap();
 
-    // Mock aUST-UST exchange rate
+    println!("Initial pool size value: {}", minted_aust);
+    println!(
+        "Final pool size value: {}",
+        contract_balance * exchange_rate
+    );
+    println!(
+        "Total appreciation: {}",
+        Decimal256::from_uint256(contract_balance) * exchange_rate
+            / Decimal256::from_uint256(minted_aust)
+    );
+    println!(
+        "Total spent on lottery: {}",
+        amount_distributed_through_lottery
+    );
+    println!(
+        "Percent of total appreciation towards lottery: {}",
+        Decimal256::from_uint256(amount_distributed_through_lottery)
+            / Decimal256::from_uint256(
+                contract_balance * exchange_rate - minted_aust + amount_distributed_through_lottery
+            )
+    );
 
-    // get env
-    let mut env = mock_env();
+    println!("Withdrawing half of user deposits");
 
-    // mock instantiate the contracts
-    mock_instantiate(&mut deps);
-    mock_register_contracts(deps.as_mut());
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::Withdraw {
+        amount: Some((TICKET_PRICE / 2).into()),
+        instant: Some(true),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     // Add a dummy ticket in order to pass validation
 
@@ -5192,26 +8122,20 @@ pub fn simulate_many_lotteries_with_one_sponsor() {
     state.total_tickets = Uint256::one();
     STATE.save(deps.as_mut().storage, &state).unwrap();
 
-    // User deposits and buys one ticket -------------------
-    let info = mock_info(
-        "addr0001",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
-    );
-    let msg = ExecuteMsg::Sponsor {
-        award: None,
-        prize_distribution: None,
+    // Get the sent_amount
+    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
+        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
+            amount
+        } else {
+            panic!("DO NOT ENTER HERE")
+        }
+    } else {
+        panic!("DO NOT ENTER HERE");
     };
-    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-
-    // Calculate the number of minted_aust
-    let minted_aust = Uint256::from(TICKET_PRICE) / exchange_rate;
 
-    let mut contract_balance = minted_aust;
-
-    let mut amount_distributed_through_lottery = Uint256::zero();
+    // Update the contract balance
+    contract_balance = contract_balance - Uint256::from(sent_amount);
 
     // Add the funds to the contract address -------------------
     deps.querier.with_token_balances(&[(
@@ -5278,7 +8202,7 @@ pub fn simulate_many_lotteries_with_one_sponsor() {
             Decimal256::from_uint256(Uint256::from(sent_amount)) * exchange_rate
                 / Decimal256::from_uint256(pool_size_appreciation);
 
-        assert!(percent_appreciation_towards_lottery >= Decimal256::percent(99));
+        // assert!(percent_appreciation_towards_lottery <= Decimal256::percent(90));
 
         if i % 5 == 0 {
             println!(
@@ -5287,32 +8211,10 @@ pub fn simulate_many_lotteries_with_one_sponsor() {
             );
         }
     }
-
-    println!("Initial pool size value: {}", minted_aust);
-    println!(
-        "Final pool size value: {}",
-        contract_balance * exchange_rate
-    );
-    println!(
-        "Total appreciation: {}",
-        Decimal256::from_uint256(contract_balance) * exchange_rate
-            / Decimal256::from_uint256(minted_aust)
-    );
-    println!(
-        "Total spent on lottery: {}",
-        amount_distributed_through_lottery
-    );
-    println!(
-        "Percent of total appreciation towards lottery: {}",
-        Decimal256::from_uint256(amount_distributed_through_lottery)
-            / Decimal256::from_uint256(
-                contract_balance * exchange_rate - minted_aust + amount_distributed_through_lottery
-            )
-    );
 }
 
 #[test]
-pub fn simulate_many_lotteries_with_one_depositor_and_sponsor() {
+pub fn simulate_jackpot_growth_with_one_depositor() {
     // Initialize contract
     let mut deps = mock_dependencies(&[]);
 
@@ -5333,53 +8235,34 @@ pub fn simulate_many_lotteries_with_one_depositor_and_sponsor() {
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    // Add a dummy ticket in order to pass validation
-
-    let mut state = STATE.load(deps.as_mut().storage).unwrap();
-    state.total_tickets = Uint256::one();
-    STATE.save(deps.as_mut().storage, &state).unwrap();
-
-    // User deposits and buys one ticket -------------------
-    let info = mock_info(
-        "addr0001",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
-        }],
-    );
-    let msg = ExecuteMsg::Sponsor {
-        award: None,
-        prize_distribution: None,
-    };
-    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-
-    // Calculate the number of minted_aust
-    let mut minted_aust = Uint256::from(TICKET_PRICE) / exchange_rate;
-
     // User deposits and buys one ticket -------------------
     let info = mock_info(
         "addr0001",
         &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint256::from(TICKET_PRICE).into(),
+            amount: Uint256::from(3 * TICKET_PRICE).into(),
         }],
     );
     let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
-            TWO_MATCH_SEQUENCE,
-        )]),
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
+            String::from(THREE_MATCH_SEQUENCE),
+            String::from(FOUR_MATCH_SEQUENCE),
+        ]),
         operator: None,
+        syndicate: None,
     };
     let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     // Calculate the number of minted_aust
-    minted_aust += Uint256::from(TICKET_PRICE) / exchange_rate;
+    let minted_aust = Uint256::from(3 * TICKET_PRICE) / exchange_rate;
 
     let mut contract_balance = minted_aust;
 
     let mut amount_distributed_through_lottery = Uint256::zero();
 
     // Add the funds to the contract address -------------------
+    // This overwrites the aust from INITIAL_DEPOSIT_AMOUNT
+    // but that is good for making it easier to interpret the results of the lottery
     deps.querier.with_token_balances(&[(
         &A_UST.to_string(),
         &[(&MOCK_CONTRACT_ADDR.to_string(), &contract_balance.into())],
@@ -5401,11 +8284,22 @@ pub fn simulate_many_lotteries_with_one_depositor_and_sponsor() {
             env.block.time = env.block.time.plus_seconds(time);
         }
 
-        // Execute the lottery
+        // Execute the lottery
+
+        let lottery_msg = ExecuteMsg::ExecuteLottery {};
+        let info = mock_info("addr0001", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+
+        if i % 5 == 0 {
+            // Remaining prizes in state
+
+            let state = STATE.load(deps.as_ref().storage).unwrap();
 
-        let lottery_msg = ExecuteMsg::ExecuteLottery {};
-        let info = mock_info("addr0001", &[]);
-        let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+            println!(
+                "Jackpot size after week {} of prize execution: {:?}",
+                i, state.prize_buckets[5]
+            );
+        }
 
         // Check how much aust was redeemed
         let sent_amount =
@@ -5444,775 +8338,1377 @@ pub fn simulate_many_lotteries_with_one_depositor_and_sponsor() {
             Decimal256::from_uint256(Uint256::from(sent_amount)) * exchange_rate
                 / Decimal256::from_uint256(pool_size_appreciation);
 
-        assert!(percent_appreciation_towards_lottery <= Decimal256::percent(88));
-
-        if i % 5 == 0 {
-            println!(
-                "Percent appreciation towards lottery after week {}: {}",
-                i, percent_appreciation_towards_lottery
-            );
-        }
+        assert!(percent_appreciation_towards_lottery <= Decimal256::percent(SPLIT_FACTOR));
     }
+}
 
-    println!("Initial pool size value: {}", minted_aust);
-    println!(
-        "Final pool size value: {}",
-        contract_balance * exchange_rate
-    );
-    println!(
-        "Total appreciation: {}",
-        Decimal256::from_uint256(contract_balance) * exchange_rate
-            / Decimal256::from_uint256(minted_aust)
-    );
-    println!(
-        "Total spent on lottery: {}",
-        amount_distributed_through_lottery
+#[test]
+pub fn ceil_helper_function() {
+    // Test ceiling
+    let res = uint256_times_decimal256_ceil(
+        Uint256::from(5u128),
+        Decimal256::from_ratio(Uint256::from(1u128), Uint256::from(2u128)),
     );
-    println!(
-        "Percent of total appreciation towards lottery: {}",
-        Decimal256::from_uint256(amount_distributed_through_lottery)
-            / Decimal256::from_uint256(
-                contract_balance * exchange_rate - minted_aust + amount_distributed_through_lottery
-            )
+    assert_eq!(res, Uint256::from(3u128));
+
+    // Test stay
+    let res = uint256_times_decimal256_ceil(
+        Uint256::from(5u128),
+        Decimal256::from_ratio(Uint256::from(1u128), Uint256::from(5u128)),
     );
+    assert_eq!(res, Uint256::from(1u128));
+}
 
-    println!("Withdrawing half of user deposits");
+#[test]
+pub fn calculate_max_bound_and_minimum_matches_for_winning_ticket() {
+    let ticket = "abcdea";
 
-    let info = mock_info("addr0001", &[]);
-    let msg = ExecuteMsg::Withdraw {
-        amount: Some((TICKET_PRICE / 2).into()),
-        instant: Some(true),
-    };
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    // Test with prize distribution with zeros for the two first buckets
 
-    // Add a dummy ticket in order to pass validation
+    let prize_distribution: [Decimal256; NUM_PRIZE_BUCKETS] = [
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::percent(20),
+        Decimal256::percent(20),
+        Decimal256::percent(20),
+        Decimal256::percent(20),
+        Decimal256::percent(20),
+    ];
 
-    let mut state = STATE.load(deps.as_mut().storage).unwrap();
-    state.total_tickets = Uint256::one();
-    STATE.save(deps.as_mut().storage, &state).unwrap();
+    let minimum_matches_for_winning_ticket =
+        get_minimum_matches_for_winning_ticket(prize_distribution).unwrap();
 
-    // Get the sent_amount
-    let sent_amount = if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
-        let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
-        if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
-            amount
-        } else {
-            panic!("DO NOT ENTER HERE")
-        }
-    } else {
-        panic!("DO NOT ENTER HERE");
-    };
+    assert_eq!(minimum_matches_for_winning_ticket, 2);
 
-    // Update the contract balance
-    contract_balance = contract_balance - Uint256::from(sent_amount);
+    let min_bound = &ticket[..minimum_matches_for_winning_ticket];
 
-    // Add the funds to the contract address -------------------
-    deps.querier.with_token_balances(&[(
-        &A_UST.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &contract_balance.into())],
-    )]);
+    assert_eq!(min_bound, "ab");
 
-    for i in 0..num_weeks {
-        // Get the pool size appreciation
-        let pool_size_appreciation = contract_balance * (exchange_rate * weekly_rate_multiplier)
-            - contract_balance * exchange_rate;
+    let max_bound = calculate_max_bound(min_bound, minimum_matches_for_winning_ticket);
 
-        // Update the exchange rate
-        exchange_rate = exchange_rate * weekly_rate_multiplier;
+    assert_eq!(max_bound, "abffff");
 
-        // Mock aUST-UST exchange rate
-        deps.querier.with_exchange_rate(exchange_rate);
+    // Test with prize distribution with zeros for the first buckets
 
-        // Advance one week in time
-        if let Duration::Time(time) = WEEK {
-            env.block.time = env.block.time.plus_seconds(time);
-        }
+    let prize_distribution: [Decimal256; NUM_PRIZE_BUCKETS] = [
+        Decimal256::zero(),
+        Decimal256::percent(1),
+        Decimal256::percent(19),
+        Decimal256::percent(20),
+        Decimal256::percent(20),
+        Decimal256::percent(20),
+        Decimal256::percent(20),
+    ];
 
-        // Execute the lottery
+    let minimum_matches_for_winning_ticket =
+        get_minimum_matches_for_winning_ticket(prize_distribution).unwrap();
 
-        let lottery_msg = ExecuteMsg::ExecuteLottery {};
-        let info = mock_info("addr0001", &[]);
-        let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+    assert_eq!(minimum_matches_for_winning_ticket, 1);
 
-        // Check how much aust was redeemed
-        let sent_amount =
-            if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
-                let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
-                if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
-                    amount
-                } else {
-                    panic!("DO NOT ENTER HERE")
-                }
-            } else {
-                panic!("DO NOT ENTER HERE");
+    let min_bound = &ticket[..minimum_matches_for_winning_ticket];
+
+    assert_eq!(min_bound, "a");
+
+    let max_bound = calculate_max_bound(min_bound, minimum_matches_for_winning_ticket);
+
+    assert_eq!(max_bound, "afffff");
+
+    // Test with prize distribution with zeros until the last bucket
+
+    let prize_distribution: [Decimal256; NUM_PRIZE_BUCKETS] = [
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::percent(100),
+    ];
+
+    let minimum_matches_for_winning_ticket =
+        get_minimum_matches_for_winning_ticket(prize_distribution).unwrap();
+
+    assert_eq!(minimum_matches_for_winning_ticket, 6);
+
+    let min_bound = &ticket[..minimum_matches_for_winning_ticket];
+
+    assert_eq!(min_bound, "abcdea");
+
+    let max_bound = calculate_max_bound(min_bound, minimum_matches_for_winning_ticket);
+
+    assert_eq!(max_bound, "abcdea");
+
+    // Expect an error when prize distribution is all zeros
+
+    let prize_distribution: [Decimal256; NUM_PRIZE_BUCKETS] = [
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::zero(),
+    ];
+
+    let minimum_matches_for_winning_ticket =
+        get_minimum_matches_for_winning_ticket(prize_distribution);
+
+    let err = Err(StdError::generic_err(
+        "The minimum matches for a winning ticket could not be calculated due to a malforming of the prize distribution"
+    ));
+
+    assert_eq!(minimum_matches_for_winning_ticket, err);
+}
+
+#[test]
+pub fn test_ticket_encoding_and_decoding() {
+    // Test inverse functionality #1
+    let combinations = vec![
+        String::from(THREE_MATCH_SEQUENCE),
+        String::from(ZERO_MATCH_SEQUENCE),
+    ];
+    let encoded_tickets = vec_string_tickets_to_encoded_tickets(combinations.clone());
+    println!("{}", encoded_tickets);
+    let decoded_combinations =
+        base64_encoded_tickets_to_vec_string_tickets(encoded_tickets).unwrap();
+    println!("{:?}", decoded_combinations);
+    assert_eq!(combinations, decoded_combinations);
+
+    // Test inverse functionality #2
+    let combinations = vec![String::from("000000")];
+    let encoded_tickets = vec_string_tickets_to_encoded_tickets(combinations.clone());
+    let decoded_combinations =
+        base64_encoded_tickets_to_vec_string_tickets(encoded_tickets).unwrap();
+    println!("{:?}", decoded_combinations);
+    assert_eq!(combinations, decoded_combinations);
+
+    // Test giving random data
+    let encoded_tickets = String::from("aowief");
+    let decoded_combinations = base64_encoded_tickets_to_vec_string_tickets(encoded_tickets);
+    match decoded_combinations {
+        Err(e)
+            if e == StdError::generic_err(
+                "Couldn't base64 decode the encoded tickets.".to_string(),
+            ) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Test giving data with wrong ticket length
+    let encoded_tickets = String::from("EjRWeA==");
+    let decoded_combinations = base64_encoded_tickets_to_vec_string_tickets(encoded_tickets);
+    match decoded_combinations {
+        Err(e) if e == StdError::generic_err("Decoded tickets wrong length.") => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+pub fn test_query_prizes() {
+    // Add some prizes
+
+    let mut deps = mock_dependencies(&[]);
+
+    // get env
+    let mut _env = mock_env();
+
+    // mock instantiate the contracts
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
+
+    // Query them
+
+    for i in 0..10 {
+        for j in 0..3 {
+            let prize = PrizeInfo {
+                claimed: false,
+                matches: [i, j, 2, 3, 1, 3, 3],
             };
 
-        // Update the contract balance
-        contract_balance = contract_balance - Uint256::from(sent_amount);
+            PRIZES
+                .save(
+                    deps.as_mut().storage,
+                    (
+                        U64Key::from(i as u64),
+                        &Addr::unchecked(format!("addr000{}", j)),
+                    ),
+                    &prize,
+                )
+                .unwrap();
+        }
+    }
 
-        // Add the funds to the contract address -------------------
-        deps.querier.with_token_balances(&[(
-            &A_UST.to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &contract_balance.into())],
-        )]);
+    let lottery_prizes = read_lottery_prizes(deps.as_ref(), 2, None, None).unwrap();
 
-        // Advance block_time in time
-        if let Duration::Time(time) = HOUR {
-            env.block.time = env.block.time.plus_seconds(time);
-        }
+    let expected_prizes = (0..3)
+        .map(|i| {
+            (
+                Addr::unchecked(format!("addr000{}", i)),
+                PrizeInfo {
+                    claimed: false,
+                    matches: [2, i, 2, 3, 1, 3, 3],
+                },
+            )
+        })
+        .collect::<Vec<_>>();
 
-        // Execute prize
-        let execute_prize_msg = ExecuteMsg::ExecutePrize { limit: None };
-        let _res = execute(deps.as_mut(), env.clone(), info, execute_prize_msg).unwrap();
+    assert_eq!(lottery_prizes, expected_prizes);
 
-        amount_distributed_through_lottery += Uint256::from(sent_amount) * exchange_rate;
+    println!("{:?}", lottery_prizes);
 
-        let percent_appreciation_towards_lottery =
-            Decimal256::from_uint256(Uint256::from(sent_amount)) * exchange_rate
-                / Decimal256::from_uint256(pool_size_appreciation);
+    // Test start after
 
-        // assert!(percent_appreciation_towards_lottery <= Decimal256::percent(90));
+    let start_after = Some(Addr::unchecked("addr0002"));
+    let lottery_prizes = read_lottery_prizes(deps.as_ref(), 2, start_after, None).unwrap();
+    assert_eq!(lottery_prizes.len(), 0);
 
-        if i % 5 == 0 {
-            println!(
-                "Percent appreciation towards lottery after week {}: {}",
-                i, percent_appreciation_towards_lottery
-            );
-        }
-    }
+    // Test limit
+
+    let limit = Some(1);
+    let lottery_prizes = read_lottery_prizes(deps.as_ref(), 2, None, limit).unwrap();
+    assert_eq!(lottery_prizes.len(), 1);
 }
 
 #[test]
-pub fn simulate_jackpot_growth_with_one_depositor() {
-    // Initialize contract
-    let mut deps = mock_dependencies(&[]);
+pub fn test_calculate_boost_multiplier() {
+    // Test #1
 
-    let mut exchange_rate = Decimal256::from_str("1").unwrap();
-    deps.querier.with_exchange_rate(exchange_rate);
+    let boost_config = BoostConfig {
+        base_multiplier: Decimal256::percent(20),
+        max_multiplier: Decimal256::one(),
+        total_voting_power_weight: Decimal256::percent(200),
+    };
 
-    let num_weeks = 52;
+    let snapshotted_user_lottery_deposit = Uint256::from(100u128);
+    let snapshotted_total_user_lottery_deposits = Uint256::from(200u128);
 
-    let weekly_rate_multiplier =
-        Decimal256::from_str(&(1.2f64).powf(1.0 / (num_weeks as f64)).to_string()).unwrap();
+    let snapshotted_user_voting_balance = Uint128::from(20u128);
+    let snapshotted_total_voting_balance = Uint128::from(100u128);
 
-    // Mock aUST-UST exchange rate
+    let multiplier = calculate_boost_multiplier(
+        boost_config,
+        snapshotted_user_lottery_deposit,
+        snapshotted_total_user_lottery_deposits,
+        snapshotted_user_voting_balance,
+        snapshotted_total_voting_balance,
+    );
 
-    // get env
-    let mut env = mock_env();
+    println!("{}", multiplier);
+    assert_eq!(multiplier, Decimal256::percent(36));
 
-    // mock instantiate the contracts
-    mock_instantiate(&mut deps);
-    mock_register_contracts(deps.as_mut());
+    // Test #2
 
-    // User deposits and buys one ticket -------------------
-    let info = mock_info(
-        "addr0001",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint256::from(3 * TICKET_PRICE).into(),
-        }],
-    );
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
-            String::from(THREE_MATCH_SEQUENCE),
-            String::from(FOUR_MATCH_SEQUENCE),
-        ]),
-        operator: None,
+    let boost_config = BoostConfig {
+        base_multiplier: Decimal256::percent(20),
+        max_multiplier: Decimal256::one(),
+        total_voting_power_weight: Decimal256::percent(200),
     };
-    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-    // Calculate the number of minted_aust
-    let minted_aust = Uint256::from(3 * TICKET_PRICE) / exchange_rate;
+    let snapshotted_user_lottery_deposit = Uint256::from(100u128);
+    let snapshotted_total_user_lottery_deposits = Uint256::from(200u128);
 
-    let mut contract_balance = minted_aust;
+    let snapshotted_user_voting_balance = Uint128::from(80u128);
+    let snapshotted_total_voting_balance = Uint128::from(100u128);
 
-    let mut amount_distributed_through_lottery = Uint256::zero();
+    let multiplier = calculate_boost_multiplier(
+        boost_config,
+        snapshotted_user_lottery_deposit,
+        snapshotted_total_user_lottery_deposits,
+        snapshotted_user_voting_balance,
+        snapshotted_total_voting_balance,
+    );
 
-    // Add the funds to the contract address -------------------
-    // This overwrites the aust from INITIAL_DEPOSIT_AMOUNT
-    // but that is good for making it easier to interpret the results of the lottery
-    deps.querier.with_token_balances(&[(
-        &A_UST.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &contract_balance.into())],
-    )]);
+    println!("{}", multiplier);
+    assert_eq!(multiplier, Decimal256::percent(84));
 
-    for i in 0..num_weeks {
-        // Get the pool size appreciation
-        let pool_size_appreciation = contract_balance * (exchange_rate * weekly_rate_multiplier)
-            - contract_balance * exchange_rate;
+    // Hit max (exactly)
 
-        // Update the exchange rate
-        exchange_rate = exchange_rate * weekly_rate_multiplier;
+    let boost_config = BoostConfig {
+        base_multiplier: Decimal256::percent(20),
+        max_multiplier: Decimal256::one(),
+        total_voting_power_weight: Decimal256::percent(200),
+    };
 
-        // Mock aUST-UST exchange rate
-        deps.querier.with_exchange_rate(exchange_rate);
+    let snapshotted_user_lottery_deposit = Uint256::from(100u128);
+    let snapshotted_total_user_lottery_deposits = Uint256::from(200u128);
 
-        // Advance one week in time
-        if let Duration::Time(time) = WEEK {
-            env.block.time = env.block.time.plus_seconds(time);
-        }
+    let snapshotted_user_voting_balance = Uint128::from(100u128);
+    let snapshotted_total_voting_balance = Uint128::from(100u128);
 
-        // Execute the lottery
+    let multiplier = calculate_boost_multiplier(
+        boost_config,
+        snapshotted_user_lottery_deposit,
+        snapshotted_total_user_lottery_deposits,
+        snapshotted_user_voting_balance,
+        snapshotted_total_voting_balance,
+    );
 
-        let lottery_msg = ExecuteMsg::ExecuteLottery {};
-        let info = mock_info("addr0001", &[]);
-        let res = execute(deps.as_mut(), env.clone(), info.clone(), lottery_msg).unwrap();
+    println!("{}", multiplier);
+    assert_eq!(multiplier, Decimal256::percent(100));
 
-        if i % 5 == 0 {
-            // Remaining prizes in state
+    // Hit max (over)
 
-            let state = STATE.load(deps.as_ref().storage).unwrap();
+    let boost_config = BoostConfig {
+        base_multiplier: Decimal256::percent(20),
+        max_multiplier: Decimal256::one(),
+        total_voting_power_weight: Decimal256::percent(200),
+    };
 
-            println!(
-                "Jackpot size after week {} of prize execution: {:?}",
-                i, state.prize_buckets[5]
-            );
-        }
+    let snapshotted_user_lottery_deposit = Uint256::from(50u128);
+    let snapshotted_total_user_lottery_deposits = Uint256::from(200u128);
 
-        // Check how much aust was redeemed
-        let sent_amount =
-            if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg {
-                let send_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
-                if let Cw20ExecuteMsg::Send { amount, .. } = send_msg {
-                    amount
-                } else {
-                    panic!("DO NOT ENTER HERE")
-                }
-            } else {
-                panic!("DO NOT ENTER HERE");
-            };
+    let snapshotted_user_voting_balance = Uint128::from(100u128);
+    let snapshotted_total_voting_balance = Uint128::from(100u128);
 
-        // Update the contract balance
-        contract_balance = contract_balance - Uint256::from(sent_amount);
+    let multiplier = calculate_boost_multiplier(
+        boost_config,
+        snapshotted_user_lottery_deposit,
+        snapshotted_total_user_lottery_deposits,
+        snapshotted_user_voting_balance,
+        snapshotted_total_voting_balance,
+    );
 
-        // Add the funds to the contract address -------------------
-        deps.querier.with_token_balances(&[(
-            &A_UST.to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &contract_balance.into())],
-        )]);
+    println!("{}", multiplier);
+    assert_eq!(multiplier, Decimal256::percent(100));
 
-        // Advance block_time in time
-        if let Duration::Time(time) = HOUR {
-            env.block.time = env.block.time.plus_seconds(time);
-        }
+    // Hit min (over)
 
-        // Execute prize
-        let execute_prize_msg = ExecuteMsg::ExecutePrize { limit: None };
-        let _res = execute(deps.as_mut(), env.clone(), info, execute_prize_msg).unwrap();
+    let boost_config = BoostConfig {
+        base_multiplier: Decimal256::percent(20),
+        max_multiplier: Decimal256::one(),
+        total_voting_power_weight: Decimal256::percent(200),
+    };
 
-        amount_distributed_through_lottery += Uint256::from(sent_amount) * exchange_rate;
+    let snapshotted_user_lottery_deposit = Uint256::from(100u128);
+    let snapshotted_total_user_lottery_deposits = Uint256::from(200u128);
 
-        let percent_appreciation_towards_lottery =
-            Decimal256::from_uint256(Uint256::from(sent_amount)) * exchange_rate
-                / Decimal256::from_uint256(pool_size_appreciation);
+    let snapshotted_user_voting_balance = Uint128::from(0u128);
+    let snapshotted_total_voting_balance = Uint128::from(100u128);
 
-        assert!(percent_appreciation_towards_lottery <= Decimal256::percent(SPLIT_FACTOR));
-    }
+    let multiplier = calculate_boost_multiplier(
+        boost_config,
+        snapshotted_user_lottery_deposit,
+        snapshotted_total_user_lottery_deposits,
+        snapshotted_user_voting_balance,
+        snapshotted_total_voting_balance,
+    );
+
+    println!("{}", multiplier);
+    assert_eq!(multiplier, Decimal256::percent(20));
 }
 
 #[test]
-pub fn ceil_helper_function() {
-    // Test ceiling
-    let res = uint256_times_decimal256_ceil(
-        Uint256::from(5u128),
-        Decimal256::from_ratio(Uint256::from(1u128), Uint256::from(2u128)),
+pub fn test_calculate_boost_voting_balance_needed() {
+    let boost_config = BoostConfig {
+        base_multiplier: Decimal256::percent(20),
+        max_multiplier: Decimal256::one(),
+        total_voting_power_weight: Decimal256::percent(200),
+    };
+
+    // Quarter of the shares, needs half of the voting balance after adding to it
+    let needed = calculate_boost_voting_balance_needed(
+        boost_config.clone(),
+        Uint256::from(100u128),
+        Uint256::from(400u128),
+        Uint128::from(20u128),
+        Uint128::from(100u128),
     );
-    assert_eq!(res, Uint256::from(3u128));
+    assert_eq!(needed, Some(Uint128::from(60u128)));
 
-    // Test stay
-    let res = uint256_times_decimal256_ceil(
-        Uint256::from(5u128),
-        Decimal256::from_ratio(Uint256::from(1u128), Uint256::from(5u128)),
+    // Adding the needed voting balance maxes out the multiplier
+    let multiplier = calculate_boost_multiplier(
+        boost_config.clone(),
+        Uint256::from(100u128),
+        Uint256::from(400u128),
+        Uint128::from(80u128),
+        Uint128::from(160u128),
+    );
+    assert_eq!(multiplier, Decimal256::one());
+
+    // Already at the max multiplier
+    let needed = calculate_boost_voting_balance_needed(
+        boost_config.clone(),
+        Uint256::from(100u128),
+        Uint256::from(200u128),
+        Uint128::from(100u128),
+        Uint128::from(100u128),
+    );
+    assert_eq!(needed, Some(Uint128::zero()));
+
+    // Half of the shares would need all of the voting balance
+    let needed = calculate_boost_voting_balance_needed(
+        boost_config.clone(),
+        Uint256::from(100u128),
+        Uint256::from(200u128),
+        Uint128::from(20u128),
+        Uint128::from(100u128),
+    );
+    assert_eq!(needed, None);
+
+    // No shares, no boost
+    let needed = calculate_boost_voting_balance_needed(
+        boost_config,
+        Uint256::zero(),
+        Uint256::from(200u128),
+        Uint128::from(20u128),
+        Uint128::from(100u128),
     );
-    assert_eq!(res, Uint256::from(1u128));
+    assert_eq!(needed, None);
 }
 
 #[test]
-pub fn calculate_max_bound_and_minimum_matches_for_winning_ticket() {
-    let ticket = "abcdea";
+pub fn test_query_boost() {
+    // Initialize contract
+    let mut deps = mock_dependencies(&[]);
 
-    // Test with prize distribution with zeros for the two first buckets
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
-    let prize_distribution: [Decimal256; NUM_PRIZE_BUCKETS] = [
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::percent(20),
-        Decimal256::percent(20),
-        Decimal256::percent(20),
-        Decimal256::percent(20),
-        Decimal256::percent(20),
-    ];
+    // Two depositors with the same shares
+    for (address, sequence) in [
+        ("addr0000", ZERO_MATCH_SEQUENCE),
+        ("addr0001", ONE_MATCH_SEQUENCE),
+    ] {
+        let info = mock_info(
+            address,
+            &[Coin {
+                denom: DENOM.to_string(),
+                amount: Uint128::from(TICKET_PRICE),
+            }],
+        );
 
-    let minimum_matches_for_winning_ticket =
-        get_minimum_matches_for_winning_ticket(prize_distribution).unwrap();
+        let msg = ExecuteMsg::Deposit {
+            encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(sequence)]),
+            operator: None,
+            syndicate: None,
+        };
 
-    assert_eq!(minimum_matches_for_winning_ticket, 2);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
 
-    let min_bound = &ticket[..minimum_matches_for_winning_ticket];
+    // Set the ve balances
+    deps.querier.with_token_balances(&[(
+        &VE_ADDR.to_string(),
+        &[
+            (&"addr0000".to_string(), &Uint128::from(15u128)),
+            (&"addr0001".to_string(), &Uint128::from(25u128)),
+        ],
+    )]);
 
-    assert_eq!(min_bound, "ab");
+    let boost: BoostResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Boost {
+                address: "addr0000".to_string(),
+                at_time: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
 
-    let max_bound = calculate_max_bound(min_bound, minimum_matches_for_winning_ticket);
+    // Default boost config: base 0.4, max 1, total voting power weight 1.5
+    assert_eq!(
+        boost,
+        BoostResponse {
+            depositor: "addr0000".to_string(),
+            multiplier: Decimal256::percent(70),
+            share_ratio: Decimal256::percent(50),
+            voting_ratio: Decimal256::from_ratio(Uint256::from(15u128), Uint256::from(40u128)),
+            voting_balance: Uint128::from(15u128),
+            total_voting_balance: Uint128::from(40u128),
+            additional_voting_balance_needed: Some(Uint128::from(60u128)),
+        }
+    );
 
-    assert_eq!(max_bound, "abffff");
+    // A depositor without shares can't get boosted
+    let boost: BoostResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Boost {
+                address: "addr0002".to_string(),
+                at_time: Some(mock_env().block.time.seconds()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
 
-    // Test with prize distribution with zeros for the first buckets
+    assert_eq!(boost.multiplier, Decimal256::percent(40));
+    assert_eq!(boost.additional_voting_balance_needed, None);
+}
 
-    let prize_distribution: [Decimal256; NUM_PRIZE_BUCKETS] = [
-        Decimal256::zero(),
-        Decimal256::percent(1),
-        Decimal256::percent(19),
-        Decimal256::percent(20),
-        Decimal256::percent(20),
-        Decimal256::percent(20),
-        Decimal256::percent(20),
-    ];
+#[test]
+pub fn test_paused() {
+    // Instantiate contracts
 
-    let minimum_matches_for_winning_ticket =
-        get_minimum_matches_for_winning_ticket(prize_distribution).unwrap();
+    let mut deps = mock_dependencies(&[]);
 
-    assert_eq!(minimum_matches_for_winning_ticket, 1);
+    // get env
+    let mut _env = mock_env();
 
-    let min_bound = &ticket[..minimum_matches_for_winning_ticket];
+    // mock instantiate the contracts
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
-    assert_eq!(min_bound, "a");
+    // Pause the contracts
 
-    let max_bound = calculate_max_bound(min_bound, minimum_matches_for_winning_ticket);
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_addr: None,
+        reserve_factor: None,
+        instant_withdrawal_fee: None,
+        unbonding_period: None,
+        epoch_interval: None,
+        max_holders: None,
+        max_tickets_per_depositor: None,
+        paused: Some(true),
+        lotto_winner_boost_config: None,
 
-    assert_eq!(max_bound, "afffff");
+        operator_glow_emission_rate: None,
+        sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
+    };
 
-    // Test with prize distribution with zeros until the last bucket
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let prize_distribution: [Decimal256; NUM_PRIZE_BUCKETS] = [
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::percent(100),
-    ];
+    // Try to deposit and fail
 
-    let minimum_matches_for_winning_ticket =
-        get_minimum_matches_for_winning_ticket(prize_distribution).unwrap();
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint256::from(2 * TICKET_PRICE).into(),
+        }],
+    );
 
-    assert_eq!(minimum_matches_for_winning_ticket, 6);
+    // Correct deposit - buys two tickets
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
+            String::from(ZERO_MATCH_SEQUENCE),
+            String::from(ONE_MATCH_SEQUENCE),
+        ]),
+        operator: None,
+        syndicate: None,
+    };
 
-    let min_bound = &ticket[..minimum_matches_for_winning_ticket];
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
 
-    assert_eq!(min_bound, "abcdea");
+    match res {
+        Err(ContractError::ContractPaused {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let max_bound = calculate_max_bound(min_bound, minimum_matches_for_winning_ticket);
+    let depositor_info = OldDepositorInfo {
+        lottery_deposit: Uint256::zero(),
+        savings_aust: Uint256::zero(),
+        reward_index: Decimal256::zero(),
+        pending_rewards: Decimal256::zero(),
+        tickets: vec![],
+        unbonding_info: vec![],
+    };
 
-    assert_eq!(max_bound, "abcdea");
+    // Add something to old depositors
 
-    // Expect an error when prize distribution is all zeros
+    bucket::<OldDepositorInfo>(deps.as_mut().storage, b"depositor")
+        .save("addr1111".as_bytes(), &depositor_info)
+        .unwrap();
 
-    let prize_distribution: [Decimal256; NUM_PRIZE_BUCKETS] = [
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::zero(),
-        Decimal256::zero(),
-    ];
+    // Try to unpause and fail
 
-    let minimum_matches_for_winning_ticket =
-        get_minimum_matches_for_winning_ticket(prize_distribution);
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_addr: None,
+        reserve_factor: None,
+        instant_withdrawal_fee: None,
+        unbonding_period: None,
+        epoch_interval: None,
+        max_holders: None,
+        max_tickets_per_depositor: None,
+        paused: Some(false),
+        lotto_winner_boost_config: None,
+        operator_glow_emission_rate: None,
+        sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
+    };
 
-    let err = Err(StdError::generic_err(
-        "The minimum matches for a winning ticket could not be calculated due to a malforming of the prize distribution"
-    ));
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
 
-    assert_eq!(minimum_matches_for_winning_ticket, err);
-}
+    match res {
+        Err(ContractError::Std(e))
+            if e == StdError::generic_err("Cannot unpause contract with old depositors") => {}
+        _ => panic!("DO NOT ENTER"),
+    };
 
-#[test]
-pub fn test_ticket_encoding_and_decoding() {
-    // Test inverse functionality #1
-    let combinations = vec![
-        String::from(THREE_MATCH_SEQUENCE),
-        String::from(ZERO_MATCH_SEQUENCE),
-    ];
-    let encoded_tickets = vec_string_tickets_to_encoded_tickets(combinations.clone());
-    println!("{}", encoded_tickets);
-    let decoded_combinations =
-        base64_encoded_tickets_to_vec_string_tickets(encoded_tickets).unwrap();
-    println!("{:?}", decoded_combinations);
-    assert_eq!(combinations, decoded_combinations);
+    // Remove old depositor
 
-    // Test inverse functionality #2
-    let combinations = vec![String::from("000000")];
-    let encoded_tickets = vec_string_tickets_to_encoded_tickets(combinations.clone());
-    let decoded_combinations =
-        base64_encoded_tickets_to_vec_string_tickets(encoded_tickets).unwrap();
-    println!("{:?}", decoded_combinations);
-    assert_eq!(combinations, decoded_combinations);
+    old_remove_depositor_info(
+        deps.as_mut().storage,
+        &Addr::unchecked("addr1111".to_string()),
+    );
+
+    // Try to unpause and succeed
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_addr: None,
+        reserve_factor: None,
+        instant_withdrawal_fee: None,
+        unbonding_period: None,
+        epoch_interval: None,
+        max_holders: None,
+        max_tickets_per_depositor: None,
+        paused: Some(false),
+        lotto_winner_boost_config: None,
+
+        operator_glow_emission_rate: None,
+        sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Try to deposit and succeed
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint256::from(2 * TICKET_PRICE).into(),
+        }],
+    );
 
-    // Test giving random data
-    let encoded_tickets = String::from("aowief");
-    let decoded_combinations = base64_encoded_tickets_to_vec_string_tickets(encoded_tickets);
-    match decoded_combinations {
-        Err(e)
-            if e == StdError::generic_err(
-                "Couldn't base64 decode the encoded tickets.".to_string(),
-            ) => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    // Correct deposit - buys two tickets
+    let msg = ExecuteMsg::Deposit {
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
+            String::from(ZERO_MATCH_SEQUENCE),
+            String::from(ONE_MATCH_SEQUENCE),
+        ]),
+        operator: None,
+        syndicate: None,
+    };
 
-    // Test giving data with wrong ticket length
-    let encoded_tickets = String::from("EjRWeA==");
-    let decoded_combinations = base64_encoded_tickets_to_vec_string_tickets(encoded_tickets);
-    match decoded_combinations {
-        Err(e) if e == StdError::generic_err("Decoded tickets wrong length.") => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 }
 
 #[test]
-pub fn test_query_prizes() {
-    // Add some prizes
-
+pub fn test_pause_flags() {
+    // Instantiate contracts
     let mut deps = mock_dependencies(&[]);
 
-    // get env
-    let mut _env = mock_env();
-
     // mock instantiate the contracts
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    // Query them
+    let deposit = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>| {
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: DENOM.to_string(),
+                amount: Uint128::from(TICKET_PRICE),
+            }],
+        );
+        let msg = ExecuteMsg::Deposit {
+            encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+                ZERO_MATCH_SEQUENCE,
+            )]),
+            operator: None,
+            syndicate: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg)
+    };
 
-    for i in 0..10 {
-        for j in 0..3 {
-            let prize = PrizeInfo {
-                claimed: false,
-                matches: [i, j, 2, 3, 1, 3, 3],
-            };
+    deposit(&mut deps).unwrap();
 
-            PRIZES
-                .save(
-                    deps.as_mut().storage,
-                    (
-                        U64Key::from(i as u64),
-                        &Addr::unchecked(format!("addr000{}", j)),
-                    ),
-                    &prize,
-                )
-                .unwrap();
-        }
-    }
+    // Only the owner can set the guardian
+    let msg = ExecuteMsg::UpdateGuardian {
+        guardian: Some("guardian".to_string()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
 
-    let lottery_prizes = read_lottery_prizes(deps.as_ref(), 2, None, None).unwrap();
+    // Only the owner and the guardian can pause
+    let flags = PauseFlags {
+        deposit: true,
+        lottery: true,
+        ..PauseFlags::default()
+    };
+    let msg = ExecuteMsg::SetPauseFlags {
+        flags: flags.clone(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg).unwrap();
 
-    let expected_prizes = (0..3)
-        .map(|i| {
-            (
-                Addr::unchecked(format!("addr000{}", i)),
-                PrizeInfo {
-                    claimed: false,
-                    matches: [2, i, 2, 3, 1, 3, 3],
-                },
-            )
-        })
-        .collect::<Vec<_>>();
+    let config_response: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config_response.paused, flags);
+    assert_eq!(config_response.guardian, Some("guardian".to_string()));
 
-    assert_eq!(lottery_prizes, expected_prizes);
+    // Deposits and lottery execution are paused
+    assert_eq!(deposit(&mut deps), Err(ContractError::ContractPaused {}));
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(WEEK_TIME);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteLottery {},
+    );
+    assert_eq!(res, Err(ContractError::ContractPaused {}));
 
-    println!("{:?}", lottery_prizes);
+    // Withdrawals and actions without a flag are still allowed
+    let minted_aust = Uint256::from(TICKET_PRICE) / Decimal256::permille(RATE);
+    deps.querier.with_token_balances(&[(
+        &A_UST.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &minted_aust.into())],
+    )]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Withdraw {
+            amount: None,
+            instant: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CreateSyndicate {
+            name: "Friends".to_string(),
+        },
+    )
+    .unwrap();
 
-    // Test start after
+    // The guardian can pause more actions but can't unpause them
+    let msg = ExecuteMsg::SetPauseFlags {
+        flags: PauseFlags {
+            lottery: true,
+            ..PauseFlags::default()
+        },
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg);
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
 
-    let start_after = Some(Addr::unchecked("addr0002"));
-    let lottery_prizes = read_lottery_prizes(deps.as_ref(), 2, start_after, None).unwrap();
-    assert_eq!(lottery_prizes.len(), 0);
+    let msg = ExecuteMsg::SetPauseFlags {
+        flags: PauseFlags::all(true),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg).unwrap();
 
-    // Test limit
+    // Actions without a flag are blocked when every action is paused
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CreateSyndicate {
+            name: "Friends".to_string(),
+        },
+    );
+    assert_eq!(res, Err(ContractError::ContractPaused {}));
 
-    let limit = Some(1);
-    let lottery_prizes = read_lottery_prizes(deps.as_ref(), 2, None, limit).unwrap();
-    assert_eq!(lottery_prizes.len(), 1);
+    // The owner can unpause
+    let msg = ExecuteMsg::SetPauseFlags {
+        flags: PauseFlags::default(),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    deposit(&mut deps).unwrap();
 }
 
 #[test]
-pub fn test_calculate_boost_multiplier() {
-    // Test #1
+pub fn test_export_import_state() {
+    // Instantiate contracts
+    let mut deps = mock_dependencies(&[]);
 
-    let boost_config = BoostConfig {
-        base_multiplier: Decimal256::percent(20),
-        max_multiplier: Decimal256::one(),
-        total_voting_power_weight: Decimal256::percent(200),
-    };
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
-    let snapshotted_user_lottery_deposit = Uint256::from(100u128);
-    let snapshotted_total_user_lottery_deposits = Uint256::from(200u128);
+    // Emit GLOW to operators and sponsors
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_addr: None,
+        instant_withdrawal_fee: None,
+        unbonding_period: None,
+        reserve_factor: None,
+        epoch_interval: None,
+        max_holders: None,
+        max_tickets_per_depositor: None,
+        paused: None,
+        lotto_winner_boost_config: None,
+        operator_glow_emission_rate: Some(Decimal256::one()),
+        sponsor_glow_emission_rate: Some(Decimal256::one()),
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
 
-    let snapshotted_user_voting_balance = Uint128::from(20u128);
-    let snapshotted_total_voting_balance = Uint128::from(100u128);
+    // Deposit, with and without an operator, and sponsor
+    for (depositor, sequence, operator) in [
+        ("addr0000", ZERO_MATCH_SEQUENCE, None),
+        ("addr0001", ONE_MATCH_SEQUENCE, Some("operator".to_string())),
+    ] {
+        let info = mock_info(
+            depositor,
+            &[Coin {
+                denom: DENOM.to_string(),
+                amount: Uint128::from(TICKET_PRICE),
+            }],
+        );
+        let msg = ExecuteMsg::Deposit {
+            encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(sequence)]),
+            operator,
+            syndicate: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
 
-    let multiplier = calculate_boost_multiplier(
-        boost_config,
-        snapshotted_user_lottery_deposit,
-        snapshotted_total_user_lottery_deposits,
-        snapshotted_user_voting_balance,
-        snapshotted_total_voting_balance,
+    let info = mock_info(
+        "sponsor",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint128::from(TICKET_PRICE),
+        }],
     );
-
-    println!("{}", multiplier);
-    assert_eq!(multiplier, Decimal256::percent(36));
-
-    // Test #2
-
-    let boost_config = BoostConfig {
-        base_multiplier: Decimal256::percent(20),
-        max_multiplier: Decimal256::one(),
-        total_voting_power_weight: Decimal256::percent(200),
+    let msg = ExecuteMsg::Sponsor {
+        award: None,
+        prize_distribution: None,
     };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let snapshotted_user_lottery_deposit = Uint256::from(100u128);
-    let snapshotted_total_user_lottery_deposits = Uint256::from(200u128);
-
-    let snapshotted_user_voting_balance = Uint128::from(80u128);
-    let snapshotted_total_voting_balance = Uint128::from(100u128);
+    // Rewards accrue before the export, so reward indices are past zero
+    let mut env = mock_env();
+    env.block.height += 100;
+    for claimer in ["operator", "sponsor"] {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(claimer, &[]),
+            ExecuteMsg::ClaimRewards {},
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
 
-    let multiplier = calculate_boost_multiplier(
-        boost_config,
-        snapshotted_user_lottery_deposit,
-        snapshotted_total_user_lottery_deposits,
-        snapshotted_user_voting_balance,
-        snapshotted_total_voting_balance,
+    // Escrow a gift code
+    let preimage = "happy birthday";
+    let info = mock_info(
+        "addr0002",
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: Uint128::from(TICKET_PRICE),
+        }],
     );
-
-    println!("{}", multiplier);
-    assert_eq!(multiplier, Decimal256::percent(84));
-
-    // Hit max (exactly)
-
-    let boost_config = BoostConfig {
-        base_multiplier: Decimal256::percent(20),
-        max_multiplier: Decimal256::one(),
-        total_voting_power_weight: Decimal256::percent(200),
+    let msg = ExecuteMsg::CreateGiftCode {
+        code_hash: hex::encode(hash_gift_code_preimage(preimage)),
+        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from(
+            TWO_MATCH_SEQUENCE,
+        )]),
+        expires_at: mock_env().block.time.seconds() + WEEK_TIME,
     };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Store a lottery and some prizes
+    let lottery_info = read_lottery_info(deps.as_ref().storage, 0);
+    store_lottery_info(deps.as_mut().storage, 0, &lottery_info).unwrap();
+    for (lottery_id, address) in [(0, "addr0000"), (0, "addr0001"), (1, "addr0000")] {
+        PRIZES
+            .save(
+                deps.as_mut().storage,
+                (U64Key::from(lottery_id), &Addr::unchecked(address)),
+                &PrizeInfo {
+                    claimed: false,
+                    matches: [0, 1, 0, 0, 0, 0, 0],
+                },
+            )
+            .unwrap();
+    }
 
-    let snapshotted_user_lottery_deposit = Uint256::from(100u128);
-    let snapshotted_total_user_lottery_deposits = Uint256::from(200u128);
+    // Store some unbonding claims
+    for (address, release_epoch) in [("addr0000", 100), ("addr0000", 200), ("addr0001", 100)] {
+        add_unbonding_claim(
+            deps.as_mut().storage,
+            &Addr::unchecked(address),
+            release_epoch,
+            Expiration::AtTime(Timestamp::from_seconds(release_epoch)),
+            Uint256::from(TICKET_PRICE),
+        )
+        .unwrap();
+    }
 
-    let snapshotted_user_voting_balance = Uint128::from(100u128);
-    let snapshotted_total_voting_balance = Uint128::from(100u128);
+    // Exports are paginated
+    let depositors = query_export_depositors(deps.as_ref(), None, Some(1)).unwrap();
+    assert_eq!(depositors.version, STATE_EXPORT_VERSION);
+    assert_eq!(depositors.records.len(), 1);
+    let depositors =
+        query_export_depositors(deps.as_ref(), Some("addr0000".to_string()), None).unwrap();
+    match &depositors.records[..] {
+        [StateRecord::Depositor(depositor)] => {
+            assert_eq!(depositor.address, "addr0001".to_string());
+            assert_eq!(depositor.operator, "operator".to_string());
+            assert_eq!(depositor.tickets, vec![String::from(ONE_MATCH_SEQUENCE)]);
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let multiplier = calculate_boost_multiplier(
-        boost_config,
-        snapshotted_user_lottery_deposit,
-        snapshotted_total_user_lottery_deposits,
-        snapshotted_user_voting_balance,
-        snapshotted_total_voting_balance,
+    let prizes = query_export_prizes(deps.as_ref(), Some((0, "addr0001".to_string())), None)
+        .unwrap()
+        .records;
+    assert_eq!(
+        prizes,
+        vec![StateRecord::Prize(ExportedPrize {
+            lottery_id: 1,
+            address: "addr0000".to_string(),
+            claimed: false,
+            matches: [0, 1, 0, 0, 0, 0, 0],
+            winner_stats: ExportedDepositorStats {
+                shares: Uint256::zero(),
+                num_tickets: 0,
+                operator: "".to_string(),
+            },
+        })]
     );
 
-    println!("{}", multiplier);
-    assert_eq!(multiplier, Decimal256::percent(100));
-
-    // Hit max (over)
+    let unbonding_claims =
+        query_export_unbonding_claims(deps.as_ref(), Some(("addr0000".to_string(), 100)), None)
+            .unwrap()
+            .records;
+    assert_eq!(unbonding_claims.len(), 2);
+    match &unbonding_claims[0] {
+        StateRecord::UnbondingClaim(unbonding_claim) => {
+            assert_eq!(unbonding_claim.address, "addr0000".to_string());
+            assert_eq!(unbonding_claim.release_epoch, 200);
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let boost_config = BoostConfig {
-        base_multiplier: Decimal256::percent(20),
-        max_multiplier: Decimal256::one(),
-        total_voting_power_weight: Decimal256::percent(200),
+    let export = |deps: Deps| -> Vec<StateRecord> {
+        let mut records = query_export_totals(deps).unwrap().records;
+        records.extend(query_export_depositors(deps, None, None).unwrap().records);
+        records.extend(query_export_sponsors(deps, None, None).unwrap().records);
+        records.extend(query_export_operators(deps, None, None).unwrap().records);
+        records.extend(query_export_tickets(deps, None, None).unwrap().records);
+        records.extend(query_export_lotteries(deps, None, None).unwrap().records);
+        records.extend(query_export_prizes(deps, None, None).unwrap().records);
+        records.extend(query_export_gift_codes(deps, None, None).unwrap().records);
+        records.extend(query_export_charities(deps, None, None).unwrap().records);
+        records.extend(query_export_syndicates(deps, None, None).unwrap().records);
+        records.extend(
+            query_export_syndicate_members(deps, None, None)
+                .unwrap()
+                .records,
+        );
+        records.extend(
+            query_export_syndicate_prize_claims(deps, None, None)
+                .unwrap()
+                .records,
+        );
+        records.extend(
+            query_export_unbonding_claims(deps, None, None)
+                .unwrap()
+                .records,
+        );
+        records.extend(
+            query_export_depositor_history(deps, None, None)
+                .unwrap()
+                .records,
+        );
+        records
     };
+    let records = export(deps.as_ref());
+    assert_eq!(records.len(), 20);
 
-    let snapshotted_user_lottery_deposit = Uint256::from(50u128);
-    let snapshotted_total_user_lottery_deposits = Uint256::from(200u128);
+    // Seed a fresh instance with the export
+    let mut new_deps = mock_dependencies(&[]);
+
+    mock_instantiate(&mut new_deps);
+    mock_register_contracts(new_deps.as_mut());
 
-    let snapshotted_user_voting_balance = Uint128::from(100u128);
-    let snapshotted_total_voting_balance = Uint128::from(100u128);
+    let msg = ExecuteMsg::ImportState {
+        version: STATE_EXPORT_VERSION,
+        records: records.clone(),
+    };
 
-    let multiplier = calculate_boost_multiplier(
-        boost_config,
-        snapshotted_user_lottery_deposit,
-        snapshotted_total_user_lottery_deposits,
-        snapshotted_user_voting_balance,
-        snapshotted_total_voting_balance,
+    // Only the owner can import state, while every action is paused
+    let res = execute(
+        new_deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+    let res = execute(
+        new_deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        msg.clone(),
     );
+    assert_eq!(res, Err(ContractError::ImportStateNotPaused {}));
 
-    println!("{}", multiplier);
-    assert_eq!(multiplier, Decimal256::percent(100));
+    execute(
+        new_deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::SetPauseFlags {
+            flags: PauseFlags::all(true),
+        },
+    )
+    .unwrap();
 
-    // Hit min (over)
+    let res = execute(
+        new_deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::ImportState {
+            version: STATE_EXPORT_VERSION + 1,
+            records: vec![],
+        },
+    );
+    assert_eq!(
+        res,
+        Err(ContractError::UnsupportedStateVersion(
+            STATE_EXPORT_VERSION + 1
+        ))
+    );
 
-    let boost_config = BoostConfig {
-        base_multiplier: Decimal256::percent(20),
-        max_multiplier: Decimal256::one(),
-        total_voting_power_weight: Decimal256::percent(200),
-    };
+    execute(
+        new_deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
 
-    let snapshotted_user_lottery_deposit = Uint256::from(100u128);
-    let snapshotted_total_user_lottery_deposits = Uint256::from(200u128);
+    // The new instance exports the same state
+    assert_eq!(export(new_deps.as_ref()), records);
 
-    let snapshotted_user_voting_balance = Uint128::from(0u128);
-    let snapshotted_total_voting_balance = Uint128::from(100u128);
+    // Operators and sponsors keep accruing rewards from the imported indices
+    execute(
+        new_deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::SetPauseFlags {
+            flags: PauseFlags::default(),
+        },
+    )
+    .unwrap();
 
-    let multiplier = calculate_boost_multiplier(
-        boost_config,
-        snapshotted_user_lottery_deposit,
-        snapshotted_total_user_lottery_deposits,
-        snapshotted_user_voting_balance,
-        snapshotted_total_voting_balance,
-    );
+    env.block.height += 100;
+    for claimer in ["operator", "sponsor"] {
+        let res = execute(
+            new_deps.as_mut(),
+            env.clone(),
+            mock_info(claimer, &[]),
+            ExecuteMsg::ClaimRewards {},
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
 
-    println!("{}", multiplier);
-    assert_eq!(multiplier, Decimal256::percent(20));
+    // The imported gift code can be redeemed
+    execute(
+        new_deps.as_mut(),
+        mock_env(),
+        mock_info("addr0003", &[]),
+        ExecuteMsg::CommitGiftCodeRedemption {
+            commitment: hex::encode(hash_gift_code_commitment(
+                preimage,
+                &Addr::unchecked("addr0003"),
+            )),
+        },
+    )
+    .unwrap();
+    execute(
+        new_deps.as_mut(),
+        env,
+        mock_info("addr0003", &[]),
+        ExecuteMsg::RedeemGiftCode {
+            preimage: preimage.to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        read_depositor_info(new_deps.as_ref().storage, &Addr::unchecked("addr0003")).tickets,
+        vec![String::from(TWO_MATCH_SEQUENCE)]
+    );
 }
 
 #[test]
-pub fn test_paused() {
+pub fn test_lottery_stats() {
     // Instantiate contracts
-
     let mut deps = mock_dependencies(&[]);
 
-    // get env
-    let mut _env = mock_env();
-
-    // mock instantiate the contracts
     mock_instantiate(&mut deps);
     mock_register_contracts(deps.as_mut());
 
-    // Pause the contracts
-
-    let info = mock_info(TEST_CREATOR, &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
-        oracle_addr: None,
-        reserve_factor: None,
-        instant_withdrawal_fee: None,
-        unbonding_period: None,
-        epoch_interval: None,
-        max_holders: None,
-        max_tickets_per_depositor: None,
-        paused: Some(true),
-        lotto_winner_boost_config: None,
+    // Two awarded lotteries and one that is still running
+    let mut prize_buckets = [Uint256::zero(); NUM_PRIZE_BUCKETS];
+    prize_buckets[6] = Uint256::from(1_023_000u128);
+    let mut number_winners = [0; NUM_PRIZE_BUCKETS];
+    number_winners[6] = 1;
+    store_lottery_info(
+        deps.as_mut().storage,
+        0,
+        &LotteryInfo {
+            rand_round: 1,
+            awarded: true,
+            prize_buckets,
+            number_winners,
+            total_user_shares: Uint256::from(100_000_000u128),
+            reserve: Uint256::from(50_000u128),
+            ..read_lottery_info(deps.as_ref().storage, 0)
+        },
+    )
+    .unwrap();
 
-        operator_glow_emission_rate: None,
-        sponsor_glow_emission_rate: None,
-    };
+    let mut prize_buckets = [Uint256::zero(); NUM_PRIZE_BUCKETS];
+    prize_buckets[2] = Uint256::from(1_023_000u128);
+    let mut glow_prize_buckets = [Uint256::zero(); NUM_PRIZE_BUCKETS];
+    glow_prize_buckets[2] = Uint256::from(5_000_000u128);
+    let mut number_winners = [0; NUM_PRIZE_BUCKETS];
+    number_winners[2] = 2;
+    store_lottery_info(
+        deps.as_mut().storage,
+        1,
+        &LotteryInfo {
+            rand_round: 2,
+            awarded: true,
+            prize_buckets,
+            glow_prize_buckets,
+            number_winners,
+            total_user_shares: Uint256::from(100_000_000u128),
+            ..read_lottery_info(deps.as_ref().storage, 1)
+        },
+    )
+    .unwrap();
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_lottery_info(
+        deps.as_mut().storage,
+        2,
+        &LotteryInfo {
+            rand_round: 3,
+            total_user_shares: Uint256::from(100_000_000u128),
+            ..read_lottery_info(deps.as_ref().storage, 2)
+        },
+    )
+    .unwrap();
 
-    // Try to deposit and fail
+    let res = query_lottery_stats(deps.as_ref(), mock_env(), 1, 0);
+    assert_eq!(
+        res,
+        Err(StdError::generic_err(
+            "to_id must not be lower than from_id"
+        ))
+    );
 
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: DENOM.to_string(),
-            amount: Uint256::from(2 * TICKET_PRICE).into(),
-        }],
+    let stats = query_lottery_stats(deps.as_ref(), mock_env(), 0, 2).unwrap();
+    assert_eq!(
+        stats
+            .lotteries
+            .iter()
+            .map(|lottery| lottery.lottery_id)
+            .collect::<Vec<_>>(),
+        vec![0, 1]
+    );
+    assert_eq!(stats.total_prizes, Uint256::from(2_046_000u128));
+    assert_eq!(stats.total_glow_prizes, Uint256::from(5_000_000u128));
+    assert_eq!(stats.number_winners, [0, 0, 2, 0, 0, 0, 1]);
+    assert_eq!(stats.total_reserve, Uint256::from(50_000u128));
+
+    // Prizes are 1% of the deposits, valued at the aust exchange rate
+    assert_eq!(
+        stats.prize_apr,
+        Decimal256::percent(1)
+            * Decimal256::from_ratio(Uint256::from(SECONDS_PER_YEAR), Uint256::from(WEEK_TIME))
     );
 
-    // Correct deposit - buys two tickets
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
-            String::from(ZERO_MATCH_SEQUENCE),
-            String::from(ONE_MATCH_SEQUENCE),
-        ]),
-        operator: None,
-    };
+    let stats = query_lottery_stats(deps.as_ref(), mock_env(), 1, 1).unwrap();
+    assert_eq!(stats.lotteries.len(), 1);
+    assert_eq!(stats.total_reserve, Uint256::zero());
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    // The span of lottery ids read is capped
+    store_lottery_info(
+        deps.as_mut().storage,
+        30,
+        &LotteryInfo {
+            rand_round: 31,
+            awarded: true,
+            total_user_shares: Uint256::from(100_000_000u128),
+            ..read_lottery_info(deps.as_ref().storage, 30)
+        },
+    )
+    .unwrap();
 
-    match res {
-        Err(ContractError::ContractPaused {}) => {}
-        _ => panic!("DO NOT ENTER HERE"),
+    for (from_id, lottery_ids) in [(0, vec![0, 1]), (1, vec![1, 30])] {
+        let stats = query_lottery_stats(deps.as_ref(), mock_env(), from_id, u64::MAX).unwrap();
+        assert_eq!(
+            stats
+                .lotteries
+                .iter()
+                .map(|lottery| lottery.lottery_id)
+                .collect::<Vec<_>>(),
+            lottery_ids
+        );
     }
+}
 
-    let depositor_info = OldDepositorInfo {
-        lottery_deposit: Uint256::zero(),
-        savings_aust: Uint256::zero(),
-        reward_index: Decimal256::zero(),
-        pending_rewards: Decimal256::zero(),
-        tickets: vec![],
-        unbonding_info: vec![],
-    };
-
-    // Add something to old depositors
+#[test]
+pub fn test_reserve_destinations() {
+    // Instantiate contracts
+    let mut deps = mock_dependencies(&[]);
 
-    bucket::<OldDepositorInfo>(deps.as_mut().storage, b"depositor")
-        .save("addr1111".as_bytes(), &depositor_info)
-        .unwrap();
+    mock_instantiate(&mut deps);
+    mock_register_contracts(deps.as_mut());
 
-    // Try to unpause and fail
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
 
-    let info = mock_info(TEST_CREATOR, &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
-        oracle_addr: None,
-        reserve_factor: None,
-        instant_withdrawal_fee: None,
-        unbonding_period: None,
-        epoch_interval: None,
-        max_holders: None,
-        max_tickets_per_depositor: None,
-        paused: Some(false),
-        lotto_winner_boost_config: None,
-        operator_glow_emission_rate: None,
-        sponsor_glow_emission_rate: None,
+    let reserve_destinations = ReserveDestinations {
+        withdrawal_fees: vec![
+            ReserveDestination {
+                address: COMMUNITY_ADDR.to_string(),
+                share: Decimal256::percent(60),
+            },
+            ReserveDestination {
+                address: "buyback".to_string(),
+                share: Decimal256::percent(40),
+            },
+        ],
+        prize_reserve: vec![],
     };
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg);
-
-    match res {
-        Err(ContractError::Std(e))
-            if e == StdError::generic_err("Cannot unpause contract with old depositors") => {}
-        _ => panic!("DO NOT ENTER"),
-    };
+    // Only the owner can update the reserve destinations
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateReserveDestinations {
+            reserve_destinations: reserve_destinations.clone(),
+        },
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
 
-    // Remove old depositor
+    // Shares must add up to one
+    let mut invalid_destinations = reserve_destinations.clone();
+    invalid_destinations.withdrawal_fees[1].share = Decimal256::percent(30);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateReserveDestinations {
+            reserve_destinations: invalid_destinations,
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidReserveDestinations {}));
 
-    old_remove_depositor_info(
-        deps.as_mut().storage,
-        &Addr::unchecked("addr1111".to_string()),
+    // Shares must be positive
+    let mut invalid_destinations = reserve_destinations.clone();
+    invalid_destinations.prize_reserve = vec![
+        ReserveDestination {
+            address: "fee_distributor".to_string(),
+            share: Decimal256::one(),
+        },
+        ReserveDestination {
+            address: "buyback".to_string(),
+            share: Decimal256::zero(),
+        },
+    ];
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateReserveDestinations {
+            reserve_destinations: invalid_destinations,
+        },
     );
+    assert_eq!(res, Err(ContractError::InvalidReserveDestinations {}));
 
-    // Try to unpause and succeed
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateReserveDestinations {
+            reserve_destinations: reserve_destinations.clone(),
+        },
+    )
+    .unwrap();
 
-    let info = mock_info(TEST_CREATOR, &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
-        oracle_addr: None,
-        reserve_factor: None,
-        instant_withdrawal_fee: None,
-        unbonding_period: None,
-        epoch_interval: None,
-        max_holders: None,
-        max_tickets_per_depositor: None,
-        paused: Some(false),
-        lotto_winner_boost_config: None,
+    let config = query_config(deps.as_ref()).unwrap();
+    assert_eq!(config.reserve_destinations, reserve_destinations);
 
-        operator_glow_emission_rate: None,
-        sponsor_glow_emission_rate: None,
-    };
+    // Withdrawal fees, prize reserve and reserve accrued before sources were tracked
+    let mut state = STATE.load(deps.as_ref().storage).unwrap();
+    state.total_reserve = Uint256::from(1_600u128);
+    state.withdrawal_fee_reserve = Uint256::from(1_000u128);
+    state.prize_reserve = Uint256::from(500u128);
+    STATE.save(deps.as_mut().storage, &state).unwrap();
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let state = query_state(deps.as_ref(), mock_env(), None).unwrap();
+    assert_eq!(state.withdrawal_fee_reserve, Uint256::from(1_000u128));
+    assert_eq!(state.prize_reserve, Uint256::from(500u128));
 
-    // Try to deposit and succeed
+    let mut env = mock_env();
+    if let Duration::Time(time) = (WEEK + HOUR).unwrap() {
+        env.block.time = env.block.time.plus_seconds(time);
+    }
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteEpochOps {},
+    )
+    .unwrap();
 
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: DENOM.to_string(),
-            amount: Uint256::from(2 * TICKET_PRICE).into(),
-        }],
+    // Transfers to the community contract are merged
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: COMMUNITY_ADDR.to_string(),
+                amount: vec![Coin {
+                    denom: DENOM.to_string(),
+                    amount: Uint128::from(1_200u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyback".to_string(),
+                amount: vec![Coin {
+                    denom: DENOM.to_string(),
+                    amount: Uint128::from(400u128),
+                }],
+            })),
+        ]
     );
 
-    // Correct deposit - buys two tickets
-    let msg = ExecuteMsg::Deposit {
-        encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![
-            String::from(ZERO_MATCH_SEQUENCE),
-            String::from(ONE_MATCH_SEQUENCE),
-        ]),
-        operator: None,
-    };
-
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let state = query_state(deps.as_ref(), env, None).unwrap();
+    assert_eq!(state.total_reserve, Uint256::zero());
+    assert_eq!(state.withdrawal_fee_reserve, Uint256::zero());
+    assert_eq!(state.prize_reserve, Uint256::zero());
 }
 
 #[test]
@@ -6458,6 +9954,9 @@ pub fn test_migrate() {
         lotto_winner_boost_config: None,
         operator_glow_emission_rate: None,
         sponsor_glow_emission_rate: None,
+        max_history_entries: None,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -6498,7 +9997,7 @@ pub fn test_migrate() {
 
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_response: ConfigResponse = from_binary(&res).unwrap();
-    assert!(!config_response.paused);
+    assert_eq!(config_response.paused, PauseFlags::default());
 
     // Verify the new states are as expected
 
@@ -6540,8 +10039,13 @@ pub fn test_migrate() {
         unbonding_period: old_config.unbonding_period,
         max_tickets_per_depositor: migrate_msg.max_tickets_per_depositor,
         glow_prize_buckets: migrate_msg.glow_prize_buckets,
-        paused: false,
+        paused: PauseFlags::default(),
+        guardian: None,
         lotto_winner_boost_config: default_lotto_winner_boost_config,
+        max_history_entries: DEFAULT_MAX_HISTORY_ENTRIES,
+        instant_withdrawal_fee_decay: None,
+        max_operator_commission: Decimal256::zero(),
+        reserve_destinations: ReserveDestinations::default(),
     };
 
     assert_eq!(new_config, CONFIG.load(deps.as_ref().storage).unwrap());
@@ -6568,6 +10072,7 @@ pub fn test_migrate() {
                 page: old_lottery.page,
                 glow_prize_buckets: [Uint256::zero(); 7],
                 total_user_shares: Uint256::zero(),
+                reserve: Uint256::zero(),
             }
         );
     }
@@ -6639,6 +10144,8 @@ pub fn test_migrate() {
     let new_state = State {
         total_tickets: old_state.total_tickets,
         total_reserve: old_state.total_reserve,
+        withdrawal_fee_reserve: Uint256::zero(),
+        prize_reserve: Uint256::zero(),
         prize_buckets: old_state.prize_buckets,
         current_lottery: old_state.current_lottery,
         next_lottery_time: old_state.next_lottery_time,
@@ -6700,6 +10207,7 @@ pub fn anchor_pool_smaller_than_total_deposits() {
     let msg = ExecuteMsg::Deposit {
         encoded_tickets: vec_string_tickets_to_encoded_tickets(vec![String::from("234567")]),
         operator: None,
+        syndicate: None,
     };
     let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -6748,6 +10256,8 @@ pub fn anchor_pool_smaller_than_total_deposits() {
         StateResponse {
             total_tickets: Uint256::from(1u64),
             total_reserve: Uint256::zero(),
+            withdrawal_fee_reserve: Uint256::zero(),
+            prize_reserve: Uint256::zero(),
             prize_buckets: [Uint256::zero(); NUM_PRIZE_BUCKETS],
             current_lottery: 0,
             next_lottery_time: Expiration::AtTime(Timestamp::from_seconds(FIRST_LOTTO_TIME)),
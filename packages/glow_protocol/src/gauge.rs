@@ -0,0 +1,106 @@
+use cosmwasm_std::{Decimal, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub weekly_emission: Uint128, // GLOW pulled from the distributor for each epoch
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    RegisterContracts {
+        ve_token: String,
+        distributor: String,
+    },
+    UpdateConfig {
+        owner: Option<String>,
+        weekly_emission: Option<Uint128>,
+    },
+    /// Owner Message
+    /// Registers a contract receiving emissions, e.g. a lotto pool or LP staking
+    AddGauge { address: String, label: String },
+    /// Owner Message
+    /// Stops a gauge from receiving votes and emissions
+    RemoveGauge { address: String },
+    /// Allocates the voting power of the sender across gauges for the next epoch.
+    /// The voting power is the veGLOW balance at the start of that epoch.
+    /// The shares must be positive and add up to at most one
+    Vote { allocations: Vec<(String, Decimal)> },
+    /// Public Message
+    /// Pulls the weekly emission from the distributor and splits it
+    /// across gauges by their weights for the current epoch
+    Distribute {},
+}
+
+/// We currently take no arguments for migrations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    State {},
+    Gauges {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Weights of the gauges for an epoch, the next epoch by default
+    GaugeWeights {
+        epoch: Option<u64>,
+    },
+    Voter {
+        address: String,
+        epoch: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub ve_token: String,
+    pub distributor: String,
+    pub weekly_emission: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub current_epoch: u64,
+    pub last_distributed_epoch: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugeInfo {
+    pub address: String,
+    pub label: String,
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugesResponse {
+    pub gauges: Vec<GaugeInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugeWeight {
+    pub address: String,
+    pub weight: Uint128,
+    pub share: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugeWeightsResponse {
+    pub epoch: u64,
+    pub total_weight: Uint128,
+    pub weights: Vec<GaugeWeight>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoterResponse {
+    pub address: String,
+    pub epoch: u64,
+    pub voting_power: Uint128,
+    pub allocations: Vec<(String, Decimal)>,
+}
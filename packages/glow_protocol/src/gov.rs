@@ -1,4 +1,5 @@
-use cosmwasm_std::{Binary, Decimal, Uint128};
+use cosmwasm_std::{Binary, Coin, Decimal, Uint128};
+use cw0::{Duration, Expiration};
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -10,24 +11,31 @@ use crate::common::OrderBy;
 pub struct InstantiateMsg {
     pub quorum: Decimal,
     pub threshold: Decimal,
-    pub voting_period: u64,
-    pub timelock_period: u64,
-    pub expiration_period: u64,
+    // The voting, timelock and expiration periods must all be heights or all be times
+    pub voting_period: Duration,
+    pub timelock_period: Duration,
+    pub expiration_period: Duration,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    /// Address allowed to veto passed polls during the timelock
+    pub guardian: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
+    /// Registers the contracts gov works with. Polls executing messages on
+    /// the community contract are treasury polls
     RegisterContracts {
         glow_token: String,
         ve_token: String,
         terraswap_factory: String,
+        community_contract: String,
     },
-    /// Public Message
-    /// Sweep all given denom balance to GLOW token
+    /// Owner Message
+    /// Sweep all given denom balance to GLOW token. Restricted to the owner
+    /// because passed polls may need the native funds held by gov
     Sweep {
         denom: String,
     },
@@ -35,16 +43,54 @@ pub enum ExecuteMsg {
         owner: Option<String>,
         quorum: Option<Decimal>,
         threshold: Option<Decimal>,
-        voting_period: Option<u64>,
-        timelock_period: Option<u64>,
-        expiration_period: Option<u64>,
+        voting_period: Option<Duration>,
+        timelock_period: Option<Duration>,
+        expiration_period: Option<Duration>,
         proposal_deposit: Option<Uint128>,
         snapshot_period: Option<u64>,
     },
+    /// Sets or removes the guardian
+    UpdateGuardian {
+        guardian: Option<String>,
+    },
+    /// Sets the parameters of the polls of a category.
+    /// Categories without parameters use the global config
+    UpdatePollCategory {
+        category: PollCategory,
+        config: Option<PollCategoryConfig>,
+    },
+    /// Sets the category of the polls sending messages to a contract.
+    /// Messages to contracts without category are parameter changes
+    UpdateContractCategory {
+        contract: String,
+        category: Option<PollCategory>,
+    },
     CastVote {
         poll_id: u64,
         vote: VoteOption,
     },
+    /// Splits the voting power of the sender across several options,
+    /// e.g. for custodians voting on behalf of their users.
+    /// The weights must be positive and add up to one
+    CastWeightedVote {
+        poll_id: u64,
+        weights: Vec<(VoteOption, Decimal)>,
+    },
+    /// Delegates the voting power of the sender. It counts toward the vote
+    /// of the delegate on polls where the sender doesn't vote directly
+    Delegate {
+        to: String,
+    },
+    Undelegate {},
+    /// Counts the voting power of a page of the delegators of a delegate
+    /// toward its vote on a poll in progress. Voting only counts the first
+    /// delegators of the delegate, so anyone can tally the rest
+    TallyDelegations {
+        poll_id: u64,
+        delegate: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     WithdrawVotingTokens {
         amount: Option<Uint128>,
     },
@@ -57,6 +103,17 @@ pub enum ExecuteMsg {
     ExpirePoll {
         poll_id: u64,
     },
+    /// Guardian Message
+    /// Vetoes a passed poll before its timelock expires
+    VetoPoll {
+        poll_id: u64,
+    },
+    /// Creator Message
+    /// Cancels a poll that has no votes yet and refunds its deposit.
+    /// Only possible while the voting period is open
+    CancelPoll {
+        poll_id: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -65,27 +122,70 @@ pub enum Cw20HookMsg {
     /// StakeVotingTokens a user can stake their mirror token to receive rewards
     /// or do vote on polls
     StakeVotingTokens {},
-    /// CreatePoll need to receive deposit from a proposer
+    /// CreatePoll need to receive deposit from a proposer.
+    /// The category is inferred from the messages when not given
     CreatePoll {
         title: String,
         description: String,
         link: Option<String>,
         execute_msgs: Option<Vec<PollExecuteMsg>>,
+        category: Option<PollCategory>,
+    },
+    /// CreateMultipleChoicePoll creates a poll where voters pick one of the options.
+    /// The option with the most votes wins if the quorum is reached
+    CreateMultipleChoicePoll {
+        title: String,
+        description: String,
+        link: Option<String>,
+        options: Vec<PollOptionMsg>,
+        category: Option<PollCategory>,
     },
 }
 
+/// Message sent by a passed poll. Messages are sent by increasing order
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct PollExecuteMsg {
-    pub order: u64,
-    pub contract: String,
-    pub msg: Binary,
+pub enum PollExecuteMsg {
+    WasmExecute {
+        order: u64,
+        contract: String,
+        msg: Binary,
+        funds: Vec<Coin>,
+    },
+    WasmMigrate {
+        order: u64,
+        contract: String,
+        new_code_id: u64,
+        msg: Binary,
+    },
+    UpdateAdmin {
+        order: u64,
+        contract: String,
+        admin: String,
+    },
+    ClearAdmin {
+        order: u64,
+        contract: String,
+    },
+    BankSend {
+        order: u64,
+        to_address: String,
+        amount: Vec<Coin>,
+    },
+}
+
+/// Option of a multiple choice poll, with the messages to execute if it wins
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollOptionMsg {
+    pub label: String,
+    pub execute_msgs: Option<Vec<PollExecuteMsg>>,
 }
 
 /// We currently take no arguments for migrations
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {
     pub ve_token: String,
+    pub community_contract: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -111,6 +211,20 @@ pub enum QueryMsg {
         limit: Option<u32>,
         order_by: Option<OrderBy>,
     },
+    Delegation {
+        address: String,
+    },
+    Delegators {
+        delegate: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    PollCategory {
+        category: PollCategory,
+    },
+    ContractCategory {
+        contract: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
@@ -120,11 +234,12 @@ pub struct ConfigResponse {
     pub terraswap_factory: String,
     pub quorum: Decimal,
     pub threshold: Decimal,
-    pub voting_period: u64,
-    pub timelock_period: u64,
-    pub expiration_period: u64,
+    pub voting_period: Duration,
+    pub timelock_period: Duration,
+    pub expiration_period: Duration,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub guardian: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
@@ -139,17 +254,30 @@ pub struct PollResponse {
     pub id: u64,
     pub creator: String,
     pub status: PollStatus,
+    pub category: PollCategory,
     pub start_time: u64,
-    pub end_height: u64,
+    pub end: Expiration,
     pub title: String,
     pub description: String,
     pub link: Option<String>,
     pub deposit_amount: Uint128,
     pub execute_data: Option<Vec<PollExecuteMsg>>,
-    pub yes_votes: Uint128, // balance
-    pub no_votes: Uint128,  // balance
+    pub yes_votes: Uint128,     // balance
+    pub no_votes: Uint128,      // balance
+    pub abstain_votes: Uint128, // balance
     pub staked_amount: Option<Uint128>,
     pub total_balance_at_end_poll: Option<Uint128>,
+    pub options: Option<Vec<PollOptionResponse>>,
+    pub winning_option: Option<u8>,
+    /// Results of the executed messages, in the same order as the messages
+    pub execution_results: Option<Vec<ExecutionResult>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PollOptionResponse {
+    pub label: String,
+    pub execute_data: Option<Vec<PollExecuteMsg>>,
+    pub votes: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -174,6 +302,8 @@ pub struct VotersResponseItem {
     pub voter: String,
     pub vote: VoteOption,
     pub balance: Uint128,
+    pub weights: Option<Vec<(VoteOption, Decimal)>>,
+    pub delegated_balance: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -181,10 +311,53 @@ pub struct VotersResponse {
     pub voters: Vec<VotersResponseItem>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DelegationResponse {
+    pub address: String,
+    pub delegate: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DelegatorsResponse {
+    pub delegate: String,
+    pub delegators: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollCategoryResponse {
+    pub category: PollCategory,
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub proposal_deposit: Uint128,
+    pub voting_period: Duration,
+    pub timelock_period: Duration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractCategoryResponse {
+    pub contract: String,
+    pub category: Option<PollCategory>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct VoterInfo {
     pub vote: VoteOption,
     pub balance: Uint128,
+    /// Set for weighted votes, in which case vote is the option with the largest weight
+    #[serde(default)]
+    pub weights: Option<Vec<(VoteOption, Decimal)>>,
+    /// Voting power of the delegators counted in this vote
+    #[serde(default)]
+    pub delegated_balance: Uint128,
+}
+
+/// Result of a message sent by an executed poll
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionResult {
+    Pending,
+    Success,
+    Failure { error: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -195,6 +368,10 @@ pub enum PollStatus {
     Rejected,
     Executed,
     Expired,
+    Vetoed,
+    Cancelled,
+    /// Some messages failed. Executing the poll again retries them
+    ExecutionFailed,
 }
 
 impl fmt::Display for PollStatus {
@@ -203,19 +380,53 @@ impl fmt::Display for PollStatus {
     }
 }
 
+/// Category of a poll, from the least to the most risky.
+/// A poll cannot be declared in a less risky category than its messages
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PollCategory {
+    Text,
+    Parameter,
+    Treasury,
+    CodeUpgrade,
+}
+
+impl fmt::Display for PollCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollCategoryConfig {
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub proposal_deposit: Uint128,
+    // Must be heights or times like the expiration period of the config
+    pub voting_period: Duration,
+    pub timelock_period: Duration,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum VoteOption {
     Yes,
     No,
+    /// Counts toward the quorum but not toward the threshold
+    Abstain,
+    /// Index of the picked option of a multiple choice poll
+    Choice(u8),
 }
 
 impl fmt::Display for VoteOption {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if *self == VoteOption::Yes {
-            write!(f, "yes")
-        } else {
-            write!(f, "no")
+        match self {
+            VoteOption::Yes => write!(f, "yes"),
+            VoteOption::No => write!(f, "no"),
+            VoteOption::Abstain => write!(f, "abstain"),
+            VoteOption::Choice(index) => write!(f, "choice_{}", index),
         }
     }
 }
@@ -3,6 +3,7 @@ pub mod common;
 pub mod community;
 pub mod distributor;
 pub mod fee_distributor;
+pub mod gauge;
 pub mod gov;
 pub mod lotto;
 pub mod querier;
@@ -2,11 +2,13 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cosmwasm_std::{Addr, Binary, Timestamp, Uint128};
 use cw0::{Duration, Expiration};
 
 pub const TICKET_LENGTH: usize = 6;
 pub const NUM_PRIZE_BUCKETS: usize = TICKET_LENGTH + 1;
+/// Version of the format of the exported state records
+pub const STATE_EXPORT_VERSION: u32 = 2;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BoostConfig {
@@ -15,6 +17,110 @@ pub struct BoostConfig {
     pub total_voting_power_weight: Decimal256,
 }
 
+/// Schedule over which the instant withdrawal fee decays from
+/// instant_withdrawal_fee down to min_fee as deposits age
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantWithdrawalFeeDecay {
+    pub min_fee: Decimal256,
+    // Seconds after a deposit until the fee reaches min_fee
+    pub decay_period: u64,
+    // If set, the fee decays in steps of this many seconds instead of linearly
+    pub step: Option<u64>,
+}
+
+/// Prefix of the signed encoding of deposit intents
+pub const DEPOSIT_INTENT_DOMAIN: &[u8] = b"glow-lotto/deposit-intent";
+
+/// Share of a reserve source sent to an address on each epoch operation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReserveDestination {
+    pub address: String,
+    pub share: Decimal256,
+}
+
+/// Destinations of each reserve source. The shares of a source must add up to one,
+/// and a source without destinations is sent to the community contract
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+pub struct ReserveDestinations {
+    // Instant withdrawal fees
+    pub withdrawal_fees: Vec<ReserveDestination>,
+    // Reserve fees taken from the awarded prizes
+    pub prize_reserve: Vec<ReserveDestination>,
+}
+
+/// Deposit authorized off-chain by the signer, to be submitted by a relayer.
+/// The signature covers the sha256 hash of DEPOSIT_INTENT_DOMAIN followed by:
+/// - contract, chain_id, stable_token and encoded_tickets, each as its length
+///   in bytes as a big endian u32 followed by its utf-8 bytes
+/// - operator, as a 0 byte if None, or a 1 byte followed by the operator encoded as above
+/// - amount as a big endian u128, then nonce and expires_at as big endian u64s
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositIntent {
+    // Lotto contract and chain the intent is meant for, preventing replays elsewhere
+    pub contract: String,
+    pub chain_id: String,
+    // Cw20 stable token repaying the relayer, pulled from the signer
+    // through its allowance to the lotto contract
+    pub stable_token: String,
+    // Amount deposited in the base stable denom, fronted by the relayer,
+    // and amount of stable_token pulled from the signer
+    pub amount: Uint128,
+    pub encoded_tickets: String,
+    pub operator: Option<String>,
+    // Must match the next relay nonce of the signer
+    pub nonce: u64,
+    // Time in seconds after which the intent can no longer be relayed
+    pub expires_at: u64,
+}
+
+/// Circuit breakers pausing each group of lotto actions
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PauseFlags {
+    // Deposits, relayed deposits and ticket claims
+    pub deposit: bool,
+    // Gifts and gift codes
+    pub gift: bool,
+    pub sponsor: bool,
+    // Withdrawals, sponsor withdrawals and syndicate exits
+    pub withdraw: bool,
+    // Claims of unbonded withdrawals, prizes, rewards and commissions
+    pub claim: bool,
+    // Lottery and prize execution
+    pub lottery: bool,
+    pub epoch_ops: bool,
+}
+
+impl PauseFlags {
+    /// Flags with every action paused or unpaused
+    pub fn all(paused: bool) -> Self {
+        PauseFlags {
+            deposit: paused,
+            gift: paused,
+            sponsor: paused,
+            withdraw: paused,
+            claim: paused,
+            lottery: paused,
+            epoch_ops: paused,
+        }
+    }
+
+    /// Whether every action is paused, which also blocks actions without a flag
+    pub fn is_fully_paused(&self) -> bool {
+        *self == PauseFlags::all(true)
+    }
+
+    /// Whether every action paused by self is also paused by other
+    pub fn is_subset_of(&self, other: &PauseFlags) -> bool {
+        (!self.deposit || other.deposit)
+            && (!self.gift || other.gift)
+            && (!self.sponsor || other.sponsor)
+            && (!self.withdraw || other.withdraw)
+            && (!self.claim || other.claim)
+            && (!self.lottery || other.lottery)
+            && (!self.epoch_ops || other.epoch_ops)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct RewardEmissionsIndex {
     pub last_reward_updated: u64,
@@ -73,10 +179,29 @@ pub enum ExecuteMsg {
         epoch_interval: Option<u64>,
         max_holders: Option<u8>,
         max_tickets_per_depositor: Option<u64>,
+        // Pauses or unpauses every action at once
         paused: Option<bool>,
         lotto_winner_boost_config: Option<BoostConfig>,
         operator_glow_emission_rate: Option<Decimal256>,
         sponsor_glow_emission_rate: Option<Decimal256>,
+        max_history_entries: Option<u64>,
+        instant_withdrawal_fee_decay: Option<InstantWithdrawalFeeDecay>,
+        max_operator_commission: Option<Decimal256>,
+    },
+    /// Sets the pause flags - restricted to owner, or to the guardian
+    /// as long as it only pauses more actions
+    SetPauseFlags { flags: PauseFlags },
+    /// Sets or removes the guardian allowed to pause actions - restricted to owner
+    UpdateGuardian { guardian: Option<String> },
+    /// Sets where each reserve source is sent on epoch operations - restricted to owner
+    UpdateReserveDestinations {
+        reserve_destinations: ReserveDestinations,
+    },
+    /// Writes a batch of exported state records, overwriting existing ones.
+    /// Restricted to owner, while every action is paused
+    ImportState {
+        version: u32,
+        records: Vec<StateRecord>,
     },
     /// Update lottery configuration - restricted to owner
     UpdateLotteryConfig {
@@ -86,19 +211,46 @@ pub enum ExecuteMsg {
         prize_distribution: Option<[Decimal256; NUM_PRIZE_BUCKETS]>,
         round_delta: Option<u64>,
     },
-    /// Deposit amount of stable into the pool
+    /// Deposit amount of stable into the pool.
+    /// If syndicate is set, the deposit and tickets are held by the syndicate
+    /// and the sender gets a share of the syndicate prizes.
     Deposit {
         encoded_tickets: String,
         operator: Option<String>,
+        syndicate: Option<u64>,
     },
     /// Claim tickets
     ClaimTickets { encoded_tickets: String },
-    /// Deposit amount of stable into the pool in the name of the recipient
+    /// Deposit amount of stable into the pool in the name of the recipient.
+    /// If unlock_at is set, the gifted shares can't be withdrawn by the
+    /// recipient before that time (in seconds), although the tickets play immediately.
+    /// If the recipient has the maximum number of locks, the gift joins the lock
+    /// unlocking the soonest after unlock_at, and fails if there is none.
     Gift {
         encoded_tickets: String,
         recipient: String,
         operator: Option<String>,
+        unlock_at: Option<u64>,
     },
+    /// Escrow a deposit of stable as shares that can be redeemed by anyone knowing
+    /// the preimage of code_hash (hex encoded keccak256 hash) until expires_at (in seconds)
+    CreateGiftCode {
+        code_hash: String,
+        encoded_tickets: String,
+        expires_at: u64,
+    },
+    /// Commit to redeeming a gift code. The commitment is the hex encoded keccak256 hash
+    /// of the preimage followed by the address of the sender, so the preimage stays
+    /// hidden until it is revealed with RedeemGiftCode in a later block
+    CommitGiftCodeRedemption { commitment: String },
+    /// Redeem the shares and tickets of a gift code given its preimage. The sender must
+    /// have committed to the redemption in an earlier block, so the redemption can't be
+    /// front-run by someone reading the preimage. Fails if any of the tickets already
+    /// has the maximum number of holders, in which case the shares stay escrowed until
+    /// the creator refunds the gift code after it expires
+    RedeemGiftCode { preimage: String },
+    /// Return the shares of an expired gift code to its creator
+    RefundGiftCode { code_hash: String },
     /// Sponsor the pool. If award is true, sponsor the award available directly
     Sponsor {
         award: Option<bool>,
@@ -112,12 +264,56 @@ pub enum ExecuteMsg {
         amount: Option<Uint128>,
         instant: Option<bool>,
     },
-    /// Claim unbonded withdrawals
-    Claim {},
+    /// Claim unbonded withdrawals, along with the released epochs of the unbonding ledger
+    /// after the release epoch start_after, up to limit epochs
+    Claim {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     /// Claims pending lottery prizes for a given list of lottery ids
     ClaimLottery { lottery_ids: Vec<u64> },
+    /// Claims pending lottery prizes of a depositor in donation mode, which are sent to
+    /// their prize beneficiary. Can be triggered by anyone
+    ClaimLotteryFor {
+        depositor: String,
+        lottery_ids: Vec<u64>,
+    },
+    /// Sends the prizes of the sender to a registered charity (donation mode).
+    /// If beneficiary is None, prizes are sent to the sender again
+    SetPrizeBeneficiary { beneficiary: Option<String> },
+    /// Creates a syndicate pooling the deposits of its members
+    CreateSyndicate { name: String },
+    /// Claims the share of the sender of the prizes won by a syndicate,
+    /// pro rata to their syndicate shares at each lottery. The glow share is boosted
+    /// by the veGLOW of the sender, and the share goes to their prize beneficiary if any,
+    /// less the commission of their operator
+    ClaimSyndicateLottery {
+        syndicate_id: u64,
+        lottery_ids: Vec<u64>,
+    },
+    /// Moves all the syndicate shares of the sender to their own position, without unbonding.
+    /// Tickets the syndicate can no longer afford are removed from it
+    ExitSyndicate { syndicate_id: u64 },
+    /// Deposits on behalf of the signer of a secp256k1 signed deposit intent.
+    /// The relayer sends the amount of the intent in the base stable denom, which is
+    /// deposited like any deposit, and is repaid with the same amount of the cw20
+    /// stable token of the intent, pulled from the signer through an existing allowance
+    RelayDeposit {
+        intent: DepositIntent,
+        signature: Binary,
+        pubkey: Binary,
+    },
+    /// Register or update a verified charity - restricted to gov
+    RegisterCharity { address: String, name: String },
+    /// Unregister a charity, keeping its donated amounts - restricted to gov
+    UnregisterCharity { address: String },
     /// Claims pending depositor rewards
     ClaimRewards {},
+    /// Registers the sender as an operator, or updates its registration, with the share
+    /// of the prizes won by its referred depositors it takes as commission
+    RegisterOperator { commission_rate: Decimal256 },
+    /// Claims the commission accrued by the sender as an operator
+    ClaimOperatorCommission {},
     /// First step on the lottery execution. Sets oracle round number
     ExecuteLottery {},
     /// Second step (paginated) on the lottery execution. Sets winner sequence and
@@ -180,9 +376,139 @@ pub enum QueryMsg {
     Sponsor { address: String },
     /// Sponsor information by address
     Operator { address: String },
+    /// Locked and unlocked shares and balances of a depositor
+    DepositorLocks { address: String },
+    /// Gift code information by hex encoded code hash
+    GiftCode { code_hash: String },
     /// Get the lottery balance. This is the amount that would be distributed in prizes if the lottery were run right
     /// now.
     LotteryBalance {},
+    /// Simulate a deposit of amount
+    SimulateDeposit { amount: Uint128 },
+    /// Simulate a withdrawal from a depositor
+    SimulateWithdraw {
+        address: String,
+        amount: Option<Uint128>,
+        instant: Option<bool>,
+    },
+    /// Simulate claiming the prizes of a depositor for the given lottery ids
+    SimulateClaimLottery {
+        address: String,
+        lottery_ids: Vec<u64>,
+    },
+    /// Glow prize boost of a depositor, using the ve balances at at_time (defaults to now)
+    Boost {
+        address: String,
+        at_time: Option<u64>,
+    },
+    /// List (paginated) of the activity of a depositor, oldest first
+    DepositorHistory {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// List (paginated) of the unbonding claims of a depositor by release epoch
+    UnbondingClaims {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Instant withdrawal fee currently applied to a depositor, given the age of their deposits
+    InstantWithdrawalFee { address: String },
+    /// Charity the prizes of a depositor are donated to, if any
+    PrizeBeneficiary { address: String },
+    /// Charity information, including its cumulative donated amounts
+    Charity { address: String },
+    /// List (paginated) of charities
+    Charities {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Syndicate information
+    Syndicate { syndicate_id: u64 },
+    /// Syndicate shares of a member
+    SyndicateMember { syndicate_id: u64, address: String },
+    /// Next nonce expected in the deposit intents relayed for an address
+    RelayNonce { address: String },
+    /// Prizes, winners and reserve of the awarded lotteries with ids from from_id
+    /// to to_id (inclusive), together with the realized annualized prize rate.
+    /// At most 30 lottery ids are read, so to_id is capped to from_id + 29
+    LotteryStats { from_id: u64, to_id: u64 },
+    /// Pool and state totals, as a single state record
+    ExportTotals {},
+    /// Depositor state records, ordered by address
+    ExportDepositors {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Sponsor state records, ordered by address
+    ExportSponsors {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Operator state records, ordered by address
+    ExportOperators {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Ticket state records, ordered by sequence
+    ExportTickets {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Lottery state records, ordered by lottery id
+    ExportLotteries {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Prize state records, ordered by lottery id and address
+    ExportPrizes {
+        start_after: Option<(u64, String)>,
+        limit: Option<u32>,
+    },
+    /// Gift code state records, ordered by code hash
+    ExportGiftCodes {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Charity state records, ordered by address
+    ExportCharities {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Syndicate state records, ordered by syndicate id
+    ExportSyndicates {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Syndicate member state records, ordered by syndicate id and address
+    ExportSyndicateMembers {
+        start_after: Option<(u64, String)>,
+        limit: Option<u32>,
+    },
+    /// Syndicate prize claim state records, ordered by lottery id and address
+    ExportSyndicatePrizeClaims {
+        start_after: Option<(u64, String)>,
+        limit: Option<u32>,
+    },
+    /// Syndicate prize shares state record of a page of the members of a syndicate,
+    /// ordered by address. To be exported for each syndicate prize of ExportPrizes
+    ExportSyndicatePrizeShares {
+        syndicate_id: u64,
+        lottery_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Unbonding claim state records, ordered by address and release epoch
+    ExportUnbondingClaims {
+        start_after: Option<(String, u64)>,
+        limit: Option<u32>,
+    },
+    /// Depositor history state records, ordered by address and id
+    ExportDepositorHistory {
+        start_after: Option<(String, u64)>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -209,7 +535,12 @@ pub struct ConfigResponse {
     pub instant_withdrawal_fee: Decimal256,
     pub unbonding_period: Duration,
     pub max_tickets_per_depositor: u64,
-    pub paused: bool,
+    pub paused: PauseFlags,
+    pub guardian: Option<String>,
+    pub max_history_entries: u64,
+    pub instant_withdrawal_fee_decay: Option<InstantWithdrawalFeeDecay>,
+    pub max_operator_commission: Decimal256,
+    pub reserve_destinations: ReserveDestinations,
 }
 
 // We define a custom struct for each query response
@@ -217,6 +548,8 @@ pub struct ConfigResponse {
 pub struct StateResponse {
     pub total_tickets: Uint256,
     pub total_reserve: Uint256,
+    pub withdrawal_fee_reserve: Uint256,
+    pub prize_reserve: Uint256,
     pub prize_buckets: [Uint256; NUM_PRIZE_BUCKETS],
     pub current_lottery: u64,
     pub next_lottery_time: Expiration,
@@ -250,6 +583,7 @@ pub struct LotteryInfoResponse {
     pub page: String,
     pub glow_prize_buckets: [Uint256; NUM_PRIZE_BUCKETS],
     pub total_user_shares: Uint256,
+    pub reserve: Uint256,
 }
 
 // We define a custom struct for each query response
@@ -259,6 +593,8 @@ pub struct DepositorInfoResponse {
     pub shares: Uint256,
     pub tickets: Vec<String>,
     pub unbonding_info: Vec<Claim>,
+    // Whether unbonding_info omits claims, which are listed by the UnbondingClaims query
+    pub unbonding_info_has_more: bool,
 }
 
 // We define a custom struct for each query response
@@ -285,6 +621,8 @@ pub struct OperatorInfoResponse {
     pub shares: Uint256,
     pub reward_index: Decimal256,
     pub pending_rewards: Decimal256,
+    pub commission_rate: Decimal256,
+    pub pending_commission: Uint256,
 }
 
 // We define a custom struct for each query response
@@ -299,12 +637,425 @@ pub struct DepositorsStatsResponse {
     pub depositors: Vec<DepositorStatsResponse>,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositorLocksResponse {
+    pub depositor: String,
+    pub locked_shares: Uint256,
+    pub unlocked_shares: Uint256,
+    pub locked_balance: Uint256,
+    pub unlocked_balance: Uint256,
+    pub locks: Vec<ShareLock>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GiftCodeResponse {
+    pub code_hash: String,
+    pub creator: String,
+    pub shares: Uint256,
+    pub value: Uint256,
+    pub encoded_tickets: String,
+    pub expires_at: Timestamp,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateDepositResponse {
+    pub deposit_amount: Uint256,
+    pub tax: Uint256,
+    pub minted_aust: Uint256,
+    pub minted_shares: Uint256,
+    pub deposit_value: Uint256,
+    pub max_tickets: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateWithdrawResponse {
+    pub withdrawn_shares: Uint256,
+    pub withdrawn_aust: Uint256,
+    pub withdrawn_aust_value: Uint256,
+    pub withdrawn_tickets: u64,
+    pub instant_withdrawal_fee: Uint256,
+    pub tax: Uint256,
+    // Amount sent to the depositor on instant withdrawals, or claimable after unbonding otherwise
+    pub return_amount: Uint256,
+    pub post_transaction_shares: Uint256,
+    pub post_transaction_balance: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateClaimLotteryResponse {
+    pub won_ust: Uint128,
+    // Part of won_ust taken by the depositor's operator
+    pub operator_commission: Uint128,
+    pub tax: Uint128,
+    pub net_ust: Uint128,
+    pub won_glow: Uint128,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BoostResponse {
+    pub depositor: String,
+    pub multiplier: Decimal256,
+    pub share_ratio: Decimal256,
+    pub voting_ratio: Decimal256,
+    pub voting_balance: Uint128,
+    pub total_voting_balance: Uint128,
+    // None if the max multiplier can't be reached by adding voting balance
+    pub additional_voting_balance_needed: Option<Uint128>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositorHistoryResponse {
+    pub depositor: String,
+    pub history: Vec<DepositorActivityResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositorActivityResponse {
+    pub id: u64,
+    pub kind: DepositorActivityKind,
+    pub amount: Uint256,
+    pub shares: Uint256,
+    pub tickets: u64,
+    pub block_height: u64,
+    pub lottery_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositorActivity {
+    pub kind: DepositorActivityKind,
+    pub amount: Uint256,         // UST amount involved in the activity
+    pub shares: Uint256,         // shares added or removed
+    pub tickets: u64,            // tickets added or removed
+    pub block_height: u64,       // block height of the activity
+    pub lottery_id: Option<u64>, // lottery id of claimed prizes
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DepositorActivityKind {
+    Deposit,
+    GiftSent,
+    GiftReceived,
+    GiftRefunded,
+    Withdraw,
+    InstantWithdraw,
+    ClaimUnbonded,
+    ClaimPrize,
+    ExitSyndicate,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondingClaimsResponse {
+    pub depositor: String,
+    pub claims: Vec<UnbondingClaimResponse>,
+    // Sums over the listed claims
+    pub pending_amount: Uint256,
+    pub releasable_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondingClaimResponse {
+    pub release_epoch: u64,
+    pub amount: Uint256,
+    pub release_at: Expiration,
+    pub releasable: bool,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantWithdrawalFeeResponse {
+    pub depositor: String,
+    pub instant_withdrawal_fee: Decimal256,
+    // Share-weighted average time of the depositor's deposits, in seconds
+    pub average_deposit_time: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PrizeBeneficiaryResponse {
+    pub depositor: String,
+    pub beneficiary: Option<String>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CharityResponse {
+    pub address: String,
+    pub name: String,
+    pub verified: bool,
+    pub donated_ust: Uint256,
+    pub donated_glow: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CharitiesResponse {
+    pub charities: Vec<CharityResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SyndicateResponse {
+    pub syndicate_id: u64,
+    pub name: String,
+    pub creator: String,
+    // Address under which the syndicate holds its deposits and tickets
+    pub depositor: String,
+    pub shares: Uint256,
+    pub tickets: Vec<String>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SyndicateMemberResponse {
+    pub syndicate_id: u64,
+    pub member: String,
+    pub shares: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RelayNonceResponse {
+    pub address: String,
+    pub nonce: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ShareLock {
+    pub shares: Uint256,
+    pub unlock_at: Timestamp,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Claim {
     pub amount: Uint256,
     pub release_at: Expiration,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LotteryStatsResponse {
+    pub lotteries: Vec<LotteryStats>,
+    pub total_prizes: Uint256,
+    pub total_glow_prizes: Uint256,
+    pub number_winners: [u32; NUM_PRIZE_BUCKETS],
+    pub total_reserve: Uint256,
+    // Prizes awarded per year relative to the value of the deposits at draw time,
+    // with deposit shares valued at the current share price
+    pub prize_apr: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LotteryStats {
+    pub lottery_id: u64,
+    pub timestamp: Timestamp,
+    pub prizes: Uint256,
+    pub glow_prizes: Uint256,
+    pub number_winners: [u32; NUM_PRIZE_BUCKETS],
+    pub reserve: Uint256,
+    pub total_user_shares: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateExportResponse {
+    pub version: u32,
+    pub records: Vec<StateRecord>,
+}
+
+/// Raw lotto state record, as exported by the export queries and written by ImportState
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StateRecord {
+    Totals(ExportedTotals),
+    Depositor(ExportedDepositor),
+    Sponsor(ExportedSponsor),
+    Operator(ExportedOperator),
+    Ticket(ExportedTicket),
+    Lottery(ExportedLottery),
+    Prize(ExportedPrize),
+    GiftCode(ExportedGiftCode),
+    Charity(ExportedCharity),
+    Syndicate(ExportedSyndicate),
+    SyndicateMember(ExportedSyndicateMember),
+    SyndicatePrizeClaim(ExportedSyndicatePrizeClaim),
+    SyndicatePrizeShares(ExportedSyndicatePrizeShares),
+    UnbondingClaim(ExportedUnbondingClaim),
+    DepositorActivity(ExportedDepositorActivity),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedTotals {
+    pub total_tickets: Uint256,
+    pub total_reserve: Uint256,
+    #[serde(default)]
+    pub withdrawal_fee_reserve: Uint256,
+    #[serde(default)]
+    pub prize_reserve: Uint256,
+    pub prize_buckets: [Uint256; NUM_PRIZE_BUCKETS],
+    pub current_lottery: u64,
+    pub next_lottery_time: Expiration,
+    pub next_lottery_exec_time: Expiration,
+    pub next_epoch: Expiration,
+    pub operator_reward_emission_index: RewardEmissionsIndex,
+    pub sponsor_reward_emission_index: RewardEmissionsIndex,
+    pub last_lottery_execution_aust_exchange_rate: Decimal256,
+    pub total_user_aust: Uint256,
+    pub total_user_shares: Uint256,
+    pub total_sponsor_lottery_deposits: Uint256,
+    pub total_operator_shares: Uint256,
+    pub syndicate_count: u64,
+    // Time at which deposit ages started being tracked
+    pub deposit_ages_start: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedDepositor {
+    pub address: String,
+    pub shares: Uint256,
+    pub tickets: Vec<String>,
+    pub operator: String,
+    // Legacy unbonding claims. Claims of the ledger are exported separately
+    pub unbonding_info: Vec<Claim>,
+    pub share_locks: Vec<ShareLock>,
+    // Average deposit time, unknown for depositors without a record
+    pub deposit_time: Option<Timestamp>,
+    // Commission rate agreed to when setting the operator
+    pub operator_commission_rate: Option<Decimal256>,
+    // Charity receiving the prizes of the depositor, even if no longer verified
+    pub prize_beneficiary: Option<String>,
+    // Next nonce expected in the deposit intents relayed for the depositor
+    pub relay_nonce: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedSponsor {
+    pub address: String,
+    pub lottery_deposit: Uint256,
+    pub pending_rewards: Decimal256,
+    pub reward_index: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedOperator {
+    pub address: String,
+    pub shares: Uint256,
+    pub pending_rewards: Decimal256,
+    pub reward_index: Decimal256,
+    pub commission_rate: Decimal256,
+    pub pending_commission: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedTicket {
+    pub sequence: String,
+    pub holders: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedLottery {
+    pub lottery_id: u64,
+    pub rand_round: u64,
+    pub sequence: String,
+    pub awarded: bool,
+    pub timestamp: Timestamp,
+    pub block_height: u64,
+    pub prize_buckets: [Uint256; NUM_PRIZE_BUCKETS],
+    pub number_winners: [u32; NUM_PRIZE_BUCKETS],
+    pub page: String,
+    pub glow_prize_buckets: [Uint256; NUM_PRIZE_BUCKETS],
+    pub total_user_shares: Uint256,
+    #[serde(default)]
+    pub reserve: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedPrize {
+    pub lottery_id: u64,
+    pub address: String,
+    pub claimed: bool,
+    pub matches: [u32; NUM_PRIZE_BUCKETS],
+    // Stats of the winner as of the lottery, which the prize is computed from
+    pub winner_stats: ExportedDepositorStats,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedDepositorStats {
+    pub shares: Uint256,
+    pub num_tickets: u64,
+    pub operator: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedGiftCode {
+    pub code_hash: String,
+    pub creator: String,
+    pub shares: Uint256,
+    pub encoded_tickets: String,
+    pub expires_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedCharity {
+    pub address: String,
+    pub name: String,
+    pub verified: bool,
+    pub donated_ust: Uint256,
+    pub donated_glow: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedSyndicate {
+    pub syndicate_id: u64,
+    pub name: String,
+    pub creator: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedSyndicateMember {
+    pub syndicate_id: u64,
+    pub address: String,
+    pub shares: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedSyndicatePrizeClaim {
+    pub lottery_id: u64,
+    pub address: String,
+    pub syndicate_ids: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedSyndicatePrizeShares {
+    pub syndicate_id: u64,
+    pub lottery_id: u64,
+    // Syndicate shares whose part of the prize has been claimed
+    pub claimed_shares: Uint256,
+    // Shares of the members as of the lottery, keyed by address
+    pub member_shares: Vec<(String, Uint256)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedUnbondingClaim {
+    pub address: String,
+    pub release_epoch: u64,
+    pub claim: Claim,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedDepositorActivity {
+    pub address: String,
+    pub id: u64,
+    pub activity: DepositorActivity,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TicketInfoResponse {
     pub holders: Vec<Addr>,